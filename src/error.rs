@@ -13,6 +13,9 @@ pub enum RextCoreError {
     #[error("Rext app already exists")]
     AppAlreadyExists,
 
+    #[error("No Rext app found, rext.toml is missing")]
+    AppNotFound,
+
     #[error("Failed to get current directory, either does not exist or permission denied: {0}")]
     CurrentDir(std::io::Error),
 
@@ -28,6 +31,28 @@ pub enum RextCoreError {
     #[error("Safety check failed: {0}")]
     SafetyCheck(String),
 
+    #[error("Invalid app name: {0}")]
+    InvalidAppName(String),
+
+    #[error("Unresolved template placeholder: {0}")]
+    UnresolvedPlaceholder(String),
+
+    #[error(
+        "sea-orm-cli is not installed or not on PATH, install it with `cargo install sea-orm-cli`"
+    )]
+    SeaOrmCliNotInstalled,
+
     #[error("Failed to execute sea-orm-cli generate entities command: {0}")]
     SeaOrmCliGenerateEntities(std::io::Error),
 }
+
+impl RextCoreError {
+    /// Error for a failure to run sea-orm-cli, telling the user to install it when it's missing
+    pub(crate) fn sea_orm_cli(error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            RextCoreError::SeaOrmCliNotInstalled
+        } else {
+            RextCoreError::SeaOrmCliGenerateEntities(error)
+        }
+    }
+}
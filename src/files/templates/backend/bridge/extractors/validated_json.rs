@@ -0,0 +1,117 @@
+use axum::{
+    Json,
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+use crate::infrastructure::{app_error::AppError, json_limits::JsonLimits};
+
+/// `Json` extractor that rejects bodies over the configured nesting depth or element count
+/// with a 400 before they are deserialized
+///
+/// Use it in place of `Json` for request bodies: `ValidatedJson(payload): ValidatedJson<T>`.
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        Self::from_request_with_limits(request, state, JsonLimits::get()).await
+    }
+}
+
+impl<T: DeserializeOwned> ValidatedJson<T> {
+    /// Extracts the body, checking it against the given limits
+    pub async fn from_request_with_limits<S: Send + Sync>(
+        request: Request,
+        state: &S,
+        limits: &JsonLimits,
+    ) -> Result<Self, Response> {
+        let (parts, body) = request.into_parts();
+        let bytes = Bytes::from_request(Request::from_parts(parts.clone(), body), state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        limits.check(&bytes).map_err(|e| {
+            AppError {
+                message: e.to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            }
+            .into_response()
+        })?;
+
+        // Json still checks the content type and reports deserialization errors as usual
+        let Json(value) =
+            Json::<T>::from_request(Request::from_parts(parts, Body::from(bytes)), state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::post};
+    use tower::ServiceExt;
+
+    async fn echo(
+        ValidatedJson(value): ValidatedJson<serde_json::Value>,
+    ) -> Json<serde_json::Value> {
+        Json(value)
+    }
+
+    async fn post_json(body: String) -> StatusCode {
+        let app = Router::new().route("/echo", post(echo));
+        let request = Request::post("/echo")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap();
+        app.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_deeply_nested_payload_rejected() {
+        let depth = JsonLimits::get().max_depth + 1;
+        let body = format!(
+            "{}{}",
+            r#"{"a":"#.repeat(depth),
+            format!("1{}", "}".repeat(depth))
+        );
+
+        assert_eq!(post_json(body).await, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_normal_payload_passes() {
+        let body = r#"{"email": "user@example.com", "password": "x", "tags": ["a", "b"]}"#;
+
+        assert_eq!(post_json(body.to_string()).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_too_many_elements_rejected() {
+        let limits = JsonLimits {
+            max_depth: 8,
+            max_elements: 3,
+        };
+        let request = Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from("[1, 2, 3, 4]"))
+            .unwrap();
+
+        let rejection = ValidatedJson::<Vec<u32>>::from_request_with_limits(request, &(), &limits)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    }
+}
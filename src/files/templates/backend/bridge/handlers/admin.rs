@@ -1,22 +1,33 @@
 use axum::{
     Json,
+    body::Body,
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
 use crate::{
-    bridge::types::{
-        admin::{AdminUser, *},
-        auth::AuthUser,
-        logging::LoggingInfo,
+    bridge::{
+        extractors::validated_json::ValidatedJson,
+        middleware::auth_transport::AuthTransport,
+        types::{
+            admin::{AdminUser, *},
+            auth::AuthUser,
+            logging::LoggingInfo,
+        },
     },
     check_single_permission,
-    control::services::admin_service::AdminService,
+    control::services::{
+        admin_service::{AdminService, AuditLogPurgeMode},
+        confirmation_service::{ConfirmationService, DestructiveAction},
+        health_snapshot_service::HealthSnapshotService,
+    },
     domain::permissions::Permission::{AdminDelete, AdminRead, AdminWrite},
-    infrastructure::app_error::{AppError, ErrorResponse, MessageResponse},
+    infrastructure::app_error::{
+        AppError, ErrorResponse, MessageResponse, ValidationErrorResponse,
+    },
 };
 
 /// Admin login endpoint
@@ -26,29 +37,41 @@ use crate::{
     request_body = AdminLoginRequest,
     responses(
         (status = 200, description = "Admin login successful", body = AdminLoginResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse),
-        (status = 401, description = "Unauthorized - invalid credentials", body = ErrorResponse),
-        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized - invalid credentials or not an admin", body = ErrorResponse, examples(
+            ("invalid_credentials" = (value = json!({"message": "Invalid credentials"})))
+        )),
+        (status = 429, description = "Too many requests - locked out after repeated failed logins", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Admin login",
-    description = "Authenticates an admin user and returns a JWT token. Requires admin:read permission.",
+    description = "Authenticates an admin user and returns a JWT token. Requires admin:read permission, checked after the password so a non-admin gets the same 401 as a bad password.",
     tag = ADMIN_TAG
 )]
 pub async fn admin_login_handler(
     State(db): State<DatabaseConnection>,
     Extension(logging_info): Extension<LoggingInfo>,
-    Json(payload): Json<AdminLoginRequest>,
+    ValidatedJson(payload): ValidatedJson<AdminLoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    check_single_permission!(&payload.email, &AdminRead, &db);
-    let response = AdminService::authenticate_admin(
+    // Checks the admin permission itself, after verifying the password
+    let mut response = AdminService::authenticate_admin(
         &db,
         payload,
         logging_info.user_agent,
         logging_info.ip_address,
     )
     .await?;
-    Ok((StatusCode::OK, Json(response)))
+    let token = response.token.take().ok_or_else(|| AppError {
+        message: "Admin login did not issue a token".to_string(),
+        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
+    let expires_at = response.expires_at;
+    response.token = AuthTransport::TOKEN_IN_BODY.then(|| token.clone());
+    Ok(AuthTransport::attach_token(
+        (StatusCode::OK, Json(response)).into_response(),
+        &token,
+        expires_at,
+    ))
 }
 
 /// Admin logout endpoint
@@ -71,29 +94,29 @@ pub async fn admin_logout_handler(
     State(db): State<DatabaseConnection>,
     request: axum::extract::Request,
 ) -> Result<impl IntoResponse, AppError> {
-    // Extract token from Authorization header
-    let token =
-        crate::control::services::token_service::TokenService::extract_token_from_header(&request)?;
+    // Extract token from the configured auth transport
+    let token = AuthTransport::extract_token(&request)?;
 
     // Validate token and extract claims to get session_id
     let claims =
         crate::control::services::token_service::TokenService::validate_token_claims(&token)?;
 
     // Parse session ID
-    let session_id = uuid::Uuid::parse_str(&claims.session_id).map_err(|_| AppError {
-        message: "Invalid session ID in token".to_string(),
-        status_code: StatusCode::UNAUTHORIZED,
-    })?;
+    let session_id =
+        crate::control::services::token_service::TokenService::session_id_from_claims(&claims)?;
 
     // Invalidate the session
     crate::control::services::session_service::SessionService::invalidate_session(&db, session_id)
         .await?;
 
-    Ok((
-        StatusCode::OK,
-        Json(MessageResponse {
-            message: "Admin logged out successfully".to_string(),
-        }),
+    Ok(AuthTransport::clear_token(
+        (
+            StatusCode::OK,
+            Json(MessageResponse {
+                message: "Admin logged out successfully".to_string(),
+            }),
+        )
+            .into_response(),
     ))
 }
 
@@ -104,6 +127,7 @@ pub async fn admin_logout_handler(
     params(LogsQueryParams),
     responses(
         (status = 200, description = "Audit logs retrieved successfully", body = PaginatedResponse<AuditLogResponse>),
+        (status = 400, description = "Bad request - page beyond the maximum pagination depth", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -132,6 +156,7 @@ pub async fn get_audit_logs_handler(
     params(UsersQueryParams),
     responses(
         (status = 200, description = "Users retrieved successfully", body = PaginatedResponse<UserResponse>),
+        (status = 400, description = "Bad request - page beyond the maximum pagination depth", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -156,7 +181,7 @@ pub async fn get_users_handler(
 /// Get specific user endpoint
 #[utoipa::path(
     get,
-    path = "/users/{id}",
+    path = "/users/{{id}}",
     params(
         ("id" = String, Path, description = "User ID")
     ),
@@ -196,7 +221,7 @@ pub async fn get_user_handler(
     request_body = CreateUserRequest,
     responses(
         (status = 201, description = "User created successfully", body = UserResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 409, description = "Conflict - user already exists", body = ErrorResponse),
@@ -212,7 +237,7 @@ pub async fn get_user_handler(
 pub async fn create_user_handler(
     State(db): State<DatabaseConnection>,
     Extension(admin_user): Extension<AdminUser>,
-    Json(payload): Json<CreateUserRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     check_single_permission!(&admin_user.email, &AdminWrite, &db);
     let response = AdminService::create_user(&db, payload).await?;
@@ -222,14 +247,14 @@ pub async fn create_user_handler(
 /// Update user endpoint
 #[utoipa::path(
     put,
-    path = "/users/{id}",
+    path = "/users/{{id}}",
     params(
         ("id" = String, Path, description = "User ID")
     ),
     request_body = UpdateUserRequest,
     responses(
         (status = 200, description = "User updated successfully", body = UserResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse),
@@ -237,7 +262,7 @@ pub async fn create_user_handler(
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Update user",
-    description = "Updates an existing user account",
+    description = "Updates an existing user account, omitted or null fields are left unchanged. Use PATCH to remove a role",
     tag = ADMIN_TAG,
     security(
         ("jwt_token" = [])
@@ -247,7 +272,7 @@ pub async fn update_user_handler(
     State(db): State<DatabaseConnection>,
     Extension(admin_user): Extension<AdminUser>,
     Path(user_id): Path<String>,
-    Json(payload): Json<UpdateUserRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     check_single_permission!(&admin_user.email, &AdminWrite, &db);
     let user_id = Uuid::parse_str(&user_id).map_err(|_| AppError {
@@ -259,19 +284,100 @@ pub async fn update_user_handler(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Partially update user endpoint
+#[utoipa::path(
+    patch,
+    path = "/users/{{id}}",
+    params(
+        ("id" = String, Path, description = "User ID")
+    ),
+    request_body = PatchUserRequest,
+    responses(
+        (status = 200, description = "User updated successfully", body = UserResponse),
+        (status = 400, description = "Bad request - validation errors or null for a required field", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 409, description = "Conflict - email already taken", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Partially update user",
+    description = "Updates only the fields present in the body. Omitted fields are left unchanged, role_id: null removes the user's role, null email or password is rejected",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn patch_user_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(user_id): Path<String>,
+    ValidatedJson(payload): ValidatedJson<PatchUserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminWrite, &db);
+    let user_id = Uuid::parse_str(&user_id).map_err(|_| AppError {
+        message: "Invalid user ID format".to_string(),
+        status_code: StatusCode::BAD_REQUEST,
+    })?;
+
+    let response = AdminService::patch_user(&db, user_id, payload).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Delete user confirmation endpoint
+#[utoipa::path(
+    get,
+    path = "/users/{{id}}/delete-confirmation",
+    params(
+        ("id" = String, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Confirmation token issued", body = ConfirmationResponse),
+        (status = 400, description = "Bad request - invalid user ID", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Confirm user deletion",
+    description = "Describes the impact of deleting a user and issues a short-lived confirmation token for the delete request",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn delete_user_confirmation_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminDelete, &db);
+    let user_id = Uuid::parse_str(&user_id).map_err(|_| AppError {
+        message: "Invalid user ID format".to_string(),
+        status_code: StatusCode::BAD_REQUEST,
+    })?;
+
+    let action = DestructiveAction::DeleteUser(user_id);
+    let impact = ConfirmationService::describe_impact(&db, &action).await?;
+    let response = ConfirmationService::issue(&db, &action, admin_user.user_id, impact).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
 /// Delete user endpoint
 #[utoipa::path(
     delete,
-    path = "/users/{id}",
+    path = "/users/{{id}}",
     params(
-        ("id" = String, Path, description = "User ID")
+        ("id" = String, Path, description = "User ID"),
+        ("X-Confirmation-Token" = Option<String>, Header, description = "Token from the delete confirmation endpoint, required when REQUIRE_ADMIN_CONFIRMATION is enabled")
     ),
     responses(
         (status = 200, description = "User deleted successfully", body = MessageResponse),
         (status = 400, description = "Bad request - cannot delete own account", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
-        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required or invalid confirmation token", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 428, description = "Confirmation token required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Delete user",
@@ -285,6 +391,7 @@ pub async fn delete_user_handler(
     State(db): State<DatabaseConnection>,
     Extension(admin_user): Extension<AdminUser>,
     Path(user_id): Path<String>,
+    headers: HeaderMap,
     request: axum::extract::Request,
 ) -> Result<impl IntoResponse, AppError> {
     check_single_permission!(&admin_user.email, &AdminDelete, &db);
@@ -293,6 +400,14 @@ pub async fn delete_user_handler(
         status_code: StatusCode::BAD_REQUEST,
     })?;
 
+    ConfirmationService::require(
+        &db,
+        &headers,
+        &DestructiveAction::DeleteUser(user_id),
+        admin_user.user_id,
+    )
+    .await?;
+
     // Get current admin ID from request extensions
     let auth_user = request.extensions().get::<AuthUser>().ok_or(AppError {
         message: "User not authenticated".to_string(),
@@ -337,13 +452,14 @@ pub async fn get_database_tables_handler(
 /// Get table records endpoint
 #[utoipa::path(
     get,
-    path = "/database/tables/{table_name}",
+    path = "/database/tables/{{table_name}}",
     params(
         ("table_name" = String, Path, description = "Table name"),
         TableRecordsQueryParams
     ),
     responses(
         (status = 200, description = "Table records retrieved successfully", body = TableRecordResponse),
+        (status = 400, description = "Bad request - page beyond the maximum pagination depth", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -370,13 +486,14 @@ pub async fn get_table_records_handler(
 #[utoipa::path(
     get,
     path = "/health",
+    params(HealthQueryParams),
     responses(
         (status = 200, description = "System health check successful", body = HealthResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse)
     ),
     summary = "System health check",
-    description = "Returns system health status",
+    description = "Returns the latest background health snapshot, or a live one with fresh=true",
     tag = ADMIN_TAG,
     security(
         ("jwt_token" = [])
@@ -385,19 +502,162 @@ pub async fn get_table_records_handler(
 pub async fn health_handler(
     State(db): State<DatabaseConnection>,
     Extension(admin_user): Extension<AdminUser>,
+    Extension(snapshots): Extension<&'static HealthSnapshotService>,
+    Query(params): Query<HealthQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
     check_single_permission!(&admin_user.email, &AdminRead, &db);
-    let response = AdminService::get_health_status(&db).await;
+    let response = snapshots.get(&db, params.fresh).await;
     Ok((StatusCode::OK, Json(response)))
 }
 
-/// Get user sessions endpoint
+/// Purge user data confirmation endpoint
+#[utoipa::path(
+    get,
+    path = "/users/{{id}}/purge-confirmation",
+    params(
+        ("id" = String, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Confirmation token issued", body = ConfirmationResponse),
+        (status = 400, description = "Bad request - invalid user ID", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Confirm user data purge",
+    description = "Describes the impact of purging a user's data and issues a short-lived confirmation token for the purge request",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn purge_user_data_confirmation_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminDelete, &db);
+    let user_id = Uuid::parse_str(&user_id).map_err(|_| AppError {
+        message: "Invalid user ID format".to_string(),
+        status_code: StatusCode::BAD_REQUEST,
+    })?;
+
+    let action = DestructiveAction::PurgeUserData(user_id);
+    let impact = ConfirmationService::describe_impact(&db, &action).await?;
+    let response = ConfirmationService::issue(&db, &action, admin_user.user_id, impact).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Purge user data endpoint
+#[utoipa::path(
+    post,
+    path = "/users/{{id}}/purge",
+    params(
+        ("id" = String, Path, description = "User ID"),
+        ("X-Confirmation-Token" = Option<String>, Header, description = "Token from the purge confirmation endpoint, required when REQUIRE_ADMIN_CONFIRMATION is enabled")
+    ),
+    responses(
+        (status = 200, description = "User data purged successfully", body = UserPurgeResponse),
+        (status = 400, description = "Bad request - cannot purge own account", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required or invalid confirmation token", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 428, description = "Confirmation token required", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Purge user data",
+    description = "Deletes a user and their sessions, and deletes or anonymizes their audit log entries depending on USER_PURGE_AUDIT_LOGS, for data erasure requests",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn purge_user_data_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminDelete, &db);
+    let user_id = Uuid::parse_str(&user_id).map_err(|_| AppError {
+        message: "Invalid user ID format".to_string(),
+        status_code: StatusCode::BAD_REQUEST,
+    })?;
+
+    ConfirmationService::require(
+        &db,
+        &headers,
+        &DestructiveAction::PurgeUserData(user_id),
+        admin_user.user_id,
+    )
+    .await?;
+
+    let response = AdminService::purge_user_data(
+        &db,
+        user_id,
+        admin_user.user_id,
+        AuditLogPurgeMode::from_env(),
+    )
+    .await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Export user data endpoint
 #[utoipa::path(
     get,
-    path = "/users/{user_id}/sessions",
+    path = "/users/{{user_id}}/export",
     params(
         ("user_id" = String, Path, description = "User ID")
     ),
+    responses(
+        (status = 200, description = "User data exported successfully", body = UserDataExport),
+        (status = 400, description = "Bad request - invalid user ID", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Export user data",
+    description = "Downloads the user's record, sessions and audit log entries as one JSON document, for data subject access requests",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn export_user_data_handler(
+    State(db): State<DatabaseConnection>,
+    Path(user_id): Path<String>,
+    Extension(admin_user): Extension<AdminUser>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminRead, &db);
+
+    let user_uuid = Uuid::parse_str(&user_id).map_err(|_| AppError {
+        message: "Invalid user ID format".to_string(),
+        status_code: StatusCode::BAD_REQUEST,
+    })?;
+
+    let stream = AdminService::export_user_data_stream(db, user_uuid).await?;
+    let disposition = format!("attachment; filename=\"user-{}-export.json\"", user_uuid);
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(stream),
+    ))
+}
+
+/// Get user sessions endpoint
+#[utoipa::path(
+    get,
+    path = "/users/{{user_id}}/sessions",
+    params(
+        ("user_id" = String, Path, description = "User ID"),
+        SessionsQueryParams
+    ),
     responses(
         (status = 200, description = "User sessions retrieved successfully", body = Vec<SessionResponse>),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
@@ -406,7 +666,7 @@ pub async fn health_handler(
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Get user sessions",
-    description = "Retrieves all active sessions for a specific user",
+    description = "Retrieves the active sessions for a specific user, with include_inactive=true also the invalidated and expired ones used since `since` (default 30 days)",
     tag = ADMIN_TAG,
     security(
         ("jwt_token" = [])
@@ -415,6 +675,7 @@ pub async fn health_handler(
 pub async fn get_user_sessions_handler(
     State(db): State<DatabaseConnection>,
     Path(user_id): Path<String>,
+    Query(params): Query<SessionsQueryParams>,
     Extension(admin_user): Extension<AdminUser>,
 ) -> Result<impl IntoResponse, AppError> {
     check_single_permission!(&admin_user.email, &AdminRead, &db);
@@ -424,14 +685,16 @@ pub async fn get_user_sessions_handler(
         status_code: StatusCode::BAD_REQUEST,
     })?;
 
-    let sessions = AdminService::get_user_sessions(&db, user_uuid).await?;
+    let sessions =
+        AdminService::get_user_sessions(&db, user_uuid, params.include_inactive, params.since)
+            .await?;
     Ok((StatusCode::OK, Json(sessions)))
 }
 
 /// Invalidate specific session endpoint
 #[utoipa::path(
     delete,
-    path = "/sessions/{session_id}",
+    path = "/sessions/{{session_id}}",
     params(
         ("session_id" = String, Path, description = "Session ID")
     ),
@@ -471,18 +734,60 @@ pub async fn invalidate_session_handler(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Invalidate all user sessions confirmation endpoint
+#[utoipa::path(
+    get,
+    path = "/users/{{user_id}}/sessions/invalidate-confirmation",
+    params(
+        ("user_id" = String, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "Confirmation token issued", body = ConfirmationResponse),
+        (status = 400, description = "Bad request - invalid user ID", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Confirm invalidating all user sessions",
+    description = "Describes how many sessions would be invalidated and issues a short-lived confirmation token for the invalidate request",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn invalidate_all_user_sessions_confirmation_handler(
+    State(db): State<DatabaseConnection>,
+    Path(user_id): Path<String>,
+    Extension(admin_user): Extension<AdminUser>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminDelete, &db);
+
+    let user_uuid = Uuid::parse_str(&user_id).map_err(|_| AppError {
+        message: "Invalid user ID format".to_string(),
+        status_code: StatusCode::BAD_REQUEST,
+    })?;
+
+    let action = DestructiveAction::InvalidateUserSessions(user_uuid);
+    let impact = ConfirmationService::describe_impact(&db, &action).await?;
+    let response = ConfirmationService::issue(&db, &action, admin_user.user_id, impact).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
 /// Invalidate all user sessions endpoint
 #[utoipa::path(
     delete,
-    path = "/users/{user_id}/sessions",
+    path = "/users/{{user_id}}/sessions",
     params(
-        ("user_id" = String, Path, description = "User ID")
+        ("user_id" = String, Path, description = "User ID"),
+        ("X-Confirmation-Token" = Option<String>, Header, description = "Token from the invalidate confirmation endpoint, required when REQUIRE_ADMIN_CONFIRMATION is enabled")
     ),
     responses(
         (status = 200, description = "All user sessions invalidated successfully", body = SessionInvalidationResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required or invalid confirmation token", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse),
+        (status = 428, description = "Confirmation token required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Invalidate all user sessions",
@@ -496,6 +801,7 @@ pub async fn invalidate_all_user_sessions_handler(
     State(db): State<DatabaseConnection>,
     Path(user_id): Path<String>,
     Extension(admin_user): Extension<AdminUser>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
     check_single_permission!(&admin_user.email, &AdminDelete, &db);
 
@@ -504,6 +810,14 @@ pub async fn invalidate_all_user_sessions_handler(
         status_code: StatusCode::BAD_REQUEST,
     })?;
 
+    ConfirmationService::require(
+        &db,
+        &headers,
+        &DestructiveAction::InvalidateUserSessions(user_uuid),
+        admin_user.user_id,
+    )
+    .await?;
+
     let count = AdminService::invalidate_all_user_sessions(&db, user_uuid).await?;
 
     let response = SessionInvalidationResponse {
@@ -513,3 +827,77 @@ pub async fn invalidate_all_user_sessions_handler(
 
     Ok((StatusCode::OK, Json(response)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::models::roles;
+    use crate::infrastructure::test_support::{create_verified_user, setup_db};
+    use axum::{Router, http::Request, routing::get};
+    use sea_orm::{ActiveModelTrait, Set};
+    use tower::ServiceExt;
+
+    async fn setup_admin() -> (DatabaseConnection, AdminUser) {
+        let db = setup_db().await;
+
+        let role = roles::ActiveModel {
+            name: Set("admin".to_string()),
+            permissions: Set("[\"*\"]".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let user = create_verified_user(
+            &db,
+            "admin@example.com".to_string(),
+            "AdminPassword123!".to_string(),
+            Some(role.id),
+        )
+        .await
+        .unwrap();
+
+        let admin_user = AdminUser {
+            user_id: user.id,
+            email: user.email,
+        };
+        (db, admin_user)
+    }
+
+    async fn get_health_status(app: &Router, uri: &str, admin_user: &AdminUser) -> String {
+        let mut request = Request::get(uri).body(Body::empty()).unwrap();
+        request.extensions_mut().insert(admin_user.clone());
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let health: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        health["status"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_health_serves_snapshot_unless_fresh() {
+        let (db, admin_user) = setup_admin().await;
+        let snapshots: &'static HealthSnapshotService =
+            Box::leak(Box::new(HealthSnapshotService::default()));
+        let app = Router::new()
+            .route("/health", get(health_handler))
+            .layer(Extension(snapshots))
+            .with_state(db.clone());
+
+        let mut snapshot = AdminService::get_health_status(&db).await;
+        snapshot.status = "snapshot".to_string();
+        snapshots.store(snapshot);
+
+        assert_eq!(
+            get_health_status(&app, "/health", &admin_user).await,
+            "snapshot"
+        );
+        assert_ne!(
+            get_health_status(&app, "/health?fresh=true", &admin_user).await,
+            "snapshot"
+        );
+    }
+}
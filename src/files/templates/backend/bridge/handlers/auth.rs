@@ -1,24 +1,31 @@
 use axum::{
     Extension, Json,
-    extract::{Request, State},
+    extract::{FromRequest, Request, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
 use sea_orm::DatabaseConnection;
 
+use crate::bridge::extractors::validated_json::ValidatedJson;
+use crate::bridge::middleware::auth_transport::AuthTransport;
 use crate::bridge::types::{
     auth::{
-        AUTH_TAG, AuthUser, LoginRequest, LoginResponse, ProfileResponse, RegisterRequest,
-        RegisterResponse, VerifyEmailRequest, VerifyEmailResponse,
+        AUTH_TAG, AuthUser, ChangePasswordRequest, ConfirmEmailChangeRequest,
+        ConfirmPasswordResetRequest, LoginRequest, LoginResponse, MeResponse, PasswordResetRequest,
+        ProfileResponse, RefreshRequest, RegisterRequest, RegisterResponse,
+        ResendVerificationRequest, VerifyEmailRequest, VerifyEmailResponse,
     },
     logging::LoggingInfo,
 };
 use crate::control::services::{
-    auth_service::AuthService, session_service::SessionService, token_service::TokenService,
-    user_service::UserService,
+    auth_service::AuthService, permission_service::PermissionService,
+    session_service::SessionService, token_service::TokenService, user_service::UserService,
 };
+use crate::domain::auth::AuthToken;
 use crate::domain::user::*;
-use crate::infrastructure::app_error::{AppError, ErrorResponse, MessageResponse};
+use crate::infrastructure::app_error::{
+    AppError, ErrorResponse, MessageResponse, ValidationErrorResponse,
+};
 
 /// Registers a new user
 #[utoipa::path(
@@ -27,7 +34,7 @@ use crate::infrastructure::app_error::{AppError, ErrorResponse, MessageResponse}
     request_body = RegisterRequest,
     responses(
         (status = 201, description = "User created successfully", body = RegisterResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse, examples(
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse, examples(
             ("empty_fields" = (value = json!({"message": "Email and password are required"}))),
         )),
         (status = 409, description = "Conflict - user already exists", body = ErrorResponse, examples(
@@ -39,12 +46,12 @@ use crate::infrastructure::app_error::{AppError, ErrorResponse, MessageResponse}
         ))
     ),
     summary = "Register a new user",
-    description = "Creates a new user account with email and password. Password is securely hashed using Argon2.",
+    description = "Creates a new user account with email and password. Password is securely hashed using Argon2. A verification link is emailed once the account is stored, if sending fails it can be requested again from /resend-verification.",
     tag = AUTH_TAG
 )]
 pub async fn register_handler(
     State(db): State<DatabaseConnection>,
-    Json(payload): Json<RegisterRequest>,
+    ValidatedJson(payload): ValidatedJson<RegisterRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     // Convert request to user domain model
     let registration = UserRegistration::new(payload.email, payload.password);
@@ -70,25 +77,28 @@ pub async fn register_handler(
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login successful", body = LoginResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse, examples(
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse, examples(
             ("empty_fields" = (value = json!({"message": "Email and password are required"}))),
         )),
         (status = 401, description = "Unauthorized - invalid credentials", body = ErrorResponse, examples(
             ("invalid_credentials" = (value = json!({"message": "Invalid credentials"})))
         )),
+        (status = 429, description = "Too many requests - locked out after repeated failed logins", body = ErrorResponse, examples(
+            ("locked_out" = (value = json!({"message": "Too many failed login attempts, try again later"})))
+        )),
         (status = 500, description = "Internal server error", body = ErrorResponse, examples(
             ("hash_error" = (value = json!({"message": "Invalid password hash"}))),
             ("token_error" = (value = json!({"message": "Failed to generate token"})))
         ))
     ),
     summary = "Login user",
-    description = "Authenticates a user with email and password, returns a JWT token on success.",
+    description = "Authenticates a user with email and password, returns a short-lived JWT token and a refresh token on success. With cookie auth both are set as HttpOnly cookies instead, the refresh cookie is only sent to /auth/refresh.",
     tag = AUTH_TAG
 )]
 pub async fn login_handler(
     State(db): State<DatabaseConnection>,
     Extension(logging_info): Extension<LoggingInfo>,
-    Json(payload): Json<LoginRequest>,
+    ValidatedJson(payload): ValidatedJson<LoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     // Convert request to user domain model
     let login = UserLogin::new(payload.email, payload.password);
@@ -102,9 +112,58 @@ pub async fn login_handler(
     )
     .await?;
 
-    Ok(Json(LoginResponse {
-        token: auth_token.token,
-    }))
+    Ok(token_response(auth_token))
+}
+
+/// Exchanges a refresh token for a new access token
+#[utoipa::path(
+    post,
+    path = "/refresh",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "Token refreshed", body = LoginResponse),
+        (status = 401, description = "Unauthorized - invalid, expired or reused refresh token", body = ErrorResponse, examples(
+            ("invalid_refresh_token" = (value = json!({"message": "Invalid refresh token"})))
+        )),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Refresh token",
+    description = "Returns a new access token and a new refresh token for the session, the refresh token presented can't be used again. Presenting a refresh token that was already used logs the session out. The refresh cookie is used when present, otherwise the body's refresh token.",
+    tag = AUTH_TAG
+)]
+pub async fn refresh_handler(
+    State(db): State<DatabaseConnection>,
+    request: Request,
+) -> Result<Response, Response> {
+    // Cookie clients send the refresh cookie, bearer clients the request body
+    let refresh_token = match AuthTransport::extract_refresh_token(request.headers()) {
+        Some(refresh_token) => refresh_token,
+        None => {
+            let ValidatedJson(payload) =
+                ValidatedJson::<RefreshRequest>::from_request(request, &()).await?;
+            payload.refresh_token
+        }
+    };
+
+    let auth_token = AuthService::refresh_token(&db, &refresh_token)
+        .await
+        .map_err(IntoResponse::into_response)?;
+    Ok(token_response(auth_token))
+}
+
+/// Login and refresh response, each token goes where the auth transport carries it
+fn token_response(auth_token: AuthToken) -> Response {
+    let response = Json(LoginResponse {
+        token: AuthTransport::TOKEN_IN_BODY.then(|| auth_token.token.clone()),
+        refresh_token: AuthTransport::TOKEN_IN_BODY.then(|| auth_token.refresh_token.clone()),
+    })
+    .into_response();
+    let response = AuthTransport::attach_token(response, &auth_token.token, auth_token.expires_at);
+    AuthTransport::attach_refresh_token(
+        response,
+        &auth_token.refresh_token,
+        auth_token.refresh_expires_at,
+    )
 }
 
 /// Logs out the current user
@@ -129,24 +188,24 @@ pub async fn logout_handler(
     State(db): State<DatabaseConnection>,
     request: Request,
 ) -> Result<impl IntoResponse, AppError> {
-    // Extract token from Authorization header
-    let token = TokenService::extract_token_from_header(&request)?;
+    // Extract token from the configured auth transport
+    let token = AuthTransport::extract_token(&request)?;
 
     // Validate token and extract claims to get session_id
     let claims = TokenService::validate_token_claims(&token)?;
 
     // Parse session ID
-    let session_id = uuid::Uuid::parse_str(&claims.session_id).map_err(|_| AppError {
-        message: "Invalid session ID in token".to_string(),
-        status_code: StatusCode::UNAUTHORIZED,
-    })?;
+    let session_id = TokenService::session_id_from_claims(&claims)?;
 
     // Invalidate the session
     SessionService::invalidate_session(&db, session_id).await?;
 
-    Ok(Json(MessageResponse {
-        message: "Logged out successfully".to_string(),
-    }))
+    Ok(AuthTransport::clear_token(
+        Json(MessageResponse {
+            message: "Logged out successfully".to_string(),
+        })
+        .into_response(),
+    ))
 }
 
 /// Gets the current user's profile information
@@ -195,34 +254,317 @@ pub async fn profile_handler(
     }))
 }
 
-/// Gets the current user's profile information
+/// Gets the current user's profile with their role and effective permissions
+#[utoipa::path(
+    get,
+    path = "/me",
+    responses(
+        (status = 200, description = "Current user retrieved successfully", body = MeResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse, examples(
+            ("not_authenticated" = (value = json!({"message": "User not authenticated"})))
+        )),
+        (status = 404, description = "Not found - user not found", body = ErrorResponse, examples(
+            ("user_not_found" = (value = json!({"message": "User not found"})))
+        )),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Get current user",
+    description = "Retrieves the authenticated user's profile along with their role name and effective permissions, so clients can show or hide UI accordingly.",
+    tag = AUTH_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn me_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(auth_user): Extension<AuthUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = UserService::find_user_by_id(&db, auth_user.user_id)
+        .await?
+        .ok_or(AppError {
+            message: "User not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+        })?;
+
+    let role = match user.role_id {
+        Some(role_id) => PermissionService::get_role_name(&db, role_id).await?,
+        None => None,
+    };
+
+    let mut permissions = PermissionService::get_user_permissions(&db, user.id)
+        .await?
+        .to_strings();
+    permissions.sort();
+
+    Ok(Json(MeResponse {
+        id: user.id.to_string(),
+        email: user.email,
+        created_at: user.created_at,
+        role,
+        permissions,
+    }))
+}
+
+/// Verifies a user's email with the token from the link sent on sign up
 #[utoipa::path(
     post,
     path = "/verify-email",
     request_body = VerifyEmailRequest,
     responses(
         (status = 200, description = "Email verified successfully", body = VerifyEmailResponse),
+        (status = 400, description = "Invalid, expired or already used token", body = ErrorResponse, examples(
+            ("invalid_token" = (value = json!({"message": "Invalid email verification token"}))),
+            ("expired_token" = (value = json!({"message": "Email verification has expired"}))),
+            ("used_token" = (value = json!({"message": "Email verification link has already been used"})))
+        )),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Verify email",
-    description = "Verifies a user's email address.",
-    tag = AUTH_TAG,
-    security(
-        ("jwt_token" = [])
-    )
+    description = "Verifies a user's email address with the single-use token from the verification link.",
+    tag = AUTH_TAG
 )]
 pub async fn verify_email_handler(
     State(db): State<DatabaseConnection>,
-    Json(payload): Json<VerifyEmailRequest>,
+    ValidatedJson(payload): ValidatedJson<VerifyEmailRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let user_id = uuid::Uuid::parse_str(&payload.user_id).map_err(|_| AppError {
-        message: "Invalid user ID".to_string(),
-        status_code: StatusCode::BAD_REQUEST,
-    })?;
-    UserService::verify_email(&db, user_id).await?;
+    UserService::verify_email_token(&db, &payload.token).await?;
 
     Ok(Json(VerifyEmailResponse {
         message: "Email verified successfully".to_string(),
         success: true,
     }))
 }
+
+/// Emails a new verification link
+#[utoipa::path(
+    post,
+    path = "/resend-verification",
+    request_body = ResendVerificationRequest,
+    responses(
+        (status = 200, description = "Verification link sent if the account exists and is unverified", body = MessageResponse, examples(
+            ("success" = (value = json!({"message": "If an unverified account exists for this email, a verification link has been sent"})))
+        )),
+        (status = 400, description = "Bad request - invalid email", body = ValidationErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Resend verification email",
+    description = "Emails a new single-use verification link to an unverified account. Answers the same whether or not the email belongs to an unverified account.",
+    tag = AUTH_TAG
+)]
+pub async fn resend_verification_handler(
+    State(db): State<DatabaseConnection>,
+    ValidatedJson(payload): ValidatedJson<ResendVerificationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    UserService::resend_email_verification(&db, &payload.email).await?;
+
+    Ok(Json(MessageResponse {
+        message:
+            "If an unverified account exists for this email, a verification link has been sent"
+                .to_string(),
+    }))
+}
+
+/// Confirms a pending email change
+#[utoipa::path(
+    post,
+    path = "/confirm-email-change",
+    request_body = ConfirmEmailChangeRequest,
+    responses(
+        (status = 200, description = "Email changed successfully", body = VerifyEmailResponse),
+        (status = 400, description = "Invalid or expired token", body = ErrorResponse),
+        (status = 409, description = "Email already taken", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Confirm email change",
+    description = "Replaces a user's email with the pending address the verification link was sent to.",
+    tag = AUTH_TAG
+)]
+pub async fn confirm_email_change_handler(
+    State(db): State<DatabaseConnection>,
+    ValidatedJson(payload): ValidatedJson<ConfirmEmailChangeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    UserService::confirm_email_change(&db, &payload.token).await?;
+
+    Ok(Json(VerifyEmailResponse {
+        message: "Email changed successfully".to_string(),
+        success: true,
+    }))
+}
+
+/// Emails a password reset link
+#[utoipa::path(
+    post,
+    path = "/request-password-reset",
+    request_body = PasswordResetRequest,
+    responses(
+        (status = 200, description = "Reset link sent if the account exists", body = MessageResponse, examples(
+            ("success" = (value = json!({"message": "If an account exists for this email, a password reset link has been sent"})))
+        )),
+        (status = 400, description = "Bad request - invalid email", body = ValidationErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Request password reset",
+    description = "Emails a single-use password reset link. Answers the same whether or not the email belongs to an account.",
+    tag = AUTH_TAG
+)]
+pub async fn request_password_reset_handler(
+    State(db): State<DatabaseConnection>,
+    ValidatedJson(payload): ValidatedJson<PasswordResetRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    AuthService::request_password_reset(&db, &payload.email).await?;
+
+    Ok(Json(MessageResponse {
+        message: "If an account exists for this email, a password reset link has been sent"
+            .to_string(),
+    }))
+}
+
+/// Sets a new password with a password reset token
+#[utoipa::path(
+    post,
+    path = "/reset-password",
+    request_body = ConfirmPasswordResetRequest,
+    responses(
+        (status = 200, description = "Password reset successfully", body = MessageResponse),
+        (status = 400, description = "Invalid or expired token, or a weak password", body = ErrorResponse, examples(
+            ("invalid_token" = (value = json!({"message": "Invalid password reset token"}))),
+            ("expired_token" = (value = json!({"message": "Password reset has expired"}))),
+            ("weak_password" = (value = json!({"message": "Password must be at least 12 characters"})))
+        )),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Reset password",
+    description = "Replaces the password of the account the reset link was sent to and logs it out of every session.",
+    tag = AUTH_TAG
+)]
+pub async fn confirm_password_reset_handler(
+    State(db): State<DatabaseConnection>,
+    ValidatedJson(payload): ValidatedJson<ConfirmPasswordResetRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    AuthService::confirm_password_reset(&db, &payload.token, &payload.new_password).await?;
+
+    Ok(Json(MessageResponse {
+        message: "Password reset successfully".to_string(),
+    }))
+}
+
+/// Changes the current user's password
+#[utoipa::path(
+    post,
+    path = "/change-password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed successfully", body = MessageResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse, examples(
+            ("weak_password" = (value = json!({"message": "Password must be at least 12 characters"}))),
+            ("same_password" = (value = json!({"message": "New password must be different from the current password"})))
+        )),
+        (status = 401, description = "Unauthorized - not authenticated or wrong current password", body = ErrorResponse, examples(
+            ("wrong_password" = (value = json!({"message": "Current password is incorrect"})))
+        )),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Change password",
+    description = "Replaces the current user's password. This is the only authenticated endpoint available while a password change is required (other endpoints answer 428 Precondition Required), and it clears that requirement.",
+    tag = AUTH_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn change_password_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(auth_user): Extension<AuthUser>,
+    ValidatedJson(payload): ValidatedJson<ChangePasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    UserService::change_password(
+        &db,
+        auth_user.user_id,
+        &payload.current_password,
+        &payload.new_password,
+    )
+    .await?;
+
+    Ok(Json(MessageResponse {
+        message: "Password changed successfully".to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::models::{roles, users};
+    use crate::infrastructure::test_support::setup_db;
+    use axum::{Router, body::Body, http::Request as HttpRequest, routing::get};
+    use sea_orm::{ActiveModelTrait, Set};
+    use tower::ServiceExt;
+
+    async fn insert_user(db: &DatabaseConnection, role_id: Option<i32>) -> uuid::Uuid {
+        let user_id = uuid::Uuid::new_v4();
+        users::ActiveModel {
+            id: Set(user_id),
+            email: Set(format!("{}@example.com", user_id)),
+            password_hash: Set("hash".to_string()),
+            created_at: Set(Some(chrono::Utc::now().fixed_offset())),
+            last_login: Set(None),
+            role_id: Set(role_id),
+            email_verified: Set(true),
+            pending_email: Set(None),
+            pending_email_token: Set(None),
+            pending_email_expires_at: Set(None),
+            must_change_password: Set(false),
+            updated_at: Set(None),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        user_id
+    }
+
+    async fn get_me(db: DatabaseConnection, user_id: uuid::Uuid) -> serde_json::Value {
+        let app = Router::new().route("/me", get(me_handler)).with_state(db);
+
+        let mut request = HttpRequest::get("/me").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(AuthUser { user_id });
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_me_returns_role_permissions() {
+        let db = setup_db().await;
+        let role = roles::ActiveModel {
+            name: Set("editor".to_string()),
+            permissions: Set("[\"user:write\", \"user:read\"]".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let user_id = insert_user(&db, Some(role.id)).await;
+
+        let body = get_me(db, user_id).await;
+
+        assert_eq!(body["id"], user_id.to_string());
+        assert_eq!(body["role"], "editor");
+        assert_eq!(
+            body["permissions"],
+            serde_json::json!(["user:read", "user:write"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_me_returns_empty_permissions_without_role() {
+        let db = setup_db().await;
+        let user_id = insert_user(&db, None).await;
+
+        let body = get_me(db, user_id).await;
+
+        assert!(body["role"].is_null());
+        assert_eq!(body["permissions"], serde_json::json!([]));
+    }
+}
@@ -1,4 +1,11 @@
+#[cfg(feature = "admin")]
 pub mod admin;
 pub mod auth;
+#[cfg(feature = "admin")]
 pub mod roles;
+#[cfg(feature = "admin")]
+pub mod settings;
+pub mod setup;
+pub mod system;
+#[cfg(feature = "admin")]
 pub mod websocket;
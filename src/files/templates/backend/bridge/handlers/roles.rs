@@ -1,15 +1,21 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{Extension, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use sea_orm::DatabaseConnection;
 
 use crate::{
-    bridge::types::admin::*,
-    control::services::admin_service::AdminService,
-    infrastructure::app_error::{AppError, ErrorResponse, MessageResponse},
+    bridge::{extractors::validated_json::ValidatedJson, types::admin::*},
+    control::services::{
+        admin_service::AdminService,
+        confirmation_service::{ConfirmationService, DestructiveAction},
+    },
+    domain::permissions::Permission,
+    infrastructure::app_error::{
+        AppError, ErrorResponse, MessageResponse, ValidationErrorResponse,
+    },
 };
 
 /// Get roles endpoint
@@ -19,6 +25,7 @@ use crate::{
     params(RolesQueryParams),
     responses(
         (status = 200, description = "Roles retrieved successfully", body = PaginatedResponse<RoleResponse>),
+        (status = 400, description = "Bad request - page beyond the maximum pagination depth", body = ErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -41,7 +48,7 @@ pub async fn get_roles_handler(
 /// Get role by ID endpoint
 #[utoipa::path(
     get,
-    path = "/roles/{id}",
+    path = "/roles/{{id}}",
     params(
         ("id" = i32, Path, description = "Role ID")
     ),
@@ -74,7 +81,7 @@ pub async fn get_role_handler(
     request_body = CreateRoleRequest,
     responses(
         (status = 201, description = "Role created successfully", body = RoleResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 409, description = "Conflict - role name already exists", body = ErrorResponse),
@@ -89,7 +96,7 @@ pub async fn get_role_handler(
 )]
 pub async fn create_role_handler(
     State(db): State<DatabaseConnection>,
-    Json(payload): Json<CreateRoleRequest>,
+    ValidatedJson(payload): ValidatedJson<CreateRoleRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = AdminService::create_role(&db, payload).await?;
     Ok((StatusCode::CREATED, Json(response)))
@@ -98,14 +105,14 @@ pub async fn create_role_handler(
 /// Update role endpoint
 #[utoipa::path(
     put,
-    path = "/roles/{id}",
+    path = "/roles/{{id}}",
     params(
         ("id" = i32, Path, description = "Role ID")
     ),
     request_body = UpdateRoleRequest,
     responses(
         (status = 200, description = "Role updated successfully", body = RoleResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 404, description = "Role not found", body = ErrorResponse),
@@ -113,7 +120,7 @@ pub async fn create_role_handler(
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Update role",
-    description = "Updates an existing role with new permissions",
+    description = "Updates an existing role, omitted or null fields are left unchanged. Use PATCH to clear the description",
     tag = ADMIN_TAG,
     security(
         ("jwt_token" = [])
@@ -122,25 +129,92 @@ pub async fn create_role_handler(
 pub async fn update_role_handler(
     State(db): State<DatabaseConnection>,
     Path(role_id): Path<i32>,
-    Json(payload): Json<UpdateRoleRequest>,
+    ValidatedJson(payload): ValidatedJson<UpdateRoleRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = AdminService::update_role(&db, role_id, payload).await?;
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Partially update role endpoint
+#[utoipa::path(
+    patch,
+    path = "/roles/{{id}}",
+    params(
+        ("id" = i32, Path, description = "Role ID")
+    ),
+    request_body = PatchRoleRequest,
+    responses(
+        (status = 200, description = "Role updated successfully", body = RoleResponse),
+        (status = 400, description = "Bad request - validation errors or null for a required field", body = ValidationErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse),
+        (status = 409, description = "Conflict - role name already exists", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Partially update role",
+    description = "Updates only the fields present in the body. Omitted fields are left unchanged, description: null clears the description, null name or permissions is rejected",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn patch_role_handler(
+    State(db): State<DatabaseConnection>,
+    Path(role_id): Path<i32>,
+    ValidatedJson(payload): ValidatedJson<PatchRoleRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = AdminService::patch_role(&db, role_id, payload).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Delete role confirmation endpoint
+#[utoipa::path(
+    get,
+    path = "/roles/{{id}}/delete-confirmation",
+    params(
+        ("id" = i32, Path, description = "Role ID")
+    ),
+    responses(
+        (status = 200, description = "Confirmation token issued", body = ConfirmationResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Confirm role deletion",
+    description = "Describes the impact of deleting a role and issues a short-lived confirmation token for the delete request",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn delete_role_confirmation_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(role_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError> {
+    let action = DestructiveAction::DeleteRole(role_id);
+    let impact = ConfirmationService::describe_impact(&db, &action).await?;
+    let response = ConfirmationService::issue(&db, &action, admin_user.user_id, impact).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
 /// Delete role endpoint
 #[utoipa::path(
     delete,
-    path = "/roles/{id}",
+    path = "/roles/{{id}}",
     params(
-        ("id" = i32, Path, description = "Role ID")
+        ("id" = i32, Path, description = "Role ID"),
+        ("X-Confirmation-Token" = Option<String>, Header, description = "Token from the delete confirmation endpoint, required when REQUIRE_ADMIN_CONFIRMATION is enabled")
     ),
     responses(
         (status = 200, description = "Role deleted successfully", body = MessageResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
-        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required or invalid confirmation token", body = ErrorResponse),
         (status = 404, description = "Role not found", body = ErrorResponse),
         (status = 409, description = "Conflict - role is in use by users", body = ErrorResponse),
+        (status = 428, description = "Confirmation token required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     summary = "Delete role",
@@ -152,8 +226,18 @@ pub async fn update_role_handler(
 )]
 pub async fn delete_role_handler(
     State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
     Path(role_id): Path<i32>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
+    ConfirmationService::require(
+        &db,
+        &headers,
+        &DestructiveAction::DeleteRole(role_id),
+        admin_user.user_id,
+    )
+    .await?;
+
     AdminService::delete_role(&db, role_id).await?;
     Ok((
         StatusCode::OK,
@@ -163,6 +247,34 @@ pub async fn delete_role_handler(
     ))
 }
 
+/// Bulk role permission update endpoint
+#[utoipa::path(
+    post,
+    path = "/roles/permissions/bulk",
+    request_body = BulkRolePermissionsRequest,
+    responses(
+        (status = 200, description = "Role permissions updated successfully", body = BulkRolePermissionsResponse),
+        (status = 400, description = "Bad request - invalid permissions, nothing was applied", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "Role not found, nothing was applied", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Bulk update role permissions",
+    description = "Replaces the permissions of several roles in one transaction and returns what changed. Set dry_run to preview the diff without applying it",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn bulk_update_role_permissions_handler(
+    State(db): State<DatabaseConnection>,
+    ValidatedJson(payload): ValidatedJson<BulkRolePermissionsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = AdminService::bulk_update_role_permissions(&db, payload).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
 /// Check permission endpoint
 #[utoipa::path(
     post,
@@ -170,7 +282,7 @@ pub async fn delete_role_handler(
     request_body = PermissionCheckRequest,
     responses(
         (status = 200, description = "Permission check completed", body = PermissionCheckResponse),
-        (status = 400, description = "Bad request - validation errors", body = ErrorResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
         (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
         (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -184,8 +296,37 @@ pub async fn delete_role_handler(
 )]
 pub async fn check_permission_handler(
     State(db): State<DatabaseConnection>,
-    Json(payload): Json<PermissionCheckRequest>,
+    ValidatedJson(payload): ValidatedJson<PermissionCheckRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = AdminService::check_permission(&db, payload).await?;
     Ok((StatusCode::OK, Json(response)))
 }
+
+/// Permission catalog endpoint
+#[utoipa::path(
+    get,
+    path = "/permissions",
+    responses(
+        (status = 200, description = "Permission catalog retrieved successfully", body = Vec<PermissionCatalogEntry>),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse)
+    ),
+    summary = "Get permission catalog",
+    description = "Lists all built-in permissions with their category and description",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn get_permission_catalog_handler() -> impl IntoResponse {
+    let catalog: Vec<PermissionCatalogEntry> = Permission::all_known()
+        .into_iter()
+        .map(|permission| PermissionCatalogEntry {
+            name: permission.to_string(),
+            category: permission.category().to_string(),
+            description: permission.description().to_string(),
+        })
+        .collect();
+
+    (StatusCode::OK, Json(catalog))
+}
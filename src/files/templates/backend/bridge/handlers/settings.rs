@@ -0,0 +1,107 @@
+use axum::{
+    Json,
+    extract::{Extension, Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use sea_orm::DatabaseConnection;
+
+use crate::{
+    bridge::{extractors::validated_json::ValidatedJson, types::admin::*},
+    check_single_permission,
+    control::services::settings_service::{SETTINGS, SettingsService},
+    domain::permissions::Permission::{AdminRead, AdminWrite},
+    infrastructure::app_error::{AppError, ErrorResponse},
+};
+
+/// Get settings endpoint
+#[utoipa::path(
+    get,
+    path = "/settings",
+    responses(
+        (status = 200, description = "Settings retrieved successfully", body = Vec<SettingResponse>),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Get settings",
+    description = "Lists all runtime settings ordered by key",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn get_settings_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminRead, &db);
+    let response = SettingsService::list(&db).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Get setting by key endpoint
+#[utoipa::path(
+    get,
+    path = "/settings/{{key}}",
+    params(
+        ("key" = String, Path, description = "Setting key")
+    ),
+    responses(
+        (status = 200, description = "Setting retrieved successfully", body = SettingResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 404, description = "Setting not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Get setting",
+    description = "Retrieves a runtime setting with who last changed it and when",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn get_setting_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(key): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminRead, &db);
+    let response = SettingsService::get_setting(&db, &key).await?;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Update setting endpoint
+#[utoipa::path(
+    put,
+    path = "/settings/{{key}}",
+    params(
+        ("key" = String, Path, description = "Setting key")
+    ),
+    request_body = UpdateSettingRequest,
+    responses(
+        (status = 200, description = "Setting updated successfully", body = SettingResponse),
+        (status = 400, description = "Bad request - invalid setting key", body = ErrorResponse),
+        (status = 401, description = "Unauthorized - authentication required", body = ErrorResponse),
+        (status = 403, description = "Forbidden - admin privileges required", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "Update setting",
+    description = "Creates or updates a runtime setting, the new value takes effect immediately",
+    tag = ADMIN_TAG,
+    security(
+        ("jwt_token" = [])
+    )
+)]
+pub async fn update_setting_handler(
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+    Path(key): Path<String>,
+    ValidatedJson(payload): ValidatedJson<UpdateSettingRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    check_single_permission!(&admin_user.email, &AdminWrite, &db);
+    let response = SETTINGS
+        .set(&db, &key, &payload.value, Some(admin_user.user_id))
+        .await?;
+    Ok((StatusCode::OK, Json(response)))
+}
@@ -0,0 +1,42 @@
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use sea_orm::DatabaseConnection;
+
+use crate::{
+    bridge::{
+        extractors::validated_json::ValidatedJson,
+        types::admin::{ADMIN_TAG, SetupRequest, SetupResponse},
+    },
+    control::services::setup_service::SetupService,
+    infrastructure::app_error::{AppError, ErrorResponse, ValidationErrorResponse},
+};
+
+/// First-run setup endpoint
+#[utoipa::path(
+    post,
+    path = "/setup",
+    request_body = SetupRequest,
+    responses(
+        (status = 201, description = "Initial admin created successfully", body = SetupResponse),
+        (status = 400, description = "Bad request - validation errors", body = ValidationErrorResponse),
+        (status = 403, description = "Forbidden - setup has already been completed", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    summary = "First-run setup",
+    description = "Creates the initial admin user. Only available while no admin exists and FIRST_RUN_SETUP is enabled.",
+    tag = ADMIN_TAG
+)]
+pub async fn setup_handler(
+    State(db): State<DatabaseConnection>,
+    ValidatedJson(payload): ValidatedJson<SetupRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let user = SetupService::create_initial_admin(&db, payload.email, payload.password).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(SetupResponse {
+            message: "Initial admin created successfully".to_string(),
+            admin_id: user.id.to_string(),
+            email: user.email,
+        }),
+    ))
+}
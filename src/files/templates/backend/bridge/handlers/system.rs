@@ -0,0 +1,100 @@
+use axum::{Json, extract::State};
+use std::{path::PathBuf, sync::Arc};
+use utoipa::openapi::OpenApi;
+
+use crate::{
+    bridge::types::system::{OpenApiSpecWriteResponse, SYSTEM_TAG, VersionResponse},
+    control::services::openapi_spec_service::OpenApiSpecService,
+    infrastructure::{app_error::AppError, build_info::BUILD_INFO},
+};
+
+/// Build version endpoint
+#[utoipa::path(
+    get,
+    path = "/version",
+    responses(
+        (status = 200, description = "Version of the running build", body = VersionResponse)
+    ),
+    summary = "Build version",
+    description = "Returns the package version and git commit captured when the running binary was compiled. Disabled when VERSION_ENDPOINT_ENABLED is false.",
+    tag = SYSTEM_TAG
+)]
+pub async fn version_handler() -> Json<VersionResponse> {
+    Json(BUILD_INFO.into())
+}
+
+/// The spec the dev routes serve and the file it's written to
+#[derive(Clone)]
+pub struct OpenApiSpecState {
+    pub api: Arc<OpenApi>,
+    pub path: Arc<PathBuf>,
+}
+
+/// Writes the current spec to OPENAPI_SPEC_PATH so the frontend client is regenerated
+///
+/// Only mounted in development. Not documented in the spec, it's a dev tool and not part of the API.
+pub async fn write_openapi_spec_handler(
+    State(spec): State<OpenApiSpecState>,
+) -> Result<Json<OpenApiSpecWriteResponse>, AppError> {
+    let changed = OpenApiSpecService::write_spec(&spec.api, &spec.path)?;
+
+    Ok(Json(OpenApiSpecWriteResponse {
+        path: spec.path.display().to_string(),
+        changed,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::routes::system::{dev_router, system_router};
+    use crate::infrastructure::test_support::temp_dir;
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_version_endpoint_returns_compiled_in_build() {
+        let (router, _) = system_router().split_for_parts();
+        let response = router
+            .oneshot(Request::get("/version").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["name"], env!("CARGO_PKG_NAME"));
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["git_sha"], serde_json::json!(option_env!("GIT_SHA")));
+    }
+
+    #[tokio::test]
+    async fn test_write_openapi_spec_writes_current_spec() {
+        let dir = temp_dir();
+        let path = dir.path().join("openapi.json");
+        let (_, api) = system_router().split_for_parts();
+
+        let response = dev_router(Arc::new(api.clone()), path.clone())
+            .oneshot(
+                Request::post("/api/v1/dev/openapi-spec")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["changed"], true);
+        assert_eq!(json["path"], path.display().to_string());
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, serde_json::to_value(&api).unwrap());
+        assert!(written["paths"]["/version"].is_object());
+    }
+}
@@ -1,40 +1,118 @@
 use axum::{
     extract::{
-        State,
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        Extension, Query, State,
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade, close_code},
     },
+    http::{HeaderMap, StatusCode, header},
     response::IntoResponse,
 };
+use chrono::{DateTime, Utc};
 use futures_util::{SinkExt, StreamExt};
 use sea_orm::DatabaseConnection;
 use serde_json;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc, watch};
 use uuid::Uuid;
 
-use crate::infrastructure::websocket::{WEBSOCKET_MANAGER, WebSocketMessage};
+use crate::bridge::types::admin::{AdminUser, WebSocketQueryParams};
+use crate::check_single_permission;
+use crate::domain::permissions::Permission::AdminRead;
+use crate::infrastructure::{
+    app_error::AppError,
+    cors::CorsManager,
+    websocket::{
+        LogLevel, SHUTDOWN_CLOSE_REASON, TOO_MANY_CONNECTIONS_CLOSE_REASON, WEBSOCKET_MANAGER,
+        WebSocketManager, WebSocketSubscription, WsMessage,
+    },
+};
 
 /// WebSocket handler for real-time monitoring
+///
+/// Mounted behind `admin_middleware` and requires AdminRead, the feed carries audit logs. New
+/// connections are first sent the recently logged events, only those after the `since` query
+/// parameter when given.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
-    State(_db): State<DatabaseConnection>,
-) -> impl IntoResponse {
-    ws.on_upgrade(|socket| handle_socket(socket))
+    headers: HeaderMap,
+    Query(params): Query<WebSocketQueryParams>,
+    State(db): State<DatabaseConnection>,
+    Extension(admin_user): Extension<AdminUser>,
+) -> Result<impl IntoResponse, AppError> {
+    check_origin(&headers)?;
+    check_single_permission!(&admin_user.email, &AdminRead, &db);
+    Ok(ws.on_upgrade(move |socket| handle_socket(socket, &WEBSOCKET_MANAGER, params.since)))
+}
+
+/// Rejects upgrades from browser origins outside the CORS allowlist (WEBSOCKET_CHECK_ORIGIN, defaults to true)
+///
+/// Browsers don't apply same-origin rules to WebSockets, so without this any page could open the
+/// feed with the user's cookies. Requests without an Origin header don't come from a browser page.
+fn check_origin(headers: &HeaderMap) -> Result<(), AppError> {
+    let enabled = std::env::var("WEBSOCKET_CHECK_ORIGIN")
+        .unwrap_or_else(|_| "true".to_string())
+        .parse::<bool>()
+        .unwrap_or(true);
+    if !enabled {
+        return Ok(());
+    }
+
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return Ok(());
+    };
+
+    let allowed = origin.to_str().is_ok_and(|origin| {
+        CorsManager::is_allowed_origin(origin, &CorsManager::allowed_origins())
+    });
+
+    if !allowed {
+        tracing::warn!("Rejected WebSocket upgrade from origin {:?}", origin);
+        return Err(AppError {
+            message: "Origin not allowed".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+        });
+    }
+
+    Ok(())
+}
+
+/// Resolves once the server starts shutting down
+async fn shutting_down(shutdown_rx: &mut watch::Receiver<bool>) {
+    let _ = shutdown_rx.wait_for(|shutting_down| *shutting_down).await;
 }
 
 /// Handle individual WebSocket connection
-async fn handle_socket(socket: WebSocket) {
+async fn handle_socket(
+    mut socket: WebSocket,
+    manager: &'static WebSocketManager,
+    since: Option<DateTime<Utc>>,
+) {
     let connection_id = Uuid::new_v4().to_string();
 
+    // Register the connection and subscribe to the broadcast channel, turning it away if the
+    // connection limit is reached
+    let Some(WebSocketSubscription {
+        receiver: mut broadcast_rx,
+        backlog,
+    }) = manager.add_connection(connection_id.clone(), since).await
+    else {
+        tracing::warn!("Rejected WebSocket connection, the connection limit is reached");
+        let close_frame = CloseFrame {
+            code: close_code::POLICY,
+            reason: TOO_MANY_CONNECTIONS_CLOSE_REASON.into(),
+        };
+        let _ = socket.send(Message::Close(Some(close_frame))).await;
+        return;
+    };
+
     // Broadcast connection event
-    crate::infrastructure::websocket::broadcast_system_log(
-        "info".to_string(),
+    crate::infrastructure::websocket::broadcast(WsMessage::system_log(
+        LogLevel::Info,
         format!("WebSocket connection established: {}", connection_id),
-        "websocket".to_string(),
-    )
+        "websocket",
+    ))
     .await;
 
     // Send connection status
-    let status_message = WebSocketMessage::ConnectionStatus {
+    let status_message = WsMessage::ConnectionStatus {
         status: "connected".to_string(),
         message: "WebSocket connection established".to_string(),
         timestamp: chrono::Utc::now().to_rfc3339(),
@@ -43,48 +121,31 @@ async fn handle_socket(socket: WebSocket) {
     // Split the socket into sender and receiver
     let (mut sender, mut receiver) = socket.split();
 
-    // Subscribe to broadcast channel
-    let mut broadcast_rx = WEBSOCKET_MANAGER.subscribe();
+    // Held until the connection is cleaned up, `WebSocketManager::shutdown` waits for it
+    let shutdown_rx = manager.shutdown_signal();
+    let mut sender_shutdown_rx = shutdown_rx.clone();
 
-    // Send initial connection status
-    if let Ok(message_json) = serde_json::to_string(&status_message) {
-        let _ = sender.send(Message::Text(message_json.into())).await;
+    // Send initial connection status, then the events logged before the client connected
+    for message in std::iter::once(&status_message).chain(&backlog) {
+        if let Ok(message_json) = serde_json::to_string(message) {
+            let _ = sender.send(Message::Text(message_json.into())).await;
+        }
     }
 
     // Create a channel for sending messages from ping/pong task to sender task
     let (tx, mut rx) = mpsc::channel::<String>(100);
 
-    // Clone values for the broadcast task
-    let connection_id_broadcast = connection_id.clone();
-
-    // Spawn task to forward broadcast messages to this client
-    let tx_broadcast = tx.clone();
-    let broadcast_task = tokio::spawn(async move {
-        while let Ok(message) = broadcast_rx.recv().await {
-            if let Ok(message_json) = serde_json::to_string(&message) {
-                if let Err(e) = tx_broadcast.send(message_json).await {
-                    tracing::warn!(
-                        "Failed to send message to client {}: {}",
-                        connection_id_broadcast,
-                        e
-                    );
-                    break;
-                }
-            }
-        }
-    });
-
     // Clone values for the ping/pong task
     let connection_id_ping = connection_id.clone();
 
     // Handle incoming messages from client
-    let ping_pong_task = tokio::spawn(async move {
+    let mut ping_pong_task = tokio::spawn(async move {
         while let Some(Ok(message)) = receiver.next().await {
             match message {
                 Message::Text(text) => {
                     // Handle text messages (could be commands or ping)
                     if text == "ping" {
-                        let pong = WebSocketMessage::Pong;
+                        let pong = WsMessage::Pong;
                         if let Ok(pong_json) = serde_json::to_string(&pong) {
                             let _ = tx.send(pong_json).await;
                         }
@@ -104,39 +165,402 @@ async fn handle_socket(socket: WebSocket) {
         }
     });
 
-    // Main sender task that handles both broadcast and ping/pong messages
-    let sender_task = tokio::spawn(async move {
-        while let Some(message_json) = rx.recv().await {
+    // Clone values for the sender task
+    let connection_id_sender = connection_id.clone();
+
+    // Main sender task forwarding broadcasts and ping/pong replies, until the server shuts down
+    let mut sender_task = tokio::spawn(async move {
+        loop {
+            let message_json = tokio::select! {
+                _ = shutting_down(&mut sender_shutdown_rx) => {
+                    // Flush what was broadcast before the shutdown, then say goodbye
+                    while let Ok(message) = broadcast_rx.try_recv() {
+                        if let Ok(message_json) = serde_json::to_string(&message) {
+                            if sender.send(Message::Text(message_json.into())).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    let close_frame = CloseFrame {
+                        code: close_code::AWAY,
+                        reason: SHUTDOWN_CLOSE_REASON.into(),
+                    };
+                    let _ = sender.send(Message::Close(Some(close_frame))).await;
+                    return;
+                }
+                message = broadcast_rx.recv() => match message {
+                    Ok(message) => match serde_json::to_string(&message) {
+                        Ok(message_json) => message_json,
+                        Err(_) => continue,
+                    },
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "WebSocket client {} lagged behind, skipped {} messages",
+                            connection_id_sender,
+                            skipped
+                        );
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                Some(message_json) = rx.recv() => message_json,
+            };
+
             if let Err(e) = sender.send(Message::Text(message_json.into())).await {
                 tracing::warn!("Failed to send message to WebSocket: {}", e);
-                break;
+                return;
             }
         }
     });
 
-    // Wait for any task to complete
+    // Wait for either task to complete, then stop the other
     tokio::select! {
-        _ = broadcast_task => {
-            tracing::info!("Broadcast task ended for connection {}", connection_id);
-        }
-        _ = ping_pong_task => {
+        _ = &mut ping_pong_task => {
             tracing::info!("Ping/pong task ended for connection {}", connection_id);
+            sender_task.abort();
         }
-        _ = sender_task => {
+        _ = &mut sender_task => {
             tracing::info!("Sender task ended for connection {}", connection_id);
+            ping_pong_task.abort();
         }
     }
 
     // Clean up connection
-    WEBSOCKET_MANAGER.remove_connection(&connection_id).await;
+    manager.remove_connection(&connection_id).await;
+    drop(shutdown_rx);
 
     // Broadcast disconnection event
-    crate::infrastructure::websocket::broadcast_system_log(
-        "info".to_string(),
+    crate::infrastructure::websocket::broadcast(WsMessage::system_log(
+        LogLevel::Info,
         format!("WebSocket connection closed: {}", connection_id),
-        "websocket".to_string(),
-    )
+        "websocket",
+    ))
     .await;
 
     tracing::info!("WebSocket connection {} cleaned up", connection_id);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::middleware::admin::admin_middleware;
+    use crate::control::services::auth_service::AuthService;
+    use crate::domain::user::UserLogin;
+    use crate::entity::models::roles;
+    use crate::infrastructure::test_support::{
+        create_verified_user, set_test_jwt_secret, setup_db,
+    };
+    use axum::{Router, middleware, routing::get};
+    use sea_orm::{ActiveModelTrait, Set};
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::{
+        self, client::IntoClientRequest, protocol::frame::coding::CloseCode,
+    };
+
+    /// Feed URL and session tokens for an admin and a user without permissions
+    struct TestServer {
+        url: String,
+        admin_token: String,
+        user_token: String,
+    }
+
+    async fn login(db: &DatabaseConnection, email: &str, role_id: Option<i32>) -> String {
+        create_verified_user(
+            db,
+            email.to_string(),
+            "UserPassword123!".to_string(),
+            role_id,
+        )
+        .await
+        .unwrap();
+        AuthService::authenticate_user(
+            db,
+            UserLogin {
+                email: email.to_string(),
+                password: "UserPassword123!".to_string(),
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap()
+        .token
+    }
+
+    async fn spawn_server() -> TestServer {
+        let db = setup_db().await;
+        set_test_jwt_secret();
+        let role = roles::ActiveModel {
+            name: Set("admin".to_string()),
+            permissions: Set("[\"*\"]".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let admin_token = login(&db, "admin@example.com", Some(role.id)).await;
+        let user_token = login(&db, "user@example.com", None).await;
+
+        let app = Router::new()
+            .route("/api/v1/admin/ws", get(websocket_handler))
+            .route_layer(middleware::from_fn_with_state(db.clone(), admin_middleware))
+            .with_state(db);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        TestServer {
+            url: format!("ws://{}/api/v1/admin/ws", address),
+            admin_token,
+            user_token,
+        }
+    }
+
+    async fn connect(
+        url: &str,
+        origin: &str,
+        token: Option<&str>,
+    ) -> Result<(), tungstenite::Error> {
+        let mut request = url.into_client_request().unwrap();
+        let headers = request.headers_mut();
+        headers.insert(header::ORIGIN, origin.parse().unwrap());
+        // Sent both ways so the upgrade authenticates with either transport
+        if let Some(token) = token {
+            headers.insert(
+                header::AUTHORIZATION,
+                format!("Bearer {}", token).parse().unwrap(),
+            );
+            headers.insert(
+                header::COOKIE,
+                format!("rext_session={}", token).parse().unwrap(),
+            );
+        }
+        tokio_tungstenite::connect_async(request).await.map(|_| ())
+    }
+
+    fn assert_handshake_status(result: Result<(), tungstenite::Error>, expected: StatusCode) {
+        match result {
+            Err(tungstenite::Error::Http(response)) => assert_eq!(response.status(), expected),
+            other => panic!("expected a {} handshake failure, got {:?}", expected, other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_allowed_origin_upgrades() {
+        let server = spawn_server().await;
+        let allowed = CorsManager::allowed_origins().remove(0);
+
+        assert!(
+            connect(&server.url, &allowed, Some(&server.admin_token))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_requires_admin_read() {
+        let server = spawn_server().await;
+        let allowed = CorsManager::allowed_origins().remove(0);
+
+        assert_handshake_status(
+            connect(&server.url, &allowed, None).await,
+            StatusCode::UNAUTHORIZED,
+        );
+        assert_handshake_status(
+            connect(&server.url, &allowed, Some(&server.user_token)).await,
+            StatusCode::FORBIDDEN,
+        );
+    }
+
+    type Client = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    /// Serves `handle_socket` with its own manager, so tests don't share the global one
+    async fn spawn_manager_server(
+        manager: WebSocketManager,
+    ) -> (String, &'static WebSocketManager) {
+        let manager: &'static WebSocketManager = Box::leak(Box::new(manager));
+        let app = Router::new().route(
+            "/ws",
+            get(
+                move |ws: WebSocketUpgrade, Query(params): Query<WebSocketQueryParams>| async move {
+                    ws.on_upgrade(move |socket| handle_socket(socket, manager, params.since))
+                },
+            ),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("ws://{}/ws", address), manager)
+    }
+
+    /// Connects and reads the first message the server sends
+    async fn connect_client(url: &str) -> (Client, tungstenite::Message) {
+        let (mut client, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let first = client.next().await.unwrap().unwrap();
+        (client, first)
+    }
+
+    fn is_connection_status(message: &tungstenite::Message) -> bool {
+        message
+            .to_text()
+            .is_ok_and(|text| text.contains("ConnectionStatus"))
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_broadcasts_and_sends_close_frame() {
+        let (url, manager) =
+            spawn_manager_server(WebSocketManager::with_max_connections(None)).await;
+
+        // The connection is registered before the status message is sent
+        let (mut client, status) = connect_client(&url).await;
+        assert!(is_connection_status(&status));
+        assert_eq!(manager.connection_count().await, 1);
+
+        manager
+            .broadcast(WsMessage::system_log(LogLevel::Info, "last words", "test"))
+            .await;
+        let shutdown = tokio::spawn(manager.shutdown(Duration::from_secs(5)));
+
+        let flushed = client.next().await.unwrap().unwrap();
+        assert!(flushed.to_text().unwrap().contains("last words"));
+
+        match client.next().await.unwrap().unwrap() {
+            tungstenite::Message::Close(Some(frame)) => {
+                assert_eq!(frame.code, CloseCode::Away);
+                assert_eq!(frame.reason.as_str(), SHUTDOWN_CLOSE_REASON);
+            }
+            other => panic!("expected a close frame, got {:?}", other),
+        }
+
+        assert!(shutdown.await.unwrap());
+        assert_eq!(manager.connection_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_connections_beyond_limit_rejected() {
+        let (url, manager) =
+            spawn_manager_server(WebSocketManager::with_max_connections(Some(2))).await;
+
+        let (mut first, status) = connect_client(&url).await;
+        assert!(is_connection_status(&status));
+        let (_second, status) = connect_client(&url).await;
+        assert!(is_connection_status(&status));
+        assert_eq!(manager.connection_count().await, 2);
+
+        match connect_client(&url).await.1 {
+            tungstenite::Message::Close(Some(frame)) => {
+                assert_eq!(frame.code, CloseCode::Policy);
+                assert_eq!(frame.reason.as_str(), TOO_MANY_CONNECTIONS_CLOSE_REASON);
+            }
+            other => panic!("expected a close frame, got {:?}", other),
+        }
+        assert_eq!(manager.connection_count().await, 2);
+
+        // Closing a connection frees its slot
+        first.close(None).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while manager.connection_count().await > 1 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let (_third, status) = connect_client(&url).await;
+        assert!(is_connection_status(&status));
+        assert_eq!(manager.connection_count().await, 2);
+    }
+
+    fn system_log_at(message: &str, timestamp: &str) -> WsMessage {
+        WsMessage::SystemLog {
+            level: LogLevel::Info,
+            message: message.to_string(),
+            source: "test".to_string(),
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    /// Reads text messages until `count` have arrived
+    async fn receive_texts(client: &mut Client, count: usize) -> Vec<String> {
+        let mut texts = Vec::new();
+        while texts.len() < count {
+            let message = tokio::time::timeout(Duration::from_secs(5), client.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            texts.push(message.to_text().unwrap().to_string());
+        }
+        texts
+    }
+
+    #[tokio::test]
+    async fn test_new_connection_receives_buffered_backlog() {
+        let (url, manager) =
+            spawn_manager_server(WebSocketManager::with_max_connections(None).with_replay_size(2))
+                .await;
+
+        for (message, timestamp) in [
+            ("first", "2025-01-01T00:00:01+00:00"),
+            ("second", "2025-01-01T00:00:02+00:00"),
+            ("third", "2025-01-01T00:00:03+00:00"),
+        ] {
+            manager.broadcast(system_log_at(message, timestamp)).await;
+        }
+        // Metrics aren't replayed
+        manager
+            .broadcast(WsMessage::PerformanceMetrics {
+                total_requests: 1,
+                success_rate: 1.0,
+                avg_response_time: 1.0,
+                error_rate: 0.0,
+                active_connections: 0,
+            })
+            .await;
+
+        let (mut client, status) = connect_client(&url).await;
+        assert!(is_connection_status(&status));
+        // Only the last two fit in the buffer, oldest first
+        let backlog = receive_texts(&mut client, 2).await;
+        assert!(backlog[0].contains("second"));
+        assert!(backlog[1].contains("third"));
+
+        // Live events follow the backlog
+        manager
+            .broadcast(system_log_at("fourth", "2025-01-01T00:00:04+00:00"))
+            .await;
+        assert!(receive_texts(&mut client, 1).await[0].contains("fourth"));
+
+        // A cursor skips the events the client has already seen
+        let (mut client, status) =
+            connect_client(&format!("{}?since=2025-01-01T00:00:03Z", url)).await;
+        assert!(is_connection_status(&status));
+        manager
+            .broadcast(system_log_at("fifth", "2025-01-01T00:00:05+00:00"))
+            .await;
+        let texts = receive_texts(&mut client, 2).await;
+        assert!(texts[0].contains("fourth"));
+        assert!(texts[1].contains("fifth"));
+    }
+
+    #[tokio::test]
+    async fn test_foreign_origin_rejected() {
+        let server = spawn_server().await;
+
+        assert_handshake_status(
+            connect(
+                &server.url,
+                "https://evil.example",
+                Some(&server.admin_token),
+            )
+            .await,
+            StatusCode::FORBIDDEN,
+        );
+    }
+}
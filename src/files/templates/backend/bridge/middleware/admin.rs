@@ -8,11 +8,18 @@ use sea_orm::DatabaseConnection;
 use tracing::{info, warn};
 
 use crate::{
-    bridge::types::{admin::AdminUser, auth::AuthUser},
+    bridge::{
+        middleware::auth_transport::AuthTransport,
+        types::{admin::AdminUser, auth::AuthUser},
+    },
     control::services::{
         session_service::SessionService, token_service::TokenService, user_service::UserService,
     },
-    infrastructure::{app_error::AppError, logging::LoggingManager},
+    infrastructure::{
+        app_error::AppError,
+        background_failures::{BackgroundOperation, record_background_failure},
+        logging::LoggingManager,
+    },
 };
 
 /// Admin middleware that handles JWT extraction and validation; no permission checking here, all done at the handler
@@ -24,7 +31,7 @@ pub async fn admin_middleware(
     let request_id = LoggingManager::generate_request_id();
 
     // Extract token from Authorization header
-    let token = TokenService::extract_token_from_header(&request)?;
+    let token = AuthTransport::extract_token(&request)?;
 
     // Extract and validate token with session validation
     let (user_id, session_id) =
@@ -33,7 +40,9 @@ pub async fn admin_middleware(
     // Update session activity (fire and forget)
     let db_clone = db.clone();
     tokio::spawn(async move {
-        let _ = SessionService::update_session_activity(&db_clone, session_id).await;
+        if let Err(e) = SessionService::update_session_activity(&db_clone, session_id).await {
+            record_background_failure(BackgroundOperation::SessionActivityUpdate, &e.message).await;
+        }
     });
 
     let user = UserService::find_user_by_id(&db, user_id)
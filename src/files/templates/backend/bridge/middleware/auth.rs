@@ -2,9 +2,12 @@ use axum::{extract::Request, extract::State, middleware::Next, response::Respons
 use sea_orm::DatabaseConnection;
 
 use crate::{
-    bridge::types::auth::AuthUser,
+    bridge::{middleware::auth_transport::AuthTransport, types::auth::AuthUser},
     control::services::{session_service::SessionService, token_service::TokenService},
-    infrastructure::app_error::AppError,
+    infrastructure::{
+        app_error::AppError,
+        background_failures::{BackgroundOperation, record_background_failure},
+    },
 };
 
 // JWT middleware with session validation
@@ -13,7 +16,7 @@ pub async fn auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    let token = TokenService::extract_token_from_header(&request)?;
+    let token = AuthTransport::extract_token(&request)?;
 
     // Extract and validate token with session validation
     let (user_id, session_id) =
@@ -23,7 +26,9 @@ pub async fn auth_middleware(
     // Update session activity (fire and forget)
     let db_clone = db.clone();
     tokio::spawn(async move {
-        let _ = SessionService::update_session_activity(&db_clone, session_id).await;
+        if let Err(e) = SessionService::update_session_activity(&db_clone, session_id).await {
+            record_background_failure(BackgroundOperation::SessionActivityUpdate, &e.message).await;
+        }
     });
 
     // Add user to request extensions
@@ -31,3 +36,81 @@ pub async fn auth_middleware(
 
     Ok(next.run(request).await)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::services::auth_service::AuthService;
+    use crate::domain::user::UserLogin;
+    use crate::infrastructure::test_support::{
+        create_verified_user, set_test_jwt_secret, setup_db,
+    };
+    use axum::{
+        Router,
+        body::Body,
+        http::{StatusCode, header},
+        middleware,
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    fn app(db: DatabaseConnection) -> Router {
+        Router::new()
+            .route("/profile", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(db.clone(), auth_middleware))
+            .with_state(db)
+    }
+
+    async fn get_profile(app: &Router, token: &str) -> StatusCode {
+        // Sent both ways so the request authenticates with either transport
+        let request = Request::get("/profile")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::COOKIE, format!("rext_session={}", token))
+            .body(Body::empty())
+            .unwrap();
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_invalidated_sessions_rejected_on_next_request() {
+        let db = setup_db().await;
+        let user = create_verified_user(
+            &db,
+            "user@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        set_test_jwt_secret();
+        let login = AuthService::authenticate_user(
+            &db,
+            UserLogin {
+                email: "user@example.com".to_string(),
+                password: "UserPassword123!".to_string(),
+            },
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let app = app(db.clone());
+        assert_eq!(get_profile(&app, &login.token).await, StatusCode::OK);
+
+        let invalidated = SessionService::invalidate_all_user_sessions(&db, user.id)
+            .await
+            .unwrap();
+        assert_eq!(invalidated, 1);
+
+        // The token is still a valid JWT, its session isn't
+        assert_eq!(
+            get_profile(&app, &login.token).await,
+            StatusCode::UNAUTHORIZED
+        );
+        let err = AuthService::refresh_token(&db, &login.refresh_token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+    }
+}
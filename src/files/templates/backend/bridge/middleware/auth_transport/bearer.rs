@@ -0,0 +1,67 @@
+//! Bearer token transport: clients keep the JWT and send it in the Authorization header
+use axum::{extract::Request, http::HeaderMap, response::Response};
+use chrono::{DateTime, Utc};
+
+use crate::{control::services::token_service::TokenService, infrastructure::app_error::AppError};
+
+/// How the auth token travels between client and server
+pub struct AuthTransport;
+
+impl AuthTransport {
+    /// Bearer clients read the token from the login response body
+    pub const TOKEN_IN_BODY: bool = true;
+
+    /// Extracts the JWT from the `Authorization: Bearer` header
+    pub fn extract_token(request: &Request) -> Result<String, AppError> {
+        TokenService::extract_token_from_header(request)
+    }
+
+    /// Bearer clients read the token from the login response body, nothing to attach
+    pub fn attach_token(response: Response, _token: &str, _expires_at: DateTime<Utc>) -> Response {
+        response
+    }
+
+    /// Bearer clients send the refresh token in the request body
+    pub fn extract_refresh_token(_headers: &HeaderMap) -> Option<String> {
+        None
+    }
+
+    /// Bearer clients read the refresh token from the response body, nothing to attach
+    pub fn attach_refresh_token(
+        response: Response,
+        _refresh_token: &str,
+        _expires_at: DateTime<Utc>,
+    ) -> Response {
+        response
+    }
+
+    /// Bearer clients discard the token themselves, nothing to clear
+    pub fn clear_token(response: Response) -> Response {
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::header, response::IntoResponse};
+
+    #[test]
+    fn test_extracts_bearer_token() {
+        let request = Request::get("/")
+            .header(header::AUTHORIZATION, "Bearer abc.def.ghi")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(
+            AuthTransport::extract_token(&request).unwrap(),
+            "abc.def.ghi"
+        );
+    }
+
+    #[test]
+    fn test_login_response_sets_no_cookie() {
+        let response = AuthTransport::attach_token("ok".into_response(), "abc.def.ghi", Utc::now());
+        assert!(response.headers().get(header::SET_COOKIE).is_none());
+    }
+}
@@ -0,0 +1,211 @@
+//! Session cookie transport: the JWT lives in an HttpOnly cookie the browser sends automatically
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use std::env;
+
+use crate::infrastructure::app_error::AppError;
+
+/// Name of the cookie holding the auth token
+pub const AUTH_COOKIE_NAME: &str = "rext_session";
+
+/// Name of the cookie holding the refresh token
+pub const REFRESH_COOKIE_NAME: &str = "rext_refresh";
+
+/// The refresh cookie is only sent to the refresh endpoint
+pub const REFRESH_COOKIE_PATH: &str = "/api/v1/auth/refresh";
+
+/// How the auth token travels between client and server
+pub struct AuthTransport;
+
+impl AuthTransport {
+    /// The cookie carries the token, response bodies leave it out so scripts never see it
+    pub const TOKEN_IN_BODY: bool = false;
+
+    /// Extracts the JWT from the session cookie
+    pub fn extract_token(request: &Request) -> Result<String, AppError> {
+        Self::cookie(request.headers(), AUTH_COOKIE_NAME).ok_or(AppError {
+            message: "Missing session cookie".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+        })
+    }
+
+    /// Sets the session cookie on the login response, expiring with the token
+    pub fn attach_token(response: Response, token: &str, expires_at: DateTime<Utc>) -> Response {
+        Self::set_cookie(response, AUTH_COOKIE_NAME, "/", token, expires_at)
+    }
+
+    /// Extracts the refresh token from the refresh cookie
+    pub fn extract_refresh_token(headers: &HeaderMap) -> Option<String> {
+        Self::cookie(headers, REFRESH_COOKIE_NAME)
+    }
+
+    /// Sets the refresh cookie, scoped to the refresh endpoint and expiring with the session
+    pub fn attach_refresh_token(
+        response: Response,
+        refresh_token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Response {
+        Self::set_cookie(
+            response,
+            REFRESH_COOKIE_NAME,
+            REFRESH_COOKIE_PATH,
+            refresh_token,
+            expires_at,
+        )
+    }
+
+    /// Expires the session and refresh cookies on the logout response
+    pub fn clear_token(response: Response) -> Response {
+        let now = Utc::now();
+        let response = Self::set_cookie(response, AUTH_COOKIE_NAME, "/", "", now);
+        Self::set_cookie(response, REFRESH_COOKIE_NAME, REFRESH_COOKIE_PATH, "", now)
+    }
+
+    fn cookie(headers: &HeaderMap, cookie_name: &str) -> Option<String> {
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|cookies| cookies.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, value)| *name == cookie_name && !value.is_empty())
+            .map(|(_, value)| value.to_string())
+    }
+
+    fn set_cookie(
+        mut response: Response,
+        name: &str,
+        path: &str,
+        value: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Response {
+        // Development runs over plain HTTP, where browsers drop Secure cookies
+        let secure =
+            env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string()) == "production";
+        let max_age = (expires_at - Utc::now()).num_seconds().max(0);
+
+        let cookie = format!(
+            "{}={}; HttpOnly; SameSite=Lax; Path={}; Max-Age={}{}",
+            name,
+            value,
+            path,
+            max_age,
+            if secure { "; Secure" } else { "" }
+        );
+
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::types::auth::LoginResponse;
+    use axum::{body::Body, response::IntoResponse};
+
+    #[test]
+    fn test_extracts_token_from_cookie() {
+        let request = Request::get("/")
+            .header(header::COOKIE, "theme=dark; rext_session=abc.def.ghi")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(
+            AuthTransport::extract_token(&request).unwrap(),
+            "abc.def.ghi"
+        );
+    }
+
+    #[test]
+    fn test_ignores_authorization_header() {
+        let request = Request::get("/")
+            .header(header::AUTHORIZATION, "Bearer abc.def.ghi")
+            .body(Body::empty())
+            .unwrap();
+
+        let err = AuthTransport::extract_token(&request).unwrap_err();
+        assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_login_and_logout_cookies() {
+        let response = AuthTransport::attach_token(
+            "ok".into_response(),
+            "abc.def.ghi",
+            Utc::now() + chrono::Duration::minutes(15),
+        );
+        let cookie = response.headers().get(header::SET_COOKIE).unwrap();
+        let cookie = cookie.to_str().unwrap();
+        assert!(cookie.starts_with("rext_session=abc.def.ghi; HttpOnly"));
+        // Expires with the token, not after the configured JWT lifetime
+        let max_age: u64 = cookie
+            .split("Max-Age=")
+            .nth(1)
+            .and_then(|rest| rest.split(';').next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((890..=900).contains(&max_age), "{}", max_age);
+
+        let response = AuthTransport::clear_token("ok".into_response());
+        let cookies: Vec<_> = response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .map(|cookie| cookie.to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(cookies.len(), 2);
+        assert!(cookies.iter().all(|cookie| cookie.contains("Max-Age=0")));
+        assert!(cookies[1].starts_with("rext_refresh=;"));
+        assert!(cookies[1].contains("Path=/api/v1/auth/refresh;"));
+    }
+
+    #[test]
+    fn test_refresh_cookie_scoped_to_refresh_endpoint() {
+        let response = AuthTransport::attach_refresh_token(
+            "ok".into_response(),
+            "session.secret",
+            Utc::now() + chrono::Duration::days(7),
+        );
+        let cookie = response.headers().get(header::SET_COOKIE).unwrap();
+        let cookie = cookie.to_str().unwrap();
+        assert!(cookie.starts_with("rext_refresh=session.secret; HttpOnly"));
+        assert!(cookie.contains("Path=/api/v1/auth/refresh;"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            "rext_session=abc.def.ghi; rext_refresh=session.secret"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            AuthTransport::extract_refresh_token(&headers).as_deref(),
+            Some("session.secret")
+        );
+        assert_eq!(
+            AuthTransport::extract_refresh_token(&HeaderMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_login_response_body_leaves_tokens_out() {
+        let body = LoginResponse {
+            token: AuthTransport::TOKEN_IN_BODY.then(|| "abc.def.ghi".to_string()),
+            refresh_token: AuthTransport::TOKEN_IN_BODY.then(|| "refresh".to_string()),
+        };
+
+        let json = serde_json::to_value(&body).unwrap();
+        assert!(json.get("token").is_none());
+        assert!(json.get("refresh_token").is_none());
+    }
+}
@@ -0,0 +1,243 @@
+use axum::{
+    extract::State,
+    http::{HeaderName, HeaderValue, header},
+    response::Response,
+};
+use std::env;
+
+/// Default max-age (in seconds) for the permission catalog
+pub const PERMISSION_CATALOG_MAX_AGE: u32 = 300;
+
+/// Cache-Control directive applied to a route
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheControl {
+    /// Never store the response (auth, user data)
+    NoStore,
+    /// Only the client may cache the response
+    Private { max_age: u32 },
+    /// Clients and shared caches (CDNs) may cache the response
+    Public { max_age: u32 },
+}
+
+/// Caching headers to attach to a route's responses
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    pub cache_control: CacheControl,
+    pub vary: Vec<HeaderName>,
+}
+
+impl CachePolicy {
+    /// Policy for responses that must never be cached
+    pub fn no_store() -> Self {
+        Self {
+            cache_control: CacheControl::NoStore,
+            vary: Vec::new(),
+        }
+    }
+
+    /// Policy for responses only the requesting client may cache
+    pub fn private(max_age: u32) -> Self {
+        Self {
+            cache_control: CacheControl::Private { max_age },
+            vary: Vec::new(),
+        }
+    }
+
+    /// Policy for responses any cache may store
+    pub fn public(max_age: u32) -> Self {
+        Self {
+            cache_control: CacheControl::Public { max_age },
+            vary: Vec::new(),
+        }
+    }
+
+    /// Adds a header to the Vary list
+    pub fn vary(mut self, header: HeaderName) -> Self {
+        self.vary.push(header);
+        self
+    }
+
+    /// Policy for the permission catalog, max-age can be overridden with PERMISSION_CATALOG_CACHE_SECONDS
+    pub fn permission_catalog() -> Self {
+        let max_age = env::var("PERMISSION_CATALOG_CACHE_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(PERMISSION_CATALOG_MAX_AGE);
+
+        Self::private(max_age).vary(header::AUTHORIZATION)
+    }
+
+    /// Renders the Cache-Control header value
+    pub fn cache_control_value(&self) -> String {
+        match self.cache_control {
+            CacheControl::NoStore => "no-store".to_string(),
+            CacheControl::Private { max_age } => format!("private, max-age={}", max_age),
+            CacheControl::Public { max_age } => format!("public, max-age={}", max_age),
+        }
+    }
+
+    /// Renders the Vary header value, if any
+    pub fn vary_value(&self) -> Option<String> {
+        if self.vary.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.vary
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+}
+
+/// Response mapper that attaches the policy's caching headers
+///
+/// Use with `axum::middleware::map_response_with_state`. Headers already set by the handler
+/// are left untouched, and error responses are never cached.
+pub async fn apply_cache_policy(
+    State(policy): State<CachePolicy>,
+    mut response: Response,
+) -> Response {
+    if response.headers().contains_key(header::CACHE_CONTROL) {
+        return response;
+    }
+
+    let cache_control = if response.status().is_success() {
+        policy.cache_control_value()
+    } else {
+        CachePolicy::no_store().cache_control_value()
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&cache_control) {
+        headers.insert(header::CACHE_CONTROL, value);
+    }
+
+    if let Some(value) = policy
+        .vary_value()
+        .and_then(|vary| HeaderValue::from_str(&vary).ok())
+    {
+        headers.insert(header::VARY, value);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Json, Router,
+        body::Body,
+        http::{Request, StatusCode},
+        middleware::map_response_with_state,
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    #[cfg(feature = "admin")]
+    use crate::bridge::handlers::roles::get_permission_catalog_handler;
+
+    async fn user_handler() -> Json<&'static str> {
+        Json("user")
+    }
+
+    async fn failing_handler() -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    #[test]
+    fn test_cache_control_value() {
+        assert_eq!(CachePolicy::no_store().cache_control_value(), "no-store");
+        assert_eq!(
+            CachePolicy::private(60).cache_control_value(),
+            "private, max-age=60"
+        );
+        assert_eq!(
+            CachePolicy::public(300).cache_control_value(),
+            "public, max-age=300"
+        );
+    }
+
+    #[test]
+    fn test_vary_value() {
+        assert_eq!(CachePolicy::no_store().vary_value(), None);
+        let policy = CachePolicy::public(60)
+            .vary(header::AUTHORIZATION)
+            .vary(header::ACCEPT_ENCODING);
+        assert_eq!(
+            policy.vary_value(),
+            Some("authorization, accept-encoding".to_string())
+        );
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_permission_catalog_carries_cache_headers() {
+        let app = Router::new()
+            .route("/permissions", get(get_permission_catalog_handler))
+            .layer(map_response_with_state(
+                CachePolicy::permission_catalog(),
+                apply_cache_policy,
+            ));
+
+        let response = app
+            .oneshot(Request::get("/permissions").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            &format!("private, max-age={}", PERMISSION_CATALOG_MAX_AGE)
+        );
+        assert_eq!(
+            response.headers().get(header::VARY).unwrap(),
+            "authorization"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_user_endpoint_carries_no_store() {
+        let app =
+            Router::new()
+                .route("/profile", get(user_handler))
+                .layer(map_response_with_state(
+                    CachePolicy::no_store(),
+                    apply_cache_policy,
+                ));
+
+        let response = app
+            .oneshot(Request::get("/profile").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+        assert!(response.headers().get(header::VARY).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_are_not_cached() {
+        let app = Router::new()
+            .route("/permissions", get(failing_handler))
+            .layer(map_response_with_state(
+                CachePolicy::public(300),
+                apply_cache_policy,
+            ));
+
+        let response = app
+            .oneshot(Request::get("/permissions").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+    }
+}
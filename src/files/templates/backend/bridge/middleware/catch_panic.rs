@@ -0,0 +1,178 @@
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures_util::FutureExt;
+use std::{any::Any, panic::AssertUnwindSafe};
+use tracing::error;
+
+use crate::{
+    bridge::types::logging::{HandlerPanic, RequestId},
+    infrastructure::app_error::AppError,
+};
+
+/// Message from a panic payload, panics carry a `&str` or `String` unless raised with
+/// `std::panic::panic_any`
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
+
+/// Turns a panicking handler into a JSON 500 and logs the panic with the request id
+///
+/// Must run inside the request logging middleware so the request id is set and the 500 still
+/// reaches the audit log, which records the panic message through the `HandlerPanic` extension.
+/// The panic message is never sent to the client.
+pub async fn catch_panic_middleware(request: Request, next: Next) -> Response {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|RequestId(id)| id.clone())
+        .unwrap_or_default();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+
+    match AssertUnwindSafe(next.run(request)).catch_unwind().await {
+        Ok(response) => response,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            error!(
+                request_id = %request_id,
+                method = %method,
+                path = %path,
+                panic = %message,
+                "Handler panicked"
+            );
+
+            let mut response = AppError {
+                message: "Internal server error".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            }
+            .into_response();
+            response.extensions_mut().insert(HandlerPanic(message));
+            response
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bridge::middleware::logging::{RequestLogging, request_logging_middleware},
+        entity::models::audit_logs,
+        infrastructure::test_support::{CapturedLogs, setup_db},
+    };
+    use axum::{Router, body::Body, middleware, routing::get};
+    use sea_orm::EntityTrait;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    /// `fields` of every logged JSON line
+    fn events(logs: &CapturedLogs) -> Vec<serde_json::Value> {
+        logs.contents()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["fields"].clone())
+            .collect()
+    }
+
+    async fn boom() -> &'static str {
+        panic!("boom")
+    }
+
+    async fn boom_with_number() -> &'static str {
+        std::panic::panic_any(42)
+    }
+
+    #[tokio::test]
+    async fn test_panicking_handler_returns_json_500_and_is_audited() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let db = setup_db().await;
+
+        let app = Router::new()
+            .route("/api/v1/boom", get(boom))
+            .layer(middleware::from_fn(catch_panic_middleware))
+            .layer(middleware::from_fn_with_state(
+                RequestLogging::new(db.clone()),
+                request_logging_middleware,
+            ));
+
+        let response = app
+            .oneshot(Request::get("/api/v1/boom").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({ "message": "Internal server error" })
+        );
+
+        // The panic is logged with the same request id as the request itself
+        let events = events(&logs);
+        let panic_event = events
+            .iter()
+            .find(|fields| fields["message"] == "Handler panicked")
+            .expect("panic is logged");
+        assert_eq!(panic_event["panic"], "boom");
+        assert_eq!(panic_event["path"], "/api/v1/boom");
+        let request_event = events
+            .iter()
+            .find(|fields| fields["message"] == "Request error")
+            .expect("request is logged");
+        assert_eq!(panic_event["request_id"], request_event["request_id"]);
+
+        // Inserts run on the audit worker pool
+        let entry = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(entry) = audit_logs::Entity::find().one(&db).await.unwrap() {
+                    return entry;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(entry.path, "/api/v1/boom");
+        assert_eq!(entry.status_code, Some(500));
+        assert_eq!(
+            entry.error_message.as_deref(),
+            Some("Handler panicked: boom")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_non_string_panic_payload() {
+        let app = Router::new()
+            .route("/", get(boom_with_number))
+            .layer(middleware::from_fn(catch_panic_middleware));
+
+        let response = app
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            response.extensions().get::<HandlerPanic>().unwrap().0,
+            "unknown panic payload"
+        );
+    }
+}
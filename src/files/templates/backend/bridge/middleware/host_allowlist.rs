@@ -0,0 +1,198 @@
+use axum::{
+    extract::{Request, State},
+    http::{StatusCode, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::env;
+
+use crate::infrastructure::app_error::AppError;
+
+/// Host and Origin allowlist configuration, guards against host-header attacks and DNS rebinding
+#[derive(Debug, Clone)]
+pub struct HostAllowlistConfig {
+    /// Reject requests whose Host or Origin isn't allowed, permissive mode lets everything through
+    pub strict: bool,
+    /// Allowed hosts, `example.com`, `example.com:8443` or `*.example.com` for subdomains
+    pub allowed_hosts: Vec<String>,
+}
+
+impl HostAllowlistConfig {
+    /// Reads ENFORCE_HOST_ALLOWLIST (defaults to true in production) and ALLOWED_HOSTS
+    /// (comma separated, defaults to the host of ALLOWED_ORIGIN)
+    pub fn from_env() -> Self {
+        let environment = env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
+        let strict = env::var("ENFORCE_HOST_ALLOWLIST")
+            .ok()
+            .and_then(|value| value.parse::<bool>().ok())
+            .unwrap_or(environment == "production");
+
+        let mut allowed_hosts: Vec<String> = env::var("ALLOWED_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().to_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect();
+
+        if allowed_hosts.is_empty() {
+            allowed_hosts.extend(
+                env::var("ALLOWED_ORIGIN")
+                    .ok()
+                    .and_then(|origin| origin.parse::<Uri>().ok())
+                    .and_then(|uri| uri.authority().map(|authority| authority.to_string()))
+                    .map(|host| host.to_lowercase()),
+            );
+        }
+
+        Self {
+            strict,
+            allowed_hosts,
+        }
+    }
+
+    /// Whether the host (with optional port) matches an allowlist entry
+    pub fn is_allowed(&self, host: &str) -> bool {
+        let host = host.trim().to_lowercase();
+        let hostname = host
+            .rsplit_once(':')
+            .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+            .map(|(name, _)| name)
+            .unwrap_or(&host);
+
+        self.allowed_hosts.iter().any(|allowed| {
+            if let Some(suffix) = allowed.strip_prefix("*.") {
+                hostname.ends_with(&format!(".{}", suffix))
+            } else if allowed.contains(':') {
+                // An entry with a port only matches that port
+                *allowed == host
+            } else {
+                *allowed == hostname
+            }
+        })
+    }
+
+    /// Checks the request's Host and, if present, Origin
+    fn check(&self, request: &Request) -> Result<(), AppError> {
+        // HTTP/2 carries the host in the URI authority instead of the Host header
+        let host = request
+            .uri()
+            .authority()
+            .map(|authority| authority.as_str())
+            .or_else(|| {
+                request
+                    .headers()
+                    .get(header::HOST)
+                    .and_then(|value| value.to_str().ok())
+            });
+
+        if !host.is_some_and(|host| self.is_allowed(host)) {
+            return Err(AppError {
+                message: "Host not allowed".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        if let Some(origin) = request.headers().get(header::ORIGIN) {
+            let origin_host = origin
+                .to_str()
+                .ok()
+                .and_then(|origin| origin.parse::<Uri>().ok())
+                .and_then(|uri| uri.authority().map(|authority| authority.to_string()));
+
+            if !origin_host.is_some_and(|host| self.is_allowed(&host)) {
+                return Err(AppError {
+                    message: "Origin not allowed".to_string(),
+                    status_code: StatusCode::BAD_REQUEST,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rejects requests with a Host or Origin outside the allowlist when in strict mode
+pub async fn host_allowlist_middleware(
+    State(config): State<HostAllowlistConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !config.strict {
+        return next.run(request).await;
+    }
+
+    match config.check(&request) {
+        Ok(()) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    fn app(strict: bool) -> Router {
+        let config = HostAllowlistConfig {
+            strict,
+            allowed_hosts: vec!["example.com".to_string(), "*.example.org".to_string()],
+        };
+
+        Router::new()
+            .route("/api/v1/auth/profile", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                config,
+                host_allowlist_middleware,
+            ))
+    }
+
+    fn request(host: &str, origin: Option<&str>) -> Request {
+        let mut builder = Request::get("/api/v1/auth/profile").header(header::HOST, host);
+        if let Some(origin) = origin {
+            builder = builder.header(header::ORIGIN, origin);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_allowed_host_passes() {
+        for host in ["example.com", "example.com:3000", "api.example.org"] {
+            let response = app(true)
+                .oneshot(request(host, Some("https://example.com")))
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK, "{}", host);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spoofed_host_rejected_in_strict_mode() {
+        for host in ["evil.com", "example.com.evil.com", "example.org"] {
+            let response = app(true).oneshot(request(host, None)).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", host);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spoofed_origin_rejected_in_strict_mode() {
+        let response = app(true)
+            .oneshot(request("example.com", Some("https://evil.com")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_permissive_mode_allows_anything() {
+        let response = app(false)
+            .oneshot(request("evil.com", Some("https://evil.com")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -7,17 +7,36 @@ use axum::{
 };
 use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
 use serde_json::Value;
-use std::time::Instant;
-use tracing::{error, info};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
 
 use crate::{
-    bridge::types::{auth::AuthUser, logging::LoggingInfo},
+    bridge::types::{
+        auth::AuthUser,
+        logging::{HandlerPanic, LoggingInfo, RequestId},
+    },
     entity::models::audit_logs,
-    infrastructure::{logging::LoggingManager, websocket::broadcast_audit_log},
+    infrastructure::{
+        audit_workers::AUDIT_WORKERS,
+        background_failures::{BackgroundOperation, record_background_failure},
+        database::DatabaseManager,
+        json_limits::JsonLimits,
+        logging::{AuditLogConfig, LoggingManager, slow_request_threshold},
+        websocket::{LogLevel, WsMessage, broadcast},
+    },
 };
 
 const MAX_BODY_LOG_BYTES: usize = 4096; // 4KB
 
+/// A request or response body prepared for the audit log
+#[derive(Debug)]
+pub struct LoggedBody {
+    /// Sanitized (and possibly truncated) body content
+    pub content: String,
+    /// Length of the original body in bytes
+    pub original_len: usize,
+}
+
 /// Sensitive fields that should be redacted from logs
 const SENSITIVE_FIELDS: &[&str] = &[
     "password",
@@ -37,6 +56,11 @@ const SENSITIVE_FIELDS: &[&str] = &[
 
 /// Sanitize JSON content by redacting sensitive fields
 fn sanitize_json_content(content: &str) -> String {
+    // Don't hand pathological payloads to the JSON parser, and don't log them unredacted either
+    if let Err(e) = JsonLimits::get().check(content.as_bytes()) {
+        return format!("[body not logged: {}]", e);
+    }
+
     if let Ok(mut json) = serde_json::from_str::<Value>(content) {
         if let Some(obj) = json.as_object_mut() {
             for field in SENSITIVE_FIELDS {
@@ -68,6 +92,38 @@ fn sanitize_json_content(content: &str) -> String {
     }
 }
 
+/// Truncates a body to at most `max_bytes` on a char boundary
+///
+/// Returns the kept text and the number of bytes that were cut off.
+fn truncate_on_char_boundary(content: &str, max_bytes: usize) -> (&str, usize) {
+    if content.len() <= max_bytes {
+        return (content, 0);
+    }
+
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    (&content[..end], content.len() - end)
+}
+
+/// Prepares a body for logging: truncates it, sanitizes it, and marks any truncation
+fn prepare_body_for_log(bytes: &[u8]) -> LoggedBody {
+    let content = String::from_utf8_lossy(bytes);
+    let (kept, truncated_bytes) = truncate_on_char_boundary(&content, MAX_BODY_LOG_BYTES);
+
+    let mut content = sanitize_json_content(kept);
+    if truncated_bytes > 0 {
+        content.push_str(&format!("…[truncated {} bytes]", truncated_bytes));
+    }
+
+    LoggedBody {
+        content,
+        original_len: bytes.len(),
+    }
+}
+
 /// Extracts, copies, and sanitizes the request and response bodies so we can log them
 /// without interfering with the original request and response.
 ///
@@ -77,11 +133,12 @@ fn sanitize_json_content(content: &str) -> String {
 ///
 /// The request and response bodies are sanitized by redacting sensitive fields.
 ///
-/// The request and response bodies are truncated to MAX_BODY_LOG_BYTES.
+/// The request and response bodies are truncated to MAX_BODY_LOG_BYTES on a char boundary,
+/// with a `…[truncated N bytes]` marker appended when anything was cut off.
 pub async fn extract_request_response(
     req: Request<Body>,
     next: Next,
-) -> Result<(Response, Option<String>, Option<String>), (StatusCode, String)> {
+) -> Result<(Response, Option<LoggedBody>, Option<LoggedBody>), (StatusCode, String)> {
     // extract parts of the request so we can reconstruct it later
     let (req_parts, req_body) = req.into_parts();
 
@@ -96,17 +153,8 @@ pub async fn extract_request_response(
         }
     };
 
-    // clonse the request body and truncate the clone to MAX_BODY_LOG_BYTES
-    let copy_req_bytes = req_bytes.clone();
-    let copy_req_bytes = if copy_req_bytes.len() > MAX_BODY_LOG_BYTES {
-        &copy_req_bytes[..MAX_BODY_LOG_BYTES]
-    } else {
-        &copy_req_bytes
-    };
-
-    // stringify the cloned request body and sanitize it
-    let copy_req_string = String::from_utf8_lossy(copy_req_bytes).to_string();
-    let copy_req_sanitized = Some(sanitize_json_content(&copy_req_string));
+    // stringify, truncate and sanitize a copy of the request body
+    let copy_req_sanitized = Some(prepare_body_for_log(&req_bytes));
 
     // reconstruct the request with the original parts and original body
     let req = Request::from_parts(req_parts, Body::from(req_bytes));
@@ -126,17 +174,8 @@ pub async fn extract_request_response(
         }
     };
 
-    // clone the response body and truncate the clone to MAX_BODY_LOG_BYTES
-    let copy_res_bytes = res_bytes.clone();
-    let copy_res_bytes = if copy_res_bytes.len() > MAX_BODY_LOG_BYTES {
-        &copy_res_bytes[..MAX_BODY_LOG_BYTES]
-    } else {
-        &copy_res_bytes
-    };
-
-    // stringify the cloned response body and sanitize it
-    let copy_res_string = String::from_utf8_lossy(copy_res_bytes).to_string();
-    let copy_res_sanitized = Some(sanitize_json_content(&copy_res_string));
+    // stringify, truncate and sanitize a copy of the response body
+    let copy_res_sanitized = Some(prepare_body_for_log(&res_bytes));
 
     // reconstruct the response with the original parts and original body
     let res = Response::from_parts(res_parts, Body::from(res_bytes));
@@ -144,14 +183,42 @@ pub async fn extract_request_response(
     Ok((res, copy_req_sanitized, copy_res_sanitized))
 }
 
+/// State of the request logging middleware
+#[derive(Clone)]
+pub struct RequestLogging {
+    pub db: DatabaseConnection,
+    /// What gets audited per route
+    pub audit: &'static AuditLogConfig,
+    /// Response time past which a request is reported as slow, None turns the check off
+    pub slow_request_threshold: Option<Duration>,
+}
+
+impl RequestLogging {
+    /// Logs to `db` with the global audit log config and the threshold from SLOW_REQUEST_MS
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self {
+            db,
+            audit: AuditLogConfig::get(),
+            slow_request_threshold: slow_request_threshold(),
+        }
+    }
+}
+
 /// Request logging middleware for auditing all API requests
 pub async fn request_logging_middleware(
-    State(db): State<DatabaseConnection>,
+    State(RequestLogging {
+        db,
+        audit,
+        slow_request_threshold,
+    }): State<RequestLogging>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
     let start = Instant::now();
     let request_id = LoggingManager::generate_request_id();
+    request
+        .extensions_mut()
+        .insert(RequestId(request_id.clone()));
 
     // Extract request info
     let method = request.method().to_string();
@@ -177,6 +244,11 @@ pub async fn request_logging_middleware(
         return Ok(next.run(request).await);
     }
 
+    // Audit log inserts would only fail against a read-only database
+    if DatabaseManager::is_read_only() {
+        return Ok(next.run(request).await);
+    }
+
     // Don't log WebSocket endpoint to prevent recursive logging
     if path == "/api/v1/admin/ws" {
         return Ok(next.run(request).await);
@@ -190,8 +262,8 @@ pub async fn request_logging_middleware(
         .or_else(|| {
             request
                 .extensions()
-                .get::<std::net::SocketAddr>()
-                .map(|addr| addr.ip().to_string())
+                .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+                .map(|connect_info| connect_info.0.ip().to_string())
         });
     let user_agent = request
         .headers()
@@ -222,22 +294,34 @@ pub async fn request_logging_middleware(
     // request will have finished already.
     request.extensions_mut().insert(logging_info);
 
-    // Capture request and response bodies (runs the next handler so we get the response)
-    let (response, request_body, response_body) = extract_request_response(request, next).await.map_err(|(status, message)| {
-        error!(request_id = %request_id, error = %message, "Failed to extract request and response bodies");
-        status
-    })?;
+    // Capture request and response bodies (runs the next handler so we get the response),
+    // metadata-only routes are audited without ever reading their bodies
+    let (response, request_body, response_body) = if audit.is_metadata_only(&path) {
+        (next.run(request).await, None, None)
+    } else {
+        extract_request_response(request, next).await.map_err(|(status, message)| {
+            error!(request_id = %request_id, error = %message, "Failed to extract request and response bodies");
+            status
+        })?
+    };
 
     let duration = start.elapsed();
     let response_time_ms = duration.as_millis() as i32;
     let status_code = response.status().as_u16() as i32;
+    let exceeded_budget = slow_request_threshold.filter(|threshold| duration > *threshold);
 
-    // Error message if status is error
-    let error_message = if status_code >= 400 {
-        Some(format!("Error status: {}", status_code))
-    } else {
-        None
-    };
+    // Error responses keep their bodies, successes only when sampled, sizes are always recorded
+    let store_bodies = audit.stores_bodies(status_code);
+
+    // Error message if status is error, a caught panic carries its message
+    let error_message =
+        if let Some(HandlerPanic(message)) = response.extensions().get::<HandlerPanic>() {
+            Some(format!("Handler panicked: {}", message))
+        } else if status_code >= 400 {
+            Some(format!("Error status: {}", status_code))
+        } else {
+            None
+        };
 
     // Clone values needed after move
     let method_clone = method.clone();
@@ -248,23 +332,25 @@ pub async fn request_logging_middleware(
     let error_message_clone = error_message.clone();
     let user_id_clone = user_id.clone();
 
-    // Create audit log entry
-    let audit_log_id = uuid::Uuid::new_v4().to_string();
-    let timestamp = chrono::Utc::now().to_rfc3339();
-
-    // Clone values for WebSocket broadcast
-    let method_for_ws = method_clone.clone();
-    let path_for_ws = path_clone.clone();
-    let ip_address_for_ws = ip_address_clone.clone();
-    let user_agent_for_ws = user_agent_clone.clone();
-    let error_message_for_ws = error_message_clone.clone();
-    let user_id_for_ws = user_id_clone.clone();
+    // WebSocket message for the audit log entry
+    let audit_message = WsMessage::AuditLog {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        method: method_clone.clone(),
+        path: path_clone.clone(),
+        status_code: Some(status_code),
+        response_time_ms: Some(response_time_ms),
+        user_id: user_id_clone.map(|id| id.to_string()),
+        ip_address: ip_address_clone.clone(),
+        user_agent: user_agent_clone.clone(),
+        error_message: error_message_clone.clone(),
+    };
 
     // Clone values for system log broadcasting
     let method_for_logs = method_clone.clone();
     let path_for_logs = path_clone.clone();
 
-    // Insert audit log asynchronously (don't block response)
+    // Insert audit log in the background
     let audit_log = audit_logs::ActiveModel {
         id: Set(uuid::Uuid::new_v4()),
         timestamp: Set(Some(chrono::Utc::now().into())),
@@ -275,76 +361,79 @@ pub async fn request_logging_middleware(
         user_id: Set(user_id),
         ip_address: Set(ip_address),
         user_agent: Set(user_agent),
-        request_body: Set(request_body),
-        response_body: Set(response_body),
+        request_body: Set(request_body
+            .as_ref()
+            .filter(|_| store_bodies)
+            .map(|body| body.content.clone())),
+        response_body: Set(response_body
+            .as_ref()
+            .filter(|_| store_bodies)
+            .map(|body| body.content.clone())),
+        request_body_size: Set(request_body.as_ref().map(|body| body.original_len as i64)),
+        response_body_size: Set(response_body.as_ref().map(|body| body.original_len as i64)),
         error_message: Set(error_message_clone.clone()),
     };
     let db_clone = db.clone();
-    tokio::spawn(async move {
-        if let Err(e) = audit_log.insert(&db_clone).await {
-            error!(request_id = %request_id_clone, error = ?e, "Failed to insert audit log");
-
-            // Broadcast error log
-            crate::infrastructure::websocket::broadcast_system_log(
-                "error".to_string(),
-                format!("Failed to insert audit log: {}", e),
-                "audit_logging".to_string(),
-            )
-            .await;
-        } else {
-            info!(request_id = %request_id_clone, "Audit log inserted");
-
-            // Broadcast the audit log to WebSocket clients
-            broadcast_audit_log(
-                audit_log_id,
-                timestamp,
-                method_for_ws,
-                path_for_ws,
-                Some(status_code),
-                Some(response_time_ms),
-                user_id_for_ws.map(|id| id.to_string()),
-                ip_address_for_ws,
-                user_agent_for_ws,
-                error_message_for_ws,
-            )
-            .await;
-
-            // Broadcast info log for successful requests (but not too frequently)
-            if status_code >= 200 && status_code < 300 {
-                crate::infrastructure::websocket::broadcast_system_log(
-                    "info".to_string(),
-                    format!(
-                        "Request completed: {} {} ({}ms)",
-                        method_for_logs, path_for_logs, response_time_ms
-                    ),
-                    "request_logging".to_string(),
-                )
-                .await;
-            } else if status_code >= 400 {
-                // Broadcast warning for client errors
-                crate::infrastructure::websocket::broadcast_system_log(
-                    "warn".to_string(),
-                    format!(
-                        "Client error: {} {} - {}",
-                        method_for_logs, path_for_logs, status_code
-                    ),
-                    "request_logging".to_string(),
-                )
-                .await;
-            } else if status_code >= 500 {
-                // Broadcast error for server errors
-                crate::infrastructure::websocket::broadcast_system_log(
-                    "error".to_string(),
-                    format!(
-                        "Server error: {} {} - {}",
-                        method_for_logs, path_for_logs, status_code
-                    ),
-                    "request_logging".to_string(),
-                )
-                .await;
+    // Queued on the bounded audit worker pool (don't block response unless the queue is full)
+    AUDIT_WORKERS
+        .submit(async move {
+            if insert_audit_log(&db_clone, audit_log, &request_id_clone).await {
+                // Broadcast the audit log to WebSocket clients
+                broadcast(audit_message).await;
+
+                // Broadcast info log for successful requests (but not too frequently)
+                if status_code >= 200 && status_code < 300 {
+                    broadcast(WsMessage::system_log(
+                        LogLevel::Info,
+                        format!(
+                            "Request completed: {} {} ({}ms)",
+                            method_for_logs, path_for_logs, response_time_ms
+                        ),
+                        "request_logging",
+                    ))
+                    .await;
+                } else if status_code >= 400 {
+                    // Broadcast warning for client errors
+                    broadcast(WsMessage::system_log(
+                        LogLevel::Warn,
+                        format!(
+                            "Client error: {} {} - {}",
+                            method_for_logs, path_for_logs, status_code
+                        ),
+                        "request_logging",
+                    ))
+                    .await;
+                } else if status_code >= 500 {
+                    // Broadcast error for server errors
+                    broadcast(WsMessage::system_log(
+                        LogLevel::Error,
+                        format!(
+                            "Server error: {} {} - {}",
+                            method_for_logs, path_for_logs, status_code
+                        ),
+                        "request_logging",
+                    ))
+                    .await;
+                }
             }
-        }
-    });
+        })
+        .await;
+
+    // Report slow requests as they happen rather than only through the aggregates
+    if let Some(threshold) = exceeded_budget {
+        broadcast(WsMessage::system_log(
+            LogLevel::Warn,
+            format!(
+                "Slow request: {} {} took {}ms (budget {}ms)",
+                method_clone,
+                path_clone,
+                response_time_ms,
+                threshold.as_millis()
+            ),
+            "request_logging",
+        ))
+        .await;
+    }
 
     // log to tracing with admin label if the path starts with /api/v1/admin
     let is_admin_request = path_clone.starts_with("/api/v1/admin");
@@ -362,6 +451,20 @@ pub async fn request_logging_middleware(
             admin_request = %is_admin_request,
             "Request error"
         );
+    } else if let Some(threshold) = exceeded_budget {
+        warn!(
+            request_id = %request_id,
+            status_code,
+            user_id = ?user_id_clone,
+            path = %path_clone,
+            method = %method_clone,
+            ip_address = ?ip_address_clone,
+            user_agent = ?user_agent_clone,
+            response_time_ms,
+            slow_request_threshold_ms = threshold.as_millis() as u64,
+            admin_request = %is_admin_request,
+            "Slow request"
+        );
     } else {
         info!(
             request_id = %request_id,
@@ -379,3 +482,233 @@ pub async fn request_logging_middleware(
 
     Ok(response)
 }
+
+/// Inserts the audit entry, counting a failure so lost entries show up in the health status
+async fn insert_audit_log(
+    db: &DatabaseConnection,
+    audit_log: audit_logs::ActiveModel,
+    request_id: &str,
+) -> bool {
+    match audit_log.insert(db).await {
+        Ok(_) => {
+            info!(request_id = %request_id, "Audit log inserted");
+            true
+        }
+        Err(e) => {
+            error!(request_id = %request_id, error = ?e, "Failed to insert audit log");
+            record_background_failure(
+                BackgroundOperation::AuditLogInsert,
+                &format!("Failed to insert audit log: {}", e),
+            )
+            .await;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::{
+        background_failures::BACKGROUND_FAILURES,
+        test_support::{memory_db, setup_db},
+        websocket::WEBSOCKET_MANAGER,
+    };
+    use axum::{Router, middleware, routing::post};
+    use sea_orm::{EntityTrait, QueryOrder};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metadata_only_route_audited_without_bodies() {
+        let audit = AuditLogConfig::from_sources(None, |name| {
+            (name == "AUDIT_METADATA_ONLY_PATHS").then(|| "/api/v1/uploads".to_string())
+        });
+        let db = setup_db().await;
+        let logging = RequestLogging {
+            db: db.clone(),
+            audit: Box::leak(Box::new(audit)),
+            slow_request_threshold: None,
+        };
+
+        let app = Router::new()
+            .route(
+                "/api/v1/uploads",
+                post(|| async { (StatusCode::CREATED, "stored") }),
+            )
+            .route("/api/v1/notes", post(|| async { "noted" }))
+            .layer(middleware::from_fn_with_state(
+                logging,
+                request_logging_middleware,
+            ));
+
+        for path in ["/api/v1/uploads", "/api/v1/notes"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::post(path)
+                        .body(Body::from(r#"{"file":"secret-contents"}"#))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            // The handler still receives and answers the request
+            assert!(response.status().is_success());
+        }
+
+        // Inserts run on the audit worker pool
+        let logs = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let logs = audit_logs::Entity::find()
+                    .order_by_asc(audit_logs::Column::Path)
+                    .all(&db)
+                    .await
+                    .unwrap();
+                if logs.len() == 2 {
+                    return logs;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let (notes, uploads) = (&logs[0], &logs[1]);
+        assert_eq!(uploads.method, "POST");
+        assert_eq!(uploads.path, "/api/v1/uploads");
+        assert_eq!(uploads.status_code, Some(201));
+        assert!(uploads.request_body.is_none());
+        assert!(uploads.response_body.is_none());
+
+        assert_eq!(notes.path, "/api/v1/notes");
+        assert!(
+            notes
+                .request_body
+                .as_deref()
+                .unwrap()
+                .contains("secret-contents")
+        );
+        assert_eq!(notes.response_body.as_deref(), Some("noted"));
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_broadcasts_warning() {
+        let threshold = Duration::from_millis(50);
+        let db = setup_db().await;
+        let logging = RequestLogging {
+            db: db.clone(),
+            audit: Box::leak(Box::default()),
+            slow_request_threshold: Some(threshold),
+        };
+
+        let mut events = WEBSOCKET_MANAGER.subscribe();
+        let app = Router::new()
+            .route(
+                "/api/v1/slow",
+                post(move || async move {
+                    tokio::time::sleep(threshold + Duration::from_millis(25)).await;
+                    "done"
+                }),
+            )
+            .layer(middleware::from_fn_with_state(
+                logging,
+                request_logging_middleware,
+            ));
+
+        let response = app
+            .oneshot(Request::post("/api/v1/slow").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+
+        let (level, message) = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(WsMessage::SystemLog { level, message, .. }) = events.recv().await {
+                    if message.starts_with("Slow request: POST /api/v1/slow") {
+                        return (level, message);
+                    }
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(level, LogLevel::Warn);
+        assert!(message.contains(&format!("budget {}ms", threshold.as_millis())));
+    }
+
+    #[tokio::test]
+    async fn test_failed_audit_insert_is_counted() {
+        // No migrations, so the audit_logs table is missing and every insert fails
+        let db = memory_db().await;
+
+        let before = BACKGROUND_FAILURES.count(BackgroundOperation::AuditLogInsert);
+        let audit_log = audit_logs::ActiveModel {
+            id: Set(uuid::Uuid::new_v4()),
+            method: Set("POST".to_string()),
+            path: Set("/api/v1/notes".to_string()),
+            ..Default::default()
+        };
+
+        assert!(!insert_audit_log(&db, audit_log, "test-request").await);
+        assert!(BACKGROUND_FAILURES.count(BackgroundOperation::AuditLogInsert) > before);
+
+        // The request itself is unaffected
+        let app = Router::new()
+            .route("/api/v1/notes", post(|| async { "noted" }))
+            .layer(middleware::from_fn_with_state(
+                RequestLogging::new(db),
+                request_logging_middleware,
+            ));
+        let response = app
+            .oneshot(Request::post("/api/v1/notes").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+
+    #[test]
+    fn test_body_under_limit_is_not_truncated() {
+        let body = prepare_body_for_log(br#"{"message":"ok"}"#);
+        assert_eq!(body.content, r#"{"message":"ok"}"#);
+        assert_eq!(body.original_len, 16);
+    }
+
+    #[test]
+    fn test_body_over_limit_is_truncated_with_marker() {
+        let raw = "a".repeat(MAX_BODY_LOG_BYTES + 100);
+        let body = prepare_body_for_log(raw.as_bytes());
+
+        assert!(body.content.ends_with("…[truncated 100 bytes]"));
+        assert!(body.content.starts_with(&"a".repeat(MAX_BODY_LOG_BYTES)));
+        assert_eq!(body.original_len, MAX_BODY_LOG_BYTES + 100);
+    }
+
+    #[test]
+    fn test_truncation_respects_char_boundaries() {
+        // "é" is two bytes, so the limit falls in the middle of a character
+        let raw = format!("{}é{}", "a".repeat(MAX_BODY_LOG_BYTES - 1), "b".repeat(10));
+        let body = prepare_body_for_log(raw.as_bytes());
+
+        assert!(!body.content.contains('\u{{FFFD}}'));
+        assert!(
+            body.content
+                .starts_with(&"a".repeat(MAX_BODY_LOG_BYTES - 1))
+        );
+        assert!(body.content.ends_with("…[truncated 12 bytes]"));
+        assert_eq!(body.original_len, MAX_BODY_LOG_BYTES + 11);
+    }
+
+    #[test]
+    fn test_deeply_nested_body_not_parsed_or_logged() {
+        let depth = JsonLimits::get().max_depth + 1;
+        let raw = format!(
+            r#"{}{{"password":"hunter2"}}{}"#,
+            "[".repeat(depth),
+            "]".repeat(depth)
+        );
+        let body = prepare_body_for_log(raw.as_bytes());
+
+        assert!(body.content.starts_with("[body not logged:"));
+        assert!(!body.content.contains("hunter2"));
+    }
+}
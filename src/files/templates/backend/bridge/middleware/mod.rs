@@ -1,3 +1,13 @@
+#[cfg(feature = "admin")]
 pub mod admin;
 pub mod auth;
+pub mod auth_transport;
+pub mod cache;
+pub mod catch_panic;
+pub mod host_allowlist;
 pub mod logging;
+pub mod password_change;
+pub mod rate_limit;
+pub mod read_only;
+pub mod secure_transport;
+pub mod trace_context;
@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use sea_orm::DatabaseConnection;
+
+use crate::{
+    bridge::types::auth::AuthUser, control::services::user_service::UserService,
+    infrastructure::app_error::AppError,
+};
+
+/// Rejects requests from users flagged with `must_change_password` with 428 Precondition
+/// Required, so the frontend can send them to the change password screen
+///
+/// Must run after the auth or admin middleware, requests without an `AuthUser` pass through.
+/// The change password route itself is mounted outside of this middleware.
+pub async fn password_change_middleware(
+    State(db): State<DatabaseConnection>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(auth_user) = request.extensions().get::<AuthUser>() {
+        if UserService::must_change_password(&db, auth_user.user_id).await? {
+            return Err(AppError {
+                message: "Password change required".to_string(),
+                status_code: StatusCode::PRECONDITION_REQUIRED,
+            });
+        }
+    }
+
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bridge::handlers::auth::change_password_handler,
+        entity::models::roles,
+        infrastructure::test_support::{create_verified_user, setup_db},
+    };
+    use axum::{
+        Router,
+        body::Body,
+        middleware,
+        routing::{get, post},
+    };
+    use sea_orm::{ActiveModelTrait, Set};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    const PASSWORD: &str = "UserPassword123!";
+
+    async fn flagged_admin(db: &DatabaseConnection) -> Uuid {
+        let role = roles::ActiveModel {
+            name: Set("admin".to_string()),
+            permissions: Set("[\"*\"]".to_string()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+        let user = create_verified_user(
+            db,
+            "admin@example.com".to_string(),
+            PASSWORD.to_string(),
+            Some(role.id),
+        )
+        .await
+        .unwrap();
+        UserService::set_must_change_password(db, user.id, true)
+            .await
+            .unwrap();
+        user.id
+    }
+
+    /// Mirrors the auth router: normal routes behind the middleware, change password outside,
+    /// every request authenticated as `user_id`
+    fn app(db: DatabaseConnection, user_id: Uuid) -> Router {
+        Router::new()
+            .route("/profile", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                db.clone(),
+                password_change_middleware,
+            ))
+            .route("/change-password", post(change_password_handler))
+            .route_layer(middleware::from_fn(
+                move |mut request: Request, next: Next| async move {
+                    request.extensions_mut().insert(AuthUser { user_id });
+                    next.run(request).await
+                },
+            ))
+            .with_state(db)
+    }
+
+    async fn get_profile(app: &Router) -> StatusCode {
+        app.clone()
+            .oneshot(Request::get("/profile").body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    async fn change_password(app: &Router, current: &str, new: &str) -> StatusCode {
+        let body = serde_json::json!({ "currentPassword": current, "newPassword": new });
+        app.clone()
+            .oneshot(
+                Request::post("/change-password")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_flagged_admin_must_change_password_first() {
+        let db = setup_db().await;
+        let user_id = flagged_admin(&db).await;
+        let app = app(db.clone(), user_id);
+
+        assert_eq!(get_profile(&app).await, StatusCode::PRECONDITION_REQUIRED);
+
+        // Wrong current password and weak new passwords don't clear the flag
+        assert_eq!(
+            change_password(&app, "wrong", "NewAdminPassword456").await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            change_password(&app, PASSWORD, "short").await,
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(get_profile(&app).await, StatusCode::PRECONDITION_REQUIRED);
+
+        assert_eq!(
+            change_password(&app, PASSWORD, "NewAdminPassword456").await,
+            StatusCode::OK
+        );
+        assert!(
+            !UserService::must_change_password(&db, user_id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(get_profile(&app).await, StatusCode::OK);
+
+        // The new password is the one that works now
+        let user = UserService::find_user_by_id(&db, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(UserService::verify_password(&user, "NewAdminPassword456").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_unflagged_user_passes() {
+        let db = setup_db().await;
+        let user = create_verified_user(
+            &db,
+            "user@example.com".to_string(),
+            PASSWORD.to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(get_profile(&app(db, user.id)).await, StatusCode::OK);
+    }
+}
@@ -0,0 +1,406 @@
+//! Fixed-window rate limiting, keyed by client IP for every request and additionally by user id
+//! once a request is authenticated, so users behind a shared NAT aren't punished for each other
+//! and one account can't dodge the limit by spreading requests over several IPs
+use axum::{
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode, header::RETRY_AFTER},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    collections::HashMap,
+    env,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use uuid::Uuid;
+
+use crate::{
+    bridge::{
+        middleware::secure_transport::{peer_ip, trusted_proxies_from_env},
+        types::auth::AuthUser,
+    },
+    infrastructure::app_error::AppError,
+};
+
+/// Default requests allowed per client IP in each window
+pub const DEFAULT_RATE_LIMIT_PER_IP: u32 = 300;
+
+/// Default requests allowed per authenticated user in each window
+pub const DEFAULT_RATE_LIMIT_PER_USER: u32 = 120;
+
+/// Default window length in seconds
+pub const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
+
+/// Most keys tracked at once, reaching it sweeps expired windows and then the oldest ones
+const MAX_TRACKED_KEYS: usize = 10_000;
+
+/// Global limiter used by the server's routers
+pub static RATE_LIMITER: once_cell::sync::Lazy<RateLimiter> =
+    once_cell::sync::Lazy::new(|| RateLimiter::new(RateLimitConfig::from_env()));
+
+/// Per-IP and per-user limits, None disables that limit
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    pub per_ip: Option<u32>,
+    pub per_user: Option<u32>,
+    pub window: Duration,
+    /// Proxies whose X-Forwarded-For header is trusted to name the client
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl RateLimitConfig {
+    /// Reads RATE_LIMIT_PER_IP, RATE_LIMIT_PER_USER (0 disables either),
+    /// RATE_LIMIT_WINDOW_SECS and TRUSTED_PROXIES
+    pub fn from_env() -> Self {
+        let limit = |name: &str, default: u32| {
+            let limit = env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default);
+            (limit > 0).then_some(limit)
+        };
+        let window_secs = env::var("RATE_LIMIT_WINDOW_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(DEFAULT_RATE_LIMIT_WINDOW_SECS);
+
+        Self {
+            per_ip: limit("RATE_LIMIT_PER_IP", DEFAULT_RATE_LIMIT_PER_IP),
+            per_user: limit("RATE_LIMIT_PER_USER", DEFAULT_RATE_LIMIT_PER_USER),
+            window: Duration::from_secs(window_secs),
+            trusted_proxies: trusted_proxies_from_env(),
+        }
+    }
+}
+
+/// What a request is counted against
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    Ip(String),
+    User(Uuid),
+    /// A normalized email, for endpoints that send mail to it
+    Email(String),
+}
+
+/// Request counts per key for the current window
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    windows: Mutex<HashMap<RateLimitKey, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    /// Counts a request against `key`, returning how long until the window resets once
+    /// `limit` requests have been made in it
+    pub fn check(&self, key: RateLimitKey, limit: u32, now: Instant) -> Result<(), Duration> {
+        let window = self.config.window;
+        let mut windows = self.windows.lock().unwrap();
+
+        if windows.len() >= MAX_TRACKED_KEYS && !windows.contains_key(&key) {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < window);
+        }
+        if windows.len() >= MAX_TRACKED_KEYS && !windows.contains_key(&key) {
+            // Still full of live windows, drop the older half so this doesn't run on every request
+            let mut starts: Vec<Instant> = windows.values().map(|(start, _)| *start).collect();
+            let (_, median, _) = starts.select_nth_unstable(starts.len() / 2);
+            let median = *median;
+            windows.retain(|_, (start, _)| *start > median);
+        }
+
+        let (start, count) = windows.entry(key).or_insert((now, 0));
+        if now.duration_since(*start) >= window {
+            *start = now;
+            *count = 0;
+        }
+
+        if *count >= limit {
+            return Err(window.saturating_sub(now.duration_since(*start)));
+        }
+
+        *count += 1;
+        Ok(())
+    }
+}
+
+/// Client IP from the connection, or from X-Forwarded-For when the connection is a trusted proxy
+///
+/// The header is read from the right, each proxy appends the address it got the request from, so
+/// the first address that isn't a trusted proxy is the client. Anything left of it could be spoofed.
+fn client_ip(request: &Request, trusted_proxies: &[IpAddr]) -> Option<String> {
+    let peer = peer_ip(request)?;
+    if !trusted_proxies.contains(&peer) {
+        return Some(peer.to_string());
+    }
+
+    let forwarded = request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let client = forwarded
+        .rsplit(',')
+        .filter_map(|ip| ip.trim().parse::<IpAddr>().ok())
+        .find(|ip| !trusted_proxies.contains(ip))
+        .unwrap_or(peer);
+    Some(client.to_string())
+}
+
+/// 429 with a Retry-After header
+fn too_many_requests(retry_after: Duration) -> Response {
+    let seconds = retry_after.as_secs().max(1);
+    let mut response = AppError {
+        message: format!("Too many requests, retry in {} seconds", seconds),
+        status_code: StatusCode::TOO_MANY_REQUESTS,
+    }
+    .into_response();
+    response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(seconds));
+    response
+}
+
+/// Limits requests per client IP (RATE_LIMIT_PER_IP)
+pub async fn ip_rate_limit_middleware(
+    State(limiter): State<&'static RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let ip = client_ip(&request, &limiter.config.trusted_proxies);
+    if let (Some(limit), Some(ip)) = (limiter.config.per_ip, ip) {
+        if let Err(retry_after) = limiter.check(RateLimitKey::Ip(ip), limit, Instant::now()) {
+            return too_many_requests(retry_after);
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Limits requests per authenticated user (RATE_LIMIT_PER_USER)
+///
+/// Must run after the auth or admin middleware, requests without an `AuthUser` pass through.
+pub async fn user_rate_limit_middleware(
+    State(limiter): State<&'static RateLimiter>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user_id = request.extensions().get::<AuthUser>().map(|u| u.user_id);
+
+    if let (Some(limit), Some(user_id)) = (limiter.config.per_user, user_id) {
+        if let Err(retry_after) = limiter.check(RateLimitKey::User(user_id), limit, Instant::now())
+        {
+            return too_many_requests(retry_after);
+        }
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, extract::ConnectInfo, middleware, routing::get};
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    const PROXY_IP: &str = "192.0.2.1";
+
+    fn leaked_limiter(per_ip: Option<u32>, per_user: Option<u32>) -> &'static RateLimiter {
+        Box::leak(Box::new(RateLimiter::new(RateLimitConfig {
+            per_ip,
+            per_user,
+            window: Duration::from_secs(60),
+            trusted_proxies: vec![PROXY_IP.parse().unwrap()],
+        })))
+    }
+
+    /// Request from `peer`, forwarded for `forwarded_for` if given
+    fn request(peer: &str, forwarded_for: Option<&str>) -> Request {
+        let mut builder = Request::get("/");
+        if let Some(forwarded_for) = forwarded_for {
+            builder = builder.header("x-forwarded-for", forwarded_for);
+        }
+        let mut request = builder.body(Body::empty()).unwrap();
+        let addr: SocketAddr = format!("{}:443", peer).parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+        request
+    }
+
+    /// Router where every request is authenticated as the user in the X-Test-User header
+    fn app(limiter: &'static RateLimiter) -> Router {
+        Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                limiter,
+                user_rate_limit_middleware,
+            ))
+            .route_layer(middleware::from_fn(
+                |mut request: Request, next: Next| async move {
+                    let user_id = request
+                        .headers()
+                        .get("x-test-user")
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse().ok());
+                    if let Some(user_id) = user_id {
+                        request.extensions_mut().insert(AuthUser { user_id });
+                    }
+                    next.run(request).await
+                },
+            ))
+            .route_layer(middleware::from_fn_with_state(
+                limiter,
+                ip_rate_limit_middleware,
+            ))
+    }
+
+    /// Sends a request for `ip` through the trusted proxy
+    async fn send(app: &Router, ip: &str, user_id: Option<Uuid>) -> Response {
+        let mut request = request(PROXY_IP, Some(ip));
+        if let Some(user_id) = user_id {
+            request
+                .headers_mut()
+                .insert("x-test-user", user_id.to_string().parse().unwrap());
+        }
+        app.clone().oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_limit_applies_across_ips() {
+        let app = app(leaked_limiter(Some(100), Some(2)));
+        let user_id = Uuid::new_v4();
+
+        assert_eq!(
+            send(&app, "10.0.0.1", Some(user_id)).await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, "10.0.0.2", Some(user_id)).await.status(),
+            StatusCode::OK
+        );
+
+        let limited = send(&app, "10.0.0.2", Some(user_id)).await;
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(limited.headers().contains_key(RETRY_AFTER));
+        assert_eq!(
+            send(&app, "10.0.0.3", Some(user_id)).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // Other users on the same IPs are counted separately
+        assert_eq!(
+            send(&app, "10.0.0.1", Some(Uuid::new_v4())).await.status(),
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ip_limit_applies_without_user() {
+        let app = app(leaked_limiter(Some(1), Some(100)));
+
+        assert_eq!(send(&app, "10.0.0.1", None).await.status(), StatusCode::OK);
+        assert_eq!(
+            send(&app, "10.0.0.1", None).await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(send(&app, "10.0.0.2", None).await.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_client_ip_trusts_forwarded_for_only_from_proxies() {
+        let trusted: Vec<IpAddr> = vec![PROXY_IP.parse().unwrap()];
+
+        // A direct client can't pick its own address
+        assert_eq!(
+            client_ip(&request("203.0.113.7", Some("10.0.0.1")), &trusted).as_deref(),
+            Some("203.0.113.7")
+        );
+        // Through the proxy the address it appended wins over what the client sent
+        assert_eq!(
+            client_ip(&request(PROXY_IP, Some("10.0.0.1, 203.0.113.7")), &trusted).as_deref(),
+            Some("203.0.113.7")
+        );
+        // Without a usable header the proxy itself is the client
+        assert_eq!(
+            client_ip(&request(PROXY_IP, Some("unknown")), &trusted).as_deref(),
+            Some(PROXY_IP)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rotating_forwarded_for_does_not_dodge_ip_limit() {
+        let limiter = leaked_limiter(Some(1), None);
+        let app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(
+                limiter,
+                ip_rate_limit_middleware,
+            ));
+
+        let first = app
+            .clone()
+            .oneshot(request("203.0.113.7", Some("10.0.0.1")))
+            .await
+            .unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let second = app
+            .oneshot(request("203.0.113.7", Some("10.0.0.2")))
+            .await
+            .unwrap();
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_tracked_keys_are_capped() {
+        let limiter = leaked_limiter(Some(1), None);
+        let start = Instant::now();
+
+        for i in 0..MAX_TRACKED_KEYS * 2 {
+            let now = start + Duration::from_millis(i as u64);
+            assert!(
+                limiter
+                    .check(RateLimitKey::Ip(i.to_string()), 1, now)
+                    .is_ok()
+            );
+        }
+
+        let windows = limiter.windows.lock().unwrap();
+        assert!(windows.len() <= MAX_TRACKED_KEYS);
+        // The newest key is kept
+        assert!(windows.contains_key(&RateLimitKey::Ip((MAX_TRACKED_KEYS * 2 - 1).to_string())));
+    }
+
+    #[test]
+    fn test_window_resets() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            per_ip: None,
+            per_user: Some(1),
+            window: Duration::from_secs(10),
+            trusted_proxies: Vec::new(),
+        });
+        let key = RateLimitKey::User(Uuid::new_v4());
+        let start = Instant::now();
+
+        assert!(limiter.check(key.clone(), 1, start).is_ok());
+        assert_eq!(
+            limiter.check(key.clone(), 1, start + Duration::from_secs(4)),
+            Err(Duration::from_secs(6))
+        );
+        assert!(
+            limiter
+                .check(key, 1, start + Duration::from_secs(10))
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::infrastructure::database::DatabaseManager;
+
+/// Rejects mutating requests with a 503 while the database is read-only
+///
+/// The database rejects the writes itself, this only turns them into a clear error up front
+/// instead of whatever the handler makes of the failed query.
+pub async fn read_only_middleware(request: Request, next: Next) -> Response {
+    if is_mutating(request.method()) {
+        return DatabaseManager::read_only_error().into_response();
+    }
+
+    next.run(request).await
+}
+
+fn is_mutating(method: &Method) -> bool {
+    !matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, http::StatusCode, middleware, routing::get};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/api/v1/admin/users",
+                get(|| async { "users" }).post(|| async { "created" }),
+            )
+            .layer(middleware::from_fn(read_only_middleware))
+    }
+
+    #[tokio::test]
+    async fn test_reads_allowed() {
+        let response = app()
+            .oneshot(
+                Request::get("/api/v1/admin/users")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_writes_rejected() {
+        for method in [Method::POST, Method::PUT, Method::PATCH, Method::DELETE] {
+            let response = app()
+                .oneshot(
+                    Request::builder()
+                        .method(method.clone())
+                        .uri("/api/v1/admin/users")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response.status(),
+                StatusCode::SERVICE_UNAVAILABLE,
+                "{}",
+                method
+            );
+        }
+    }
+}
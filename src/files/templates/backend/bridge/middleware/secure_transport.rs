@@ -0,0 +1,221 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{StatusCode, Uri, header},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use std::{
+    env,
+    net::{IpAddr, SocketAddr},
+};
+
+use crate::infrastructure::app_error::AppError;
+
+/// What to do with plain-HTTP requests
+#[derive(Debug, Clone, PartialEq)]
+pub enum SecureTransportMode {
+    /// Allow plain HTTP (default)
+    Off,
+    /// 308 redirect to the https:// equivalent
+    Redirect,
+    /// Reject with 400 Bad Request
+    Reject,
+}
+
+/// Secure transport enforcement configuration
+#[derive(Debug, Clone)]
+pub struct SecureTransportConfig {
+    pub mode: SecureTransportMode,
+    /// Proxies whose X-Forwarded-Proto header is trusted
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl SecureTransportConfig {
+    /// Reads ENFORCE_HTTPS (off, redirect, reject) and TRUSTED_PROXIES (comma separated IPs)
+    pub fn from_env() -> Self {
+        let mode = match env::var("ENFORCE_HTTPS")
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+            .as_str()
+        {
+            "redirect" => SecureTransportMode::Redirect,
+            "reject" => SecureTransportMode::Reject,
+            _ => SecureTransportMode::Off,
+        };
+
+        Self {
+            mode,
+            trusted_proxies: trusted_proxies_from_env(),
+        }
+    }
+
+    /// Whether the request arrived over TLS, either directly or via a trusted proxy
+    pub fn is_secure(&self, request: &Request) -> bool {
+        if request.uri().scheme_str() == Some("https") {
+            return true;
+        }
+
+        // X-Forwarded-Proto is only honored from a trusted proxy, anyone else could spoof it
+        let from_trusted_proxy =
+            peer_ip(request).is_some_and(|ip| self.trusted_proxies.contains(&ip));
+        if !from_trusted_proxy {
+            return false;
+        }
+
+        request
+            .headers()
+            .get("x-forwarded-proto")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+    }
+}
+
+/// Proxies whose X-Forwarded-* headers are trusted, from TRUSTED_PROXIES (comma separated IPs)
+pub fn trusted_proxies_from_env() -> Vec<IpAddr> {
+    env::var("TRUSTED_PROXIES")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|ip| ip.trim().parse().ok())
+        .collect()
+}
+
+/// IP of the peer the request came in from
+pub fn peer_ip(request: &Request) -> Option<IpAddr> {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Redirects or rejects plain-HTTP requests, depending on the configured mode
+pub async fn secure_transport_middleware(
+    State(config): State<SecureTransportConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if config.mode == SecureTransportMode::Off || config.is_secure(&request) {
+        return next.run(request).await;
+    }
+
+    if config.mode == SecureTransportMode::Reject {
+        return AppError {
+            message: "HTTPS is required".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+        }
+        .into_response();
+    }
+
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or("/");
+
+    match host.and_then(|host| {
+        format!("https://{}{}", host, path_and_query)
+            .parse::<Uri>()
+            .ok()
+    }) {
+        Some(location) => Redirect::permanent(&location.to_string()).into_response(),
+        None => AppError {
+            message: "HTTPS is required".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+        }
+        .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware, routing::get};
+    use tower::ServiceExt;
+
+    const PROXY_IP: &str = "10.0.0.1";
+
+    fn app(mode: SecureTransportMode) -> Router {
+        let config = SecureTransportConfig {
+            mode,
+            trusted_proxies: vec![PROXY_IP.parse().unwrap()],
+        };
+
+        Router::new()
+            .route("/api/v1/auth/profile", get(|| async { "ok" }))
+            .layer(middleware::from_fn_with_state(
+                config,
+                secure_transport_middleware,
+            ))
+    }
+
+    fn request(peer: &str, forwarded_proto: Option<&str>) -> Request {
+        let mut builder = Request::get("/api/v1/auth/profile?tab=1").header("host", "example.com");
+        if let Some(proto) = forwarded_proto {
+            builder = builder.header("x-forwarded-proto", proto);
+        }
+
+        let mut request = builder.body(Body::empty()).unwrap();
+        let addr: SocketAddr = format!("{}:443", peer).parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+        request
+    }
+
+    #[tokio::test]
+    async fn test_secure_request_from_trusted_proxy_passes() {
+        let response = app(SecureTransportMode::Redirect)
+            .oneshot(request(PROXY_IP, Some("https")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_insecure_request_is_redirected() {
+        let response = app(SecureTransportMode::Redirect)
+            .oneshot(request(PROXY_IP, Some("http")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers().get(header::LOCATION).unwrap(),
+            "https://example.com/api/v1/auth/profile?tab=1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insecure_request_is_rejected() {
+        let response = app(SecureTransportMode::Reject)
+            .oneshot(request(PROXY_IP, None))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_spoofed_header_from_untrusted_peer_is_ignored() {
+        let response = app(SecureTransportMode::Reject)
+            .oneshot(request("203.0.113.7", Some("https")))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_off_mode_allows_plain_http() {
+        let response = app(SecureTransportMode::Off)
+            .oneshot(request("203.0.113.7", None))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,109 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use tracing::Instrument;
+
+use crate::infrastructure::trace_context::TraceContext;
+
+/// Continues the caller's W3C trace (or starts a new one) and runs the request inside its span
+///
+/// The trace context is added to the request extensions and is available to outgoing calls
+/// through `TraceContext::current()`.
+pub async fn trace_context_middleware(mut request: Request, next: Next) -> Response {
+    let trace_context = TraceContext::from_headers(request.headers());
+
+    let span = tracing::info_span!(
+        "http_request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        trace_id = %trace_context.trace_id,
+        span_id = %trace_context.span_id,
+        parent_id = ?trace_context.parent_id,
+    );
+
+    // Link the span to the remote parent so exported spans join the caller's trace
+    #[cfg(feature = "otlp")]
+    {
+        use opentelemetry::propagation::TextMapPropagator;
+        use opentelemetry_sdk::propagation::TraceContextPropagator;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let parent = TraceContextPropagator::new().extract(&HeaderExtractor(request.headers()));
+        let _ = span.set_parent(parent);
+    }
+
+    request.extensions_mut().insert(trace_context.clone());
+
+    trace_context
+        .scope(next.run(request).instrument(span))
+        .await
+}
+
+#[cfg(feature = "otlp")]
+struct HeaderExtractor<'a>(&'a axum::http::HeaderMap);
+
+#[cfg(feature = "otlp")]
+impl opentelemetry::propagation::Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        Extension, Router,
+        body::{Body, to_bytes},
+        http::Request,
+        middleware,
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    async fn trace_handler(Extension(context): Extension<TraceContext>) -> String {
+        // The task-local context must match the one in the extensions
+        let current = TraceContext::current().unwrap();
+        assert_eq!(current, context);
+        context.trace_id
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/trace", get(trace_handler))
+            .layer(middleware::from_fn(trace_context_middleware))
+    }
+
+    async fn response_trace_id(request: Request<Body>) -> String {
+        let response = app().oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_incoming_traceparent_continues_trace() {
+        let request = Request::get("/trace")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(
+            response_trace_id(request).await,
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_traceparent_starts_new_trace() {
+        let request = Request::get("/trace").body(Body::empty()).unwrap();
+        let trace_id = response_trace_id(request).await;
+
+        assert_eq!(trace_id.len(), 32);
+        assert_ne!(trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+    }
+}
@@ -1,19 +1,64 @@
-use axum::middleware;
+use axum::{
+    Extension,
+    middleware::{self, map_response_with_state},
+};
 use sea_orm::DatabaseConnection;
+use std::env;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::bridge::middleware::admin::admin_middleware;
+use crate::bridge::middleware::{
+    admin::admin_middleware,
+    cache::{CachePolicy, apply_cache_policy},
+    password_change::password_change_middleware,
+    rate_limit::{RATE_LIMITER, user_rate_limit_middleware},
+};
+use crate::control::services::health_snapshot_service::HEALTH_SNAPSHOTS;
+
+/// Whether the database browser routes (/database/*) are mounted (DATABASE_BROWSER_ENABLED,
+/// defaults to true)
+pub fn database_browser_enabled() -> bool {
+    env::var("DATABASE_BROWSER_ENABLED")
+        .map(|value| value.to_lowercase() != "false")
+        .unwrap_or(true)
+}
 
 pub fn admin_router(db: DatabaseConnection) -> OpenApiRouter {
+    admin_router_with_database_browser(db, database_browser_enabled())
+}
+
+/// Admin router, with the database browser routes left out entirely unless `database_browser`
+pub fn admin_router_with_database_browser(
+    db: DatabaseConnection,
+    database_browser: bool,
+) -> OpenApiRouter {
     // Admin authentication routes (no middleware needed)
     let auth_routes = OpenApiRouter::new()
         .routes(routes!(crate::bridge::handlers::admin::admin_login_handler))
         .routes(routes!(
             crate::bridge::handlers::admin::admin_logout_handler
+        ))
+        .route_layer(map_response_with_state(
+            CachePolicy::no_store(),
+            apply_cache_policy,
         ));
 
+    // Permission catalog is static between deploys, so clients may cache it
+    let catalog_routes = OpenApiRouter::new()
+        .routes(routes!(
+            crate::bridge::handlers::roles::get_permission_catalog_handler
+        ))
+        .route_layer(map_response_with_state(
+            CachePolicy::permission_catalog(),
+            apply_cache_policy,
+        ));
+
+    // Health is served from the snapshots the background task keeps refreshing
+    let health_routes = OpenApiRouter::new()
+        .routes(routes!(crate::bridge::handlers::admin::health_handler))
+        .route_layer(Extension(&*HEALTH_SNAPSHOTS));
+
     // Protected admin routes (require admin middleware)
-    let protected_routes = OpenApiRouter::new()
+    let mut protected_routes = OpenApiRouter::new()
         // Audit logs
         .routes(routes!(
             crate::bridge::handlers::admin::get_audit_logs_handler
@@ -23,7 +68,20 @@ pub fn admin_router(db: DatabaseConnection) -> OpenApiRouter {
         .routes(routes!(crate::bridge::handlers::admin::create_user_handler))
         .routes(routes!(crate::bridge::handlers::admin::get_user_handler))
         .routes(routes!(crate::bridge::handlers::admin::update_user_handler))
+        .routes(routes!(crate::bridge::handlers::admin::patch_user_handler))
         .routes(routes!(crate::bridge::handlers::admin::delete_user_handler))
+        .routes(routes!(
+            crate::bridge::handlers::admin::delete_user_confirmation_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::admin::export_user_data_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::admin::purge_user_data_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::admin::purge_user_data_confirmation_handler
+        ))
         // Session management
         .routes(routes!(
             crate::bridge::handlers::admin::get_user_sessions_handler
@@ -34,27 +92,147 @@ pub fn admin_router(db: DatabaseConnection) -> OpenApiRouter {
         .routes(routes!(
             crate::bridge::handlers::admin::invalidate_all_user_sessions_handler
         ))
+        .routes(routes!(
+            crate::bridge::handlers::admin::invalidate_all_user_sessions_confirmation_handler
+        ))
         // Role management
         .routes(routes!(crate::bridge::handlers::roles::get_roles_handler))
         .routes(routes!(crate::bridge::handlers::roles::create_role_handler))
         .routes(routes!(crate::bridge::handlers::roles::get_role_handler))
         .routes(routes!(crate::bridge::handlers::roles::update_role_handler))
+        .routes(routes!(crate::bridge::handlers::roles::patch_role_handler))
         .routes(routes!(crate::bridge::handlers::roles::delete_role_handler))
+        .routes(routes!(
+            crate::bridge::handlers::roles::delete_role_confirmation_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::roles::bulk_update_role_permissions_handler
+        ))
         .routes(routes!(
             crate::bridge::handlers::roles::check_permission_handler
         ))
-        // Database inspection
+        // Runtime settings
+        .routes(routes!(
+            crate::bridge::handlers::settings::get_settings_handler
+        ))
         .routes(routes!(
-            crate::bridge::handlers::admin::get_database_tables_handler
+            crate::bridge::handlers::settings::get_setting_handler
         ))
         .routes(routes!(
-            crate::bridge::handlers::admin::get_table_records_handler
+            crate::bridge::handlers::settings::update_setting_handler
         ))
         // System health
-        .routes(routes!(crate::bridge::handlers::admin::health_handler))
+        .merge(health_routes);
+
+    // Database inspection, not even routed when disabled
+    if database_browser {
+        protected_routes = protected_routes
+            .routes(routes!(
+                crate::bridge::handlers::admin::get_database_tables_handler
+            ))
+            .routes(routes!(
+                crate::bridge::handlers::admin::get_table_records_handler
+            ));
+    }
+
+    let protected_routes = protected_routes
+        .route_layer(map_response_with_state(
+            CachePolicy::no_store(),
+            apply_cache_policy,
+        ))
+        .merge(catalog_routes)
+        // Blocked until a required password change is done (POST /api/v1/auth/change-password)
+        .route_layer(middleware::from_fn_with_state(
+            db.clone(),
+            password_change_middleware,
+        ))
+        // Runs after admin_middleware has set AuthUser
+        .route_layer(middleware::from_fn_with_state(
+            &*RATE_LIMITER,
+            user_rate_limit_middleware,
+        ))
         // Combined auth and admin middleware
         .route_layer(middleware::from_fn_with_state(db.clone(), admin_middleware));
 
     // Combine auth and protected routes
     auth_routes.merge(protected_routes).with_state(db)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::memory_db;
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    async fn app(database_browser: bool) -> (Router, utoipa::openapi::OpenApi) {
+        let db = memory_db().await;
+        let (router, api) = OpenApiRouter::new()
+            .nest(
+                "/api/v1/admin",
+                admin_router_with_database_browser(db, database_browser),
+            )
+            .split_for_parts();
+        (router, api)
+    }
+
+    async fn status(router: &Router, uri: &str) -> StatusCode {
+        router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_database_browser_disabled_routes_are_absent() {
+        let (router, api) = app(false).await;
+
+        for uri in [
+            "/api/v1/admin/database/tables",
+            "/api/v1/admin/database/tables/users",
+        ] {
+            assert_eq!(status(&router, uri).await, StatusCode::NOT_FOUND, "{}", uri);
+        }
+        assert!(
+            api.paths
+                .paths
+                .keys()
+                .all(|path| !path.starts_with("/api/v1/admin/database"))
+        );
+
+        // The rest of the admin API is still mounted
+        assert_eq!(
+            status(&router, "/api/v1/admin/health").await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[tokio::test]
+    async fn test_database_browser_enabled_routes_are_mounted() {
+        let (router, api) = app(true).await;
+
+        // Routed, so the admin middleware answers instead of a 404
+        for uri in [
+            "/api/v1/admin/database/tables",
+            "/api/v1/admin/database/tables/users",
+        ] {
+            assert_eq!(
+                status(&router, uri).await,
+                StatusCode::UNAUTHORIZED,
+                "{}",
+                uri
+            );
+        }
+        assert!(
+            api.paths
+                .paths
+                .contains_key("/api/v1/admin/database/tables")
+        );
+    }
+}
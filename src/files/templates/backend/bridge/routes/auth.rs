@@ -1,22 +1,60 @@
-use axum::middleware;
+use axum::middleware::{self, map_response_with_state};
 use sea_orm::DatabaseConnection;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-use crate::bridge::middleware::auth::auth_middleware;
+use crate::bridge::middleware::{
+    auth::auth_middleware,
+    cache::{CachePolicy, apply_cache_policy},
+    password_change::password_change_middleware,
+    rate_limit::{RATE_LIMITER, user_rate_limit_middleware},
+};
 
 pub fn auth_router(db: DatabaseConnection) -> OpenApiRouter {
     // Routes that don't need authentication
     let public_routes = OpenApiRouter::new()
         .routes(routes!(crate::bridge::handlers::auth::register_handler))
         .routes(routes!(crate::bridge::handlers::auth::login_handler))
+        .routes(routes!(crate::bridge::handlers::auth::refresh_handler))
         .routes(routes!(crate::bridge::handlers::auth::logout_handler))
-        .routes(routes!(crate::bridge::handlers::auth::verify_email_handler));
+        .routes(routes!(crate::bridge::handlers::auth::verify_email_handler))
+        .routes(routes!(
+            crate::bridge::handlers::auth::resend_verification_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::auth::confirm_email_change_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::auth::request_password_reset_handler
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::auth::confirm_password_reset_handler
+        ));
 
     // Routes that need authentication
     let protected_routes = OpenApiRouter::new()
         .routes(routes!(crate::bridge::handlers::auth::profile_handler))
+        .routes(routes!(crate::bridge::handlers::auth::me_handler))
+        // Blocked until a required password change is done
+        .route_layer(middleware::from_fn_with_state(
+            db.clone(),
+            password_change_middleware,
+        ))
+        .routes(routes!(
+            crate::bridge::handlers::auth::change_password_handler
+        ))
+        // Runs after auth_middleware has set AuthUser
+        .route_layer(middleware::from_fn_with_state(
+            &*RATE_LIMITER,
+            user_rate_limit_middleware,
+        ))
         .route_layer(middleware::from_fn_with_state(db.clone(), auth_middleware));
 
-    // Combine both route groups - retains the middleware layers
-    public_routes.merge(protected_routes).with_state(db)
+    // Combine both route groups - retains the middleware layers; auth responses are never cached
+    public_routes
+        .merge(protected_routes)
+        .route_layer(map_response_with_state(
+            CachePolicy::no_store(),
+            apply_cache_policy,
+        ))
+        .with_state(db)
 }
@@ -1,2 +1,5 @@
+#[cfg(feature = "admin")]
 pub mod admin;
 pub mod auth;
+pub mod setup;
+pub mod system;
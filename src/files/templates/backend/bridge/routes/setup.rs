@@ -0,0 +1,15 @@
+use axum::middleware::map_response_with_state;
+use sea_orm::DatabaseConnection;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::bridge::middleware::cache::{CachePolicy, apply_cache_policy};
+
+pub fn setup_router(db: DatabaseConnection) -> OpenApiRouter {
+    OpenApiRouter::new()
+        .routes(routes!(crate::bridge::handlers::setup::setup_handler))
+        .route_layer(map_response_with_state(
+            CachePolicy::no_store(),
+            apply_cache_policy,
+        ))
+        .with_state(db)
+}
@@ -0,0 +1,33 @@
+use axum::{Router, middleware::map_response_with_state, routing::post};
+use std::{path::PathBuf, sync::Arc};
+use utoipa::openapi::OpenApi;
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::bridge::{
+    handlers::system::OpenApiSpecState,
+    middleware::cache::{CachePolicy, apply_cache_policy},
+};
+
+pub fn system_router() -> OpenApiRouter {
+    OpenApiRouter::new()
+        .routes(routes!(crate::bridge::handlers::system::version_handler))
+        // A redeploy changes the answer, so it's never cached
+        .route_layer(map_response_with_state(
+            CachePolicy::no_store(),
+            apply_cache_policy,
+        ))
+}
+
+/// Development-only routes, POST /api/v1/dev/openapi-spec rewrites the frontend's spec file at
+/// `spec_path`
+pub fn dev_router(api: Arc<OpenApi>, spec_path: PathBuf) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/dev/openapi-spec",
+            post(crate::bridge::handlers::system::write_openapi_spec_handler),
+        )
+        .with_state(OpenApiSpecState {
+            api,
+            path: Arc::new(spec_path),
+        })
+}
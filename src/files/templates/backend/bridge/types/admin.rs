@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
+use crate::infrastructure::pagination::{Paginated, PaginatedEntity};
+
 pub const ADMIN_TAG: &str = "Admin";
 
 // Admin Authentication
@@ -12,7 +14,26 @@ pub struct AdminLoginRequest {
 
 #[derive(Serialize, ToSchema)]
 pub struct AdminLoginResponse {
-    pub token: String,
+    /// Left out when the token is set as a cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    pub admin_id: String,
+    pub email: String,
+    /// When the token expires, the session cookie lives as long
+    #[serde(skip)]
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+// First-run Setup
+#[derive(Deserialize, ToSchema)]
+pub struct SetupRequest {
+    pub email: String,
+    pub password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SetupResponse {
+    pub message: String,
     pub admin_id: String,
     pub email: String,
 }
@@ -22,8 +43,23 @@ pub struct AdminLoginResponse {
 pub struct PaginationMeta {
     pub page: u64,
     pub limit: u64,
-    pub total: u64,
-    pub total_pages: u64,
+    /// Null when counting was skipped with `with_total=false`
+    pub total: Option<u64>,
+    pub total_pages: Option<u64>,
+    /// Whether another page follows this one
+    pub has_more: bool,
+}
+
+impl PaginationMeta {
+    pub fn new(page: u64, limit: u64, total: Option<u64>, has_more: bool) -> Self {
+        Self {
+            page,
+            limit,
+            total,
+            total_pages: total.map(|total| total.div_ceil(limit)),
+            has_more,
+        }
+    }
 }
 
 #[derive(Serialize, ToSchema)]
@@ -37,13 +73,15 @@ pub struct PaginatedResponse<T> {
 pub struct LogsQueryParams {
     #[serde(default = "default_page")]
     pub page: u64,
-    #[serde(default = "default_limit")]
-    pub limit: u64,
+    pub limit: Option<u64>,
     pub method: Option<String>,
     pub status_code: Option<i32>,
     pub user_id: Option<String>,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
+    /// Count the matching entries for total and total_pages, false skips the count on large tables
+    #[serde(default = "default_with_total")]
+    pub with_total: bool,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -67,9 +105,11 @@ pub struct AuditLogResponse {
 pub struct UsersQueryParams {
     #[serde(default = "default_page")]
     pub page: u64,
-    #[serde(default = "default_limit")]
-    pub limit: u64,
+    pub limit: Option<u64>,
     pub search: Option<String>,
+    /// Count the matching users for total and total_pages, false skips the count on large tables
+    #[serde(default = "default_with_total")]
+    pub with_total: bool,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -77,6 +117,12 @@ pub struct CreateUserRequest {
     pub email: String,
     pub password: String,
     pub role_id: Option<i32>,
+    /// Email the user a verification link, defaults to true. Turning it off requires mark_verified
+    #[serde(default = "default_send_verification")]
+    pub send_verification: bool,
+    /// Create the account already verified, no verification email is sent
+    #[serde(default)]
+    pub mark_verified: bool,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -86,6 +132,35 @@ pub struct UpdateUserRequest {
     pub role_id: Option<i32>,
 }
 
+/// Partial user update (PATCH), omitted fields are left unchanged
+#[derive(Deserialize, ToSchema)]
+pub struct PatchUserRequest {
+    /// New email, can't be null
+    #[serde(default, deserialize_with = "deserialize_patch_field")]
+    #[schema(value_type = Option<String>)]
+    pub email: Option<Option<String>>,
+    /// New password, can't be null
+    #[serde(default, deserialize_with = "deserialize_patch_field")]
+    #[schema(value_type = Option<String>)]
+    pub password: Option<Option<String>>,
+    /// Role to assign, null removes the user's role
+    #[serde(default, deserialize_with = "deserialize_patch_field")]
+    #[schema(value_type = Option<i32>, nullable)]
+    pub role_id: Option<Option<i32>>,
+}
+
+/// Keeps an explicit null apart from an omitted field in PATCH requests
+///
+/// With `#[serde(default)]` an omitted field is `None`, `null` is `Some(None)` and a value is
+/// `Some(Some(value))`.
+fn deserialize_patch_field<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 #[derive(Serialize, ToSchema)]
 pub struct UserResponse {
     pub id: String,
@@ -106,18 +181,19 @@ pub struct DatabaseTableResponse {
 pub struct TableRecordsQueryParams {
     #[serde(default = "default_page")]
     pub page: u64,
-    #[serde(default = "default_limit")]
-    pub limit: u64,
+    pub limit: Option<u64>,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct TableRecordResponse {
     pub columns: Vec<String>,
     pub records: Vec<Vec<serde_json::Value>>,
+    /// Whether another page follows this one
+    pub has_more: bool,
 }
 
 // Database Performance Metrics
-#[derive(Serialize, ToSchema)]
+#[derive(Clone, Serialize, ToSchema)]
 pub struct DatabasePerformanceResponse {
     pub total_queries: u64,
     pub avg_execution_time_ms: f64,
@@ -132,17 +208,25 @@ pub struct DatabasePerformanceResponse {
 }
 
 // System Health
-#[derive(Serialize, ToSchema)]
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct HealthQueryParams {
+    /// Compute the health status live instead of returning the latest background snapshot
+    #[serde(default)]
+    pub fresh: bool,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub timestamp: String,
     pub uptime: String,
-    pub cpu_usage: f32,
-    pub memory_usage: f32,
+    /// Usage percentages are null when the metric is unavailable, the status is then "Unknown"
+    pub cpu_usage: Option<f32>,
+    pub memory_usage: Option<f32>,
     pub memory_total: String,
     pub memory_used: String,
     pub memory_available: String,
-    pub disk_usage: f32,
+    pub disk_usage: Option<f32>,
     pub disk_total: String,
     pub disk_used: String,
     pub disk_available: String,
@@ -172,6 +256,19 @@ pub struct HealthResponse {
     pub server_port: u16,
     pub server_protocol: String,
     pub environment: String,
+    // Background Failures
+    pub background_failures: BackgroundFailureCounts,
+    /// Email sends that failed while a request waited on them
+    pub request_email_failures: u64,
+}
+
+/// Failures since startup of operations that run off the request path
+#[derive(Clone, Serialize, ToSchema)]
+pub struct BackgroundFailureCounts {
+    pub audit_log_insert: u64,
+    pub email_send: u64,
+    pub session_activity_update: u64,
+    pub last_login_update: u64,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -189,7 +286,7 @@ pub struct SystemInfoResponse {
 }
 
 /// Role response
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RoleResponse {
     pub id: i32,
     pub name: String,
@@ -215,11 +312,29 @@ pub struct UpdateRoleRequest {
     pub permissions: Option<Vec<String>>,
 }
 
+/// Partial role update (PATCH), omitted fields are left unchanged
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PatchRoleRequest {
+    /// New name, can't be null
+    #[serde(default, deserialize_with = "deserialize_patch_field")]
+    #[schema(value_type = Option<String>)]
+    pub name: Option<Option<String>>,
+    /// New description, null clears it
+    #[serde(default, deserialize_with = "deserialize_patch_field")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub description: Option<Option<String>>,
+    /// Replaces all permissions of the role, can't be null
+    #[serde(default, deserialize_with = "deserialize_patch_field")]
+    #[schema(value_type = Option<Vec<String>>)]
+    pub permissions: Option<Option<Vec<String>>>,
+}
+
 /// Role query parameters
 #[derive(Debug, Deserialize, ToSchema, IntoParams)]
 pub struct RolesQueryParams {
+    #[serde(default = "default_page")]
     pub page: u64,
-    pub limit: u64,
+    pub limit: Option<u64>,
     pub search: Option<String>,
 }
 
@@ -238,6 +353,125 @@ pub struct PermissionCheckResponse {
     pub required_permission: String,
 }
 
+/// Permission catalog entry
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PermissionCatalogEntry {
+    pub name: String,
+    pub category: String,
+    pub description: String,
+}
+
+/// Permissions to assign to a single role in a bulk update
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RolePermissionAssignment {
+    pub role_id: i32,
+    /// The role's complete permission set after the update
+    pub permissions: Vec<String>,
+}
+
+/// Bulk role permission update request
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkRolePermissionsRequest {
+    pub assignments: Vec<RolePermissionAssignment>,
+    /// Only compute the diff, don't apply it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Permission changes for a single role
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RolePermissionChange {
+    pub role_id: i32,
+    pub role_name: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Bulk role permission update response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkRolePermissionsResponse {
+    /// False for dry runs
+    pub applied: bool,
+    pub roles_changed: usize,
+    pub changes: Vec<RolePermissionChange>,
+}
+
+/// Confirmation token for a destructive admin action
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfirmationResponse {
+    /// Send back in the X-Confirmation-Token header of the destructive request
+    pub confirmation_token: String,
+    pub action: String,
+    /// Human readable description of what the action will do
+    pub impact: String,
+    pub expires_at: String,
+}
+
+/// Runtime setting stored in the settings table
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SettingResponse {
+    pub key: String,
+    /// Any JSON value
+    #[schema(value_type = Object)]
+    pub value: serde_json::Value,
+    /// ID of the admin who last changed it, None if set by the application
+    pub updated_by: Option<String>,
+    pub updated_at: String,
+}
+
+/// Request to create or update a setting
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSettingRequest {
+    /// Any JSON value
+    #[schema(value_type = Object)]
+    pub value: serde_json::Value,
+}
+
+/// Everything the system holds about a user, for data subject access requests
+#[derive(Serialize, ToSchema)]
+pub struct UserDataExport {
+    pub exported_at: String,
+    pub user: ExportedUser,
+    /// All sessions, including expired and invalidated ones
+    pub sessions: Vec<ExportedSession>,
+    /// Audit log entries for the user's requests, oldest first
+    pub audit_logs: Vec<AuditLogResponse>,
+}
+
+/// User record in a data export, without the password hash
+#[derive(Serialize, ToSchema)]
+pub struct ExportedUser {
+    pub id: String,
+    pub email: String,
+    pub created_at: Option<String>,
+    pub last_login: Option<String>,
+    pub email_verified: bool,
+    pub role_name: Option<String>,
+}
+
+/// Session in a data export, without the session token
+#[derive(Serialize, ToSchema)]
+pub struct ExportedSession {
+    pub id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: Option<String>,
+    pub last_activity: Option<String>,
+    pub expires_at: String,
+    pub is_active: bool,
+}
+
+/// Result of purging a user's data
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UserPurgeResponse {
+    pub user_id: String,
+    pub sessions_deleted: u64,
+    /// Audit log entries removed, when purging in delete mode
+    pub audit_logs_deleted: u64,
+    /// Audit log entries kept with personal data scrubbed, when purging in anonymize mode
+    pub audit_logs_anonymized: u64,
+}
+
 /// Session response for admin endpoints
 #[derive(Serialize, ToSchema)]
 pub struct SessionResponse {
@@ -249,6 +483,18 @@ pub struct SessionResponse {
     pub last_activity: String,
     pub expires_at: String,
     pub is_current: bool, // If this is the current session
+    /// active, invalidated or expired
+    pub status: String,
+}
+
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SessionsQueryParams {
+    /// Also list invalidated and expired sessions, for reviewing recently revoked ones
+    #[serde(default)]
+    pub include_inactive: bool,
+    /// Only list inactive sessions created or last used after this RFC 3339 timestamp, defaults
+    /// to 30 days ago
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Request to invalidate a session
@@ -269,8 +515,77 @@ pub struct SessionInvalidationResponse {
 fn default_page() -> u64 {
     1
 }
-fn default_limit() -> u64 {
-    25
+
+fn default_with_total() -> bool {
+    true
+}
+
+fn default_send_verification() -> bool {
+    true
+}
+
+// Page size limits, see infrastructure::pagination
+impl Paginated for LogsQueryParams {
+    const ENTITY: PaginatedEntity = PaginatedEntity::AuditLogs;
+
+    fn requested_page(&self) -> u64 {
+        self.page
+    }
+
+    fn requested_limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    fn with_total(&self) -> bool {
+        self.with_total
+    }
+}
+
+impl Paginated for UsersQueryParams {
+    const ENTITY: PaginatedEntity = PaginatedEntity::Users;
+
+    fn requested_page(&self) -> u64 {
+        self.page
+    }
+
+    fn requested_limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    fn with_total(&self) -> bool {
+        self.with_total
+    }
+}
+
+impl Paginated for RolesQueryParams {
+    const ENTITY: PaginatedEntity = PaginatedEntity::Roles;
+
+    fn requested_page(&self) -> u64 {
+        self.page
+    }
+
+    fn requested_limit(&self) -> Option<u64> {
+        self.limit
+    }
+}
+
+impl Paginated for TableRecordsQueryParams {
+    const ENTITY: PaginatedEntity = PaginatedEntity::TableRecords;
+
+    fn requested_page(&self) -> u64 {
+        self.page
+    }
+
+    fn requested_limit(&self) -> Option<u64> {
+        self.limit
+    }
+}
+
+/// Live feed query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct WebSocketQueryParams {
+    /// Only replay buffered events logged after this RFC 3339 timestamp
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 /// Admin user information for downstream handlers
@@ -24,7 +24,18 @@ pub struct LoginRequest {
 
 #[derive(Serialize, ToSchema)]
 pub struct LoginResponse {
-    pub token: String,
+    /// Short-lived access token, left out when it's set as a cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// Exchanged for a new token at /auth/refresh, valid once, left out when it's set as a cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+}
+
+/// Only read when the request has no refresh cookie
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -55,6 +66,20 @@ pub struct ProfileResponse {
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+/// Profile of the authenticated user with their effective permissions
+#[derive(Serialize, ToSchema)]
+pub struct MeResponse {
+    pub id: String,
+    pub email: String,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Name of the user's role, None if they have no role
+    #[schema(example = "admin", nullable = true)]
+    pub role: Option<String>,
+    /// Effective permissions granted by the role, sorted
+    #[schema(example = json!(["admin:read", "user:profile"]))]
+    pub permissions: Vec<String>,
+}
+
 // JWT token extractor
 #[derive(Clone, ToSchema)]
 pub struct AuthUser {
@@ -64,7 +89,15 @@ pub struct AuthUser {
 
 #[derive(Deserialize, ToSchema)]
 pub struct VerifyEmailRequest {
-    pub user_id: String,
+    /// Token from the verification link
+    pub token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ResendVerificationRequest {
+    /// Email of the account to verify
+    #[schema(example = "user@example.com")]
+    pub email: String,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -72,3 +105,37 @@ pub struct VerifyEmailResponse {
     pub message: String,
     pub success: bool,
 }
+
+#[derive(Deserialize, ToSchema)]
+pub struct ConfirmEmailChangeRequest {
+    pub token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PasswordResetRequest {
+    /// Email of the account to reset
+    #[schema(example = "user@example.com")]
+    pub email: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmPasswordResetRequest {
+    /// Token from the password reset link
+    pub token: String,
+
+    /// The new password, at least 12 characters with uppercase, lowercase and numeric characters
+    #[schema(example = "NewSecurePassword123")]
+    pub new_password: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordRequest {
+    /// The password being replaced
+    pub current_password: String,
+
+    /// The new password, at least 12 characters with uppercase, lowercase and numeric characters
+    #[schema(example = "NewSecurePassword123")]
+    pub new_password: String,
+}
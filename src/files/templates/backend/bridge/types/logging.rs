@@ -8,3 +8,11 @@ pub struct LoggingInfo {
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
 }
+
+/// Id the request logging middleware assigned to the request, matches its log lines
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+/// Response extension marking a 500 produced by a caught handler panic, with the panic message
+#[derive(Clone, Debug)]
+pub struct HandlerPanic(pub String);
@@ -0,0 +1,35 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::infrastructure::build_info::BuildInfo;
+
+/// OpenAPI tag for system endpoints
+pub const SYSTEM_TAG: &str = "System";
+
+#[derive(Serialize, ToSchema)]
+pub struct VersionResponse {
+    pub name: String,
+    pub version: String,
+    /// Commit the binary was built from, null if it wasn't built from a git checkout
+    pub git_sha: Option<String>,
+}
+
+impl From<BuildInfo> for VersionResponse {
+    fn from(build_info: BuildInfo) -> Self {
+        Self {
+            name: build_info.name.to_string(),
+            version: build_info.version.to_string(),
+            git_sha: build_info.git_sha.map(str::to_string),
+        }
+    }
+}
+
+/// Result of writing the OpenAPI spec for the frontend client generator
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenApiSpecWriteResponse {
+    /// File the spec was written to
+    pub path: String,
+    /// False if the file already held the current spec
+    pub changed: bool,
+}
@@ -1,28 +1,138 @@
 use base64::Engine;
-use sea_orm::*;
+use sea_orm::{
+    sea_query::{Asterisk, Expr, Query, SimpleExpr},
+    *,
+};
 use uuid::Uuid;
 
 use crate::{
     bridge::types::admin::*,
     control::services::{
-        database_service::DatabaseMonitorService, session_service::SessionService,
-        system_monitor::SystemMonitorService, user_service::UserService,
+        database_service::DatabaseMonitorService,
+        login_attempt_service::{LoginAttemptConfig, LoginAttemptService},
+        permission_service::PermissionService,
+        role_cache_service::{ROLE_CACHE, RoleCache},
+        security_event_service::{SECURITY_EVENTS, SecurityEventKind},
+        session_service::{DEFAULT_INACTIVE_SESSION_WINDOW_DAYS, SessionService},
+        system_monitor::SystemMonitorService,
+        token_service::{TokenConfig, TokenService},
+        user_service::{NewUserOptions, UserService},
+    },
+    domain::{permissions::Permission, user::User, validation::*},
+    entity::models::{audit_logs, roles, settings, user_sessions, users},
+    infrastructure::{
+        app_error::AppError,
+        background_failures::{BACKGROUND_FAILURES, BackgroundOperation},
+        clock::system_clock,
+        column_masking::{ColumnMask, ColumnMaskingPolicy, REDACTED_VALUE},
+        jwt_claims::Claims,
+        pagination::{Paginated, split_page},
+        session_policy::SessionPolicyConfig,
+        singleflight::SingleFlight,
+        timestamps::update_touched,
     },
-    domain::validation::*,
-    entity::models::{audit_logs, roles, users},
-    infrastructure::{app_error::AppError, jwt_claims::Claims},
 };
 use axum::http::StatusCode;
+use futures_util::{Stream, StreamExt, stream};
 use jsonwebtoken::{EncodingKey, Header, encode};
+use std::collections::HashSet;
 use std::env;
+#[cfg(feature = "email")]
+use std::sync::atomic::Ordering;
+
+/// Audit log entries fetched per query when exporting user data
+const EXPORT_PAGE_SIZE: u64 = 500;
+
+/// Largest integer a JavaScript number holds exactly (2^53 - 1)
+pub const MAX_SAFE_INTEGER: i64 = 9_007_199_254_740_991;
+
+/// What purging a user does with their audit log entries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditLogPurgeMode {
+    /// Delete the entries outright
+    Delete,
+    /// Keep the entries for aggregate metrics but scrub who made the request
+    Anonymize,
+}
+
+impl AuditLogPurgeMode {
+    /// Reads USER_PURGE_AUDIT_LOGS ("delete" or "anonymize", defaults to anonymize)
+    pub fn from_env() -> Self {
+        match env::var("USER_PURGE_AUDIT_LOGS").as_deref() {
+            Ok("delete") => AuditLogPurgeMode::Delete,
+            _ => AuditLogPurgeMode::Anonymize,
+        }
+    }
+}
+
+/// Default cap on the number of roles
+pub const DEFAULT_MAX_ROLES: u64 = 100;
+
+/// Default cap on the number of permissions in a single role
+pub const DEFAULT_MAX_PERMISSIONS_PER_ROLE: usize = 50;
+
+/// Caps on roles and their permissions, which are deserialized on every permission check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoleLimits {
+    pub max_roles: u64,
+    pub max_permissions_per_role: usize,
+}
+
+impl RoleLimits {
+    /// Reads MAX_ROLES and MAX_PERMISSIONS_PER_ROLE
+    pub fn from_env() -> Self {
+        Self {
+            max_roles: env::var("MAX_ROLES")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_MAX_ROLES),
+            max_permissions_per_role: env::var("MAX_PERMISSIONS_PER_ROLE")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_MAX_PERMISSIONS_PER_ROLE),
+        }
+    }
+
+    /// Rejects a permission list longer than the cap
+    pub fn check_permission_count(&self, permissions: &[String]) -> Result<(), AppError> {
+        if permissions.len() > self.max_permissions_per_role {
+            return Err(AppError {
+                message: format!(
+                    "A role can have at most {} permissions, got {}",
+                    self.max_permissions_per_role,
+                    permissions.len()
+                ),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Value of a PATCH field that can't be cleared, explicit null is rejected with a 400
+fn required_patch_field<T>(field: &str, value: Option<Option<T>>) -> Result<Option<T>, AppError> {
+    match value {
+        Some(None) => Err(AppError {
+            message: format!("{} cannot be null", field),
+            status_code: StatusCode::BAD_REQUEST,
+        }),
+        value => Ok(value.flatten()),
+    }
+}
+
+/// Concurrent health checks share one sampling of the system and database
+static HEALTH_CHECKS: once_cell::sync::Lazy<SingleFlight<HealthResponse>> =
+    once_cell::sync::Lazy::new(SingleFlight::default);
 
 /// Service for admin-related business operations
 pub struct AdminService;
 
 impl AdminService {
     /// Authenticates an admin user and returns a JWT token
-    /// Specifically for "super admin" privileges, defined with the "*" permission
-    /// Other "admin" permissions like "admin:read" are handled by the user_can_perform_action function
+    /// Credentials are verified before the "admin:read" check, and a bad password and a missing
+    /// admin permission both return the same 401 so the endpoint can't be used to find admin emails
+    /// Other "admin" permissions like "admin:write" are handled by the user_can_perform_action function
     ///
     /// Example:
     /// ```rust,no_run
@@ -40,17 +150,52 @@ impl AdminService {
         // Validate input
         validate_login_input(&login.email, &login.password)?;
 
+        // Reject IPs locked out after repeated suspicious attempts (ADMIN_LOGIN_LOCKOUT)
+        SECURITY_EVENTS.check_lockout(ip_address.as_deref())?;
+
+        // Reject emails locked out for this IP after repeated failed logins (LOGIN_MAX_ATTEMPTS)
+        let lockout = LoginAttemptConfig::from_env();
+        let ip = ip_address.as_deref();
+        LoginAttemptService::check(db, lockout, &login.email, ip, system_clock()).await?;
+
         // Find user by email
-        let user = UserService::find_user_by_email(db, &login.email)
-            .await?
-            .ok_or(AppError {
-                message: "Invalid credentials".to_string(),
-                status_code: StatusCode::UNAUTHORIZED,
-            })?;
+        let user = match UserService::find_user_by_email(db, &login.email).await? {
+            Some(user) => user,
+            None => {
+                SECURITY_EVENTS
+                    .record(
+                        SecurityEventKind::AdminLoginUnknownEmail,
+                        ip_address.as_deref(),
+                    )
+                    .await;
+                LoginAttemptService::record_failure(db, lockout, &login.email, ip, system_clock())
+                    .await?;
+                return Err(AppError {
+                    message: "Invalid credentials".to_string(),
+                    status_code: StatusCode::UNAUTHORIZED,
+                });
+            }
+        };
 
         // Verify password
         let is_valid = UserService::verify_password(&user, &login.password)?;
         if !is_valid {
+            LoginAttemptService::record_failure(db, lockout, &login.email, ip, system_clock())
+                .await?;
+            return Err(AppError {
+                message: "Invalid credentials".to_string(),
+                status_code: StatusCode::UNAUTHORIZED,
+            });
+        }
+        LoginAttemptService::clear(db, &login.email, ip).await?;
+
+        // Only now check admin rights, a non-admin gets the same error as a bad password
+        let is_admin =
+            PermissionService::has_permission(db, user.id, &Permission::AdminRead).await?;
+        if !is_admin {
+            SECURITY_EVENTS
+                .record(SecurityEventKind::AdminLoginNonAdmin, ip_address.as_deref())
+                .await;
             return Err(AppError {
                 message: "Invalid credentials".to_string(),
                 status_code: StatusCode::UNAUTHORIZED,
@@ -59,14 +204,15 @@ impl AdminService {
 
         // Generate session ID and JWT token
         let session_id = Uuid::new_v4();
-        let jwt_secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
+        let jwt_secret = TokenService::jwt_secret()?;
         let encoding_key = EncodingKey::from_secret(jwt_secret.as_ref());
 
-        let claims = Claims {
-            sub: user.id.to_string(),
-            exp: (chrono::Utc::now() + chrono::Duration::hours(24)).timestamp() as usize,
-            session_id: session_id.to_string(),
-        };
+        // Expires no later than the session
+        let policy =
+            SessionService::session_policy_for_user(db, SessionPolicyConfig::get(), user.id)
+                .await?;
+        let expires_at = TokenConfig::get().jwt_expires_at(chrono::Utc::now(), policy.expiry_hours);
+        let claims = Claims::for_session(&user.id, &session_id, expires_at.timestamp() as usize);
 
         let token = encode(&Header::default(), &claims, &encoding_key).map_err(|_| AppError {
             message: "Failed to generate token".to_string(),
@@ -74,8 +220,9 @@ impl AdminService {
         })?;
 
         // Create session record
-        SessionService::create_session(
+        SessionService::create_session_with_policy(
             db,
+            policy,
             user.id,
             user_agent,
             ip_address,
@@ -84,9 +231,10 @@ impl AdminService {
         .await?;
 
         Ok(AdminLoginResponse {
-            token,
+            token: Some(token),
             admin_id: user.id.to_string(),
             email: user.email,
+            expires_at,
         })
     }
 
@@ -95,7 +243,7 @@ impl AdminService {
         db: &DatabaseConnection,
         params: LogsQueryParams,
     ) -> Result<PaginatedResponse<AuditLogResponse>, AppError> {
-        let offset = (params.page - 1) * params.limit;
+        let (page, limit, offset) = (params.page(), params.limit(), params.offset()?);
 
         // Build query with filters
         let mut query = audit_logs::Entity::find();
@@ -126,52 +274,34 @@ impl AdminService {
             }
         }
 
-        // Get total count
-        let total = query.clone().count(db).await.map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
+        // Get total count, unless skipped
+        let total = if params.with_total {
+            Some(query.clone().count(db).await.map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?)
+        } else {
+            None
+        };
 
-        // Get paginated results
+        // Get paginated results, one extra row tells whether another page follows
         let logs = query
             .order_by_desc(audit_logs::Column::Timestamp)
-            .offset(offset as u64)
-            .limit(params.limit as u64)
+            .offset(offset)
+            .limit(limit.saturating_add(1))
             .all(db)
             .await
             .map_err(|e| AppError {
                 message: format!("Database error: {}", e),
                 status_code: StatusCode::INTERNAL_SERVER_ERROR,
             })?;
+        let (logs, has_more) = split_page(logs, limit);
 
-        let data = logs
-            .into_iter()
-            .map(|log| AuditLogResponse {
-                id: log.id.to_string(),
-                timestamp: log.timestamp.map(|t| t.to_rfc3339()),
-                method: log.method,
-                path: log.path,
-                status_code: log.status_code,
-                response_time_ms: log.response_time_ms,
-                user_id: log.user_id.map(|id| id.to_string()),
-                ip_address: log.ip_address,
-                user_agent: log.user_agent,
-                request_body: log.request_body,
-                response_body: log.response_body,
-                error_message: log.error_message,
-            })
-            .collect();
-
-        let total_pages = (total + params.limit - 1) / params.limit;
+        let data = logs.into_iter().map(Self::to_audit_log_response).collect();
 
         Ok(PaginatedResponse {
             data,
-            pagination: PaginationMeta {
-                page: params.page,
-                limit: params.limit,
-                total,
-                total_pages,
-            },
+            pagination: PaginationMeta::new(page, limit, total, has_more),
         })
     }
 
@@ -180,7 +310,16 @@ impl AdminService {
         db: &DatabaseConnection,
         params: UsersQueryParams,
     ) -> Result<PaginatedResponse<UserResponse>, AppError> {
-        let offset = (params.page - 1) * params.limit;
+        Self::get_users_with_cache(db, params, &ROLE_CACHE).await
+    }
+
+    /// Get paginated users with filtering, looking up role names in the given roles cache
+    pub async fn get_users_with_cache(
+        db: &DatabaseConnection,
+        params: UsersQueryParams,
+        role_cache: &RoleCache,
+    ) -> Result<PaginatedResponse<UserResponse>, AppError> {
+        let (page, limit, offset) = (params.page(), params.limit(), params.offset()?);
 
         // Build query with filters
         let mut query = users::Entity::find();
@@ -189,28 +328,34 @@ impl AdminService {
             query = query.filter(users::Column::Email.contains(&search));
         }
 
-        // Get total count
-        let total = query.clone().count(db).await.map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
+        // Get total count, unless skipped
+        let total = if params.with_total {
+            Some(query.clone().count(db).await.map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?)
+        } else {
+            None
+        };
 
-        // Get paginated results
+        // Get paginated results, one extra row tells whether another page follows
         let users = query
             .order_by_desc(users::Column::CreatedAt)
-            .offset(offset as u64)
-            .limit(params.limit as u64)
+            .offset(offset)
+            .limit(limit.saturating_add(1))
             .all(db)
             .await
             .map_err(|e| AppError {
                 message: format!("Database error: {}", e),
                 status_code: StatusCode::INTERNAL_SERVER_ERROR,
             })?;
+        let (users, has_more) = split_page(users, limit);
 
-        let roles = roles::Entity::find().all(db).await.map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
+        // Only the roles of the users on this page
+        let mut role_ids: Vec<i32> = users.iter().filter_map(|user| user.role_id).collect();
+        role_ids.sort_unstable();
+        role_ids.dedup();
+        let role_names = role_cache.role_names(db, &role_ids).await?;
 
         let data = users
             .into_iter()
@@ -219,23 +364,15 @@ impl AdminService {
                 email: user.email,
                 created_at: user.created_at.map(|t| t.to_rfc3339()),
                 role_id: user.role_id,
-                role_name: roles
-                    .iter()
-                    .find(|role| role.id == user.role_id.unwrap_or_default())
-                    .map(|role| role.name.clone()),
+                role_name: user
+                    .role_id
+                    .and_then(|role_id| role_names.get(&role_id).cloned()),
             })
             .collect();
 
-        let total_pages = (total + params.limit - 1) / params.limit;
-
         Ok(PaginatedResponse {
             data,
-            pagination: PaginationMeta {
-                page: params.page,
-                limit: params.limit,
-                total,
-                total_pages,
-            },
+            pagination: PaginationMeta::new(page, limit, total, has_more),
         })
     }
 
@@ -265,14 +402,23 @@ impl AdminService {
         db: &DatabaseConnection,
         request: CreateUserRequest,
     ) -> Result<UserResponse, AppError> {
-        let user = UserService::create_user_with_role(
+        let user = UserService::create_user_with_options(
             db,
             request.email,
             request.password,
             request.role_id,
+            NewUserOptions {
+                send_verification: request.send_verification,
+                mark_verified: request.mark_verified,
+            },
         )
         .await?;
 
+        // The admin chose this password, the user replaces it on first use
+        if UserService::password_change_required_by_policy() {
+            UserService::set_must_change_password(db, user.id, true).await?;
+        }
+
         Ok(UserResponse {
             id: user.id.to_string(),
             email: user.email,
@@ -293,17 +439,35 @@ impl AdminService {
             user_id,
             request.email,
             request.password,
-            request.role_id,
+            request.role_id.map(Some),
         )
         .await?;
 
-        Ok(UserResponse {
+        Ok(Self::to_updated_user_response(user))
+    }
+
+    /// Partially update a user, omitted fields are left unchanged and a null role_id removes the role
+    pub async fn patch_user(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        request: PatchUserRequest,
+    ) -> Result<UserResponse, AppError> {
+        let email = required_patch_field("email", request.email)?;
+        let password = required_patch_field("password", request.password)?;
+
+        let user = UserService::update_user(db, user_id, email, password, request.role_id).await?;
+
+        Ok(Self::to_updated_user_response(user))
+    }
+
+    fn to_updated_user_response(user: User) -> UserResponse {
+        UserResponse {
             id: user.id.to_string(),
             email: user.email,
             created_at: user.created_at.map(|t| t.to_rfc3339()),
             role_id: user.role_id,
             role_name: None, // Will be populated in a separate query if needed
-        })
+        }
     }
 
     /// Delete a user using UserService
@@ -323,17 +487,125 @@ impl AdminService {
         UserService::delete_user(db, user_id).await
     }
 
+    /// Removes a user and their personal data in one transaction
+    ///
+    /// Deletes the user and all their sessions, and deletes or anonymizes their audit log entries
+    /// depending on the mode. Anonymized entries keep the method, path, status and timing.
+    pub async fn purge_user_data(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        current_admin_id: Uuid,
+        mode: AuditLogPurgeMode,
+    ) -> Result<UserPurgeResponse, AppError> {
+        if user_id == current_admin_id {
+            return Err(AppError {
+                message: "Cannot purge your own account".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        let db_error = |e: DbErr| AppError {
+            message: format!("Database error: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let txn = db.begin().await.map_err(db_error)?;
+
+        let user = users::Entity::find_by_id(user_id)
+            .one(&txn)
+            .await
+            .map_err(db_error)?
+            .ok_or(AppError {
+                message: "User not found".to_string(),
+                status_code: StatusCode::NOT_FOUND,
+            })?;
+
+        let sessions_deleted = user_sessions::Entity::delete_many()
+            .filter(user_sessions::Column::UserId.eq(user_id))
+            .exec(&txn)
+            .await
+            .map_err(db_error)?
+            .rows_affected;
+
+        let (audit_logs_deleted, audit_logs_anonymized) = match mode {
+            AuditLogPurgeMode::Delete => {
+                let result = audit_logs::Entity::delete_many()
+                    .filter(audit_logs::Column::UserId.eq(user_id))
+                    .exec(&txn)
+                    .await
+                    .map_err(db_error)?;
+                (result.rows_affected, 0)
+            }
+            AuditLogPurgeMode::Anonymize => {
+                let result = audit_logs::Entity::update_many()
+                    .col_expr(
+                        audit_logs::Column::UserId,
+                        Expr::value(Option::<Uuid>::None),
+                    )
+                    .col_expr(
+                        audit_logs::Column::IpAddress,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .col_expr(
+                        audit_logs::Column::UserAgent,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .col_expr(
+                        audit_logs::Column::RequestBody,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .col_expr(
+                        audit_logs::Column::ResponseBody,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .col_expr(
+                        audit_logs::Column::ErrorMessage,
+                        Expr::value(Option::<String>::None),
+                    )
+                    .filter(audit_logs::Column::UserId.eq(user_id))
+                    .exec(&txn)
+                    .await
+                    .map_err(db_error)?;
+                (0, result.rows_affected)
+            }
+        };
+
+        // Settings outlive the admin who changed them
+        settings::Entity::update_many()
+            .col_expr(
+                settings::Column::UpdatedBy,
+                Expr::value(Option::<Uuid>::None),
+            )
+            .filter(settings::Column::UpdatedBy.eq(user_id))
+            .exec(&txn)
+            .await
+            .map_err(db_error)?;
+
+        // Failed login counts hold the email
+        LoginAttemptService::delete_for_email(&txn, &user.email).await?;
+
+        users::Entity::delete_by_id(user_id)
+            .exec(&txn)
+            .await
+            .map_err(db_error)?;
+
+        txn.commit().await.map_err(db_error)?;
+
+        Ok(UserPurgeResponse {
+            user_id: user_id.to_string(),
+            sessions_deleted,
+            audit_logs_deleted,
+            audit_logs_anonymized,
+        })
+    }
+
     /// Get list of database tables
     pub async fn get_database_tables(
         db: &DatabaseConnection,
     ) -> Result<Vec<DatabaseTableResponse>, AppError> {
-        // For SQLite, we can query the sqlite_master table
+        let backend = db.get_database_backend();
         let tables = db
-            .query_all(Statement::from_sql_and_values(
-                db.get_database_backend(),
-                r#"SELECT name FROM sqlite_master WHERE type='table' ORDER BY name"#,
-                vec![],
-            ))
+            .query_all(Self::list_tables_statement(backend))
             .await
             .map_err(|e| AppError {
                 message: format!("Database error: {}", e),
@@ -357,10 +629,12 @@ impl AdminService {
 
             // Get record count for each table
             let count_result = db
-                .query_one(Statement::from_sql_and_values(
-                    db.get_database_backend(),
-                    format!("SELECT COUNT(*) as count FROM \"{}\"", table_name),
-                    vec![],
+                .query_one(Statement::from_string(
+                    backend,
+                    format!(
+                        "SELECT COUNT(*) as count FROM {}",
+                        Self::quote_identifier(backend, &table_name)
+                    ),
                 ))
                 .await
                 .map_err(|e| AppError {
@@ -405,15 +679,12 @@ impl AdminService {
         table_name: String,
         params: TableRecordsQueryParams,
     ) -> Result<TableRecordResponse, AppError> {
-        let offset = (params.page - 1) * params.limit;
+        let (limit, offset) = (params.limit(), params.offset()?);
 
         // Get column names
+        let backend = db.get_database_backend();
         let columns_result = db
-            .query_all(Statement::from_sql_and_values(
-                db.get_database_backend(),
-                format!("PRAGMA table_info(\"{}\")", table_name),
-                vec![],
-            ))
+            .query_all(Self::table_columns_statement(backend, &table_name))
             .await
             .map_err(|e| AppError {
                 message: format!("Database error: {}", e),
@@ -421,59 +692,205 @@ impl AdminService {
             })?;
 
         let mut columns = Vec::new();
+        let mut column_types = Vec::new();
         for row in columns_result {
             let column_name: String = row.try_get("", "name").map_err(|_| AppError {
                 message: "Failed to parse column name".to_string(),
                 status_code: StatusCode::INTERNAL_SERVER_ERROR,
             })?;
+            let column_type: String = row.try_get("", "type").unwrap_or_default();
             columns.push(column_name);
+            column_types.push(column_type);
         }
 
         // Get records
         let records_result = db
-            .query_all(Statement::from_sql_and_values(
-                db.get_database_backend(),
+            .query_all(Statement::from_string(
+                backend,
+                // One extra row tells whether another page follows
                 format!(
-                    "SELECT * FROM \"{}\" LIMIT {} OFFSET {}",
-                    table_name, params.limit, offset
+                    "SELECT * FROM {} LIMIT {} OFFSET {}",
+                    Self::quote_identifier(backend, &table_name),
+                    limit.saturating_add(1),
+                    offset
                 ),
-                vec![],
             ))
             .await
             .map_err(|e| AppError {
                 message: format!("Database error: {}", e),
                 status_code: StatusCode::INTERNAL_SERVER_ERROR,
             })?;
+        let (records_result, has_more) = split_page(records_result, limit);
+
+        // Sensitive columns are masked for every admin, whatever their permissions
+        let masking_policy = ColumnMaskingPolicy::get();
+        let masks: Vec<Option<ColumnMask>> = columns
+            .iter()
+            .map(|column| masking_policy.mask_for(&table_name, column))
+            .collect();
+        let large_integers_as_strings = Self::large_integers_as_strings();
 
         let mut records = Vec::new();
         for row in records_result {
             let mut record = Vec::new();
-            for column in &columns {
-                // Try to get the value as different types and convert to JSON
-                let value = if let Ok(v) = row.try_get::<String>("", column) {
-                    serde_json::Value::String(v)
-                } else if let Ok(v) = row.try_get::<i64>("", column) {
-                    serde_json::Value::Number(serde_json::Number::from(v))
-                } else if let Ok(v) = row.try_get::<f64>("", column) {
-                    if let Some(n) = serde_json::Number::from_f64(v) {
-                        serde_json::Value::Number(n)
-                    } else {
-                        serde_json::Value::Null
+            for ((column, column_type), mask) in columns.iter().zip(&column_types).zip(&masks) {
+                match mask {
+                    Some(ColumnMask::Omit) => {}
+                    Some(ColumnMask::Redact) => {
+                        record.push(serde_json::Value::String(REDACTED_VALUE.to_string()))
                     }
-                } else if let Ok(v) = row.try_get::<bool>("", column) {
-                    serde_json::Value::Bool(v)
-                } else if let Ok(v) = row.try_get::<Vec<u8>>("", column) {
-                    // Convert blob to base64 string
-                    serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(v))
-                } else {
-                    serde_json::Value::Null
-                };
-                record.push(value);
+                    None => record.push(Self::extract_column_value(
+                        &row,
+                        column,
+                        column_type,
+                        large_integers_as_strings,
+                    )),
+                }
             }
             records.push(record);
         }
 
-        Ok(TableRecordResponse { columns, records })
+        let columns = columns
+            .into_iter()
+            .zip(&masks)
+            .filter(|(_, mask)| **mask != Some(ColumnMask::Omit))
+            .map(|(column, _)| column)
+            .collect();
+
+        Ok(TableRecordResponse {
+            columns,
+            records,
+            has_more,
+        })
+    }
+
+    /// Lists the app's tables as rows with a `name` column
+    fn list_tables_statement(backend: DbBackend) -> Statement {
+        let sql = match backend {
+            DbBackend::Sqlite => "SELECT name FROM sqlite_master WHERE type='table' ORDER BY name",
+            DbBackend::Postgres => {
+                "SELECT table_name AS name FROM information_schema.tables \
+                 WHERE table_schema = current_schema() AND table_type = 'BASE TABLE' \
+                 ORDER BY table_name"
+            }
+            DbBackend::MySql => {
+                "SELECT table_name AS name FROM information_schema.tables \
+                 WHERE table_schema = DATABASE() AND table_type = 'BASE TABLE' \
+                 ORDER BY table_name"
+            }
+        };
+        Statement::from_string(backend, sql)
+    }
+
+    /// Lists a table's columns as rows with `name` and `type` columns, in table order
+    fn table_columns_statement(backend: DbBackend, table_name: &str) -> Statement {
+        match backend {
+            DbBackend::Sqlite => Statement::from_string(
+                backend,
+                format!(
+                    "PRAGMA table_info({})",
+                    Self::quote_identifier(backend, table_name)
+                ),
+            ),
+            DbBackend::Postgres => Statement::from_sql_and_values(
+                backend,
+                "SELECT column_name AS name, data_type AS type FROM information_schema.columns \
+                 WHERE table_schema = current_schema() AND table_name = $1 \
+                 ORDER BY ordinal_position",
+                [table_name.into()],
+            ),
+            DbBackend::MySql => Statement::from_sql_and_values(
+                backend,
+                "SELECT column_name AS name, data_type AS type FROM information_schema.columns \
+                 WHERE table_schema = DATABASE() AND table_name = ? \
+                 ORDER BY ordinal_position",
+                [table_name.into()],
+            ),
+        }
+    }
+
+    /// Quotes a table name for the backend, doubling any quote inside it
+    fn quote_identifier(backend: DbBackend, name: &str) -> String {
+        let quote = if backend == DbBackend::MySql {
+            '`'
+        } else {
+            '"'
+        };
+        let doubled = String::from_iter([quote, quote]);
+        format!("{}{}{}", quote, name.replace(quote, &doubled), quote)
+    }
+
+    /// Whether table record integers outside JavaScript's safe range are sent as strings
+    /// (DATABASE_BROWSER_LARGE_INTEGERS_AS_STRINGS, defaults to true)
+    fn large_integers_as_strings() -> bool {
+        env::var("DATABASE_BROWSER_LARGE_INTEGERS_AS_STRINGS")
+            .map(|value| value.to_lowercase() != "false")
+            .unwrap_or(true)
+    }
+
+    /// Converts an integer to JSON, as a string when `large_as_string` is set and a JavaScript
+    /// client would lose precision
+    fn integer_value(v: i64, large_as_string: bool) -> serde_json::Value {
+        if large_as_string && !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&v) {
+            serde_json::Value::String(v.to_string())
+        } else {
+            serde_json::Value::Number(serde_json::Number::from(v))
+        }
+    }
+
+    /// Converts a column value to JSON, using the declared column type for JSON/array columns
+    fn extract_column_value(
+        row: &QueryResult,
+        column: &str,
+        column_type: &str,
+        large_integers_as_strings: bool,
+    ) -> serde_json::Value {
+        // JSON and array columns are returned as structured values instead of strings or null
+        if Self::is_json_column_type(column_type) {
+            if let Ok(v) = row.try_get::<serde_json::Value>("", column) {
+                return v;
+            }
+            if let Ok(v) = row.try_get::<String>("", column) {
+                return serde_json::from_str(&v).unwrap_or(serde_json::Value::String(v));
+            }
+        }
+
+        // Try to get the value as different types and convert to JSON
+        if let Ok(v) = row.try_get::<String>("", column) {
+            serde_json::Value::String(v)
+        } else if let Ok(v) = row.try_get::<i64>("", column) {
+            Self::integer_value(v, large_integers_as_strings)
+        } else if let Ok(v) = row.try_get::<f64>("", column) {
+            if let Some(n) = serde_json::Number::from_f64(v) {
+                serde_json::Value::Number(n)
+            } else {
+                serde_json::Value::Null
+            }
+        } else if let Ok(v) = row.try_get::<bool>("", column) {
+            serde_json::Value::Bool(v)
+        } else if let Ok(v) = row.try_get::<Vec<u8>>("", column) {
+            // Convert blob to base64 string
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(v))
+        } else {
+            serde_json::Value::Null
+        }
+    }
+
+    /// Whether a declared column type holds JSON or array data (JSON, JSONB, TEXT[], ARRAY)
+    fn is_json_column_type(column_type: &str) -> bool {
+        let column_type = column_type.trim().to_uppercase();
+        column_type == "JSON"
+            || column_type == "JSONB"
+            || column_type.ends_with("[]")
+            || column_type.starts_with("ARRAY")
+    }
+
+    /// Get system health status, joining a check already in flight instead of sampling again
+    pub async fn get_health_status_coalesced(db: &DatabaseConnection) -> HealthResponse {
+        let db = db.clone();
+        HEALTH_CHECKS
+            .run(|| async move { Self::get_health_status(&db).await })
+            .await
     }
 
     /// Get system health status
@@ -516,9 +933,14 @@ impl AdminService {
         // Calculate health status based on metrics
         let status = SystemMonitorService::get_health_status(&system_metrics);
 
-        // Format memory and disk values
+        // Usage percentages, None where the metric is unavailable
+        let cpu_usage = SystemMonitorService::get_cpu_usage_percentage(&system_metrics);
         let memory_usage = SystemMonitorService::get_memory_usage_percentage(&system_metrics);
         let disk_usage = SystemMonitorService::get_disk_usage_percentage(&system_metrics);
+        let format_memory =
+            |bytes| SystemMonitorService::format_bytes_if_known(bytes, memory_usage.is_some());
+        let format_disk =
+            |bytes| SystemMonitorService::format_bytes_if_known(bytes, disk_usage.is_some());
 
         // Get project information
         let (project_name, project_version) = SystemMonitorService::get_project_info();
@@ -531,15 +953,15 @@ impl AdminService {
             status,
             timestamp: chrono::Utc::now().to_rfc3339(),
             uptime: SystemMonitorService::format_uptime(system_metrics.uptime),
-            cpu_usage: system_metrics.cpu_usage,
+            cpu_usage,
             memory_usage,
-            memory_total: SystemMonitorService::format_bytes(system_metrics.memory_total),
-            memory_used: SystemMonitorService::format_bytes(system_metrics.memory_used),
-            memory_available: SystemMonitorService::format_bytes(system_metrics.memory_available),
+            memory_total: format_memory(system_metrics.memory_total),
+            memory_used: format_memory(system_metrics.memory_used),
+            memory_available: format_memory(system_metrics.memory_available),
             disk_usage,
-            disk_total: SystemMonitorService::format_bytes(system_metrics.disk_total),
-            disk_used: SystemMonitorService::format_bytes(system_metrics.disk_used),
-            disk_available: SystemMonitorService::format_bytes(system_metrics.disk_available),
+            disk_total: format_disk(system_metrics.disk_total),
+            disk_used: format_disk(system_metrics.disk_used),
+            disk_available: format_disk(system_metrics.disk_available),
             network_bytes_sent: SystemMonitorService::format_bytes(
                 system_metrics.network_bytes_sent,
             ),
@@ -570,74 +992,82 @@ impl AdminService {
             server_port,
             server_protocol,
             environment,
+            // Background Failures
+            background_failures: Self::background_failure_counts(),
+            request_email_failures: Self::request_email_failures(),
+        }
+    }
+
+    /// Background operation failures since startup
+    pub fn background_failure_counts() -> BackgroundFailureCounts {
+        BackgroundFailureCounts {
+            audit_log_insert: BACKGROUND_FAILURES.count(BackgroundOperation::AuditLogInsert),
+            email_send: BACKGROUND_FAILURES.count(BackgroundOperation::EmailSend),
+            session_activity_update: BACKGROUND_FAILURES
+                .count(BackgroundOperation::SessionActivityUpdate),
+            last_login_update: BACKGROUND_FAILURES.count(BackgroundOperation::LastLoginUpdate),
         }
     }
 
+    /// Sends that failed while a request waited on them
+    #[cfg(feature = "email")]
+    fn request_email_failures() -> u64 {
+        crate::infrastructure::email::REQUEST_EMAIL_FAILURES.load(Ordering::Relaxed)
+    }
+
+    /// Nothing is sent without the email module, so nothing fails
+    #[cfg(not(feature = "email"))]
+    fn request_email_failures() -> u64 {
+        0
+    }
+
     /// Get paginated roles with filtering
     pub async fn get_roles(
         db: &DatabaseConnection,
         params: RolesQueryParams,
     ) -> Result<PaginatedResponse<RoleResponse>, AppError> {
-        let offset = (params.page - 1) * params.limit;
-
-        // Build query with filters
-        let mut query = roles::Entity::find();
-
-        if let Some(search) = params.search {
-            if !search.is_empty() {
-                query = query.filter(
-                    roles::Column::Name
-                        .contains(&search)
-                        .or(roles::Column::Description.contains(&search)),
-                );
-            }
-        }
-
-        // Get total count
-        let total = query.clone().count(db).await.map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
-
-        // Get paginated results
-        let roles = query
-            .offset(offset as u64)
-            .limit(params.limit as u64)
-            .all(db)
-            .await
-            .map_err(|e| AppError {
-                message: format!("Database error: {}", e),
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            })?;
+        Self::get_roles_with_cache(db, params, &ROLE_CACHE).await
+    }
 
-        // Convert to response format
-        let role_responses: Vec<RoleResponse> = roles
-            .into_iter()
-            .map(|role| {
-                let permissions: Vec<String> =
-                    serde_json::from_str(&role.permissions).unwrap_or_else(|_| vec![]);
-
-                RoleResponse {
-                    id: role.id,
-                    name: role.name,
-                    description: role.description,
-                    permissions,
-                    created_at: role.created_at.map(|dt| dt.to_rfc3339()),
-                    updated_at: role.updated_at.map(|dt| dt.to_rfc3339()),
-                }
+    /// Get paginated roles with filtering, served from the given roles cache
+    pub async fn get_roles_with_cache(
+        db: &DatabaseConnection,
+        params: RolesQueryParams,
+        role_cache: &RoleCache,
+    ) -> Result<PaginatedResponse<RoleResponse>, AppError> {
+        let (page, limit, offset) = (params.page(), params.limit(), params.offset()?);
+        let search = params
+            .search
+            .filter(|search| !search.is_empty())
+            .map(|search| search.to_lowercase());
+
+        // Match name or description case-insensitively, like the LIKE filter this replaced
+        let roles = role_cache.roles(db).await?;
+        let matching: Vec<&RoleResponse> = roles
+            .values()
+            .filter(|role| {
+                search.as_ref().is_none_or(|search| {
+                    role.name.to_lowercase().contains(search)
+                        || role
+                            .description
+                            .as_ref()
+                            .is_some_and(|description| description.to_lowercase().contains(search))
+                })
             })
             .collect();
 
-        let total_pages = (total as f64 / params.limit as f64).ceil() as u32;
+        let total = matching.len() as u64;
+        let role_responses: Vec<RoleResponse> = matching
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        let has_more = offset + (role_responses.len() as u64) < total;
 
         Ok(PaginatedResponse {
             data: role_responses,
-            pagination: PaginationMeta {
-                page: params.page as u64,
-                limit: params.limit as u64,
-                total: total as u64,
-                total_pages: total_pages as u64,
-            },
+            pagination: PaginationMeta::new(page, limit, Some(total), has_more),
         })
     }
 
@@ -673,6 +1103,17 @@ impl AdminService {
         db: &DatabaseConnection,
         request: CreateRoleRequest,
     ) -> Result<RoleResponse, AppError> {
+        Self::create_role_with_limits(db, request, RoleLimits::from_env()).await
+    }
+
+    /// Create a new role, rejecting it if it would exceed the role or permission caps
+    pub async fn create_role_with_limits(
+        db: &DatabaseConnection,
+        request: CreateRoleRequest,
+        limits: RoleLimits,
+    ) -> Result<RoleResponse, AppError> {
+        limits.check_permission_count(&request.permissions)?;
+
         // Check if role name already exists
         let existing_role = roles::Entity::find()
             .filter(roles::Column::Name.eq(&request.name))
@@ -697,18 +1138,67 @@ impl AdminService {
                 status_code: StatusCode::BAD_REQUEST,
             })?;
 
-        // Create new role
-        let role_model = roles::ActiveModel {
-            name: Set(request.name),
-            description: Set(request.description),
-            permissions: Set(permissions_json),
-            ..Default::default()
-        };
-
-        let role = role_model.insert(db).await.map_err(|e| AppError {
+        let db_error = |e: DbErr| AppError {
             message: format!("Database error: {}", e),
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
+        };
+
+        // Create new role, the count is checked in the same statement so concurrent requests
+        // can't both take the last slot
+        let role_count = Query::select()
+            .expr(Expr::col(Asterisk).count())
+            .from(roles::Entity)
+            .to_owned();
+        let mut insert = Query::insert();
+        insert
+            .into_table(roles::Entity)
+            .columns([
+                roles::Column::Name,
+                roles::Column::Description,
+                roles::Column::Permissions,
+            ])
+            .select_from(
+                Query::select()
+                    .exprs([
+                        Expr::val(request.name.clone()),
+                        Expr::val(request.description.clone()),
+                        Expr::val(permissions_json),
+                    ])
+                    .and_where(
+                        Expr::expr(SimpleExpr::SubQuery(
+                            None,
+                            Box::new(role_count.into_sub_query_statement()),
+                        ))
+                        .lt(limits.max_roles),
+                    )
+                    .to_owned(),
+            )
+            .map_err(|e| AppError {
+                message: format!("Failed to build role insert: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        let result = db
+            .execute(db.get_database_backend().build(&insert))
+            .await
+            .map_err(db_error)?;
+        if result.rows_affected() == 0 {
+            return Err(AppError {
+                message: format!("Role limit of {} reached", limits.max_roles),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+        RoleCache::invalidate();
+
+        let role = roles::Entity::find()
+            .filter(roles::Column::Name.eq(&request.name))
+            .one(db)
+            .await
+            .map_err(db_error)?
+            .ok_or(AppError {
+                message: "Created role not found".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
 
         Ok(RoleResponse {
             id: role.id,
@@ -726,29 +1216,83 @@ impl AdminService {
         role_id: i32,
         request: UpdateRoleRequest,
     ) -> Result<RoleResponse, AppError> {
-        // Get existing role
-        let role = roles::Entity::find_by_id(role_id)
-            .one(db)
-            .await
-            .map_err(|e| AppError {
-                message: format!("Database error: {}", e),
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            })?
-            .ok_or(AppError {
-                message: "Role not found".to_string(),
-                status_code: StatusCode::NOT_FOUND,
-            })?;
+        Self::update_role_with_limits(db, role_id, request, RoleLimits::from_env()).await
+    }
 
-        // Check if new name conflicts with existing role
-        if let Some(new_name) = &request.name {
-            let existing_role = roles::Entity::find()
-                .filter(roles::Column::Name.eq(new_name))
-                .filter(roles::Column::Id.ne(role_id))
-                .one(db)
-                .await
-                .map_err(|e| AppError {
-                    message: format!("Database error: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+    /// Update an existing role, rejecting more permissions than the cap
+    pub async fn update_role_with_limits(
+        db: &DatabaseConnection,
+        role_id: i32,
+        request: UpdateRoleRequest,
+        limits: RoleLimits,
+    ) -> Result<RoleResponse, AppError> {
+        Self::apply_role_changes(
+            db,
+            role_id,
+            request.name,
+            request.description.map(Some),
+            request.permissions,
+            limits,
+        )
+        .await
+    }
+
+    /// Partially update a role, omitted fields are left unchanged and a null description clears it
+    pub async fn patch_role(
+        db: &DatabaseConnection,
+        role_id: i32,
+        request: PatchRoleRequest,
+    ) -> Result<RoleResponse, AppError> {
+        let name = required_patch_field("name", request.name)?;
+        let permissions = required_patch_field("permissions", request.permissions)?;
+
+        Self::apply_role_changes(
+            db,
+            role_id,
+            name,
+            request.description,
+            permissions,
+            RoleLimits::from_env(),
+        )
+        .await
+    }
+
+    /// Applies the given changes to a role, None leaves a field unchanged
+    async fn apply_role_changes(
+        db: &DatabaseConnection,
+        role_id: i32,
+        name: Option<String>,
+        description: Option<Option<String>>,
+        permissions: Option<Vec<String>>,
+        limits: RoleLimits,
+    ) -> Result<RoleResponse, AppError> {
+        if let Some(permissions) = &permissions {
+            limits.check_permission_count(permissions)?;
+        }
+
+        // Get existing role
+        let role = roles::Entity::find_by_id(role_id)
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?
+            .ok_or(AppError {
+                message: "Role not found".to_string(),
+                status_code: StatusCode::NOT_FOUND,
+            })?;
+
+        // Check if new name conflicts with existing role
+        if let Some(new_name) = &name {
+            let existing_role = roles::Entity::find()
+                .filter(roles::Column::Name.eq(new_name))
+                .filter(roles::Column::Id.ne(role_id))
+                .one(db)
+                .await
+                .map_err(|e| AppError {
+                    message: format!("Database error: {}", e),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
                 })?;
 
             if existing_role.is_some() {
@@ -762,15 +1306,15 @@ impl AdminService {
         // Prepare update model
         let mut role_model: roles::ActiveModel = role.into();
 
-        if let Some(name) = request.name {
+        if let Some(name) = name {
             role_model.name = Set(name);
         }
 
-        if let Some(description) = request.description {
-            role_model.description = Set(Some(description));
+        if let Some(description) = description {
+            role_model.description = Set(description);
         }
 
-        if let Some(permissions) = request.permissions {
+        if let Some(permissions) = permissions {
             let permissions_json = serde_json::to_string(&permissions).map_err(|_| AppError {
                 message: "Invalid permissions format".to_string(),
                 status_code: StatusCode::BAD_REQUEST,
@@ -778,14 +1322,12 @@ impl AdminService {
             role_model.permissions = Set(permissions_json);
         }
 
-        // Update timestamp
-        role_model.updated_at = Set(Some(chrono::Utc::now().fixed_offset()));
-
         // Save updated role
-        let updated_role = role_model.update(db).await.map_err(|e| AppError {
+        let updated_role = update_touched(db, role_model).await.map_err(|e| AppError {
             message: format!("Database error: {}", e),
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
         })?;
+        RoleCache::invalidate();
 
         // Get permissions for response
         let permissions: Vec<String> =
@@ -841,10 +1383,138 @@ impl AdminService {
                 message: format!("Database error: {}", e),
                 status_code: StatusCode::INTERNAL_SERVER_ERROR,
             })?;
+        RoleCache::invalidate();
 
         Ok(())
     }
 
+    /// Replace the permissions of several roles in a single transaction
+    ///
+    /// Every permission string is validated up front, any invalid entry or unknown role rejects
+    /// the whole batch. Roles whose permissions don't change are left out of the summary.
+    pub async fn bulk_update_role_permissions(
+        db: &DatabaseConnection,
+        request: BulkRolePermissionsRequest,
+    ) -> Result<BulkRolePermissionsResponse, AppError> {
+        let invalid_permissions: Vec<String> = request
+            .assignments
+            .iter()
+            .flat_map(|assignment| assignment.permissions.iter())
+            .filter(|permission| !PermissionService::is_valid_permission(permission))
+            .cloned()
+            .collect();
+
+        if !invalid_permissions.is_empty() {
+            return Err(AppError {
+                message: format!("Invalid permissions: {}", invalid_permissions.join(", ")),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        let limits = RoleLimits::from_env();
+        for assignment in &request.assignments {
+            limits.check_permission_count(&assignment.permissions)?;
+        }
+
+        let mut seen_role_ids = HashSet::new();
+        if let Some(assignment) = request
+            .assignments
+            .iter()
+            .find(|assignment| !seen_role_ids.insert(assignment.role_id))
+        {
+            return Err(AppError {
+                message: format!("Role {} is assigned more than once", assignment.role_id),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        let txn = db.begin().await.map_err(|e| AppError {
+            message: format!("Database error: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        let mut changes = Vec::new();
+        for assignment in request.assignments {
+            let role = roles::Entity::find_by_id(assignment.role_id)
+                .one(&txn)
+                .await
+                .map_err(|e| AppError {
+                    message: format!("Database error: {}", e),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                })?
+                .ok_or(AppError {
+                    message: format!("Role {} not found", assignment.role_id),
+                    status_code: StatusCode::NOT_FOUND,
+                })?;
+
+            let current: Vec<String> =
+                serde_json::from_str(&role.permissions).unwrap_or_else(|_| vec![]);
+            let mut desired = assignment.permissions;
+            desired.sort();
+            desired.dedup();
+
+            let added: Vec<String> = desired
+                .iter()
+                .filter(|permission| !current.contains(permission))
+                .cloned()
+                .collect();
+            let removed: Vec<String> = current
+                .iter()
+                .filter(|permission| !desired.contains(permission))
+                .cloned()
+                .collect();
+
+            if added.is_empty() && removed.is_empty() {
+                continue;
+            }
+
+            let role_id = role.id;
+            let role_name = role.name.clone();
+
+            if !request.dry_run {
+                let permissions_json = serde_json::to_string(&desired).map_err(|_| AppError {
+                    message: "Invalid permissions format".to_string(),
+                    status_code: StatusCode::BAD_REQUEST,
+                })?;
+
+                let mut role_model: roles::ActiveModel = role.into();
+                role_model.permissions = Set(permissions_json);
+                update_touched(&txn, role_model)
+                    .await
+                    .map_err(|e| AppError {
+                        message: format!("Database error: {}", e),
+                        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                    })?;
+            }
+
+            changes.push(RolePermissionChange {
+                role_id,
+                role_name,
+                added,
+                removed,
+            });
+        }
+
+        let result = if request.dry_run {
+            txn.rollback().await
+        } else {
+            txn.commit().await
+        };
+        result.map_err(|e| AppError {
+            message: format!("Database error: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+        if !request.dry_run {
+            RoleCache::invalidate();
+        }
+
+        Ok(BulkRolePermissionsResponse {
+            applied: !request.dry_run,
+            roles_changed: changes.len(),
+            changes,
+        })
+    }
+
     /// Check if a user has a specific permission
     pub async fn check_permission(
         db: &DatabaseConnection,
@@ -914,17 +1584,42 @@ impl AdminService {
     }
 
     /// Get sessions for a specific user
+    ///
+    /// Inactive sessions are only listed back to `since`, DEFAULT_INACTIVE_SESSION_WINDOW_DAYS
+    /// ago without it.
     pub async fn get_user_sessions(
         db: &DatabaseConnection,
         user_id: Uuid,
+        include_inactive: bool,
+        since: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<Vec<SessionResponse>, AppError> {
+        let now = system_clock().now();
+
         // Get sessions from SessionService
-        let sessions = SessionService::get_user_sessions(db, user_id).await?;
+        let sessions = if include_inactive {
+            let since = since.unwrap_or_else(|| {
+                now - chrono::Duration::days(DEFAULT_INACTIVE_SESSION_WINDOW_DAYS)
+            });
+            SessionService::get_all_user_sessions(db, user_id, since).await?
+        } else {
+            SessionService::get_user_sessions(db, user_id).await?
+        };
 
         // Convert to response format
         let session_responses: Vec<SessionResponse> = sessions
             .into_iter()
-            .map(|session| SessionResponse {
+            .map(|session| {
+                let status = if !session.is_active {
+                    "invalidated"
+                } else if session.expires_at.to_utc() < now {
+                    "expired"
+                } else {
+                    "active"
+                };
+                (session, status)
+            })
+            .filter(|(_, status)| include_inactive || *status == "active")
+            .map(|(session, status)| SessionResponse {
                 id: session.id.to_string(),
                 user_id: session.user_id.to_string(),
                 device_info: session
@@ -941,12 +1636,216 @@ impl AdminService {
                     .unwrap_or_default(),
                 expires_at: session.expires_at.to_rfc3339(),
                 is_current: false, // Will be determined on frontend based on current session
+                status: status.to_string(),
             })
             .collect();
 
         Ok(session_responses)
     }
 
+    /// Gathers everything held about a user (record, sessions, audit log entries) into one document
+    pub async fn export_user_data(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<UserDataExport, AppError> {
+        let (user, sessions) = Self::export_user_and_sessions(db, user_id).await?;
+        let since = Self::export_audit_log_cutoff();
+
+        let mut audit_logs = Vec::new();
+        loop {
+            let page = Self::export_audit_log_page(
+                db,
+                user_id,
+                since,
+                audit_logs.len() as u64,
+                EXPORT_PAGE_SIZE,
+            )
+            .await?;
+            let last_page = (page.len() as u64) < EXPORT_PAGE_SIZE;
+            audit_logs.extend(page);
+            if last_page {
+                break;
+            }
+        }
+
+        Ok(UserDataExport {
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            user,
+            sessions,
+            audit_logs,
+        })
+    }
+
+    /// Same document as `export_user_data` as a stream of JSON chunks, audit log entries are
+    /// fetched a page at a time so large exports aren't held in memory
+    pub async fn export_user_data_stream(
+        db: DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<impl Stream<Item = Result<String, AppError>> + Send + 'static, AppError> {
+        let (user, sessions) = Self::export_user_and_sessions(&db, user_id).await?;
+        let since = Self::export_audit_log_cutoff();
+
+        let head = format!(
+            "{{\"exported_at\":{},\"user\":{},\"sessions\":{},\"audit_logs\":[",
+            Self::to_json(&chrono::Utc::now().to_rfc3339())?,
+            Self::to_json(&user)?,
+            Self::to_json(&sessions)?,
+        );
+
+        let pages = stream::unfold(Some(0u64), move |offset| {
+            let db = db.clone();
+            async move {
+                let offset = offset?;
+                let page = match Self::export_audit_log_page(
+                    &db,
+                    user_id,
+                    since,
+                    offset,
+                    EXPORT_PAGE_SIZE,
+                )
+                .await
+                {
+                    Ok(page) => page,
+                    Err(e) => return Some((Err(e), None)),
+                };
+
+                let next = if (page.len() as u64) < EXPORT_PAGE_SIZE {
+                    None
+                } else {
+                    Some(offset + page.len() as u64)
+                };
+
+                let mut chunk = String::new();
+                for (index, entry) in page.iter().enumerate() {
+                    if offset > 0 || index > 0 {
+                        chunk.push(',');
+                    }
+                    match Self::to_json(entry) {
+                        Ok(json) => chunk.push_str(&json),
+                        Err(e) => return Some((Err(e), None)),
+                    }
+                }
+
+                Some((Ok(chunk), next))
+            }
+        });
+
+        Ok(stream::once(async move { Ok(head) })
+            .chain(pages)
+            .chain(stream::once(async { Ok("]}".to_string()) })))
+    }
+
+    async fn export_user_and_sessions(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<(ExportedUser, Vec<ExportedSession>), AppError> {
+        let user = UserService::find_user_by_id(db, user_id)
+            .await?
+            .ok_or(AppError {
+                message: "User not found".to_string(),
+                status_code: StatusCode::NOT_FOUND,
+            })?;
+
+        let role_name = match user.role_id {
+            Some(role_id) => PermissionService::get_role_name(db, role_id).await?,
+            None => None,
+        };
+
+        // All sessions, not just the active ones SessionService returns
+        let sessions = user_sessions::Entity::find()
+            .filter(user_sessions::Column::UserId.eq(user_id))
+            .order_by_asc(user_sessions::Column::CreatedAt)
+            .all(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?
+            .into_iter()
+            .map(|session| ExportedSession {
+                id: session.id.to_string(),
+                user_agent: session.user_agent,
+                ip_address: session.ip_address,
+                created_at: session.created_at.map(|dt| dt.to_rfc3339()),
+                last_activity: session.last_activity.map(|dt| dt.to_rfc3339()),
+                expires_at: session.expires_at.to_rfc3339(),
+                is_active: session.is_active,
+            })
+            .collect();
+
+        let user = ExportedUser {
+            id: user.id.to_string(),
+            email: user.email,
+            created_at: user.created_at.map(|dt| dt.to_rfc3339()),
+            last_login: user.last_login.map(|dt| dt.to_rfc3339()),
+            email_verified: user.email_verified,
+            role_name,
+        };
+
+        Ok((user, sessions))
+    }
+
+    /// Oldest audit log entry included in exports (USER_EXPORT_AUDIT_LOG_DAYS, all entries if unset)
+    fn export_audit_log_cutoff() -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        env::var("USER_EXPORT_AUDIT_LOG_DAYS")
+            .ok()
+            .and_then(|days| days.parse::<i64>().ok())
+            .map(|days| (chrono::Utc::now() - chrono::Duration::days(days)).fixed_offset())
+    }
+
+    async fn export_audit_log_page(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        since: Option<chrono::DateTime<chrono::FixedOffset>>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<AuditLogResponse>, AppError> {
+        let mut query = audit_logs::Entity::find()
+            .filter(audit_logs::Column::UserId.eq(user_id))
+            .order_by_asc(audit_logs::Column::Timestamp)
+            .order_by_asc(audit_logs::Column::Id);
+
+        if let Some(since) = since {
+            query = query.filter(audit_logs::Column::Timestamp.gte(since));
+        }
+
+        let logs = query
+            .offset(offset)
+            .limit(limit)
+            .all(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(logs.into_iter().map(Self::to_audit_log_response).collect())
+    }
+
+    fn to_audit_log_response(log: audit_logs::Model) -> AuditLogResponse {
+        AuditLogResponse {
+            id: log.id.to_string(),
+            timestamp: log.timestamp.map(|t| t.to_rfc3339()),
+            method: log.method,
+            path: log.path,
+            status_code: log.status_code,
+            response_time_ms: log.response_time_ms,
+            user_id: log.user_id.map(|id| id.to_string()),
+            ip_address: log.ip_address,
+            user_agent: log.user_agent,
+            request_body: log.request_body,
+            response_body: log.response_body,
+            error_message: log.error_message,
+        }
+    }
+
+    fn to_json<T: serde::Serialize>(value: &T) -> Result<String, AppError> {
+        serde_json::to_string(value).map_err(|e| AppError {
+            message: format!("Failed to serialize export: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })
+    }
+
     /// Invalidate a specific session
     pub async fn invalidate_user_session(
         db: &DatabaseConnection,
@@ -963,3 +1862,1251 @@ impl AdminService {
         SessionService::invalidate_all_user_sessions(db, user_id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "email")]
+    use crate::infrastructure::email::logged_emails_to;
+    use crate::infrastructure::pagination::{PaginatedEntity, PaginationConfig};
+    use crate::infrastructure::test_support::{
+        CapturedLogs, create_verified_user, memory_db, set_test_jwt_secret, setup_db,
+    };
+
+    async fn setup_roles_db() -> (DatabaseConnection, i32, i32) {
+        let db = setup_db().await;
+
+        let mut role_ids = Vec::new();
+        for (name, permissions) in [("editor", "[\"user:read\"]"), ("viewer", "[]")] {
+            let role = roles::ActiveModel {
+                name: Set(name.to_string()),
+                permissions: Set(permissions.to_string()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+            role_ids.push(role.id);
+        }
+
+        (db, role_ids[0], role_ids[1])
+    }
+
+    async fn role_permissions(db: &DatabaseConnection, role_id: i32) -> Vec<String> {
+        AdminService::get_role(db, role_id)
+            .await
+            .unwrap()
+            .permissions
+    }
+
+    #[tokio::test]
+    async fn test_bulk_role_permissions_applied() {
+        let (db, editor_id, viewer_id) = setup_roles_db().await;
+
+        let response = AdminService::bulk_update_role_permissions(
+            &db,
+            BulkRolePermissionsRequest {
+                assignments: vec![
+                    RolePermissionAssignment {
+                        role_id: editor_id,
+                        permissions: vec!["user:write".to_string(), "user:read".to_string()],
+                    },
+                    RolePermissionAssignment {
+                        role_id: viewer_id,
+                        permissions: vec!["reports:view".to_string()],
+                    },
+                ],
+                dry_run: false,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(response.applied);
+        assert_eq!(response.roles_changed, 2);
+        assert_eq!(response.changes[0].added, vec!["user:write"]);
+        assert!(response.changes[0].removed.is_empty());
+        assert_eq!(
+            role_permissions(&db, editor_id).await,
+            vec!["user:read", "user:write"]
+        );
+        assert_eq!(role_permissions(&db, viewer_id).await, vec!["reports:view"]);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_role_permissions_rejects_invalid_batch() {
+        let (db, editor_id, viewer_id) = setup_roles_db().await;
+
+        let err = AdminService::bulk_update_role_permissions(
+            &db,
+            BulkRolePermissionsRequest {
+                assignments: vec![
+                    RolePermissionAssignment {
+                        role_id: editor_id,
+                        permissions: vec!["user:write".to_string()],
+                    },
+                    RolePermissionAssignment {
+                        role_id: viewer_id,
+                        permissions: vec!["Not A Permission".to_string()],
+                    },
+                ],
+                dry_run: false,
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(role_permissions(&db, editor_id).await, vec!["user:read"]);
+        assert!(role_permissions(&db, viewer_id).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bulk_role_permissions_unknown_role_rolls_back() {
+        let (db, editor_id, _) = setup_roles_db().await;
+
+        let err = AdminService::bulk_update_role_permissions(
+            &db,
+            BulkRolePermissionsRequest {
+                assignments: vec![
+                    RolePermissionAssignment {
+                        role_id: editor_id,
+                        permissions: vec!["user:write".to_string()],
+                    },
+                    RolePermissionAssignment {
+                        role_id: 9999,
+                        permissions: vec!["user:read".to_string()],
+                    },
+                ],
+                dry_run: false,
+            },
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.status_code, StatusCode::NOT_FOUND);
+        assert_eq!(role_permissions(&db, editor_id).await, vec!["user:read"]);
+    }
+
+    #[tokio::test]
+    async fn test_roles_list_uses_configured_limits() {
+        let (db, _, _) = setup_roles_db().await;
+        let limits = PaginationConfig::get().limits(PaginatedEntity::Roles);
+
+        let params = |limit| RolesQueryParams {
+            page: 1,
+            limit,
+            search: None,
+        };
+
+        let role_cache = RoleCache::default();
+        let response = AdminService::get_roles_with_cache(&db, params(None), &role_cache)
+            .await
+            .unwrap();
+        assert_eq!(response.pagination.limit, limits.default_limit);
+
+        let response = AdminService::get_roles_with_cache(
+            &db,
+            params(Some(limits.max_limit + 1000)),
+            &role_cache,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.pagination.limit, limits.max_limit);
+        assert_eq!(response.pagination.total, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_audit_logs_list_uses_configured_limits() {
+        let db = setup_db().await;
+        let limits = PaginationConfig::get().limits(PaginatedEntity::AuditLogs);
+
+        let params = |limit| LogsQueryParams {
+            page: 1,
+            limit,
+            method: None,
+            status_code: None,
+            user_id: None,
+            start_date: None,
+            end_date: None,
+            with_total: true,
+        };
+
+        let response = AdminService::get_audit_logs(&db, params(None))
+            .await
+            .unwrap();
+        assert_eq!(response.pagination.limit, limits.default_limit);
+
+        let response = AdminService::get_audit_logs(&db, params(Some(u64::MAX)))
+            .await
+            .unwrap();
+        assert_eq!(response.pagination.limit, limits.max_limit);
+    }
+
+    #[tokio::test]
+    async fn test_page_beyond_max_offset_rejected() {
+        let db = setup_users_db(1).await;
+        let limits = PaginationConfig::get().limits(PaginatedEntity::Users);
+        let max_offset = limits.max_offset.expect("max offset configured");
+        let last_page = max_offset / limits.default_limit + 1;
+
+        let params = |page| UsersQueryParams {
+            page,
+            limit: None,
+            search: None,
+            with_total: true,
+        };
+        let role_cache = RoleCache::new(false);
+
+        let response = AdminService::get_users_with_cache(&db, params(last_page), &role_cache)
+            .await
+            .unwrap();
+        assert!(response.data.is_empty());
+
+        let err = AdminService::get_users_with_cache(&db, params(last_page + 1), &role_cache)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+    }
+
+    /// Whether any SQL statement in the captured log output counts rows
+    fn ran_count_query(queries: &CapturedLogs) -> bool {
+        queries.contents().to_uppercase().contains("COUNT(")
+    }
+
+    async fn setup_users_db(count: usize) -> DatabaseConnection {
+        let db = setup_db().await;
+        for i in 0..count {
+            create_verified_user(
+                &db,
+                format!("user{}@example.com", i),
+                "UserPassword123!".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        db
+    }
+
+    fn users_page(page: u64, with_total: bool) -> UsersQueryParams {
+        UsersQueryParams {
+            page,
+            limit: Some(2),
+            search: None,
+            with_total,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lists_skip_count_without_total() {
+        let db = setup_users_db(3).await;
+        let role_cache = RoleCache::new(false);
+
+        let queries = CapturedLogs::default();
+        let writer = queries.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let response = AdminService::get_users_with_cache(&db, users_page(1, false), &role_cache)
+            .await
+            .unwrap();
+        let logs = AdminService::get_audit_logs(
+            &db,
+            LogsQueryParams {
+                page: 1,
+                limit: None,
+                method: None,
+                status_code: None,
+                user_id: None,
+                start_date: None,
+                end_date: None,
+                with_total: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(!ran_count_query(&queries));
+        assert_eq!(response.pagination.total, None);
+        assert_eq!(response.pagination.total_pages, None);
+        assert_eq!(logs.pagination.total, None);
+
+        // Counting is still the default
+        let response = AdminService::get_users_with_cache(&db, users_page(1, true), &role_cache)
+            .await
+            .unwrap();
+        assert!(ran_count_query(&queries));
+        assert_eq!(response.pagination.total, Some(3));
+        assert_eq!(response.pagination.total_pages, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_has_more_at_page_boundary() {
+        // Exactly two full pages
+        let db = setup_users_db(4).await;
+        let role_cache = RoleCache::new(false);
+
+        let first = AdminService::get_users_with_cache(&db, users_page(1, false), &role_cache)
+            .await
+            .unwrap();
+        assert_eq!(first.data.len(), 2);
+        assert!(first.pagination.has_more);
+
+        let second = AdminService::get_users_with_cache(&db, users_page(2, false), &role_cache)
+            .await
+            .unwrap();
+        assert_eq!(second.data.len(), 2);
+        assert!(!second.pagination.has_more);
+
+        // The extra row fetched for has_more never reaches the page
+        let emails: std::collections::HashSet<_> = first
+            .data
+            .iter()
+            .chain(&second.data)
+            .map(|user| user.email.clone())
+            .collect();
+        assert_eq!(emails.len(), 4);
+    }
+
+    #[test]
+    fn test_is_json_column_type() {
+        assert!(AdminService::is_json_column_type("JSON"));
+        assert!(AdminService::is_json_column_type("jsonb"));
+        assert!(AdminService::is_json_column_type("TEXT[]"));
+        assert!(AdminService::is_json_column_type("ARRAY"));
+        assert!(!AdminService::is_json_column_type("TEXT"));
+        assert!(!AdminService::is_json_column_type("INTEGER"));
+    }
+
+    async fn admin_login(
+        db: &DatabaseConnection,
+        email: &str,
+        password: &str,
+    ) -> Result<AdminLoginResponse, AppError> {
+        AdminService::authenticate_admin(
+            db,
+            AdminLoginRequest {
+                email: email.to_string(),
+                password: password.to_string(),
+            },
+            None,
+            None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_repeated_non_admin_admin_logins_escalate() {
+        use crate::control::services::security_event_service::DEFAULT_ALERT_THRESHOLD;
+        use crate::infrastructure::websocket::{LogLevel, WEBSOCKET_MANAGER, WsMessage};
+
+        let (db, editor_id, _) = setup_roles_db().await;
+        create_verified_user(
+            &db,
+            "editor@example.com".to_string(),
+            "EditorPassword123!".to_string(),
+            Some(editor_id),
+        )
+        .await
+        .unwrap();
+        let mut receiver = WEBSOCKET_MANAGER.subscribe();
+
+        for _ in 0..DEFAULT_ALERT_THRESHOLD {
+            let err = AdminService::authenticate_admin(
+                &db,
+                AdminLoginRequest {
+                    email: "editor@example.com".to_string(),
+                    password: "EditorPassword123!".to_string(),
+                },
+                None,
+                Some("198.51.100.77".to_string()),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+        }
+
+        let mut escalated = false;
+        while let Ok(message) = receiver.try_recv() {
+            if let WsMessage::SystemLog { level, message, .. } = message {
+                escalated |= level == LogLevel::Warn
+                    && message.contains("admin_login_non_admin")
+                    && message.contains("198.51.100.77");
+            }
+        }
+        assert!(escalated);
+    }
+
+    #[tokio::test]
+    async fn test_admin_login_uniform_unauthorized() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let admin_role = roles::ActiveModel {
+            name: Set("admin".to_string()),
+            permissions: Set("[\"admin:read\"]".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        create_verified_user(
+            &db,
+            "admin@example.com".to_string(),
+            "AdminPassword123!".to_string(),
+            Some(admin_role.id),
+        )
+        .await
+        .unwrap();
+        create_verified_user(
+            &db,
+            "editor@example.com".to_string(),
+            "EditorPassword123!".to_string(),
+            Some(editor_id),
+        )
+        .await
+        .unwrap();
+
+        // Valid credentials but not an admin
+        let not_admin = admin_login(&db, "editor@example.com", "EditorPassword123!")
+            .await
+            .unwrap_err();
+        // Admin with a bad password
+        let bad_password = admin_login(&db, "admin@example.com", "WrongPassword123!")
+            .await
+            .unwrap_err();
+
+        assert_eq!(not_admin.status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(bad_password.status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(not_admin.message, bad_password.message);
+
+        set_test_jwt_secret();
+        assert!(
+            admin_login(&db, "admin@example.com", "AdminPassword123!")
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_table_records_preserves_json_column() {
+        let db = memory_db().await;
+        db.execute_unprepared(
+            "CREATE TABLE documents (id INTEGER PRIMARY KEY, title TEXT, body JSON, tags TEXT[])",
+        )
+        .await
+        .unwrap();
+        db.execute_unprepared(
+            r#"INSERT INTO documents (id, title, body, tags) VALUES (1, 'first', '{"key":"value","count":2}', '["a","b"]')"#,
+        )
+        .await
+        .unwrap();
+
+        let response = AdminService::get_table_records(
+            &db,
+            "documents".to_string(),
+            TableRecordsQueryParams {
+                page: 1,
+                limit: Some(25),
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.columns, vec!["id", "title", "body", "tags"]);
+        let record = &response.records[0];
+        assert_eq!(record[0], serde_json::json!(1));
+        assert_eq!(record[1], serde_json::json!("first"));
+        assert_eq!(record[2], serde_json::json!({"key": "value", "count": 2}));
+        assert_eq!(record[3], serde_json::json!(["a", "b"]));
+    }
+
+    #[tokio::test]
+    async fn test_get_table_records_sends_large_integers_as_strings() {
+        let db = memory_db().await;
+        db.execute_unprepared(
+            "CREATE TABLE counters (id INTEGER PRIMARY KEY, total INTEGER, delta INTEGER)",
+        )
+        .await
+        .unwrap();
+        // 2^53 + 1 can't be represented by a JavaScript number
+        db.execute_unprepared(
+            "INSERT INTO counters (id, total, delta) VALUES (1, 9007199254740993, -9007199254740993)",
+        )
+        .await
+        .unwrap();
+
+        let response = AdminService::get_table_records(
+            &db,
+            "counters".to_string(),
+            TableRecordsQueryParams {
+                page: 1,
+                limit: Some(25),
+            },
+        )
+        .await
+        .unwrap();
+
+        let record = &response.records[0];
+        assert_eq!(record[0], serde_json::json!(1));
+        assert_eq!(record[1], serde_json::json!("9007199254740993"));
+        assert_eq!(record[2], serde_json::json!("-9007199254740993"));
+    }
+
+    #[test]
+    fn test_integer_value() {
+        assert_eq!(
+            AdminService::integer_value(MAX_SAFE_INTEGER, true),
+            serde_json::json!(9007199254740991_i64)
+        );
+        assert_eq!(
+            AdminService::integer_value(MAX_SAFE_INTEGER + 1, true),
+            serde_json::json!("9007199254740992")
+        );
+        assert_eq!(
+            AdminService::integer_value(-MAX_SAFE_INTEGER - 1, true),
+            serde_json::json!("-9007199254740992")
+        );
+        // Without the option large integers stay numbers
+        assert_eq!(
+            AdminService::integer_value(i64::MAX, false),
+            serde_json::json!(i64::MAX)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_table_records_masks_password_hash() {
+        let db = setup_db().await;
+        create_verified_user(
+            &db,
+            "browsed@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let response = AdminService::get_table_records(
+            &db,
+            "users".to_string(),
+            TableRecordsQueryParams {
+                page: 1,
+                limit: Some(25),
+            },
+        )
+        .await
+        .unwrap();
+
+        let column = |name: &str| response.columns.iter().position(|c| c == name).unwrap();
+        let record = &response.records[0];
+        assert_eq!(record.len(), response.columns.len());
+        assert_eq!(
+            record[column("password_hash")],
+            serde_json::json!("[REDACTED]")
+        );
+        assert_eq!(
+            record[column("email")],
+            serde_json::json!("browsed@example.com")
+        );
+    }
+
+    async fn create_user_with_activity(
+        db: &DatabaseConnection,
+        email: &str,
+        paths: &[&str],
+    ) -> Uuid {
+        let user =
+            create_verified_user(db, email.to_string(), "UserPassword123!".to_string(), None)
+                .await
+                .unwrap();
+
+        let old_token = Uuid::new_v4();
+        SessionService::create_session(
+            db,
+            user.id,
+            Some("test-agent".to_string()),
+            Some("203.0.113.5".to_string()),
+            &old_token.to_string(),
+        )
+        .await
+        .unwrap();
+        SessionService::invalidate_session(db, old_token)
+            .await
+            .unwrap();
+        SessionService::create_session(db, user.id, None, None, &Uuid::new_v4().to_string())
+            .await
+            .unwrap();
+
+        for path in paths {
+            audit_logs::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                timestamp: Set(Some(chrono::Utc::now().fixed_offset())),
+                method: Set("GET".to_string()),
+                path: Set(path.to_string()),
+                user_id: Set(Some(user.id)),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .unwrap();
+        }
+
+        user.id
+    }
+
+    #[tokio::test]
+    async fn test_export_user_data_contains_only_that_user() {
+        let (db, _, _) = setup_roles_db().await;
+        let user_id = create_user_with_activity(
+            &db,
+            "subject@example.com",
+            &["/api/v1/auth/profile", "/api/v1/auth/me"],
+        )
+        .await;
+        create_user_with_activity(&db, "other@example.com", &["/api/v1/other"]).await;
+
+        let export = AdminService::export_user_data(&db, user_id).await.unwrap();
+
+        assert_eq!(export.user.email, "subject@example.com");
+        // Inactive sessions are part of the user's data too
+        assert_eq!(export.sessions.len(), 2);
+        assert_eq!(export.sessions.iter().filter(|s| s.is_active).count(), 1);
+        let paths: Vec<&str> = export
+            .audit_logs
+            .iter()
+            .map(|log| log.path.as_str())
+            .collect();
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains(&"/api/v1/auth/profile"));
+        assert!(!paths.contains(&"/api/v1/other"));
+        assert!(
+            export
+                .audit_logs
+                .iter()
+                .all(|log| log.user_id == Some(user_id.to_string()))
+        );
+
+        // Credentials never leave the database
+        let json = serde_json::to_string(&export).unwrap();
+        assert!(!json.contains("password"));
+        assert!(!json.contains("session_token"));
+    }
+
+    #[tokio::test]
+    async fn test_export_user_data_stream_matches_export() {
+        let (db, _, _) = setup_roles_db().await;
+        let paths: Vec<String> = (0..EXPORT_PAGE_SIZE + 3)
+            .map(|i| format!("/api/v1/items/{}", i))
+            .collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let user_id = create_user_with_activity(&db, "subject@example.com", &paths).await;
+        create_user_with_activity(&db, "other@example.com", &["/api/v1/other"]).await;
+
+        let chunks: Vec<String> = AdminService::export_user_data_stream(db.clone(), user_id)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let streamed: serde_json::Value = serde_json::from_str(&chunks.concat()).unwrap();
+
+        assert_eq!(streamed["user"]["email"], "subject@example.com");
+        assert_eq!(streamed["sessions"].as_array().unwrap().len(), 2);
+        let logs = streamed["audit_logs"].as_array().unwrap();
+        assert_eq!(logs.len() as u64, EXPORT_PAGE_SIZE + 3);
+        assert!(logs.iter().all(|log| log["path"] != "/api/v1/other"));
+    }
+
+    #[tokio::test]
+    async fn test_export_unknown_user_not_found() {
+        let (db, _, _) = setup_roles_db().await;
+        let err = AdminService::export_user_data(&db, Uuid::new_v4())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::NOT_FOUND);
+    }
+
+    async fn audit_logs_for(db: &DatabaseConnection, path_prefix: &str) -> Vec<audit_logs::Model> {
+        audit_logs::Entity::find()
+            .filter(audit_logs::Column::Path.starts_with(path_prefix))
+            .all(db)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_purge_user_data_anonymizes_audit_logs() {
+        let (db, _, _) = setup_roles_db().await;
+        let user_id = create_user_with_activity(
+            &db,
+            "subject@example.com",
+            &["/api/v1/subject/a", "/api/v1/subject/b"],
+        )
+        .await;
+        let other_id =
+            create_user_with_activity(&db, "other@example.com", &["/api/v1/other"]).await;
+        LoginAttemptService::record_failure(
+            &db,
+            LoginAttemptConfig::default(),
+            "subject@example.com",
+            Some("203.0.113.5"),
+            system_clock(),
+        )
+        .await
+        .unwrap();
+
+        let response = AdminService::purge_user_data(
+            &db,
+            user_id,
+            Uuid::new_v4(),
+            AuditLogPurgeMode::Anonymize,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.sessions_deleted, 2);
+        assert_eq!(response.audit_logs_anonymized, 2);
+        assert_eq!(response.audit_logs_deleted, 0);
+
+        assert!(
+            UserService::find_user_by_id(&db, user_id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let sessions = user_sessions::Entity::find()
+            .filter(user_sessions::Column::UserId.eq(user_id))
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(sessions, 0);
+        let login_attempts = crate::entity::models::login_attempts::Entity::find()
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(login_attempts, 0);
+
+        // Rows stay for aggregate metrics, personal data is gone
+        let logs = audit_logs_for(&db, "/api/v1/subject").await;
+        assert_eq!(logs.len(), 2);
+        for log in logs {
+            assert_eq!(log.method, "GET");
+            assert!(log.user_id.is_none());
+            assert!(log.ip_address.is_none());
+            assert!(log.user_agent.is_none());
+            assert!(log.request_body.is_none());
+        }
+
+        // Other users are untouched
+        assert!(
+            UserService::find_user_by_id(&db, other_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        let other_logs = audit_logs_for(&db, "/api/v1/other").await;
+        assert_eq!(other_logs[0].user_id, Some(other_id));
+    }
+
+    #[tokio::test]
+    async fn test_purge_user_data_deletes_audit_logs() {
+        let (db, _, _) = setup_roles_db().await;
+        let user_id =
+            create_user_with_activity(&db, "subject@example.com", &["/api/v1/subject/a"]).await;
+        create_user_with_activity(&db, "other@example.com", &["/api/v1/other"]).await;
+
+        let response =
+            AdminService::purge_user_data(&db, user_id, Uuid::new_v4(), AuditLogPurgeMode::Delete)
+                .await
+                .unwrap();
+        assert_eq!(response.audit_logs_deleted, 1);
+        assert_eq!(response.audit_logs_anonymized, 0);
+
+        assert!(audit_logs_for(&db, "/api/v1/subject").await.is_empty());
+        assert_eq!(audit_logs_for(&db, "/api/v1/other").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_user_data_refuses_current_admin() {
+        let (db, _, _) = setup_roles_db().await;
+        let admin_id =
+            create_user_with_activity(&db, "admin@example.com", &["/api/v1/admin"]).await;
+
+        let err = AdminService::purge_user_data(&db, admin_id, admin_id, AuditLogPurgeMode::Delete)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert!(
+            UserService::find_user_by_id(&db, admin_id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(audit_logs_for(&db, "/api/v1/admin").await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_health_checks_coalesced() {
+        let (db, _, _) = setup_roles_db().await;
+
+        let responses = futures_util::future::join_all(
+            (0..10).map(|_| AdminService::get_health_status_coalesced(&db)),
+        )
+        .await;
+
+        // One sampling served every caller
+        assert!(
+            responses
+                .iter()
+                .all(|response| response.timestamp == responses[0].timestamp)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_roles_served_from_cache() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let role_cache = RoleCache::default();
+        let user = create_verified_user(
+            &db,
+            "cached@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            Some(editor_id),
+        )
+        .await
+        .unwrap();
+
+        let roles_params = || RolesQueryParams {
+            page: 1,
+            limit: None,
+            search: Some("EDIT".to_string()),
+        };
+        let users_params = || UsersQueryParams {
+            page: 1,
+            limit: None,
+            search: None,
+            with_total: true,
+        };
+
+        let response = AdminService::get_roles_with_cache(&db, roles_params(), &role_cache)
+            .await
+            .unwrap();
+        assert_eq!(response.pagination.total, Some(1));
+        assert_eq!(response.data[0].permissions, vec!["user:read"]);
+
+        let response = AdminService::get_users_with_cache(&db, users_params(), &role_cache)
+            .await
+            .unwrap();
+        let listed = response
+            .data
+            .iter()
+            .find(|listed| listed.id == user.id.to_string())
+            .unwrap();
+        assert_eq!(listed.role_name.as_deref(), Some("editor"));
+
+        // Both lists were served by a single load
+        assert_eq!(role_cache.loads(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_users_list_loads_only_referenced_roles() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        for name in ["auditor", "support"] {
+            roles::ActiveModel {
+                name: Set(name.to_string()),
+                permissions: Set("[]".to_string()),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        for (email, role_id) in [
+            ("first@example.com", Some(editor_id)),
+            ("second@example.com", Some(editor_id)),
+            ("third@example.com", None),
+        ] {
+            create_verified_user(
+                &db,
+                email.to_string(),
+                "UserPassword123!".to_string(),
+                role_id,
+            )
+            .await
+            .unwrap();
+        }
+
+        // Without the cache only the editor role is read, not the four role table rows
+        let role_cache = RoleCache::new(false);
+        let params = UsersQueryParams {
+            page: 1,
+            limit: None,
+            search: None,
+            with_total: true,
+        };
+        let response = AdminService::get_users_with_cache(&db, params, &role_cache)
+            .await
+            .unwrap();
+
+        assert_eq!(role_cache.loads(), 1);
+        assert_eq!(role_cache.roles_loaded(), 1);
+        for user in response.data {
+            let expected = user.role_id.map(|_| "editor");
+            assert_eq!(user.role_name.as_deref(), expected, "{}", user.email);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_role_mutations_invalidate_cache() {
+        let (db, editor_id, viewer_id) = setup_roles_db().await;
+        let role_cache = RoleCache::default();
+        let names = |roles: &std::collections::BTreeMap<i32, RoleResponse>| {
+            roles
+                .values()
+                .map(|role| role.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let roles = role_cache.roles(&db).await.unwrap();
+        assert_eq!(names(&roles), ["editor", "viewer"]);
+        let loads = role_cache.loads();
+
+        AdminService::create_role_with_limits(
+            &db,
+            role_request("support", 1),
+            RoleLimits::from_env(),
+        )
+        .await
+        .unwrap();
+        let roles = role_cache.roles(&db).await.unwrap();
+        assert_eq!(names(&roles), ["editor", "viewer", "support"]);
+        assert!(role_cache.loads() > loads);
+        let loads = role_cache.loads();
+
+        let update = UpdateRoleRequest {
+            name: Some("content-editor".to_string()),
+            description: None,
+            permissions: None,
+        };
+        AdminService::update_role_with_limits(&db, editor_id, update, RoleLimits::from_env())
+            .await
+            .unwrap();
+        let roles = role_cache.roles(&db).await.unwrap();
+        assert_eq!(roles[&editor_id].name, "content-editor");
+        assert!(role_cache.loads() > loads);
+        let loads = role_cache.loads();
+
+        AdminService::delete_role(&db, viewer_id).await.unwrap();
+        let roles = role_cache.roles(&db).await.unwrap();
+        assert!(!roles.contains_key(&viewer_id));
+        assert!(role_cache.loads() > loads);
+    }
+
+    fn role_request(name: &str, permissions: usize) -> CreateRoleRequest {
+        CreateRoleRequest {
+            name: name.to_string(),
+            description: None,
+            permissions: (0..permissions)
+                .map(|i| format!("custom:permission{}", i))
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_role_up_to_cap_then_rejected() {
+        let (db, _, _) = setup_roles_db().await;
+        let limits = RoleLimits {
+            max_roles: 4,
+            max_permissions_per_role: 5,
+        };
+
+        // Two roles exist already, two more fit under the cap
+        for name in ["support", "auditor"] {
+            AdminService::create_role_with_limits(&db, role_request(name, 5), limits)
+                .await
+                .unwrap();
+        }
+
+        let err = AdminService::create_role_with_limits(&db, role_request("extra", 1), limits)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(roles::Entity::find().count(&db).await.unwrap(), 4);
+    }
+
+    #[test]
+    fn test_quote_identifier_per_backend() {
+        assert_eq!(
+            AdminService::quote_identifier(DbBackend::Sqlite, "users"),
+            "\"users\""
+        );
+        assert_eq!(
+            AdminService::quote_identifier(DbBackend::Postgres, "odd\"name"),
+            "\"odd\"\"name\""
+        );
+        assert_eq!(
+            AdminService::quote_identifier(DbBackend::MySql, "odd`name"),
+            "`odd``name`"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_role_creates_stay_under_cap() {
+        let (db, _, _) = setup_roles_db().await;
+        let limits = RoleLimits {
+            max_roles: 4,
+            max_permissions_per_role: 5,
+        };
+
+        // Each request awaits between its steps, so a separate count would let all of them through
+        let results =
+            futures_util::future::join_all(["a", "b", "c", "d", "e"].map(|name| {
+                AdminService::create_role_with_limits(&db, role_request(name, 1), limits)
+            }))
+            .await;
+
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 2);
+        assert_eq!(roles::Entity::find().count(&db).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_role_with_too_many_permissions_rejected() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let limits = RoleLimits {
+            max_roles: 100,
+            max_permissions_per_role: 3,
+        };
+
+        let err = AdminService::create_role_with_limits(&db, role_request("support", 4), limits)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+
+        let update = UpdateRoleRequest {
+            name: None,
+            description: None,
+            permissions: Some(role_request("editor", 4).permissions),
+        };
+        let err = AdminService::update_role_with_limits(&db, editor_id, update, limits)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(role_permissions(&db, editor_id).await, vec!["user:read"]);
+    }
+
+    #[tokio::test]
+    async fn test_patch_user_omitted_role_unchanged_and_null_clears() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let user = create_verified_user(
+            &db,
+            "patched@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            Some(editor_id),
+        )
+        .await
+        .unwrap();
+
+        // The new email stays pending until verified
+        let request: PatchUserRequest =
+            serde_json::from_str(r#"{"email": "renamed@example.com"}"#).unwrap();
+        let response = AdminService::patch_user(&db, user.id, request)
+            .await
+            .unwrap();
+        assert_eq!(response.email, "patched@example.com");
+        assert_eq!(response.role_id, Some(editor_id));
+
+        let request: PatchUserRequest = serde_json::from_str(r#"{"role_id": null}"#).unwrap();
+        let response = AdminService::patch_user(&db, user.id, request)
+            .await
+            .unwrap();
+        assert_eq!(response.email, "patched@example.com");
+        assert_eq!(response.role_id, None);
+
+        let request: PatchUserRequest = serde_json::from_str(r#"{"email": null}"#).unwrap();
+        let err = AdminService::patch_user(&db, user.id, request)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_patch_role_omitted_fields_unchanged_and_null_clears_description() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let request: PatchRoleRequest =
+            serde_json::from_str(r#"{"description": "Edits content"}"#).unwrap();
+        AdminService::patch_role(&db, editor_id, request)
+            .await
+            .unwrap();
+
+        let request: PatchRoleRequest =
+            serde_json::from_str(r#"{"name": "content-editor"}"#).unwrap();
+        let response = AdminService::patch_role(&db, editor_id, request)
+            .await
+            .unwrap();
+        assert_eq!(response.name, "content-editor");
+        assert_eq!(response.description.as_deref(), Some("Edits content"));
+        assert_eq!(response.permissions, vec!["user:read"]);
+
+        let request: PatchRoleRequest = serde_json::from_str(r#"{"description": null}"#).unwrap();
+        let response = AdminService::patch_role(&db, editor_id, request)
+            .await
+            .unwrap();
+        assert_eq!(response.description, None);
+        assert_eq!(response.permissions, vec!["user:read"]);
+
+        let request: PatchRoleRequest = serde_json::from_str(r#"{"permissions": null}"#).unwrap();
+        let err = AdminService::patch_role(&db, editor_id, request)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(role_permissions(&db, editor_id).await, vec!["user:read"]);
+    }
+
+    async fn verification_state(db: &DatabaseConnection, user_id: Uuid) -> (bool, u64) {
+        let verified = users::Entity::find_by_id(user_id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+            .email_verified;
+        let links = crate::entity::models::email_verifications::Entity::find()
+            .count(db)
+            .await
+            .unwrap();
+        (verified, links)
+    }
+
+    #[tokio::test]
+    async fn test_create_pre_verified_user_sends_no_email() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let request: CreateUserRequest = serde_json::from_value(serde_json::json!({
+            "email": "provisioned@example.com",
+            "password": "UserPassword123!",
+            "role_id": editor_id,
+            "mark_verified": true,
+        }))
+        .unwrap();
+
+        let response = AdminService::create_user(&db, request).await.unwrap();
+        let user_id = Uuid::parse_str(&response.id).unwrap();
+
+        // No verification link was sent or stored
+        assert_eq!(verification_state(&db, user_id).await, (true, 0));
+        #[cfg(feature = "email")]
+        assert!(logged_emails_to("provisioned@example.com").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_user_sends_verification_by_default() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let request: CreateUserRequest = serde_json::from_value(serde_json::json!({
+            "email": "invited@example.com",
+            "password": "UserPassword123!",
+            "role_id": editor_id,
+        }))
+        .unwrap();
+        assert!(request.send_verification);
+        assert!(!request.mark_verified);
+
+        let response = AdminService::create_user(&db, request).await.unwrap();
+        let user_id = Uuid::parse_str(&response.id).unwrap();
+
+        assert_eq!(verification_state(&db, user_id).await, (false, 1));
+        #[cfg(feature = "email")]
+        assert_eq!(
+            logged_emails_to("invited@example.com"),
+            vec!["Please verify your email address"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_unverified_user_without_link_rejected() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let request: CreateUserRequest = serde_json::from_value(serde_json::json!({
+            "email": "stranded@example.com",
+            "password": "UserPassword123!",
+            "role_id": editor_id,
+            "send_verification": false,
+        }))
+        .unwrap();
+
+        let err = AdminService::create_user(&db, request).await.unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert!(
+            UserService::find_user_by_email(&db, "stranded@example.com")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inactive_sessions_listed_only_when_requested() {
+        let (db, editor_id, _) = setup_roles_db().await;
+        let user = create_verified_user(
+            &db,
+            "sessions@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            Some(editor_id),
+        )
+        .await
+        .unwrap();
+
+        let mut sessions = Vec::new();
+        for _ in 0..3 {
+            let session = SessionService::create_session(
+                &db,
+                user.id,
+                None,
+                None,
+                &Uuid::new_v4().to_string(),
+            )
+            .await
+            .unwrap();
+            sessions.push(session);
+        }
+        SessionService::invalidate_session(
+            &db,
+            Uuid::parse_str(&sessions[1].session_token).unwrap(),
+        )
+        .await
+        .unwrap();
+        user_sessions::ActiveModel {
+            id: Set(sessions[2].id),
+            expires_at: Set((chrono::Utc::now() - chrono::Duration::hours(1)).fixed_offset()),
+            ..Default::default()
+        }
+        .update(&db)
+        .await
+        .unwrap();
+
+        let listed = AdminService::get_user_sessions(&db, user.id, false, None)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, sessions[0].id.to_string());
+        assert_eq!(listed[0].status, "active");
+
+        let listed = AdminService::get_user_sessions(&db, user.id, true, None)
+            .await
+            .unwrap();
+        let status_of = |session: &user_sessions::Model| {
+            listed
+                .iter()
+                .find(|listed| listed.id == session.id.to_string())
+                .map(|listed| listed.status.as_str())
+        };
+        assert_eq!(listed.len(), 3);
+        assert_eq!(status_of(&sessions[0]), Some("active"));
+        assert_eq!(status_of(&sessions[1]), Some("invalidated"));
+        assert_eq!(status_of(&sessions[2]), Some("expired"));
+
+        // Inactive sessions from before the window are left out, the live one always stays
+        let since = chrono::Utc::now() + chrono::Duration::minutes(1);
+        let listed = AdminService::get_user_sessions(&db, user.id, true, Some(since))
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, sessions[0].id.to_string());
+    }
+}
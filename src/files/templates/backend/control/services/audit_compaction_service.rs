@@ -0,0 +1,198 @@
+//! Audit log compaction, strips the request and response bodies from entries older than the
+//! configured retention while keeping the rest of the entry. The scheduler runs it on
+//! AUDIT_COMPACTION_SCHEDULE.
+use chrono::{DateTime, Utc};
+use sea_orm::{sea_query::Expr, *};
+use uuid::Uuid;
+
+use crate::{
+    entity::models::audit_logs,
+    infrastructure::app_error::{AppError, DbResultExt},
+};
+
+/// Cron schedule compaction runs on, hourly
+pub const AUDIT_COMPACTION_SCHEDULE: &str = "0 0 * * * *";
+
+/// Entries compacted per update, so one run doesn't hold a long write lock on the table
+pub const AUDIT_COMPACTION_BATCH_SIZE: u64 = 1000;
+
+/// Service compacting old audit log entries
+pub struct AuditCompactionService;
+
+impl AuditCompactionService {
+    /// Removes the bodies of entries logged before `cutoff`, returning how many were compacted
+    ///
+    /// Method, path, status, timing, user and body sizes are kept.
+    pub async fn compact(db: &DatabaseConnection, cutoff: DateTime<Utc>) -> Result<u64, AppError> {
+        Self::compact_in_batches(db, cutoff, AUDIT_COMPACTION_BATCH_SIZE).await
+    }
+
+    /// `compact`, updating at most `batch_size` entries at a time, one id range after another
+    pub async fn compact_in_batches(
+        db: &DatabaseConnection,
+        cutoff: DateTime<Utc>,
+        batch_size: u64,
+    ) -> Result<u64, AppError> {
+        let mut compacted = 0;
+        let mut after: Option<Uuid> = None;
+
+        loop {
+            let mut batch = audit_logs::Entity::find()
+                .select_only()
+                .column(audit_logs::Column::Id)
+                .filter(Self::has_stale_bodies(cutoff))
+                .order_by_asc(audit_logs::Column::Id)
+                .limit(batch_size);
+            if let Some(after) = after {
+                batch = batch.filter(audit_logs::Column::Id.gt(after));
+            }
+            let ids: Vec<Uuid> = batch
+                .into_tuple()
+                .all(db)
+                .await
+                .with_context("audit_logs", "find compaction batch")?;
+            let (Some(first), Some(last)) = (ids.first(), ids.last()) else {
+                break;
+            };
+
+            let result = audit_logs::Entity::update_many()
+                .col_expr(
+                    audit_logs::Column::RequestBody,
+                    Expr::value(Option::<String>::None),
+                )
+                .col_expr(
+                    audit_logs::Column::ResponseBody,
+                    Expr::value(Option::<String>::None),
+                )
+                .filter(audit_logs::Column::Id.between(*first, *last))
+                .filter(Self::has_stale_bodies(cutoff))
+                .exec(db)
+                .await
+                .with_context("audit_logs", "compact")?;
+            compacted += result.rows_affected;
+
+            if (ids.len() as u64) < batch_size {
+                break;
+            }
+            after = Some(*last);
+        }
+
+        Ok(compacted)
+    }
+
+    /// Entries logged before `cutoff` that still have a body
+    fn has_stale_bodies(cutoff: DateTime<Utc>) -> Condition {
+        Condition::all()
+            .add(audit_logs::Column::Timestamp.lt(cutoff))
+            .add(
+                Condition::any()
+                    .add(audit_logs::Column::RequestBody.is_not_null())
+                    .add(audit_logs::Column::ResponseBody.is_not_null()),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::setup_db;
+    use sea_orm::Set;
+    use uuid::Uuid;
+
+    async fn insert_entry(db: &DatabaseConnection, path: &str, age: chrono::Duration) -> Uuid {
+        audit_logs::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            timestamp: Set(Some((Utc::now() - age).into())),
+            method: Set("POST".to_string()),
+            path: Set(path.to_string()),
+            status_code: Set(Some(201)),
+            response_time_ms: Set(Some(12)),
+            user_id: Set(None),
+            ip_address: Set(Some("10.0.0.1".to_string())),
+            user_agent: Set(Some("test".to_string())),
+            request_body: Set(Some("{\"name\":\"widget\"}".to_string())),
+            response_body: Set(Some("{\"id\":1}".to_string())),
+            request_body_size: Set(Some(17)),
+            response_body_size: Set(Some(8)),
+            error_message: Set(None),
+        }
+        .insert(db)
+        .await
+        .unwrap()
+        .id
+    }
+
+    #[tokio::test]
+    async fn test_compact_strips_bodies_of_old_entries_only() {
+        let db = setup_db().await;
+        let old = insert_entry(&db, "/api/v1/old", chrono::Duration::days(40)).await;
+        let recent = insert_entry(&db, "/api/v1/recent", chrono::Duration::days(1)).await;
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+
+        assert_eq!(
+            AuditCompactionService::compact(&db, cutoff).await.unwrap(),
+            1
+        );
+
+        let old = audit_logs::Entity::find_by_id(old)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(old.request_body, None);
+        assert_eq!(old.response_body, None);
+        assert_eq!(old.method, "POST");
+        assert_eq!(old.path, "/api/v1/old");
+        assert_eq!(old.status_code, Some(201));
+        assert_eq!(old.response_time_ms, Some(12));
+        assert_eq!(old.ip_address.as_deref(), Some("10.0.0.1"));
+        assert_eq!(old.request_body_size, Some(17));
+        assert_eq!(old.response_body_size, Some(8));
+
+        let recent = audit_logs::Entity::find_by_id(recent)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            recent.request_body.as_deref(),
+            Some("{\"name\":\"widget\"}")
+        );
+        assert_eq!(recent.response_body.as_deref(), Some("{\"id\":1}"));
+
+        // Already compacted entries aren't counted again
+        assert_eq!(
+            AuditCompactionService::compact(&db, cutoff).await.unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compact_works_through_every_batch() {
+        let db = setup_db().await;
+        for i in 0..5 {
+            insert_entry(
+                &db,
+                &format!("/api/v1/old/{}", i),
+                chrono::Duration::days(40),
+            )
+            .await;
+        }
+        let recent = insert_entry(&db, "/api/v1/recent", chrono::Duration::days(1)).await;
+        let cutoff = Utc::now() - chrono::Duration::days(30);
+
+        assert_eq!(
+            AuditCompactionService::compact_in_batches(&db, cutoff, 2)
+                .await
+                .unwrap(),
+            5
+        );
+        let remaining = audit_logs::Entity::find()
+            .filter(audit_logs::Column::RequestBody.is_not_null())
+            .all(&db)
+            .await
+            .unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, recent);
+    }
+}
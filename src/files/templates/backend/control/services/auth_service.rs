@@ -1,45 +1,106 @@
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{EncodingKey, Header, encode};
+use sea_orm::prelude::Expr;
 use sea_orm::*;
-use std::env;
-use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
-use crate::control::services::{session_service::SessionService, user_service::UserService};
+use crate::bridge::middleware::rate_limit::{RateLimitConfig, RateLimitKey, RateLimiter};
+use crate::control::services::{
+    database_service::DatabaseService,
+    login_attempt_service::{LoginAttemptConfig, LoginAttemptService},
+    session_service::SessionService,
+    token_service::{TokenConfig, TokenService},
+    user_service::UserService,
+};
 use crate::domain::{auth::*, user::*, validation::*};
+use crate::entity::models::{password_resets, prelude::*, users};
 use crate::infrastructure::app_error::AppError;
+use crate::infrastructure::background_failures::{BackgroundOperation, record_background_failure};
+use crate::infrastructure::clock::{Clock, system_clock};
+#[cfg(feature = "email")]
+use crate::infrastructure::email::{EmailResult, EmailService};
 use crate::infrastructure::jwt_claims::Claims;
+use crate::infrastructure::session_policy::SessionPolicyConfig;
+use crate::infrastructure::timestamps::update_touched;
 use axum::http::StatusCode;
 
+/// Default minutes a password reset link stays valid
+pub const DEFAULT_PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+
+/// Default password reset requests allowed per email in each window
+pub const DEFAULT_PASSWORD_RESETS_PER_EMAIL: u32 = 3;
+
+/// Default password reset window in minutes
+pub const DEFAULT_PASSWORD_RESET_WINDOW_MINUTES: u64 = 60;
+
+/// Password reset requests per email, counted whether or not the email has an account
+static PASSWORD_RESET_LIMITER: once_cell::sync::Lazy<RateLimiter> =
+    once_cell::sync::Lazy::new(|| {
+        let window_minutes = std::env::var("PASSWORD_RESET_WINDOW_MINUTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_PASSWORD_RESET_WINDOW_MINUTES);
+        RateLimiter::new(RateLimitConfig {
+            per_ip: None,
+            per_user: None,
+            window: std::time::Duration::from_secs(window_minutes * 60),
+            trusted_proxies: Vec::new(),
+        })
+    });
+
 /// Service for authentication-related business operations
 pub struct AuthService;
 
 impl AuthService {
-    /// Authenticates a user and returns a JWT token with session tracking
+    /// Authenticates a user and returns a short-lived JWT token and a refresh token for the new session
+    ///
+    /// After LOGIN_MAX_ATTEMPTS failed logins in a row from one IP the email is rejected with 429
+    /// for LOGIN_LOCKOUT_MINUTES from that IP, a successful login resets the count.
     pub async fn authenticate_user(
         db: &DatabaseConnection,
         login: UserLogin,
         user_agent: Option<String>,
         ip_address: Option<String>,
+    ) -> Result<AuthToken, AppError> {
+        Self::authenticate_user_with_clock(db, login, user_agent, ip_address, system_clock()).await
+    }
+
+    /// `authenticate_user` taking the time from the given clock
+    pub async fn authenticate_user_with_clock(
+        db: &DatabaseConnection,
+        login: UserLogin,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        clock: &dyn Clock,
     ) -> Result<AuthToken, AppError> {
         // Validate input
         validate_login_input(&login.email, &login.password)?;
 
-        // Find user by email
-        let user = UserService::find_user_by_email(db, &login.email)
-            .await?
-            .ok_or(AppError {
+        // Reject emails locked out for this IP after repeated failed logins
+        let lockout = LoginAttemptConfig::from_env();
+        let ip = ip_address.as_deref();
+        LoginAttemptService::check(db, lockout, &login.email, ip, clock).await?;
+
+        // Find user by email, an unknown email counts as a failed login too
+        let Some(user) = UserService::find_user_by_email(db, &login.email).await? else {
+            LoginAttemptService::record_failure(db, lockout, &login.email, ip, clock).await?;
+            return Err(AppError {
                 message: "Invalid credentials".to_string(),
                 status_code: StatusCode::UNAUTHORIZED,
-            })?;
+            });
+        };
 
         // Verify password
         let is_valid = UserService::verify_password(&user, &login.password)?;
         if !is_valid {
+            LoginAttemptService::record_failure(db, lockout, &login.email, ip, clock).await?;
             return Err(AppError {
                 message: "Invalid credentials".to_string(),
                 status_code: StatusCode::UNAUTHORIZED,
             });
         }
+        LoginAttemptService::clear(db, &login.email, ip).await?;
 
         // Verify email
         if !user.email_verified {
@@ -59,50 +120,562 @@ impl AuthService {
         // Generate session ID
         let session_id = Uuid::new_v4();
 
-        // Generate JWT token with session ID
-        let token = Self::generate_jwt_token(&user.id, &session_id)?;
+        // Generate JWT token with session ID, expiring no later than the session
+        let policy =
+            SessionService::session_policy_for_user(db, SessionPolicyConfig::get(), user.id)
+                .await?;
+        let now = clock.now();
+        let expires_at = TokenConfig::get()
+            .access_token_expires_at(now, now + Duration::hours(policy.expiry_hours));
+        let token = Self::generate_jwt_token(&user.id, &session_id, expires_at)?;
 
         // Create session record (after successful token generation)
-        SessionService::create_session(
+        let session = SessionService::create_session_with_policy(
             db,
+            policy,
             user.id,
             user_agent,
             ip_address,
             &session_id.to_string(),
         )
         .await?;
+        let refresh_token = SessionService::issue_refresh_token(db, &session).await?;
 
-        Ok(token)
+        Ok(AuthToken::new(
+            token,
+            user.id,
+            expires_at,
+            refresh_token,
+            session.expires_at.to_utc(),
+        ))
     }
 
-    /// Generates a JWT token for a user with session tracking
-    fn generate_jwt_token(user_id: &uuid::Uuid, session_id: &Uuid) -> Result<AuthToken, AppError> {
-        let jwt_secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
-        let encoding_key = EncodingKey::from_secret(jwt_secret.as_ref());
+    /// Exchanges a refresh token for a new access token and refresh token on the same session
+    ///
+    /// Reusing a refresh token that was already exchanged invalidates the session.
+    pub async fn refresh_token(
+        db: &DatabaseConnection,
+        refresh_token: &str,
+    ) -> Result<AuthToken, AppError> {
+        let (session, refresh_token) =
+            SessionService::rotate_refresh_token(db, refresh_token).await?;
+        let session_id = Uuid::parse_str(&session.session_token).map_err(|_| AppError {
+            message: "Invalid refresh token".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+        })?;
 
-        let expiration = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + 24 * 60 * 60; // 24 hours
+        let expires_at = TokenConfig::get()
+            .access_token_expires_at(system_clock().now(), session.expires_at.to_utc());
+        let token = Self::generate_jwt_token(&session.user_id, &session_id, expires_at)?;
+
+        Ok(AuthToken::new(
+            token,
+            session.user_id,
+            expires_at,
+            refresh_token,
+            session.expires_at.to_utc(),
+        ))
+    }
+
+    /// Emails a password reset link to the user with this email
+    ///
+    /// Succeeds whether or not the email belongs to a user. The lookup and the email run in the
+    /// background and their failures are only recorded, so neither the response nor its timing
+    /// tells which addresses have accounts. Requests past PASSWORD_RESETS_PER_EMAIL for an email
+    /// in the window are dropped, so the endpoint can't be used to flood an inbox.
+    pub async fn request_password_reset(
+        db: &DatabaseConnection,
+        email: &str,
+    ) -> Result<(), AppError> {
+        validate_email(email)?;
+
+        if !Self::password_reset_allowed(email) {
+            tracing::debug!("Password reset limit reached for an email, request dropped");
+            return Ok(());
+        }
+
+        let db = db.clone();
+        let email = email.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = Self::send_password_reset(&db, &email).await {
+                record_background_failure(
+                    BackgroundOperation::EmailSend,
+                    &format!("Failed to issue password reset: {}", e.message),
+                )
+                .await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Counts a password reset request for `email` against PASSWORD_RESETS_PER_EMAIL (0 disables)
+    fn password_reset_allowed(email: &str) -> bool {
+        let limit = std::env::var("PASSWORD_RESETS_PER_EMAIL")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_PASSWORD_RESETS_PER_EMAIL);
+        if limit == 0 {
+            return true;
+        }
+
+        let key = RateLimitKey::Email(normalize_email(email));
+        PASSWORD_RESET_LIMITER
+            .check(key, limit, std::time::Instant::now())
+            .is_ok()
+    }
 
-        let claims = Claims {
-            sub: user_id.to_string(),
-            exp: expiration as usize,
-            session_id: session_id.to_string(),
+    /// Issues a password reset for the user with this email and sends the link, unknown emails
+    /// are ignored
+    async fn send_password_reset(db: &DatabaseConnection, email: &str) -> Result<(), AppError> {
+        let Some(user) = UserService::find_user_by_email(db, email).await? else {
+            tracing::debug!("Password reset requested for an unknown email");
+            return Ok(());
         };
 
-        let token_string =
-            encode(&Header::default(), &claims, &encoding_key).map_err(|_| AppError {
-                message: "Failed to generate token".to_string(),
+        let token = Self::issue_password_reset(db, user.id).await?;
+        let reset_link = format!("http://localhost:5173/reset-password?token={}", token);
+        Self::send_password_reset_link(&user.email, &reset_link).await;
+
+        Ok(())
+    }
+
+    /// Sends a password reset link, a failure is only recorded
+    #[cfg(feature = "email")]
+    async fn send_password_reset_link(address: &str, link: &str) {
+        let result = match EmailService::from_env() {
+            Ok(email_service) => {
+                email_service
+                    .send_password_reset_email(address, address, link, "Rext App")
+                    .await
+            }
+            Err(e) => EmailResult::Failed(e),
+        };
+        if let EmailResult::Failed(e) = result {
+            record_background_failure(
+                BackgroundOperation::EmailSend,
+                &format!("Failed to send password reset email: {}", e),
+            )
+            .await;
+        }
+    }
+
+    /// Without the email module there is nothing to send with, the link is only logged
+    #[cfg(not(feature = "email"))]
+    async fn send_password_reset_link(address: &str, link: &str) {
+        tracing::info!(
+            to = address,
+            link,
+            "Email module disabled, password reset link not sent"
+        );
+    }
+
+    /// Issues a single-use password reset token for the user, valid for
+    /// PASSWORD_RESET_TTL_MINUTES, replacing the links sent before
+    pub async fn issue_password_reset(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<String, AppError> {
+        Self::discard_password_resets(db, user_id).await?;
+
+        let token = Uuid::new_v4().simple().to_string();
+        let now = system_clock().now();
+        password_resets::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            token_hash: Set(TokenService::hash_token(&token)),
+            created_at: Set(Some(now.fixed_offset())),
+            expires_at: Set(
+                (now + Duration::minutes(Self::password_reset_ttl_minutes())).fixed_offset(),
+            ),
+            used_at: Set(None),
+        }
+        .insert(db)
+        .await
+        .map_err(|_| AppError {
+            message: "Failed to store password reset".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        Ok(token)
+    }
+
+    /// Sets a new password with a reset token, logging the user out everywhere
+    pub async fn confirm_password_reset(
+        db: &DatabaseConnection,
+        token: &str,
+        new_password: &str,
+    ) -> Result<(), AppError> {
+        Self::confirm_password_reset_with_clock(db, token, new_password, system_clock()).await
+    }
+
+    /// `confirm_password_reset` checking the expiry against the given clock
+    pub async fn confirm_password_reset_with_clock(
+        db: &DatabaseConnection,
+        token: &str,
+        new_password: &str,
+        clock: &dyn Clock,
+    ) -> Result<(), AppError> {
+        let reset = DatabaseService::find_one_with_tracking(
+            db,
+            "password_resets",
+            PasswordResets::find()
+                .filter(password_resets::Column::TokenHash.eq(TokenService::hash_token(token)))
+                .filter(password_resets::Column::UsedAt.is_null()),
+        )
+        .await
+        .map_err(|_| AppError {
+            message: "Database error".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?
+        .ok_or(AppError {
+            message: "Invalid password reset token".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+        })?;
+
+        if reset.expires_at.to_utc() < clock.now() {
+            return Err(AppError {
+                message: "Password reset has expired".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        // Checked before the token is used up, so a rejected password can be retried
+        validate_strong_password(new_password)?;
+        let password_hash = UserService::hash_password(new_password)?;
+
+        // Uses the token only if it wasn't already, so it resets once even when followed twice
+        let result = PasswordResets::update_many()
+            .col_expr(
+                password_resets::Column::UsedAt,
+                Expr::value(clock.now().fixed_offset()),
+            )
+            .filter(password_resets::Column::Id.eq(reset.id))
+            .filter(password_resets::Column::UsedAt.is_null())
+            .exec(db)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to reset password".to_string(),
                 status_code: StatusCode::INTERNAL_SERVER_ERROR,
             })?;
+        if result.rows_affected == 0 {
+            return Err(AppError {
+                message: "Invalid password reset token".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
 
-        let expires_at = chrono::DateTime::from_timestamp(expiration as i64, 0)
-            .unwrap_or_else(|| chrono::Utc::now() + chrono::Duration::hours(24));
+        update_touched(
+            db,
+            users::ActiveModel {
+                id: Set(reset.user_id),
+                password_hash: Set(password_hash),
+                must_change_password: Set(false),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|_| AppError {
+            message: "Failed to reset password".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        Self::discard_password_resets(db, reset.user_id).await?;
+        SessionService::invalidate_all_user_sessions(db, reset.user_id).await?;
+
+        Ok(())
+    }
 
-        let auth_token = AuthToken::new(token_string, *user_id, expires_at);
+    /// Deletes the user's unused password reset tokens
+    async fn discard_password_resets(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<(), AppError> {
+        PasswordResets::delete_many()
+            .filter(password_resets::Column::UserId.eq(user_id))
+            .filter(password_resets::Column::UsedAt.is_null())
+            .exec(db)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to discard password resets".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(())
+    }
+
+    /// Reads PASSWORD_RESET_TTL_MINUTES
+    fn password_reset_ttl_minutes() -> i64 {
+        std::env::var("PASSWORD_RESET_TTL_MINUTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(DEFAULT_PASSWORD_RESET_TTL_MINUTES)
+    }
+
+    /// Generates a JWT token for a user's session, expiring at `expires_at`
+    fn generate_jwt_token(
+        user_id: &uuid::Uuid,
+        session_id: &Uuid,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String, AppError> {
+        let jwt_secret = TokenService::jwt_secret()?;
+        let encoding_key = EncodingKey::from_secret(jwt_secret.as_ref());
+
+        let claims = Claims::for_session(user_id, session_id, expires_at.timestamp() as usize);
+
+        encode(&Header::default(), &claims, &encoding_key).map_err(|_| AppError {
+            message: "Failed to generate token".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::clock::ManualClock;
+    use crate::infrastructure::test_support::{
+        create_verified_user, set_test_jwt_secret, setup_db,
+    };
+
+    const NEW_PASSWORD: &str = "NewSecurePassword123";
+
+    async fn setup_user() -> (DatabaseConnection, User) {
+        let db = setup_db().await;
+
+        let user = create_verified_user(
+            &db,
+            "user@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        (db, user)
+    }
+
+    async fn current_user(db: &DatabaseConnection, user_id: Uuid) -> User {
+        UserService::find_user_by_id(db, user_id)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_happy_path() {
+        let (db, user) = setup_user().await;
+        SessionService::create_session(&db, user.id, None, None, &Uuid::new_v4().to_string())
+            .await
+            .unwrap();
+        let token = AuthService::issue_password_reset(&db, user.id)
+            .await
+            .unwrap();
+
+        // A weak password is rejected without using up the token
+        let err = AuthService::confirm_password_reset(&db, &token, "short")
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+
+        AuthService::confirm_password_reset(&db, &token, NEW_PASSWORD)
+            .await
+            .unwrap();
+
+        let user = current_user(&db, user.id).await;
+        assert!(UserService::verify_password(&user, NEW_PASSWORD).unwrap());
+        assert!(!UserService::verify_password(&user, "UserPassword123!").unwrap());
+        assert_eq!(
+            SessionService::get_user_active_session_count(&db, user.id)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // The link only works once
+        let err = AuthService::confirm_password_reset(&db, &token, "AnotherPassword456")
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "Invalid password reset token");
+    }
+
+    #[tokio::test]
+    async fn test_expired_password_reset_rejected() {
+        let (db, user) = setup_user().await;
+        let token = AuthService::issue_password_reset(&db, user.id)
+            .await
+            .unwrap();
+
+        let clock = ManualClock::new(
+            chrono::Utc::now() + Duration::minutes(AuthService::password_reset_ttl_minutes() + 1),
+        );
+        let err = AuthService::confirm_password_reset_with_clock(&db, &token, NEW_PASSWORD, &clock)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Password reset has expired");
+
+        let user = current_user(&db, user.id).await;
+        assert!(UserService::verify_password(&user, "UserPassword123!").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_password_reset_request_for_unknown_email_succeeds() {
+        let (db, user) = setup_user().await;
+
+        AuthService::request_password_reset(&db, "nobody@example.com")
+            .await
+            .unwrap();
+        AuthService::send_password_reset(&db, "nobody@example.com")
+            .await
+            .unwrap();
+        assert_eq!(PasswordResets::find().count(&db).await.unwrap(), 0);
+
+        AuthService::send_password_reset(&db, &user.email)
+            .await
+            .unwrap();
+        assert_eq!(PasswordResets::find().count(&db).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_password_reset_requests_limited_per_email() {
+        for _ in 0..DEFAULT_PASSWORD_RESETS_PER_EMAIL {
+            assert!(AuthService::password_reset_allowed("limited@example.com"));
+        }
+        assert!(!AuthService::password_reset_allowed(" Limited@Example.com"));
+
+        // Other emails are counted separately
+        assert!(AuthService::password_reset_allowed("other@example.com"));
+    }
+
+    async fn login(
+        db: &DatabaseConnection,
+        password: &str,
+        clock: &dyn Clock,
+    ) -> Result<AuthToken, AppError> {
+        login_from(db, password, None, clock).await
+    }
+
+    async fn login_from(
+        db: &DatabaseConnection,
+        password: &str,
+        ip_address: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Result<AuthToken, AppError> {
+        AuthService::authenticate_user_with_clock(
+            db,
+            UserLogin {
+                email: "User@Example.com".to_string(),
+                password: password.to_string(),
+            },
+            None,
+            ip_address.map(str::to_string),
+            clock,
+        )
+        .await
+    }
+
+    async fn setup_verified_user() -> (DatabaseConnection, User) {
+        let (db, user) = setup_user().await;
+        set_test_jwt_secret();
+        (db, user)
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failed_logins_lock_out() {
+        let (db, _) = setup_verified_user().await;
+        let clock = ManualClock::new(Utc::now());
+        let config = LoginAttemptConfig::from_env();
+
+        for _ in 1..config.max_attempts {
+            let err = login(&db, "WrongPassword123!", &clock).await.unwrap_err();
+            assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+        }
+        // The Nth failure locks the email, even the right password is rejected until it passes
+        let err = login(&db, "WrongPassword123!", &clock).await.unwrap_err();
+        assert_eq!(err.status_code, StatusCode::TOO_MANY_REQUESTS);
+        let err = login(&db, "UserPassword123!", &clock).await.unwrap_err();
+        assert_eq!(err.status_code, StatusCode::TOO_MANY_REQUESTS);
+
+        clock.advance(Duration::minutes(config.lockout_minutes + 1));
+        assert!(login(&db, "UserPassword123!", &clock).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_clears_failed_attempts() {
+        let (db, _) = setup_verified_user().await;
+        let clock = ManualClock::new(Utc::now());
+        let config = LoginAttemptConfig::from_env();
+
+        for _ in 1..config.max_attempts {
+            login(&db, "WrongPassword123!", &clock).await.unwrap_err();
+        }
+        assert!(login(&db, "UserPassword123!", &clock).await.is_ok());
+        assert_eq!(LoginAttempts::find().count(&db).await.unwrap(), 0);
+
+        // The count starts over, the next failure isn't the Nth
+        let err = login(&db, "WrongPassword123!", &clock).await.unwrap_err();
+        assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_lockout_only_applies_to_the_failing_ip() {
+        let (db, _) = setup_verified_user().await;
+        let clock = ManualClock::new(Utc::now());
+        let config = LoginAttemptConfig::from_env();
+
+        for _ in 0..config.max_attempts {
+            login_from(&db, "WrongPassword123!", Some("198.51.100.7"), &clock)
+                .await
+                .unwrap_err();
+        }
+        let err = login_from(&db, "UserPassword123!", Some("198.51.100.7"), &clock)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::TOO_MANY_REQUESTS);
+
+        // The owner logging in from elsewhere isn't locked out
+        assert!(
+            login_from(&db, "UserPassword123!", Some("203.0.113.9"), &clock)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_failed_logins_swept() {
+        let (db, _) = setup_verified_user().await;
+        let clock = ManualClock::new(Utc::now());
+        let config = LoginAttemptConfig::from_env();
+
+        for _ in 0..config.max_attempts {
+            login_from(&db, "WrongPassword123!", Some("198.51.100.7"), &clock)
+                .await
+                .unwrap_err();
+        }
+        LoginAttemptService::record_failure(
+            &db,
+            config,
+            "nobody@example.com",
+            Some("198.51.100.7"),
+            &clock,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            LoginAttemptService::sweep_expired(&db, config, &clock)
+                .await
+                .unwrap(),
+            0
+        );
 
-        Ok(auth_token)
+        clock.advance(Duration::minutes(config.lockout_minutes + 1));
+        assert_eq!(
+            LoginAttemptService::sweep_expired(&db, config, &clock)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(LoginAttempts::find().count(&db).await.unwrap(), 0);
     }
 }
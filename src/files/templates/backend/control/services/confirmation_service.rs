@@ -0,0 +1,429 @@
+//! Confirmation tokens for destructive admin operations
+use axum::http::{HeaderMap, StatusCode};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use sea_orm::{prelude::Expr, *};
+use serde::{Deserialize, Serialize};
+use std::env;
+use uuid::Uuid;
+
+use crate::{
+    bridge::types::admin::ConfirmationResponse,
+    control::services::{
+        admin_service::AuditLogPurgeMode, session_service::SessionService,
+        token_service::TokenService,
+    },
+    entity::models::{audit_logs, confirmation_tokens, roles, users},
+    infrastructure::app_error::{AppError, DbResultExt},
+};
+
+/// Header carrying the confirmation token on destructive requests
+pub const CONFIRMATION_TOKEN_HEADER: &str = "x-confirmation-token";
+
+/// Default lifetime of a confirmation token in seconds
+pub const DEFAULT_CONFIRMATION_TTL_SECONDS: i64 = 120;
+
+/// Destructive admin operations that can require confirmation
+#[derive(Debug, Clone, PartialEq)]
+pub enum DestructiveAction {
+    DeleteUser(Uuid),
+    DeleteRole(i32),
+    InvalidateUserSessions(Uuid),
+    PurgeUserData(Uuid),
+}
+
+impl DestructiveAction {
+    /// Identifies the action and its target, a token only confirms this exact action
+    pub fn key(&self) -> String {
+        match self {
+            DestructiveAction::DeleteUser(user_id) => format!("delete_user:{}", user_id),
+            DestructiveAction::DeleteRole(role_id) => format!("delete_role:{}", role_id),
+            DestructiveAction::InvalidateUserSessions(user_id) => {
+                format!("invalidate_user_sessions:{}", user_id)
+            }
+            DestructiveAction::PurgeUserData(user_id) => format!("purge_user_data:{}", user_id),
+        }
+    }
+}
+
+/// Claims of a confirmation token, deliberately incompatible with auth token claims
+#[derive(Debug, Serialize, Deserialize)]
+struct ConfirmationClaims {
+    /// Id of the confirmation_tokens row, marked used when the token is spent
+    jti: String,
+    action: String,
+    admin_id: String,
+    exp: usize,
+}
+
+/// Service for issuing and checking destructive action confirmations
+pub struct ConfirmationService;
+
+impl ConfirmationService {
+    /// Whether destructive endpoints require a confirmation token (REQUIRE_ADMIN_CONFIRMATION, defaults to false)
+    pub fn is_enabled() -> bool {
+        env::var("REQUIRE_ADMIN_CONFIRMATION")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .unwrap_or(false)
+    }
+
+    /// Confirmation token lifetime (ADMIN_CONFIRMATION_TTL_SECONDS)
+    pub fn ttl_seconds() -> i64 {
+        env::var("ADMIN_CONFIRMATION_TTL_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_CONFIRMATION_TTL_SECONDS)
+    }
+
+    /// Describes what the action will do, fails if the target doesn't exist
+    pub async fn describe_impact(
+        db: &DatabaseConnection,
+        action: &DestructiveAction,
+    ) -> Result<String, AppError> {
+        match action {
+            DestructiveAction::DeleteUser(user_id) => {
+                let user = Self::find_user(db, *user_id).await?;
+                let sessions = SessionService::get_user_active_session_count(db, *user_id).await?;
+                Ok(format!(
+                    "Will delete user {} and {} active session(s)",
+                    user.email, sessions
+                ))
+            }
+            DestructiveAction::DeleteRole(role_id) => {
+                let role = roles::Entity::find_by_id(*role_id)
+                    .one(db)
+                    .await
+                    .map_err(|e| AppError {
+                        message: format!("Database error: {}", e),
+                        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                    })?
+                    .ok_or(AppError {
+                        message: "Role not found".to_string(),
+                        status_code: StatusCode::NOT_FOUND,
+                    })?;
+                Ok(format!("Will delete role '{}'", role.name))
+            }
+            DestructiveAction::InvalidateUserSessions(user_id) => {
+                let user = Self::find_user(db, *user_id).await?;
+                let sessions = SessionService::get_user_active_session_count(db, *user_id).await?;
+                Ok(format!(
+                    "Will invalidate {} active session(s) for {}",
+                    sessions, user.email
+                ))
+            }
+            DestructiveAction::PurgeUserData(user_id) => {
+                let user = Self::find_user(db, *user_id).await?;
+                let audit_logs = audit_logs::Entity::find()
+                    .filter(audit_logs::Column::UserId.eq(*user_id))
+                    .count(db)
+                    .await
+                    .map_err(|e| AppError {
+                        message: format!("Database error: {}", e),
+                        status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                    })?;
+                Ok(format!(
+                    "Will delete user {} with all their sessions and {} {} audit log entries",
+                    user.email,
+                    match AuditLogPurgeMode::from_env() {
+                        AuditLogPurgeMode::Delete => "delete",
+                        AuditLogPurgeMode::Anonymize => "anonymize",
+                    },
+                    audit_logs
+                ))
+            }
+        }
+    }
+
+    /// Issues a short-lived, single-use token confirming the action for this admin
+    pub async fn issue(
+        db: &DatabaseConnection,
+        action: &DestructiveAction,
+        admin_id: Uuid,
+        impact: String,
+    ) -> Result<ConfirmationResponse, AppError> {
+        Self::issue_with_ttl(db, action, admin_id, impact, Self::ttl_seconds()).await
+    }
+
+    async fn issue_with_ttl(
+        db: &DatabaseConnection,
+        action: &DestructiveAction,
+        admin_id: Uuid,
+        impact: String,
+        ttl_seconds: i64,
+    ) -> Result<ConfirmationResponse, AppError> {
+        let now = chrono::Utc::now();
+        let expires_at = now + chrono::Duration::seconds(ttl_seconds);
+        let token_id = Uuid::new_v4();
+
+        // Expired tokens can't be spent anymore, clear them out while we're here
+        confirmation_tokens::Entity::delete_many()
+            .filter(confirmation_tokens::Column::ExpiresAt.lt(now))
+            .exec(db)
+            .await
+            .with_context("confirmation_tokens", "delete expired")?;
+        confirmation_tokens::ActiveModel {
+            id: Set(token_id),
+            action: Set(action.key()),
+            admin_id: Set(admin_id),
+            expires_at: Set(expires_at.fixed_offset()),
+            used_at: Set(None),
+        }
+        .insert(db)
+        .await
+        .with_context("confirmation_tokens", "create")?;
+
+        let claims = ConfirmationClaims {
+            jti: token_id.to_string(),
+            action: action.key(),
+            admin_id: admin_id.to_string(),
+            exp: expires_at.timestamp().max(0) as usize,
+        };
+
+        let confirmation_token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(TokenService::jwt_secret()?.as_ref()),
+        )
+        .map_err(|_| AppError {
+            message: "Failed to generate confirmation token".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        Ok(ConfirmationResponse {
+            confirmation_token,
+            action: claims.action,
+            impact,
+            expires_at: expires_at.to_rfc3339(),
+        })
+    }
+
+    /// Checks and spends the confirmation header if confirmations are enabled
+    pub async fn require(
+        db: &DatabaseConnection,
+        headers: &HeaderMap,
+        action: &DestructiveAction,
+        admin_id: Uuid,
+    ) -> Result<(), AppError> {
+        if !Self::is_enabled() {
+            return Ok(());
+        }
+
+        let token = headers
+            .get(CONFIRMATION_TOKEN_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        let token_id = Self::verify(token, action, admin_id)?;
+        Self::spend(db, token_id).await
+    }
+
+    /// Checks that the token confirms this action for this admin and hasn't expired, returning
+    /// the token's id
+    pub fn verify(
+        token: Option<&str>,
+        action: &DestructiveAction,
+        admin_id: Uuid,
+    ) -> Result<Uuid, AppError> {
+        let token = token.ok_or(AppError {
+            message: format!(
+                "Confirmation required, send the token from the confirmation endpoint in the {} header",
+                CONFIRMATION_TOKEN_HEADER
+            ),
+            status_code: StatusCode::PRECONDITION_REQUIRED,
+        })?;
+
+        let mut validation = Validation::default();
+        validation.leeway = 0;
+
+        let claims = decode::<ConfirmationClaims>(
+            token,
+            &DecodingKey::from_secret(TokenService::jwt_secret()?.as_ref()),
+            &validation,
+        )
+        .map_err(|_| AppError {
+            message: "Invalid or expired confirmation token".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+        })?
+        .claims;
+
+        if claims.action != action.key() || claims.admin_id != admin_id.to_string() {
+            return Err(AppError {
+                message: "Confirmation token does not match this action".to_string(),
+                status_code: StatusCode::FORBIDDEN,
+            });
+        }
+
+        Uuid::parse_str(&claims.jti).map_err(|_| AppError {
+            message: "Invalid or expired confirmation token".to_string(),
+            status_code: StatusCode::FORBIDDEN,
+        })
+    }
+
+    /// Marks the token used, in one statement so two requests can't both spend it
+    async fn spend(db: &DatabaseConnection, token_id: Uuid) -> Result<(), AppError> {
+        let result = confirmation_tokens::Entity::update_many()
+            .col_expr(
+                confirmation_tokens::Column::UsedAt,
+                Expr::value(chrono::Utc::now().fixed_offset()),
+            )
+            .filter(confirmation_tokens::Column::Id.eq(token_id))
+            .filter(confirmation_tokens::Column::UsedAt.is_null())
+            .exec(db)
+            .await
+            .with_context("confirmation_tokens", "spend")?;
+
+        if result.rows_affected == 0 {
+            return Err(AppError {
+                message: "Confirmation token has already been used".to_string(),
+                status_code: StatusCode::FORBIDDEN,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn find_user(db: &DatabaseConnection, user_id: Uuid) -> Result<users::Model, AppError> {
+        users::Entity::find_by_id(user_id)
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?
+            .ok_or(AppError {
+                message: "User not found".to_string(),
+                status_code: StatusCode::NOT_FOUND,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::{set_test_jwt_secret, setup_db};
+
+    async fn setup_db_with_user() -> (DatabaseConnection, Uuid) {
+        let db = setup_db().await;
+
+        let user_id = Uuid::new_v4();
+        users::ActiveModel {
+            id: Set(user_id),
+            email: Set("target@example.com".to_string()),
+            password_hash: Set("hash".to_string()),
+            created_at: Set(Some(chrono::Utc::now().fixed_offset())),
+            last_login: Set(None),
+            role_id: Set(None),
+            email_verified: Set(true),
+            pending_email: Set(None),
+            pending_email_token: Set(None),
+            pending_email_expires_at: Set(None),
+            must_change_password: Set(false),
+            updated_at: Set(None),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        for _ in 0..3 {
+            SessionService::create_session(&db, user_id, None, None, &Uuid::new_v4().to_string())
+                .await
+                .unwrap();
+        }
+
+        (db, user_id)
+    }
+
+    #[tokio::test]
+    async fn test_two_step_confirmation_flow() {
+        set_test_jwt_secret();
+        let (db, user_id) = setup_db_with_user().await;
+        let admin_id = Uuid::new_v4();
+        let action = DestructiveAction::InvalidateUserSessions(user_id);
+
+        // Step 1: describe the impact and get a token
+        let impact = ConfirmationService::describe_impact(&db, &action)
+            .await
+            .unwrap();
+        assert_eq!(
+            impact,
+            "Will invalidate 3 active session(s) for target@example.com"
+        );
+        let confirmation = ConfirmationService::issue(&db, &action, admin_id, impact)
+            .await
+            .unwrap();
+
+        // Step 2: the destructive request carries the token
+        let token_id =
+            ConfirmationService::verify(Some(&confirmation.confirmation_token), &action, admin_id)
+                .unwrap();
+        ConfirmationService::spend(&db, token_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_token_only_spent_once() {
+        set_test_jwt_secret();
+        let (db, user_id) = setup_db_with_user().await;
+        let admin_id = Uuid::new_v4();
+        let action = DestructiveAction::DeleteUser(user_id);
+        let confirmation = ConfirmationService::issue(&db, &action, admin_id, String::new())
+            .await
+            .unwrap();
+
+        let token_id =
+            ConfirmationService::verify(Some(&confirmation.confirmation_token), &action, admin_id)
+                .unwrap();
+        ConfirmationService::spend(&db, token_id).await.unwrap();
+
+        let err = ConfirmationService::spend(&db, token_id).await.unwrap_err();
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+        assert_eq!(err.message, "Confirmation token has already been used");
+    }
+
+    #[test]
+    fn test_missing_token_rejected() {
+        let action = DestructiveAction::DeleteRole(7);
+        let err = ConfirmationService::verify(None, &action, Uuid::new_v4()).unwrap_err();
+        assert_eq!(err.status_code, StatusCode::PRECONDITION_REQUIRED);
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_rejected() {
+        set_test_jwt_secret();
+        let db = setup_db().await;
+        let admin_id = Uuid::new_v4();
+        let action = DestructiveAction::DeleteUser(Uuid::new_v4());
+        let confirmation =
+            ConfirmationService::issue_with_ttl(&db, &action, admin_id, String::new(), -60)
+                .await
+                .unwrap();
+
+        let err =
+            ConfirmationService::verify(Some(&confirmation.confirmation_token), &action, admin_id)
+                .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_token_only_confirms_its_own_action() {
+        set_test_jwt_secret();
+        let db = setup_db().await;
+        let admin_id = Uuid::new_v4();
+        let confirmation = ConfirmationService::issue(
+            &db,
+            &DestructiveAction::DeleteRole(1),
+            admin_id,
+            String::new(),
+        )
+        .await
+        .unwrap();
+        let token = Some(confirmation.confirmation_token.as_str());
+
+        let other_action = DestructiveAction::DeleteRole(2);
+        assert!(ConfirmationService::verify(token, &other_action, admin_id).is_err());
+
+        let other_admin = Uuid::new_v4();
+        assert!(
+            ConfirmationService::verify(token, &DestructiveAction::DeleteRole(1), other_admin)
+                .is_err()
+        );
+    }
+}
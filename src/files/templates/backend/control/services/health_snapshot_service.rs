@@ -0,0 +1,126 @@
+//! Background health snapshots, so the health endpoint doesn't sample the system and run the
+//! user analytics queries on every request
+use sea_orm::DatabaseConnection;
+use std::{env, sync::RwLock, time::Duration};
+use tokio::task::JoinHandle;
+
+use crate::{bridge::types::admin::HealthResponse, control::services::admin_service::AdminService};
+
+/// Default seconds between background health snapshots
+pub const DEFAULT_HEALTH_SNAPSHOT_INTERVAL_SECS: u64 = 30;
+
+/// Global snapshot store read by the health endpoint and refreshed by the background task
+pub static HEALTH_SNAPSHOTS: once_cell::sync::Lazy<HealthSnapshotService> =
+    once_cell::sync::Lazy::new(HealthSnapshotService::default);
+
+/// Holds the latest precomputed `HealthResponse`
+#[derive(Default)]
+pub struct HealthSnapshotService {
+    snapshot: RwLock<Option<HealthResponse>>,
+}
+
+impl HealthSnapshotService {
+    /// Reads HEALTH_SNAPSHOT_INTERVAL_SECS
+    pub fn interval_from_env() -> Duration {
+        let seconds = env::var("HEALTH_SNAPSHOT_INTERVAL_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(DEFAULT_HEALTH_SNAPSHOT_INTERVAL_SECS);
+        Duration::from_secs(seconds)
+    }
+
+    /// The latest snapshot, None until the first one is computed
+    pub fn latest(&self) -> Option<HealthResponse> {
+        self.snapshot.read().unwrap().clone()
+    }
+
+    /// Replaces the stored snapshot
+    pub fn store(&self, snapshot: HealthResponse) {
+        *self.snapshot.write().unwrap() = Some(snapshot);
+    }
+
+    /// Computes a live health status and stores it as the latest snapshot
+    pub async fn refresh(&self, db: &DatabaseConnection) -> HealthResponse {
+        let snapshot = AdminService::get_health_status_coalesced(db).await;
+        self.store(snapshot.clone());
+        snapshot
+    }
+
+    /// The stored snapshot, or a live one if `fresh` is set or nothing is stored yet
+    pub async fn get(&self, db: &DatabaseConnection, fresh: bool) -> HealthResponse {
+        if !fresh {
+            if let Some(snapshot) = self.latest() {
+                return snapshot;
+            }
+        }
+
+        self.refresh(db).await
+    }
+
+    /// Spawns the task refreshing the snapshot every `interval`, starting immediately
+    pub fn start(&'static self, db: DatabaseConnection, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                self.refresh(&db).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::setup_db;
+
+    #[tokio::test]
+    async fn test_get_serves_stored_snapshot_unless_fresh() {
+        let db = setup_db().await;
+        let service = HealthSnapshotService::default();
+
+        let mut snapshot = service.get(&db, false).await;
+        snapshot.status = "cached".to_string();
+        service.store(snapshot);
+
+        assert_eq!(service.get(&db, false).await.status, "cached");
+        assert_ne!(service.get(&db, true).await.status, "cached");
+        assert_ne!(service.latest().unwrap().status, "cached");
+    }
+
+    #[tokio::test]
+    async fn test_background_task_refreshes_snapshot() {
+        let db = setup_db().await;
+        let service: &'static HealthSnapshotService =
+            Box::leak(Box::new(HealthSnapshotService::default()));
+        assert!(service.latest().is_none());
+
+        let task = service.start(db, Duration::from_millis(20));
+
+        // The first refresh runs right away
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while service.latest().is_none() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // Later ticks replace a stale snapshot
+        let mut stale = service.latest().unwrap();
+        stale.status = "stale".to_string();
+        service.store(stale);
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while service.latest().unwrap().status == "stale" {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        task.abort();
+    }
+}
@@ -0,0 +1,258 @@
+//! Failed login counting per email and client IP, with a temporary lockout once too many fail in
+//! a row
+//!
+//! Counting the pair means bad passwords sent from one IP only lock that IP out of the account, the
+//! owner and other admins can still log in. Guessing from many IPs is left to the per-IP rate limit.
+use axum::http::StatusCode;
+use chrono::Duration;
+use sea_orm::{prelude::Expr, sea_query::OnConflict, *};
+
+use crate::domain::validation::normalize_email;
+use crate::entity::models::{login_attempts, prelude::*};
+use crate::infrastructure::app_error::AppError;
+use crate::infrastructure::clock::Clock;
+
+/// Default failed logins in a row before the email is locked out for the IP
+pub const DEFAULT_LOGIN_MAX_ATTEMPTS: i32 = 5;
+
+/// Default minutes a locked out email has to wait before trying again
+pub const DEFAULT_LOGIN_LOCKOUT_MINUTES: i64 = 15;
+
+/// Message returned while an email is locked out for the IP
+pub const LOCKED_OUT_MESSAGE: &str = "Too many failed login attempts, try again later";
+
+/// Login lockout thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoginAttemptConfig {
+    /// Failed logins in a row before locking out, 0 disables the lockout
+    pub max_attempts: i32,
+    /// Minutes the lockout lasts
+    pub lockout_minutes: i64,
+}
+
+impl Default for LoginAttemptConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_LOGIN_MAX_ATTEMPTS,
+            lockout_minutes: DEFAULT_LOGIN_LOCKOUT_MINUTES,
+        }
+    }
+}
+
+impl LoginAttemptConfig {
+    /// Reads LOGIN_MAX_ATTEMPTS and LOGIN_LOCKOUT_MINUTES
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_attempts: std::env::var("LOGIN_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|attempts| *attempts >= 0)
+                .unwrap_or(defaults.max_attempts),
+            lockout_minutes: std::env::var("LOGIN_LOCKOUT_MINUTES")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|minutes| *minutes > 0)
+                .unwrap_or(defaults.lockout_minutes),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.max_attempts > 0
+    }
+}
+
+/// Service tracking failed logins
+pub struct LoginAttemptService;
+
+impl LoginAttemptService {
+    /// Fails with 429 while the email is locked out for `ip_address`
+    pub async fn check(
+        db: &DatabaseConnection,
+        config: LoginAttemptConfig,
+        email: &str,
+        ip_address: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Result<(), AppError> {
+        if !config.enabled() {
+            return Ok(());
+        }
+
+        let attempts = LoginAttempts::find_by_id(Self::key(email, ip_address))
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        match attempts.and_then(|attempts| attempts.locked_until) {
+            Some(locked_until) if locked_until.to_utc() > clock.now() => Err(Self::locked_out()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a failed login, locking the email out for `ip_address` once `max_attempts` fail in
+    /// a row
+    ///
+    /// Returns the 429 error when this attempt started the lockout, the counter restarts from
+    /// zero so the pair gets `max_attempts` more tries once the lockout passes. Expired rows are
+    /// swept first, so failures older than the lockout stop counting.
+    pub async fn record_failure(
+        db: &DatabaseConnection,
+        config: LoginAttemptConfig,
+        email: &str,
+        ip_address: Option<&str>,
+        clock: &dyn Clock,
+    ) -> Result<(), AppError> {
+        if !config.enabled() {
+            return Ok(());
+        }
+
+        Self::sweep_expired(db, config, clock).await?;
+
+        let (email, ip_address) = Self::key(email, ip_address);
+        let now = clock.now().fixed_offset();
+
+        // Increment in the database so concurrent failures are all counted
+        LoginAttempts::insert(login_attempts::ActiveModel {
+            email: Set(email.clone()),
+            ip_address: Set(ip_address.clone()),
+            failed_attempts: Set(1),
+            last_failed_at: Set(Some(now)),
+            locked_until: Set(None),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                login_attempts::Column::Email,
+                login_attempts::Column::IpAddress,
+            ])
+            .value(
+                login_attempts::Column::FailedAttempts,
+                Expr::col((
+                    login_attempts::Entity,
+                    login_attempts::Column::FailedAttempts,
+                ))
+                .add(1),
+            )
+            .update_column(login_attempts::Column::LastFailedAt)
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .map_err(|e| AppError {
+            message: format!("Database error: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        // Only the attempt reaching the threshold starts the lockout
+        let locked = LoginAttempts::update_many()
+            .col_expr(login_attempts::Column::FailedAttempts, Expr::value(0))
+            .col_expr(
+                login_attempts::Column::LockedUntil,
+                Expr::value(now + Duration::minutes(config.lockout_minutes)),
+            )
+            .filter(login_attempts::Column::Email.eq(email))
+            .filter(login_attempts::Column::IpAddress.eq(ip_address))
+            .filter(login_attempts::Column::FailedAttempts.gte(config.max_attempts))
+            .exec(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        if locked.rows_affected > 0 {
+            tracing::warn!(
+                target: "security",
+                event = "login_lockout",
+                minutes = config.lockout_minutes,
+                "Login locked out after {} failed attempts",
+                config.max_attempts
+            );
+            return Err(Self::locked_out());
+        }
+
+        Ok(())
+    }
+
+    /// Clears the failed login count after a successful login from `ip_address`
+    pub async fn clear(
+        db: &DatabaseConnection,
+        email: &str,
+        ip_address: Option<&str>,
+    ) -> Result<(), AppError> {
+        LoginAttempts::delete_by_id(Self::key(email, ip_address))
+            .exec(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+        Ok(())
+    }
+
+    /// Deletes the counts for an email from every IP, returning how many were deleted
+    pub async fn delete_for_email<C: ConnectionTrait>(
+        db: &C,
+        email: &str,
+    ) -> Result<u64, AppError> {
+        let result = LoginAttempts::delete_many()
+            .filter(login_attempts::Column::Email.eq(normalize_email(email)))
+            .exec(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+        Ok(result.rows_affected)
+    }
+
+    /// Deletes the rows whose lockout has passed and whose last failure is older than the
+    /// lockout, returning how many were deleted
+    ///
+    /// Failed logins for unknown emails are stored too, without this the table grows forever.
+    pub async fn sweep_expired(
+        db: &DatabaseConnection,
+        config: LoginAttemptConfig,
+        clock: &dyn Clock,
+    ) -> Result<u64, AppError> {
+        let now = clock.now();
+        let stale_before = (now - Duration::minutes(config.lockout_minutes)).fixed_offset();
+
+        let result = LoginAttempts::delete_many()
+            .filter(
+                Condition::any()
+                    .add(login_attempts::Column::LockedUntil.is_null())
+                    .add(login_attempts::Column::LockedUntil.lte(now.fixed_offset())),
+            )
+            .filter(
+                Condition::any()
+                    .add(login_attempts::Column::LastFailedAt.is_null())
+                    .add(login_attempts::Column::LastFailedAt.lt(stale_before)),
+            )
+            .exec(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+        Ok(result.rows_affected)
+    }
+
+    /// Emails are counted case-insensitively so changing case doesn't reset the count, logins
+    /// without a known client IP share one count per email
+    fn key(email: &str, ip_address: Option<&str>) -> (String, String) {
+        (
+            normalize_email(email),
+            ip_address.unwrap_or_default().trim().to_string(),
+        )
+    }
+
+    fn locked_out() -> AppError {
+        AppError {
+            message: LOCKED_OUT_MESSAGE.to_string(),
+            status_code: StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
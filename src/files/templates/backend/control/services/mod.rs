@@ -1,9 +1,19 @@
 pub mod admin_service;
+pub mod audit_compaction_service;
 pub mod auth_service;
+pub mod confirmation_service;
 pub mod database_service;
+pub mod health_snapshot_service;
+pub mod login_attempt_service;
+pub mod openapi_spec_service;
 pub mod permission_service;
+pub mod role_cache_service;
+pub mod security_event_service;
+pub mod seed_service;
 pub mod server_config;
+pub mod settings_service;
 pub mod session_service;
+pub mod setup_service;
 pub mod startup;
 pub mod system_monitor;
 pub mod token_service;
@@ -0,0 +1,99 @@
+use axum::http::StatusCode;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+use utoipa::openapi::OpenApi;
+
+use crate::infrastructure::app_error::AppError;
+
+/// Where the frontend's openapi-ts config reads the spec from, relative to the project root
+pub const DEFAULT_OPENAPI_SPEC_PATH: &str = "frontend/openapi.json";
+
+/// Service writing the OpenAPI spec to disk for the frontend client generator
+pub struct OpenApiSpecService;
+
+impl OpenApiSpecService {
+    /// Spec file path (OPENAPI_SPEC_PATH, defaults to frontend/openapi.json)
+    pub fn spec_path() -> PathBuf {
+        env::var("OPENAPI_SPEC_PATH")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_OPENAPI_SPEC_PATH))
+    }
+
+    /// Writes the spec as pretty JSON, returning whether the file changed
+    ///
+    /// An unchanged spec isn't rewritten so the generator's watcher only fires on real API
+    /// changes. The file is replaced through a rename, so a watcher never reads half a spec.
+    pub fn write_spec(api: &OpenApi, path: &Path) -> Result<bool, AppError> {
+        let write_error = |e: std::io::Error| AppError {
+            message: format!("Failed to write OpenAPI spec to {}: {}", path.display(), e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        let mut spec = api.to_pretty_json().map_err(|e| AppError {
+            message: format!("Failed to serialize OpenAPI spec: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+        spec.push('\n');
+
+        if std::fs::read_to_string(path).is_ok_and(|current| current == spec) {
+            return Ok(false);
+        }
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(write_error)?;
+        }
+        let mut temp_path = path.as_os_str().to_os_string();
+        temp_path.push(".tmp");
+        std::fs::write(&temp_path, &spec).map_err(write_error)?;
+        std::fs::rename(&temp_path, path).map_err(write_error)?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::temp_dir;
+    use utoipa::openapi::{InfoBuilder, OpenApiBuilder, PathsBuilder};
+
+    fn api(version: &str) -> OpenApi {
+        OpenApiBuilder::new()
+            .info(InfoBuilder::new().title("Test").version(version).build())
+            .paths(PathsBuilder::new().build())
+            .build()
+    }
+
+    #[test]
+    fn test_write_spec_writes_current_spec() {
+        let dir = temp_dir();
+        let path = dir.path().join("frontend/openapi.json");
+
+        assert!(OpenApiSpecService::write_spec(&api("1.0.0"), &path).unwrap());
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, serde_json::to_value(api("1.0.0")).unwrap());
+    }
+
+    #[test]
+    fn test_write_spec_only_rewrites_changed_spec() {
+        let dir = temp_dir();
+        let path = dir.path().join("frontend/openapi.json");
+        OpenApiSpecService::write_spec(&api("1.0.0"), &path).unwrap();
+
+        assert!(!OpenApiSpecService::write_spec(&api("1.0.0"), &path).unwrap());
+        assert!(OpenApiSpecService::write_spec(&api("1.1.0"), &path).unwrap());
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["info"]["version"], "1.1.0");
+    }
+}
@@ -12,7 +12,7 @@ use crate::{
     control::services::database_service::DatabaseService,
     domain::permissions::{Permission, PermissionSet},
     entity::models::{roles, users},
-    infrastructure::app_error::AppError,
+    infrastructure::app_error::{AppError, DbResultExt},
 };
 use axum::http::StatusCode;
 
@@ -34,10 +34,7 @@ impl PermissionService {
             users::Entity::find_by_id(user_id),
         )
         .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
+        .with_context("users", "find")?
         .ok_or(AppError {
             message: "User not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
@@ -48,10 +45,7 @@ impl PermissionService {
             let role = roles::Entity::find_by_id(role_id)
                 .one(db)
                 .await
-                .map_err(|e| AppError {
-                    message: format!("Database error: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                })?;
+                .with_context("roles", "find")?;
 
             if let Some(role_model) = role {
                 let permissions: Vec<String> =
@@ -72,48 +66,10 @@ impl PermissionService {
     pub async fn has_any_permission(
         db: &DatabaseConnection,
         user_id: Uuid,
-        _permissions: &[Permission],
+        permissions: &[Permission],
     ) -> Result<bool, AppError> {
-        let user = DatabaseService::find_one_with_tracking(
-            db,
-            "users",
-            users::Entity::find_by_id(user_id),
-        )
-        .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
-        .ok_or(AppError {
-            message: "User not found".to_string(),
-            status_code: StatusCode::NOT_FOUND,
-        })?;
-
-        if let Some(role_id) = user.role_id {
-            let role = roles::Entity::find_by_id(role_id)
-                .one(db)
-                .await
-                .map_err(|e| AppError {
-                    message: format!("Database error: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                })?;
-
-            if let Some(role_model) = role {
-                let permissions: Vec<String> =
-                    serde_json::from_str(&role_model.permissions).unwrap_or_else(|_| vec![]);
-
-                let permission_set = PermissionSet::from_strings(permissions.clone());
-                let permission_vec: Vec<Permission> = permissions
-                    .iter()
-                    .map(|p| Permission::from_string(p))
-                    .collect();
-                Ok(permission_set.contains_any(&permission_vec))
-            } else {
-                Ok(false)
-            }
-        } else {
-            Ok(false)
-        }
+        let permission_set = Self::get_user_permissions(db, user_id).await?;
+        Ok(permission_set.contains_any(permissions))
     }
 
     /// Check if a user has all of the given permissions
@@ -121,52 +77,13 @@ impl PermissionService {
     pub async fn has_all_permissions(
         db: &DatabaseConnection,
         user_id: Uuid,
-        _permissions: &[Permission],
+        permissions: &[Permission],
     ) -> Result<bool, AppError> {
-        let user = DatabaseService::find_one_with_tracking(
-            db,
-            "users",
-            users::Entity::find_by_id(user_id),
-        )
-        .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
-        .ok_or(AppError {
-            message: "User not found".to_string(),
-            status_code: StatusCode::NOT_FOUND,
-        })?;
-
-        if let Some(role_id) = user.role_id {
-            let role = roles::Entity::find_by_id(role_id)
-                .one(db)
-                .await
-                .map_err(|e| AppError {
-                    message: format!("Database error: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                })?;
-
-            if let Some(role_model) = role {
-                let permissions: Vec<String> =
-                    serde_json::from_str(&role_model.permissions).unwrap_or_else(|_| vec![]);
-
-                let permission_set = PermissionSet::from_strings(permissions.clone());
-                let permission_vec: Vec<Permission> = permissions
-                    .iter()
-                    .map(|p| Permission::from_string(p))
-                    .collect();
-                Ok(permission_set.contains_all(&permission_vec))
-            } else {
-                Ok(false)
-            }
-        } else {
-            Ok(false)
-        }
+        let permission_set = Self::get_user_permissions(db, user_id).await?;
+        Ok(permission_set.contains_all(permissions))
     }
 
     /// Get all permissions for a user
-    #[allow(dead_code)]
     pub async fn get_user_permissions(
         db: &DatabaseConnection,
         user_id: Uuid,
@@ -177,10 +94,7 @@ impl PermissionService {
             users::Entity::find_by_id(user_id),
         )
         .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
+        .with_context("users", "find")?
         .ok_or(AppError {
             message: "User not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
@@ -190,10 +104,7 @@ impl PermissionService {
             let role = roles::Entity::find_by_id(role_id)
                 .one(db)
                 .await
-                .map_err(|e| AppError {
-                    message: format!("Database error: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                })?;
+                .with_context("roles", "find")?;
 
             if let Some(role_model) = role {
                 let permissions: Vec<String> =
@@ -208,30 +119,23 @@ impl PermissionService {
         }
     }
 
+    /// Get the name of a role, None if it doesn't exist
+    pub async fn get_role_name(
+        db: &DatabaseConnection,
+        role_id: i32,
+    ) -> Result<Option<String>, AppError> {
+        let role = roles::Entity::find_by_id(role_id)
+            .one(db)
+            .await
+            .with_context("roles", "find")?;
+
+        Ok(role.map(|role| role.name))
+    }
+
     /// Get all available permissions in the system
     #[allow(dead_code)]
     pub fn get_all_permissions() -> Vec<Permission> {
-        vec![
-            Permission::All,
-            Permission::AdminRead,
-            Permission::AdminWrite,
-            Permission::AdminDelete,
-            Permission::AdminUsers,
-            Permission::AdminRoles,
-            Permission::AdminLogs,
-            Permission::AdminDatabase,
-            Permission::AdminHealth,
-            Permission::AdminMetrics,
-            Permission::UserRead,
-            Permission::UserWrite,
-            Permission::UserDelete,
-            Permission::UserProfile,
-            Permission::UserCreate,
-            Permission::SystemHealth,
-            Permission::SystemMetrics,
-            Permission::SystemLogs,
-            Permission::SystemDatabase,
-        ]
+        Permission::all_known()
     }
 
     /// Get permissions by category
@@ -265,14 +169,39 @@ impl PermissionService {
     }
 
     /// Check if a permission string is valid
-    #[allow(dead_code)]
     pub fn is_valid_permission(permission_str: &str) -> bool {
-        match permission_str {
-            "*" | "admin:read" | "admin:write" | "admin:delete" | "admin:users" | "admin:roles"
-            | "admin:logs" | "admin:database" | "admin:health" | "admin:metrics" | "user:read"
-            | "user:write" | "user:delete" | "user:profile" | "user:create" | "system:health"
-            | "system:metrics" | "system:logs" | "system:database" => true,
-            _ => permission_str.contains(':'), // Custom permissions must contain ':'
+        // Custom permissions must contain ':'
+        Permission::is_known(permission_str) || permission_str.contains(':')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_known_permission_round_trips_and_is_valid() {
+        let permissions = PermissionService::get_all_permissions();
+        assert_eq!(
+            permissions.len(),
+            crate::domain::permissions::KNOWN_PERMISSIONS.len()
+        );
+
+        for permission in permissions {
+            let name = permission.to_string();
+            assert_eq!(Permission::from_string(&name), permission, "{}", name);
+            assert!(PermissionService::is_valid_permission(&name), "{}", name);
+            assert_ne!(permission.category(), "custom", "{}", name);
         }
     }
+
+    #[test]
+    fn test_unknown_strings_are_custom() {
+        assert_eq!(
+            Permission::from_string("reports:export"),
+            Permission::Custom("reports:export".to_string())
+        );
+        assert!(PermissionService::is_valid_permission("reports:export"));
+        assert!(!PermissionService::is_valid_permission("reports"));
+    }
 }
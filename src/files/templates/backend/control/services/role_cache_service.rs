@@ -0,0 +1,167 @@
+//! Read-through cache of the roles table, with permissions already deserialized
+use axum::http::StatusCode;
+use sea_orm::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    sync::{
+        Arc, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::{
+    bridge::types::admin::RoleResponse, entity::models::roles, infrastructure::app_error::AppError,
+};
+
+/// Global roles cache used by the admin user and role lists
+pub static ROLE_CACHE: once_cell::sync::Lazy<RoleCache> =
+    once_cell::sync::Lazy::new(RoleCache::from_env);
+
+/// Bumped on every role mutation, caches loaded at an older generation reload on next read
+static ROLE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Roles by ID, loaded from the database once per role mutation
+///
+/// Like the settings cache this is per process, a role changed through another instance is only
+/// seen here after a role mutation on this instance or a restart.
+pub struct RoleCache {
+    enabled: bool,
+    /// Generation the roles were loaded at, and the roles
+    cached: RwLock<Option<(u64, Arc<BTreeMap<i32, RoleResponse>>)>>,
+    /// Number of times the roles were loaded from the database
+    loads: AtomicU64,
+    /// Number of role rows read from the database
+    roles_loaded: AtomicU64,
+}
+
+impl Default for RoleCache {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl RoleCache {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            cached: RwLock::new(None),
+            loads: AtomicU64::new(0),
+            roles_loaded: AtomicU64::new(0),
+        }
+    }
+
+    /// Reads ROLE_CACHE_ENABLED (defaults to true), disabled loads the roles on every read
+    pub fn from_env() -> Self {
+        let enabled = env::var("ROLE_CACHE_ENABLED")
+            .map(|v| v.to_lowercase() != "false")
+            .unwrap_or(true);
+        Self::new(enabled)
+    }
+
+    /// Invalidates every role cache, call after any write to the roles table
+    pub fn invalidate() {
+        ROLE_GENERATION.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// All roles by ID, served from the cache unless a role changed since it was loaded
+    pub async fn roles(
+        &self,
+        db: &DatabaseConnection,
+    ) -> Result<Arc<BTreeMap<i32, RoleResponse>>, AppError> {
+        let generation = ROLE_GENERATION.load(Ordering::Acquire);
+        if self.enabled {
+            if let Some((cached_generation, roles)) = &*self.cached.read().unwrap() {
+                if *cached_generation == generation {
+                    return Ok(roles.clone());
+                }
+            }
+        }
+
+        let roles = Arc::new(self.load(db).await?);
+
+        // Tagged with the generation read before loading, a write racing the load makes the
+        // next read load again
+        if self.enabled {
+            *self.cached.write().unwrap() = Some((generation, roles.clone()));
+        }
+
+        Ok(roles)
+    }
+
+    /// Names of the given roles by ID, unknown IDs are left out
+    ///
+    /// Served from the cache when enabled, otherwise only the given roles are queried.
+    pub async fn role_names(
+        &self,
+        db: &DatabaseConnection,
+        role_ids: &[i32],
+    ) -> Result<HashMap<i32, String>, AppError> {
+        if self.enabled {
+            let roles = self.roles(db).await?;
+            return Ok(role_ids
+                .iter()
+                .filter_map(|role_id| roles.get(role_id).map(|role| (role.id, role.name.clone())))
+                .collect());
+        }
+
+        if role_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let roles = roles::Entity::find()
+            .filter(roles::Column::Id.is_in(role_ids.iter().copied()))
+            .all(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+        self.record_load(roles.len());
+
+        Ok(roles.into_iter().map(|role| (role.id, role.name)).collect())
+    }
+
+    /// Number of times roles were loaded from the database
+    pub fn loads(&self) -> u64 {
+        self.loads.load(Ordering::Relaxed)
+    }
+
+    /// Number of role rows read from the database
+    pub fn roles_loaded(&self) -> u64 {
+        self.roles_loaded.load(Ordering::Relaxed)
+    }
+
+    fn record_load(&self, rows: usize) {
+        self.loads.fetch_add(1, Ordering::Relaxed);
+        self.roles_loaded.fetch_add(rows as u64, Ordering::Relaxed);
+    }
+
+    async fn load(&self, db: &DatabaseConnection) -> Result<BTreeMap<i32, RoleResponse>, AppError> {
+        let roles = roles::Entity::find().all(db).await.map_err(|e| AppError {
+            message: format!("Database error: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+        self.record_load(roles.len());
+
+        Ok(roles
+            .into_iter()
+            .map(|role| {
+                let permissions: Vec<String> =
+                    serde_json::from_str(&role.permissions).unwrap_or_else(|_| vec![]);
+
+                (
+                    role.id,
+                    RoleResponse {
+                        id: role.id,
+                        name: role.name,
+                        description: role.description,
+                        permissions,
+                        created_at: role.created_at.map(|dt| dt.to_rfc3339()),
+                        updated_at: role.updated_at.map(|dt| dt.to_rfc3339()),
+                    },
+                )
+            })
+            .collect())
+    }
+}
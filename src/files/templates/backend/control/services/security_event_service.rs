@@ -0,0 +1,312 @@
+//! Security events worth surfacing, with per-IP escalation and optional lockout
+use axum::http::StatusCode;
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::infrastructure::{
+    app_error::AppError,
+    websocket::{LogLevel, WsMessage, broadcast},
+};
+
+/// Default number of events from one IP within the window before escalating
+pub const DEFAULT_ALERT_THRESHOLD: usize = 5;
+
+/// Default sliding window for counting events in seconds
+pub const DEFAULT_ALERT_WINDOW_SECONDS: u64 = 600;
+
+/// Kinds of security events
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SecurityEventKind {
+    /// Admin login with valid credentials for a user without admin rights
+    AdminLoginNonAdmin,
+    /// Admin login for an email that doesn't exist
+    AdminLoginUnknownEmail,
+}
+
+impl SecurityEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SecurityEventKind::AdminLoginNonAdmin => "admin_login_non_admin",
+            SecurityEventKind::AdminLoginUnknownEmail => "admin_login_unknown_email",
+        }
+    }
+}
+
+/// Raised once when an IP reaches the alert threshold within the window
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityEscalation {
+    pub ip_address: String,
+    pub kind: SecurityEventKind,
+    pub count: usize,
+}
+
+/// Escalation thresholds
+#[derive(Debug, Clone)]
+pub struct SecurityEventConfig {
+    /// Events from one IP within the window before escalating
+    pub threshold: usize,
+    /// Sliding window the events are counted in
+    pub window: Duration,
+    /// Reject further admin logins from an escalated IP until the window passes
+    pub lockout: bool,
+}
+
+impl SecurityEventConfig {
+    /// Reads ADMIN_LOGIN_ALERT_THRESHOLD, ADMIN_LOGIN_ALERT_WINDOW_SECONDS and ADMIN_LOGIN_LOCKOUT
+    pub fn from_env() -> Self {
+        let threshold = env::var("ADMIN_LOGIN_ALERT_THRESHOLD")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|threshold| *threshold > 0)
+            .unwrap_or(DEFAULT_ALERT_THRESHOLD);
+        let window_seconds = env::var("ADMIN_LOGIN_ALERT_WINDOW_SECONDS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_ALERT_WINDOW_SECONDS);
+        let lockout = env::var("ADMIN_LOGIN_LOCKOUT")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .unwrap_or(false);
+
+        Self {
+            threshold,
+            window: Duration::from_secs(window_seconds),
+            lockout,
+        }
+    }
+}
+
+/// Counts security events per IP in a sliding window
+pub struct SecurityEventTracker {
+    config: SecurityEventConfig,
+    events: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+/// Global tracker, configured from the environment
+pub static SECURITY_EVENTS: once_cell::sync::Lazy<SecurityEventTracker> =
+    once_cell::sync::Lazy::new(|| SecurityEventTracker::new(SecurityEventConfig::from_env()));
+
+impl SecurityEventTracker {
+    pub fn new(config: SecurityEventConfig) -> Self {
+        Self {
+            config,
+            events: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records the event, logging it and broadcasting a warning when the IP reaches the threshold
+    pub async fn record(
+        &self,
+        kind: SecurityEventKind,
+        ip_address: Option<&str>,
+    ) -> Option<SecurityEscalation> {
+        let ip_address = ip_address.unwrap_or("unknown").to_string();
+        let count = self.count_event(&ip_address, Instant::now());
+
+        tracing::warn!(
+            target: "security",
+            event = kind.as_str(),
+            ip_address = %ip_address,
+            count,
+            "Security event"
+        );
+
+        // Escalate once per crossing, not on every event above the threshold
+        if count != self.config.threshold {
+            return None;
+        }
+
+        let message = format!(
+            "{} security events ({}) from {} within {}s{}",
+            count,
+            kind.as_str(),
+            ip_address,
+            self.config.window.as_secs(),
+            if self.config.lockout {
+                ", admin login locked out for this IP"
+            } else {
+                ""
+            }
+        );
+        tracing::warn!(target: "security", "{}", message);
+        broadcast(WsMessage::system_log(LogLevel::Warn, message, "security")).await;
+
+        Some(SecurityEscalation {
+            ip_address,
+            kind,
+            count,
+        })
+    }
+
+    /// Fails with 429 if lockout is enabled and the IP has reached the threshold
+    pub fn check_lockout(&self, ip_address: Option<&str>) -> Result<(), AppError> {
+        if !self.config.lockout {
+            return Ok(());
+        }
+
+        let ip_address = ip_address.unwrap_or("unknown");
+        let mut events = self.events.lock().unwrap();
+        let count = events
+            .get_mut(ip_address)
+            .map(|timestamps| {
+                Self::prune(timestamps, Instant::now(), self.config.window);
+                timestamps.len()
+            })
+            .unwrap_or(0);
+
+        if count >= self.config.threshold {
+            return Err(AppError {
+                message: "Too many failed admin login attempts, try again later".to_string(),
+                status_code: StatusCode::TOO_MANY_REQUESTS,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn count_event(&self, ip_address: &str, now: Instant) -> usize {
+        let mut events = self.events.lock().unwrap();
+        // Drop IPs with no recent events so the map doesn't grow forever
+        events.retain(|_, timestamps| {
+            Self::prune(timestamps, now, self.config.window);
+            !timestamps.is_empty()
+        });
+
+        let timestamps = events.entry(ip_address.to_string()).or_default();
+        timestamps.push_back(now);
+        timestamps.len()
+    }
+
+    fn prune(timestamps: &mut VecDeque<Instant>, now: Instant, window: Duration) {
+        while timestamps
+            .front()
+            .is_some_and(|timestamp| now.duration_since(*timestamp) > window)
+        {
+            timestamps.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::websocket::WEBSOCKET_MANAGER;
+
+    fn tracker(lockout: bool) -> SecurityEventTracker {
+        SecurityEventTracker::new(SecurityEventConfig {
+            threshold: 3,
+            window: Duration::from_secs(600),
+            lockout,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_repeated_non_admin_attempts_escalate() {
+        let tracker = tracker(false);
+        let mut receiver = WEBSOCKET_MANAGER.subscribe();
+        let ip = Some("203.0.113.10");
+
+        for _ in 0..2 {
+            assert!(
+                tracker
+                    .record(SecurityEventKind::AdminLoginNonAdmin, ip)
+                    .await
+                    .is_none()
+            );
+        }
+
+        let escalation = tracker
+            .record(SecurityEventKind::AdminLoginNonAdmin, ip)
+            .await
+            .unwrap();
+        assert_eq!(escalation.count, 3);
+        assert_eq!(escalation.ip_address, "203.0.113.10");
+
+        // Only escalates once per crossing
+        assert!(
+            tracker
+                .record(SecurityEventKind::AdminLoginNonAdmin, ip)
+                .await
+                .is_none()
+        );
+
+        // The warning reached the WebSocket broadcast
+        let mut broadcast = false;
+        while let Ok(message) = receiver.try_recv() {
+            if let WsMessage::SystemLog { level, message, .. } = message {
+                broadcast |= level == LogLevel::Warn && message.contains("203.0.113.10");
+            }
+        }
+        assert!(broadcast);
+    }
+
+    #[tokio::test]
+    async fn test_events_counted_per_ip() {
+        let tracker = tracker(false);
+
+        for ip in ["203.0.113.1", "203.0.113.2", "203.0.113.3"] {
+            assert!(
+                tracker
+                    .record(SecurityEventKind::AdminLoginUnknownEmail, Some(ip))
+                    .await
+                    .is_none()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_escalated_ip_does_not_escalate_others() {
+        let tracker = tracker(false);
+        let noisy = Some("203.0.113.30");
+        let other = Some("203.0.113.31");
+
+        for _ in 0..2 {
+            tracker
+                .record(SecurityEventKind::AdminLoginNonAdmin, noisy)
+                .await;
+        }
+        assert!(
+            tracker
+                .record(SecurityEventKind::AdminLoginNonAdmin, noisy)
+                .await
+                .is_some()
+        );
+
+        // The other IP starts its own count and stays below the threshold
+        for _ in 0..2 {
+            assert!(
+                tracker
+                    .record(SecurityEventKind::AdminLoginNonAdmin, other)
+                    .await
+                    .is_none()
+            );
+        }
+        let escalation = tracker
+            .record(SecurityEventKind::AdminLoginNonAdmin, other)
+            .await
+            .unwrap();
+        assert_eq!(escalation.ip_address, "203.0.113.31");
+        assert_eq!(escalation.count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_lockout_after_threshold() {
+        let tracker = tracker(true);
+        let ip = Some("203.0.113.20");
+
+        for _ in 0..3 {
+            assert!(tracker.check_lockout(ip).is_ok());
+            tracker
+                .record(SecurityEventKind::AdminLoginUnknownEmail, ip)
+                .await;
+        }
+
+        let err = tracker.check_lockout(ip).unwrap_err();
+        assert_eq!(err.status_code, StatusCode::TOO_MANY_REQUESTS);
+        assert!(tracker.check_lockout(Some("203.0.113.21")).is_ok());
+    }
+}
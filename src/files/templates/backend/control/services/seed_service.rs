@@ -0,0 +1,135 @@
+//! One-time database seeders tracked in the seed_history table
+use axum::http::StatusCode;
+use sea_orm::*;
+use std::future::Future;
+
+use crate::{entity::models::seed_history, infrastructure::app_error::AppError};
+
+/// Service for running seeders that must only ever run once
+pub struct SeedService;
+
+impl SeedService {
+    /// Runs the seeder unless a seeder with this key has run before, returns whether it ran
+    ///
+    /// The key is recorded only after the seeder succeeds, so a failed seeder is retried on the
+    /// next startup. Once recorded the seeder never runs again, even if its data is later deleted.
+    pub async fn seed_once<F, Fut>(
+        key: &str,
+        db: &DatabaseConnection,
+        seeder: F,
+    ) -> Result<bool, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(), AppError>>,
+    {
+        if Self::has_run(db, key).await? {
+            println!("Seeder already ran: {}", key);
+            return Ok(false);
+        }
+
+        seeder().await?;
+
+        seed_history::ActiveModel {
+            key: Set(key.to_string()),
+            ran_at: Set(chrono::Utc::now().fixed_offset()),
+        }
+        .insert(db)
+        .await
+        .map_err(|e| AppError {
+            message: format!("Failed to record seeder {}: {}", key, e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        Ok(true)
+    }
+
+    /// Whether a seeder with this key has already run
+    pub async fn has_run(db: &DatabaseConnection, key: &str) -> Result<bool, AppError> {
+        let entry = seed_history::Entity::find_by_id(key.to_string())
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(entry.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::models::roles;
+    use crate::infrastructure::test_support::setup_db;
+
+    async fn seed_editor_role(db: &DatabaseConnection) -> Result<bool, AppError> {
+        SeedService::seed_once("editor_role", db, || async {
+            roles::ActiveModel {
+                name: Set("editor".to_string()),
+                permissions: Set("[]".to_string()),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .map(|_| ())
+            .map_err(|e| AppError {
+                message: e.to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })
+        })
+        .await
+    }
+
+    async fn editor_exists(db: &DatabaseConnection) -> bool {
+        roles::Entity::find()
+            .filter(roles::Column::Name.eq("editor"))
+            .one(db)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn test_seeder_runs_once() {
+        let db = setup_db().await;
+
+        assert!(seed_editor_role(&db).await.unwrap());
+        assert!(editor_exists(&db).await);
+
+        // Second startup skips it
+        assert!(!seed_editor_role(&db).await.unwrap());
+        assert!(SeedService::has_run(&db, "editor_role").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_seeder_skipped_after_data_removed() {
+        let db = setup_db().await;
+        seed_editor_role(&db).await.unwrap();
+
+        roles::Entity::delete_many()
+            .filter(roles::Column::Name.eq("editor"))
+            .exec(&db)
+            .await
+            .unwrap();
+
+        assert!(!seed_editor_role(&db).await.unwrap());
+        assert!(!editor_exists(&db).await);
+    }
+
+    #[tokio::test]
+    async fn test_failed_seeder_not_recorded() {
+        let db = setup_db().await;
+
+        let result = SeedService::seed_once("failing", &db, || async {
+            Err(AppError {
+                message: "boom".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert!(!SeedService::has_run(&db, "failing").await.unwrap());
+    }
+}
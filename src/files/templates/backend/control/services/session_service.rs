@@ -1,18 +1,26 @@
-use chrono::{Duration, Utc};
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use rand_core::{OsRng, RngCore};
 use sea_orm::prelude::Expr;
 use sea_orm::*;
 use uuid::Uuid;
 
-use crate::control::services::database_service::DatabaseService;
+use crate::control::services::{database_service::DatabaseService, token_service::TokenService};
 use crate::entity::models::{prelude::*, user_sessions};
-use crate::infrastructure::app_error::AppError;
+use crate::infrastructure::app_error::{AppError, DbResultExt};
+use crate::infrastructure::clock::{Clock, system_clock};
+use crate::infrastructure::session_policy::{SessionPolicy, SessionPolicyConfig};
+use crate::infrastructure::timestamps::update_touched;
 use axum::http::StatusCode;
 
+/// Default days back inactive sessions are listed for, see `SessionService::get_all_user_sessions`
+pub const DEFAULT_INACTIVE_SESSION_WINDOW_DAYS: i64 = 30;
+
 /// Service for session-related business operations
 pub struct SessionService;
 
 impl SessionService {
-    /// Creates a new session on login
+    /// Creates a new session on login, following the session policy of the user's role
     pub async fn create_session(
         db: &DatabaseConnection,
         user_id: Uuid,
@@ -20,11 +28,55 @@ impl SessionService {
         ip_address: Option<String>,
         session_token: &str,
     ) -> Result<user_sessions::Model, AppError> {
+        let policy = Self::session_policy_for_user(db, SessionPolicyConfig::get(), user_id).await?;
+        Self::create_session_with_policy(db, policy, user_id, user_agent, ip_address, session_token)
+            .await
+    }
+
+    /// Resolves the session policy for a user from their role name
+    pub async fn session_policy_for_user(
+        db: &DatabaseConnection,
+        config: &SessionPolicyConfig,
+        user_id: Uuid,
+    ) -> Result<SessionPolicy, AppError> {
+        let role_id = Users::find_by_id(user_id)
+            .one(db)
+            .await
+            .with_context("users", "find")?
+            .and_then(|user| user.role_id);
+
+        let role_name = match role_id {
+            Some(role_id) => Roles::find_by_id(role_id)
+                .one(db)
+                .await
+                .with_context("roles", "find")?
+                .map(|role| role.name),
+            None => None,
+        };
+
+        Ok(config.policy_for(role_name.as_deref()))
+    }
+
+    /// Creates a new session with the given expiry and concurrent session limit
+    pub async fn create_session_with_policy(
+        db: &DatabaseConnection,
+        policy: SessionPolicy,
+        user_id: Uuid,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        session_token: &str,
+    ) -> Result<user_sessions::Model, AppError> {
+        // Make room for the new session by invalidating the oldest ones over the limit
+        if let Some(max_sessions) = policy.max_sessions {
+            Self::invalidate_oldest_sessions(db, user_id, max_sessions.saturating_sub(1)).await?;
+        }
+
         // Use the session token directly (UUID from JWT claims)
         let session_token_str = session_token.to_string();
 
-        // Calculate expiration time (24 hours from now)
-        let expires_at = Utc::now() + Duration::hours(24);
+        // Calculate expiration time from the policy
+        let now = system_clock().now();
+        let expires_at = now + Duration::hours(policy.expiry_hours);
 
         // Create session ID
         let session_id = Uuid::new_v4();
@@ -36,10 +88,11 @@ impl SessionService {
             session_token: Set(session_token_str),
             user_agent: Set(user_agent),
             ip_address: Set(ip_address),
-            created_at: Set(Some(Utc::now().fixed_offset())),
-            last_activity: Set(Some(Utc::now().fixed_offset())),
+            created_at: Set(Some(now.fixed_offset())),
+            last_activity: Set(Some(now.fixed_offset())),
             expires_at: Set(expires_at.fixed_offset()),
             is_active: Set(true),
+            refresh_token_hash: Set(None),
         };
 
         // Insert into database
@@ -55,9 +108,22 @@ impl SessionService {
     }
 
     /// Validates that a session exists and is active
+    ///
+    /// Every request reads the session row, nothing is cached, so an invalidation rejects the
+    /// session's next request. A cache added here has to be cleared by the `invalidate_*`
+    /// functions before they return.
     pub async fn validate_session(
         db: &DatabaseConnection,
         session_token: &str,
+    ) -> Result<user_sessions::Model, AppError> {
+        Self::validate_session_with_clock(db, session_token, system_clock()).await
+    }
+
+    /// Validates that a session exists and is active, checking expiry against the given clock
+    pub async fn validate_session_with_clock(
+        db: &DatabaseConnection,
+        session_token: &str,
+        clock: &dyn Clock,
     ) -> Result<user_sessions::Model, AppError> {
         // Find session by session token
         let session = DatabaseService::find_one_with_tracking(
@@ -66,10 +132,7 @@ impl SessionService {
             UserSessions::find().filter(user_sessions::Column::SessionToken.eq(session_token)),
         )
         .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
+        .with_context("user_sessions", "validate")?
         .ok_or(AppError {
             message: "Session not found".to_string(),
             status_code: StatusCode::UNAUTHORIZED,
@@ -84,8 +147,7 @@ impl SessionService {
         }
 
         // Check if session is expired
-        let now = Utc::now();
-        if session.expires_at.to_utc() < now {
+        if session.expires_at.to_utc() < clock.now() {
             return Err(AppError {
                 message: "Session expired".to_string(),
                 status_code: StatusCode::UNAUTHORIZED,
@@ -95,6 +157,98 @@ impl SessionService {
         Ok(session)
     }
 
+    /// Issues a refresh token for the session, replacing any previous one
+    ///
+    /// The token is `<session token>.<random secret>`, only its SHA-256 hash is stored.
+    pub async fn issue_refresh_token(
+        db: &DatabaseConnection,
+        session: &user_sessions::Model,
+    ) -> Result<String, AppError> {
+        let refresh_token = Self::new_refresh_token(&session.session_token);
+
+        user_sessions::ActiveModel {
+            id: Set(session.id),
+            refresh_token_hash: Set(Some(TokenService::hash_token(&refresh_token))),
+            ..Default::default()
+        }
+        .update(db)
+        .await
+        .map_err(|e| AppError {
+            message: format!("Failed to issue refresh token: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        Ok(refresh_token)
+    }
+
+    /// Exchanges a refresh token for a new one, returning the session it belongs to
+    ///
+    /// Every refresh token is used once. Presenting one that has already been rotated away means
+    /// it was copied, so the session is invalidated along with every token issued for it, and
+    /// whoever holds the latest one has to log in again.
+    pub async fn rotate_refresh_token(
+        db: &DatabaseConnection,
+        refresh_token: &str,
+    ) -> Result<(user_sessions::Model, String), AppError> {
+        let invalid = || AppError {
+            message: "Invalid refresh token".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+        };
+
+        let (session_token, _) = refresh_token.split_once('.').ok_or_else(invalid)?;
+        let session = Self::validate_session(db, session_token)
+            .await
+            .map_err(|_| invalid())?;
+
+        let presented_hash = TokenService::hash_token(refresh_token);
+        let rotated = Self::new_refresh_token(&session.session_token);
+
+        // Only replaces the hash that was presented, so of two concurrent uses of the same token
+        // one wins and the other is treated as a replay
+        let result = UserSessions::update_many()
+            .col_expr(
+                user_sessions::Column::RefreshTokenHash,
+                Expr::value(TokenService::hash_token(&rotated)),
+            )
+            .col_expr(
+                user_sessions::Column::LastActivity,
+                Expr::value(system_clock().now().fixed_offset()),
+            )
+            .filter(user_sessions::Column::Id.eq(session.id))
+            .filter(user_sessions::Column::RefreshTokenHash.eq(presented_hash))
+            // Invalidated after the check above, the refresh token must not outlive it
+            .filter(user_sessions::Column::IsActive.eq(true))
+            .exec(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Failed to rotate refresh token: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        if result.rows_affected == 0 {
+            tracing::warn!(
+                target: "security",
+                user_id = %session.user_id,
+                session_id = %session.id,
+                "Refresh token reused, invalidating the session"
+            );
+            Self::invalidate_session_by_id(db, session.id).await?;
+            return Err(invalid());
+        }
+
+        Ok((session, rotated))
+    }
+
+    fn new_refresh_token(session_token: &str) -> String {
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        format!(
+            "{}.{}",
+            session_token,
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret)
+        )
+    }
+
     /// Updates session activity timestamp
     pub async fn update_session_activity(
         db: &DatabaseConnection,
@@ -109,10 +263,7 @@ impl SessionService {
                 .filter(user_sessions::Column::SessionToken.eq(session_id.to_string())),
         )
         .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
+        .with_context("user_sessions", "find")?
         .ok_or(AppError {
             message: "Session not found".to_string(),
             status_code: StatusCode::UNAUTHORIZED,
@@ -121,12 +272,10 @@ impl SessionService {
         // Update the found session's last activity
         let session_active_model = user_sessions::ActiveModel {
             id: Set(session.id),
-            last_activity: Set(Some(Utc::now().fixed_offset())),
             ..Default::default()
         };
 
-        session_active_model
-            .update(db)
+        update_touched(db, session_active_model)
             .await
             .map_err(|e| AppError {
                 message: format!("Failed to update session activity: {}", e),
@@ -150,10 +299,41 @@ impl SessionService {
                 .order_by_desc(user_sessions::Column::LastActivity),
         )
         .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
+        .with_context("user_sessions", "list")?;
+
+        Ok(sessions)
+    }
+
+    /// Gets a user's live sessions, plus the invalidated and expired ones created or last used
+    /// after `since`
+    ///
+    /// Bounded by `since` so a long-lived account doesn't return every session it ever had.
+    pub async fn get_all_user_sessions(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<user_sessions::Model>, AppError> {
+        let now = system_clock().now().fixed_offset();
+        let since = since.fixed_offset();
+        let sessions = DatabaseService::find_all_with_tracking(
+            db,
+            "user_sessions",
+            UserSessions::find()
+                .filter(user_sessions::Column::UserId.eq(user_id))
+                .filter(
+                    Condition::any()
+                        .add(
+                            Condition::all()
+                                .add(user_sessions::Column::IsActive.eq(true))
+                                .add(user_sessions::Column::ExpiresAt.gt(now)),
+                        )
+                        .add(user_sessions::Column::LastActivity.gt(since))
+                        .add(user_sessions::Column::CreatedAt.gt(since)),
+                )
+                .order_by_desc(user_sessions::Column::LastActivity),
+        )
+        .await
+        .with_context("user_sessions", "list")?;
 
         Ok(sessions)
     }
@@ -172,19 +352,22 @@ impl SessionService {
                 .filter(user_sessions::Column::SessionToken.eq(session_id.to_string())),
         )
         .await
-        .map_err(|e| AppError {
-            message: format!("Database error: {}", e),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?
+        .with_context("user_sessions", "find")?
         .ok_or(AppError {
             message: "Session not found".to_string(),
             status_code: StatusCode::UNAUTHORIZED,
         })?;
 
-        // Update the found session to set is_active = false
+        Self::invalidate_session_by_id(db, session.id).await
+    }
+
+    /// Sets is_active = false on the session row with the given primary key, dropping its refresh
+    /// token
+    async fn invalidate_session_by_id(db: &DatabaseConnection, id: Uuid) -> Result<(), AppError> {
         let session_active_model = user_sessions::ActiveModel {
-            id: Set(session.id),
+            id: Set(id),
             is_active: Set(false),
+            refresh_token_hash: Set(None),
             ..Default::default()
         };
 
@@ -199,13 +382,17 @@ impl SessionService {
         Ok(())
     }
 
-    /// Invalidates all sessions for a user
+    /// Invalidates all sessions for a user, effective from their next request
     pub async fn invalidate_all_user_sessions(
         db: &DatabaseConnection,
         user_id: Uuid,
     ) -> Result<u64, AppError> {
         let result = UserSessions::update_many()
             .col_expr(user_sessions::Column::IsActive, Expr::value(false))
+            .col_expr(
+                user_sessions::Column::RefreshTokenHash,
+                Expr::value(Option::<String>::None),
+            )
             .filter(user_sessions::Column::UserId.eq(user_id))
             .filter(user_sessions::Column::IsActive.eq(true))
             .exec(db)
@@ -218,10 +405,44 @@ impl SessionService {
         Ok(result.rows_affected)
     }
 
+    /// Invalidates the user's least recently active sessions so at most `keep` stay active
+    async fn invalidate_oldest_sessions(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        keep: u64,
+    ) -> Result<(), AppError> {
+        let sessions = Self::get_user_sessions(db, user_id).await?;
+        let excess: Vec<Uuid> = sessions
+            .into_iter()
+            .skip(keep as usize)
+            .map(|session| session.id)
+            .collect();
+
+        if excess.is_empty() {
+            return Ok(());
+        }
+
+        UserSessions::update_many()
+            .col_expr(user_sessions::Column::IsActive, Expr::value(false))
+            .col_expr(
+                user_sessions::Column::RefreshTokenHash,
+                Expr::value(Option::<String>::None),
+            )
+            .filter(user_sessions::Column::Id.is_in(excess))
+            .exec(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Failed to invalidate sessions over the limit: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(())
+    }
+
     /// Cleanup expired sessions (background task)
     #[allow(dead_code)]
     pub async fn cleanup_expired_sessions(db: &DatabaseConnection) -> Result<u64, AppError> {
-        let now = Utc::now();
+        let now = system_clock().now();
 
         let result = UserSessions::delete_many()
             .filter(user_sessions::Column::ExpiresAt.lt(now.fixed_offset()))
@@ -236,7 +457,6 @@ impl SessionService {
     }
 
     /// Gets active session count for a user
-    #[allow(dead_code)]
     pub async fn get_user_active_session_count(
         db: &DatabaseConnection,
         user_id: Uuid,
@@ -244,14 +464,220 @@ impl SessionService {
         let count = UserSessions::find()
             .filter(user_sessions::Column::UserId.eq(user_id))
             .filter(user_sessions::Column::IsActive.eq(true))
-            .filter(user_sessions::Column::ExpiresAt.gt(Utc::now().fixed_offset()))
+            .filter(user_sessions::Column::ExpiresAt.gt(system_clock().now().fixed_offset()))
             .count(db)
             .await
-            .map_err(|e| AppError {
-                message: format!("Database error: {}", e),
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            })?;
+            .with_context("user_sessions", "count")?;
 
         Ok(count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::models::roles;
+    use crate::infrastructure::clock::ManualClock;
+    use crate::infrastructure::test_support::{create_verified_user, setup_db};
+
+    const REXT_TOML: &str = r#"
+[sessions.roles.admin]
+expiry_hours = 1
+max_sessions = 2
+"#;
+
+    async fn create_user_with_role(db: &DatabaseConnection, email: &str, role: &str) -> Uuid {
+        let role = roles::ActiveModel {
+            name: Set(role.to_string()),
+            permissions: Set("[]".to_string()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+        .unwrap();
+
+        create_verified_user(
+            db,
+            email.to_string(),
+            "UserPassword123!".to_string(),
+            Some(role.id),
+        )
+        .await
+        .unwrap()
+        .id
+    }
+
+    async fn create_session(
+        db: &DatabaseConnection,
+        config: &SessionPolicyConfig,
+        user_id: Uuid,
+    ) -> user_sessions::Model {
+        let policy = SessionService::session_policy_for_user(db, config, user_id)
+            .await
+            .unwrap();
+        SessionService::create_session_with_policy(
+            db,
+            policy,
+            user_id,
+            None,
+            None,
+            &Uuid::new_v4().to_string(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_admin_role_gets_shorter_session_than_user_role() {
+        let db = setup_db().await;
+        let config = SessionPolicyConfig::from_sources(Some(REXT_TOML), |_| None);
+        let admin_id = create_user_with_role(&db, "admin@example.com", "admin").await;
+        let user_id = create_user_with_role(&db, "user@example.com", "user").await;
+
+        let admin_session = create_session(&db, &config, admin_id).await;
+        let user_session = create_session(&db, &config, user_id).await;
+
+        let admin_lifetime = admin_session.expires_at - admin_session.created_at.unwrap();
+        let user_lifetime = user_session.expires_at - user_session.created_at.unwrap();
+        assert!(admin_lifetime < user_lifetime);
+        assert!(admin_lifetime <= Duration::hours(1));
+        assert!(user_lifetime > Duration::hours(23));
+    }
+
+    #[tokio::test]
+    async fn test_session_expiry_boundary_follows_clock() {
+        let db = setup_db().await;
+        let config = SessionPolicyConfig::from_sources(Some(REXT_TOML), |_| None);
+        let admin_id = create_user_with_role(&db, "admin@example.com", "admin").await;
+        let session = create_session(&db, &config, admin_id).await;
+        let expires_at = session.expires_at.to_utc();
+        let clock = ManualClock::new(expires_at - Duration::seconds(1));
+
+        assert!(
+            SessionService::validate_session_with_clock(&db, &session.session_token, &clock)
+                .await
+                .is_ok()
+        );
+
+        clock.set(expires_at);
+        assert!(
+            SessionService::validate_session_with_clock(&db, &session.session_token, &clock)
+                .await
+                .is_ok()
+        );
+
+        clock.advance(Duration::seconds(1));
+        let err = SessionService::validate_session_with_clock(&db, &session.session_token, &clock)
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "Session expired");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_rotation() {
+        let db = setup_db().await;
+        let config = SessionPolicyConfig::from_sources(None, |_| None);
+        let user_id = create_user_with_role(&db, "user@example.com", "user").await;
+        let session = create_session(&db, &config, user_id).await;
+
+        let first = SessionService::issue_refresh_token(&db, &session)
+            .await
+            .unwrap();
+        assert!(first.starts_with(&session.session_token));
+
+        let (refreshed, second) = SessionService::rotate_refresh_token(&db, &first)
+            .await
+            .unwrap();
+        assert_eq!(refreshed.id, session.id);
+        assert_ne!(second, first);
+
+        // The rotated token keeps working, only its hash is stored
+        let (_, third) = SessionService::rotate_refresh_token(&db, &second)
+            .await
+            .unwrap();
+        let stored = UserSessions::find_by_id(session.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.is_active);
+        assert_eq!(
+            stored.refresh_token_hash,
+            Some(TokenService::hash_token(&third))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_replay_invalidates_session() {
+        let db = setup_db().await;
+        let config = SessionPolicyConfig::from_sources(None, |_| None);
+        let user_id = create_user_with_role(&db, "user@example.com", "user").await;
+        let session = create_session(&db, &config, user_id).await;
+
+        let first = SessionService::issue_refresh_token(&db, &session)
+            .await
+            .unwrap();
+        let (_, second) = SessionService::rotate_refresh_token(&db, &first)
+            .await
+            .unwrap();
+
+        // Replaying the rotated token kills the session
+        let err = SessionService::rotate_refresh_token(&db, &first)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+        let err = SessionService::validate_session(&db, &session.session_token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "Session has been invalidated");
+
+        // Including the latest token in the chain
+        assert!(
+            SessionService::rotate_refresh_token(&db, &second)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_malformed_refresh_token_rejected() {
+        let db = setup_db().await;
+        let config = SessionPolicyConfig::from_sources(None, |_| None);
+        let user_id = create_user_with_role(&db, "user@example.com", "user").await;
+        let session = create_session(&db, &config, user_id).await;
+        SessionService::issue_refresh_token(&db, &session)
+            .await
+            .unwrap();
+
+        for token in ["", "no-separator", "unknown-session.secret"] {
+            let err = SessionService::rotate_refresh_token(&db, token)
+                .await
+                .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+        }
+        assert!(
+            SessionService::validate_session(&db, &session.session_token)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_role_session_limit_invalidates_oldest() {
+        let db = setup_db().await;
+        let config = SessionPolicyConfig::from_sources(Some(REXT_TOML), |_| None);
+        let admin_id = create_user_with_role(&db, "admin@example.com", "admin").await;
+
+        let first = create_session(&db, &config, admin_id).await;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        create_session(&db, &config, admin_id).await;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        create_session(&db, &config, admin_id).await;
+
+        let active = SessionService::get_user_sessions(&db, admin_id)
+            .await
+            .unwrap();
+        assert_eq!(active.len(), 2);
+        assert!(active.iter().all(|session| session.id != first.id));
+    }
+}
@@ -0,0 +1,321 @@
+//! Runtime-tunable settings stored as JSON in the settings table
+use axum::http::StatusCode;
+use sea_orm::{sea_query::OnConflict, *};
+use serde::{Serialize, de::DeserializeOwned};
+use std::{
+    collections::HashMap,
+    sync::{
+        RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use uuid::Uuid;
+
+use crate::{
+    bridge::types::admin::SettingResponse, entity::models::settings,
+    infrastructure::app_error::AppError,
+};
+
+/// Maximum length of a setting key
+pub const MAX_SETTING_KEY_LENGTH: usize = 100;
+
+/// Global settings service shared by handlers and runtime toggles
+pub static SETTINGS: once_cell::sync::Lazy<SettingsService> =
+    once_cell::sync::Lazy::new(SettingsService::default);
+
+/// Typed settings with an in-memory cache, invalidated on every write through the service
+///
+/// The cache is per process, with several instances a write on one is only seen by the others
+/// after they restart or call `invalidate_all`.
+#[derive(Default)]
+pub struct SettingsService {
+    /// Cached values by key, None caches a setting that isn't set
+    cache: RwLock<HashMap<String, Option<serde_json::Value>>>,
+    /// Bumped on every invalidation, so a read racing a write doesn't cache the stale value
+    generation: AtomicU64,
+}
+
+impl SettingsService {
+    /// Gets a setting deserialized into T, None if it isn't set
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        db: &DatabaseConnection,
+        key: &str,
+    ) -> Result<Option<T>, AppError> {
+        self.get_value(db, key)
+            .await?
+            .map(|value| {
+                serde_json::from_value(value).map_err(|e| AppError {
+                    message: format!("Setting {} has an unexpected type: {}", key, e),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                })
+            })
+            .transpose()
+    }
+
+    /// Gets a setting, falling back to the default if it isn't set
+    pub async fn get_or<T: DeserializeOwned>(
+        &self,
+        db: &DatabaseConnection,
+        key: &str,
+        default: T,
+    ) -> Result<T, AppError> {
+        Ok(self.get(db, key).await?.unwrap_or(default))
+    }
+
+    /// Gets the raw JSON value of a setting, served from the cache when possible
+    pub async fn get_value(
+        &self,
+        db: &DatabaseConnection,
+        key: &str,
+    ) -> Result<Option<serde_json::Value>, AppError> {
+        if let Some(cached) = self.cache.read().unwrap().get(key) {
+            return Ok(cached.clone());
+        }
+
+        let generation = self.generation.load(Ordering::Acquire);
+        let value = match Self::find(db, key).await? {
+            Some(model) => Some(Self::parse_value(&model)?),
+            None => None,
+        };
+
+        let mut cache = self.cache.write().unwrap();
+        if self.generation.load(Ordering::Acquire) == generation {
+            cache.insert(key.to_string(), value.clone());
+        }
+
+        Ok(value)
+    }
+
+    /// Sets a setting and invalidates its cached value
+    pub async fn set<T: Serialize>(
+        &self,
+        db: &DatabaseConnection,
+        key: &str,
+        value: &T,
+        updated_by: Option<Uuid>,
+    ) -> Result<SettingResponse, AppError> {
+        Self::validate_key(key)?;
+
+        let json = serde_json::to_string(value).map_err(|e| AppError {
+            message: format!("Failed to serialize setting: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        let model = settings::ActiveModel {
+            key: Set(key.to_string()),
+            value: Set(json),
+            updated_by: Set(updated_by),
+            updated_at: Set(chrono::Utc::now().fixed_offset()),
+        };
+
+        settings::Entity::insert(model)
+            .on_conflict(
+                OnConflict::column(settings::Column::Key)
+                    .update_columns([
+                        settings::Column::Value,
+                        settings::Column::UpdatedBy,
+                        settings::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        self.invalidate(key);
+
+        let model = Self::find(db, key).await?.ok_or(AppError {
+            message: "Setting not found after update".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+        Self::to_response(&model)
+    }
+
+    /// Gets a setting with its metadata, 404 if it isn't set
+    pub async fn get_setting(
+        db: &DatabaseConnection,
+        key: &str,
+    ) -> Result<SettingResponse, AppError> {
+        let model = Self::find(db, key).await?.ok_or(AppError {
+            message: "Setting not found".to_string(),
+            status_code: StatusCode::NOT_FOUND,
+        })?;
+        Self::to_response(&model)
+    }
+
+    /// Lists all settings ordered by key
+    pub async fn list(db: &DatabaseConnection) -> Result<Vec<SettingResponse>, AppError> {
+        settings::Entity::find()
+            .order_by_asc(settings::Column::Key)
+            .all(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?
+            .iter()
+            .map(Self::to_response)
+            .collect()
+    }
+
+    /// Drops the cached value of one setting
+    pub fn invalidate(&self, key: &str) {
+        let mut cache = self.cache.write().unwrap();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        cache.remove(key);
+    }
+
+    /// Drops all cached values
+    pub fn invalidate_all(&self) {
+        let mut cache = self.cache.write().unwrap();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        cache.clear();
+    }
+
+    /// Keys are lowercase letters, digits, '_', '.' and '-'
+    fn validate_key(key: &str) -> Result<(), AppError> {
+        let valid = !key.is_empty()
+            && key.len() <= MAX_SETTING_KEY_LENGTH
+            && key.chars().all(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '.' | '-')
+            });
+
+        if !valid {
+            return Err(AppError {
+                message: format!(
+                    "Invalid setting key, use up to {} lowercase letters, digits, '_', '.' or '-'",
+                    MAX_SETTING_KEY_LENGTH
+                ),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn find(db: &DatabaseConnection, key: &str) -> Result<Option<settings::Model>, AppError> {
+        settings::Entity::find_by_id(key.to_string())
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })
+    }
+
+    fn parse_value(model: &settings::Model) -> Result<serde_json::Value, AppError> {
+        serde_json::from_str(&model.value).map_err(|e| AppError {
+            message: format!("Setting {} is not valid JSON: {}", model.key, e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })
+    }
+
+    fn to_response(model: &settings::Model) -> Result<SettingResponse, AppError> {
+        Ok(SettingResponse {
+            key: model.key.clone(),
+            value: Self::parse_value(model)?,
+            updated_by: model.updated_by.map(|id| id.to_string()),
+            updated_at: model.updated_at.to_rfc3339(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::setup_db;
+    use sea_orm::sea_query::Expr;
+
+    #[tokio::test]
+    async fn test_set_and_get_typed_setting() {
+        let db = setup_db().await;
+        let service = SettingsService::default();
+        let admin_id = Uuid::new_v4();
+
+        let response = service
+            .set(&db, "maintenance.message", &"Back soon", Some(admin_id))
+            .await
+            .unwrap();
+        assert_eq!(response.value, serde_json::json!("Back soon"));
+        assert_eq!(response.updated_by, Some(admin_id.to_string()));
+
+        let message: Option<String> = service.get(&db, "maintenance.message").await.unwrap();
+        assert_eq!(message.as_deref(), Some("Back soon"));
+
+        let enabled = service
+            .get_or(&db, "maintenance.enabled", false)
+            .await
+            .unwrap();
+        assert!(!enabled);
+    }
+
+    #[tokio::test]
+    async fn test_get_is_cached() {
+        let db = setup_db().await;
+        let service = SettingsService::default();
+        service
+            .set(&db, "feature.signup", &true, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.get::<bool>(&db, "feature.signup").await.unwrap(),
+            Some(true)
+        );
+
+        // A write that bypasses the service isn't seen until the cache is invalidated
+        settings::Entity::update_many()
+            .col_expr(settings::Column::Value, Expr::value("false"))
+            .filter(settings::Column::Key.eq("feature.signup"))
+            .exec(&db)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.get::<bool>(&db, "feature.signup").await.unwrap(),
+            Some(true)
+        );
+
+        service.invalidate("feature.signup");
+        assert_eq!(
+            service.get::<bool>(&db, "feature.signup").await.unwrap(),
+            Some(false)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_invalidates_cache() {
+        let db = setup_db().await;
+        let service = SettingsService::default();
+
+        // Cache the missing value, then the first write must replace it
+        assert_eq!(
+            service.get::<u32>(&db, "uploads.max_mb").await.unwrap(),
+            None
+        );
+        service.set(&db, "uploads.max_mb", &10, None).await.unwrap();
+        assert_eq!(
+            service.get::<u32>(&db, "uploads.max_mb").await.unwrap(),
+            Some(10)
+        );
+
+        service.set(&db, "uploads.max_mb", &25, None).await.unwrap();
+        assert_eq!(
+            service.get::<u32>(&db, "uploads.max_mb").await.unwrap(),
+            Some(25)
+        );
+        assert_eq!(SettingsService::list(&db).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_key_rejected() {
+        let db = setup_db().await;
+        let err = SettingsService::default()
+            .set(&db, "Not A Key", &1, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+    }
+}
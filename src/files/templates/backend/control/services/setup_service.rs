@@ -0,0 +1,262 @@
+use axum::http::StatusCode;
+use sea_orm::*;
+use std::env;
+use tokio::sync::Mutex;
+
+use crate::control::services::user_service::UserService;
+use crate::domain::{
+    user::User,
+    validation::{normalize_email, validate_email, validate_strong_password},
+};
+use crate::entity::models::{prelude::*, *};
+use crate::infrastructure::app_error::AppError;
+
+/// Serializes setup attempts in this process, the setup claim row guards across instances
+static SETUP_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Primary key of the single setup claim row
+const SETUP_CLAIM_ID: i32 = 1;
+
+/// Service for the one-time first-run setup flow
+pub struct SetupService;
+
+impl SetupService {
+    /// Whether first-run setup mode is enabled (FIRST_RUN_SETUP, defaults to false)
+    pub fn is_enabled() -> bool {
+        env::var("FIRST_RUN_SETUP")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse::<bool>()
+            .unwrap_or(false)
+    }
+
+    /// Checks if any user holds the admin role
+    pub async fn admin_exists(db: &DatabaseConnection) -> Result<bool, AppError> {
+        let admin_role = Self::find_admin_role(db).await?;
+
+        let Some(admin_role) = admin_role else {
+            return Ok(false);
+        };
+
+        let admin_count = Users::find()
+            .filter(users::Column::RoleId.eq(admin_role.id))
+            .count(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(admin_count > 0)
+    }
+
+    /// Creates the initial admin user, rejected once any admin exists
+    pub async fn create_initial_admin(
+        db: &DatabaseConnection,
+        email: String,
+        password: String,
+    ) -> Result<User, AppError> {
+        let _guard = SETUP_LOCK.lock().await;
+
+        if Self::admin_exists(db).await? {
+            return Err(AppError {
+                message: "Setup has already been completed".to_string(),
+                status_code: StatusCode::FORBIDDEN,
+            });
+        }
+
+        let email = normalize_email(&email);
+        validate_email(&email)?;
+        validate_strong_password(&password)?;
+
+        let admin_role = Self::find_admin_role(db).await?.ok_or(AppError {
+            message: "Admin role not found, enable CREATE_DEFAULT_ROLES".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        let existing_user = Users::find()
+            .filter(users::Column::Email.eq(&email))
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        if existing_user.is_some() {
+            return Err(AppError {
+                message: "User already exists".to_string(),
+                status_code: StatusCode::CONFLICT,
+            });
+        }
+
+        let password_hash = UserService::hash_password(&password)?;
+        let mut user = User::create_new(email, password_hash);
+        user.role_id = Some(admin_role.id);
+        // The operator chose this address themselves, no verification email needed
+        user.email_verified = true;
+
+        let user_active_model = users::ActiveModel {
+            id: Set(user.id),
+            email: Set(user.email.clone()),
+            password_hash: Set(user.password_hash.clone()),
+            created_at: Set(user.created_at.map(|dt| dt.fixed_offset())),
+            last_login: Set(None),
+            role_id: Set(user.role_id),
+            email_verified: Set(user.email_verified),
+            pending_email: Set(None),
+            pending_email_token: Set(None),
+            pending_email_expires_at: Set(None),
+            must_change_password: Set(false),
+            updated_at: Set(None),
+        };
+
+        let db_error = |e: DbErr| AppError {
+            message: format!("Failed to create admin user: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        // The claim's fixed primary key only lets one instance complete setup, the admin is
+        // rolled back with it
+        let txn = db.begin().await.map_err(db_error)?;
+        Users::insert(user_active_model)
+            .exec(&txn)
+            .await
+            .map_err(db_error)?;
+        let claim = SetupClaims::insert(setup_claims::ActiveModel {
+            id: Set(SETUP_CLAIM_ID),
+            admin_id: Set(user.id),
+            claimed_at: Set(chrono::Utc::now().fixed_offset()),
+        })
+        .exec(&txn)
+        .await;
+        match claim {
+            Ok(_) => txn.commit().await.map_err(db_error)?,
+            Err(e) if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+                return Err(AppError {
+                    message: "Setup has already been completed".to_string(),
+                    status_code: StatusCode::FORBIDDEN,
+                });
+            }
+            Err(e) => return Err(db_error(e)),
+        }
+
+        Ok(user)
+    }
+
+    async fn find_admin_role(db: &DatabaseConnection) -> Result<Option<roles::Model>, AppError> {
+        Roles::find()
+            .filter(roles::Column::Name.eq("admin"))
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::setup_db;
+
+    const STRONG_PASSWORD: &str = "Sup3rSecretPassw0rd";
+
+    async fn setup_db_with_admin_role() -> DatabaseConnection {
+        let db = setup_db().await;
+
+        roles::ActiveModel {
+            name: Set("admin".to_string()),
+            description: Set(Some("Full system access".to_string())),
+            permissions: Set("[\"*\"]".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        db
+    }
+
+    #[tokio::test]
+    async fn test_setup_creates_admin_when_none_exists() {
+        let db = setup_db_with_admin_role().await;
+        assert!(!SetupService::admin_exists(&db).await.unwrap());
+
+        let user = SetupService::create_initial_admin(
+            &db,
+            "owner@example.com".to_string(),
+            STRONG_PASSWORD.to_string(),
+        )
+        .await
+        .unwrap();
+
+        assert!(user.role_id.is_some());
+        assert!(SetupService::admin_exists(&db).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_setup_rejected_once_admin_exists() {
+        let db = setup_db_with_admin_role().await;
+        SetupService::create_initial_admin(
+            &db,
+            "owner@example.com".to_string(),
+            STRONG_PASSWORD.to_string(),
+        )
+        .await
+        .unwrap();
+
+        let err = SetupService::create_initial_admin(
+            &db,
+            "second@example.com".to_string(),
+            STRONG_PASSWORD.to_string(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_setup_rejected_when_another_instance_claimed_it() {
+        let db = setup_db_with_admin_role().await;
+        SetupClaims::insert(setup_claims::ActiveModel {
+            id: Set(SETUP_CLAIM_ID),
+            admin_id: Set(uuid::Uuid::new_v4()),
+            claimed_at: Set(chrono::Utc::now().fixed_offset()),
+        })
+        .exec(&db)
+        .await
+        .unwrap();
+
+        let err = SetupService::create_initial_admin(
+            &db,
+            "owner@example.com".to_string(),
+            STRONG_PASSWORD.to_string(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+        // The admin inserted before the claim failed is rolled back
+        assert_eq!(Users::find().count(&db).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_setup_enforces_password_policy() {
+        let db = setup_db_with_admin_role().await;
+
+        for weak in ["admin", "alllowercase123", "NoDigitsInThisOne", "Sh0rt"] {
+            let err = SetupService::create_initial_admin(
+                &db,
+                "owner@example.com".to_string(),
+                weak.to_string(),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        }
+
+        assert!(!SetupService::admin_exists(&db).await.unwrap());
+    }
+}
@@ -6,14 +6,21 @@ use sea_orm::{
 use sea_orm_migration::prelude::*;
 use std::env;
 
-use crate::control::services::{server_config::ServerConfigService, user_service::UserService};
-use crate::domain::permissions::DefaultPermissions;
+use crate::control::services::{
+    health_snapshot_service::{HEALTH_SNAPSHOTS, HealthSnapshotService},
+    role_cache_service::RoleCache,
+    seed_service::SeedService,
+    server_config::ServerConfigService,
+    setup_service::SetupService,
+    token_service::TokenService,
+    user_service::{NewUserOptions, UserService},
+};
+use crate::domain::permissions::{DefaultPermissions, PermissionSet};
 use crate::entity::models::roles;
 use crate::infrastructure::app_error::AppError;
-use crate::infrastructure::{
-    database::DatabaseManager, job_queue::JobQueueManager, scheduler::SchedulerManager,
-    server::ServerManager,
-};
+use crate::infrastructure::{database::DatabaseManager, secrets::Secrets, server::ServerManager};
+#[cfg(feature = "queue")]
+use crate::infrastructure::{job_queue::JobQueueManager, scheduler::SchedulerManager};
 use migration;
 
 /// Application startup orchestrator
@@ -25,6 +32,12 @@ impl StartupService {
         // Load environment variables from .env file
         dotenvy::dotenv().ok();
 
+        // Resolve secrets before anything reads them
+        Secrets::initialize().await?;
+
+        // Fail fast on a missing or too short JWT_SECRET rather than on the first login
+        TokenService::jwt_secret()?;
+
         // Initialize server configuration
         ServerConfigService::initialize();
 
@@ -35,23 +48,38 @@ impl StartupService {
         // Create database connection
         let db = DatabaseManager::create_connection().await?;
 
-        // Run migrations
-        println!("Running database migrations...");
-        Self::run_migrations().await?;
-        println!("Migrations completed successfully");
+        // Migrations, job queue setup and seeding all write, skip them against a read-only database
+        if DatabaseManager::is_read_only() {
+            println!("Read-only mode, skipping migrations, job queue setup and seeding");
+            return Ok(db);
+        }
 
-        // Create pool for job queue
-        let pool = DatabaseManager::create_pool().await?;
+        // Run migrations unless they are run separately with the migration binary
+        if Self::auto_migrate() {
+            println!("Running database migrations...");
+            Self::run_migrations().await?;
+            println!("Migrations completed successfully");
+        } else {
+            println!(
+                "AUTO_MIGRATE is false, skipping migrations (run `cargo run -p migration -- up`)"
+            );
+        }
 
-        // Setup job queue storage
-        DatabaseManager::setup_job_queue_storage(&pool).await?;
+        #[cfg(feature = "queue")]
+        {
+            // Create pool for job queue
+            let pool = DatabaseManager::create_pool().await?;
 
-        // Create job storage
-        let job_storage = JobQueueManager::create_storage(pool);
+            // Setup job queue storage
+            DatabaseManager::setup_job_queue_storage(&pool).await?;
+
+            // Create job storage
+            let job_storage = JobQueueManager::create_storage(pool);
 
-        // Queue test job
-        println!("Queuing test job!");
-        JobQueueManager::produce_messages(&job_storage).await?;
+            // Queue test job
+            println!("Queuing test job!");
+            JobQueueManager::produce_messages(&job_storage).await?;
+        }
 
         // Seed default roles if enabled
         Self::seed_default_roles(&db).await?;
@@ -62,10 +90,17 @@ impl StartupService {
         Ok(db)
     }
 
+    /// Whether startup applies pending migrations, from AUTO_MIGRATE (defaults to true)
+    fn auto_migrate() -> bool {
+        env::var("AUTO_MIGRATE")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse::<bool>()
+            .unwrap_or(true)
+    }
+
     /// Runs database migrations using SeaORM Migration API
     async fn run_migrations() -> Result<(), Box<dyn std::error::Error>> {
-        let database_url = env::var("DATABASE_URL")
-            .map_err(|_| "DATABASE_URL environment variable is required")?;
+        let database_url = DatabaseManager::database_url()?;
 
         println!("Executing migrations with SeaORM Migration API...");
 
@@ -83,36 +118,23 @@ impl StartupService {
             .map_err(|e| format!("Migration failed: {}", e))?;
 
         // Verify that migrations were applied successfully
-        assert!(
-            schema_manager
-                .has_table("users")
-                .await
-                .map_err(|e| format!("Failed to verify users table: {}", e))?
-        );
-        assert!(
-            schema_manager
-                .has_table("roles")
-                .await
-                .map_err(|e| format!("Failed to verify roles table: {}", e))?
-        );
-        assert!(
-            schema_manager
-                .has_table("audit_logs")
+        for table in [
+            "users",
+            "roles",
+            "audit_logs",
+            "database_metrics",
+            "user_sessions",
+            "seed_history",
+            "settings",
+        ] {
+            let exists = schema_manager
+                .has_table(table)
                 .await
-                .map_err(|e| format!("Failed to verify audit_logs table: {}", e))?
-        );
-        assert!(
-            schema_manager
-                .has_table("database_metrics")
-                .await
-                .map_err(|e| format!("Failed to verify database_metrics table: {}", e))?
-        );
-        assert!(
-            schema_manager
-                .has_table("user_sessions")
-                .await
-                .map_err(|e| format!("Failed to verify user_sessions table: {}", e))?
-        );
+                .map_err(|e| format!("Failed to verify {} table: {}", table, e))?;
+            if !exists {
+                return Err(format!("Table {} is missing after running migrations", table).into());
+            }
+        }
 
         println!("✅ Database migrations completed successfully");
         Ok(())
@@ -131,6 +153,24 @@ impl StartupService {
             return Ok(());
         }
 
+        // First-run setup replaces seeding, the admin is created through POST /api/v1/setup
+        if SetupService::is_enabled() {
+            if SetupService::admin_exists(db).await? {
+                println!("First-run setup already completed");
+            } else {
+                println!("⚠️  No admin user found, complete setup at POST /api/v1/setup");
+            }
+            return Ok(());
+        }
+
+        // Only ever seeded once, a deleted admin is not recreated with the default password
+        SeedService::seed_once("admin_user", db, || Self::create_admin_user(db)).await?;
+
+        Ok(())
+    }
+
+    /// Creates the admin user from ADMIN_EMAIL and ADMIN_PASSWORD if it doesn't exist
+    async fn create_admin_user(db: &DatabaseConnection) -> Result<(), AppError> {
         // Get admin credentials from environment variables
         let admin_email = env::var("ADMIN_EMAIL").unwrap_or_else(|_| "admin@localhost".to_string());
         let admin_password = env::var("ADMIN_PASSWORD").unwrap_or_else(|_| "admin".to_string());
@@ -154,12 +194,17 @@ impl StartupService {
 
                 let admin_role_id = admin_role.map(|role| role.id);
 
-                // Create admin user with admin role
-                match UserService::create_user_with_role(
+                // Create admin user with admin role, the operator chose the address so it
+                // starts out verified and no email is sent
+                match UserService::create_user_with_options(
                     db,
                     admin_email.clone(),
                     admin_password,
                     admin_role_id,
+                    NewUserOptions {
+                        send_verification: false,
+                        mark_verified: true,
+                    },
                 )
                 .await
                 {
@@ -168,18 +213,27 @@ impl StartupService {
                             "✅ Admin user created successfully: {} (ID: {})",
                             admin_email, user.id
                         );
-                        println!("⚠️  IMPORTANT: Change the default admin password immediately!");
+                        if UserService::password_change_required_by_policy() {
+                            UserService::set_must_change_password(db, user.id, true).await?;
+                            println!(
+                                "⚠️  The admin password must be changed before the API can be used"
+                            );
+                        } else {
+                            println!(
+                                "⚠️  IMPORTANT: Change the default admin password immediately!"
+                            );
+                        }
                         Ok(())
                     }
                     Err(e) => {
                         eprintln!("❌ Failed to create admin user: {}", e.message);
-                        Err(Box::new(e))
+                        Err(e)
                     }
                 }
             }
             Err(e) => {
                 eprintln!("❌ Error checking for existing admin user: {}", e.message);
-                Err(Box::new(e))
+                Err(e)
             }
         }
     }
@@ -209,60 +263,97 @@ impl StartupService {
             ("user", "Basic user access", DefaultPermissions::user()),
         ];
 
-        // Create default roles (admin, user, only if found in .env)
+        // Create default roles (admin, user, only if found in .env), each is only ever seeded once
         for role_name in default_roles {
             if let Some((_, description, permission_set)) =
                 role_configs.iter().find(|(name, _, _)| name == &role_name)
             {
-                // Check if role already exists
-                let existing_role = roles::Entity::find()
-                    .filter(roles::Column::Name.eq(&role_name))
-                    .one(db)
-                    .await
-                    .map_err(|e| AppError {
-                        message: format!("Database error: {}", e),
-                        status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                    })?;
-
-                if existing_role.is_some() {
-                    println!("Role already exists: {}", role_name);
-                    continue;
-                }
-
-                // Convert permission set to JSON string
-                let permissions_json = serde_json::to_string(&permission_set.to_strings())
-                    .map_err(|e| AppError {
-                        message: format!("Failed to serialize permissions: {}", e),
-                        status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                    })?;
+                let key = format!("default_role:{}", role_name);
+                SeedService::seed_once(&key, db, || {
+                    Self::create_role(db, &role_name, description, permission_set)
+                })
+                .await?;
+            }
+        }
 
-                let role_model = roles::ActiveModel {
-                    name: Set(role_name.clone()),
-                    description: Set(Some(ToString::to_string(description))),
-                    permissions: Set(permissions_json),
-                    ..Default::default()
-                };
+        Ok(())
+    }
 
-                let role = role_model.insert(db).await.map_err(|e| AppError {
-                    message: format!("Database error: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                })?;
+    /// Creates a role with the given permissions if it doesn't exist
+    async fn create_role(
+        db: &DatabaseConnection,
+        role_name: &str,
+        description: &str,
+        permission_set: &PermissionSet,
+    ) -> Result<(), AppError> {
+        // Check if role already exists
+        let existing_role = roles::Entity::find()
+            .filter(roles::Column::Name.eq(role_name))
+            .one(db)
+            .await
+            .map_err(|e| AppError {
+                message: format!("Database error: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
 
-                println!("✅ Role created successfully: {}", role.name);
-            }
+        if existing_role.is_some() {
+            println!("Role already exists: {}", role_name);
+            return Ok(());
         }
 
+        // Convert permission set to JSON string
+        let permissions_json =
+            serde_json::to_string(&permission_set.to_strings()).map_err(|e| AppError {
+                message: format!("Failed to serialize permissions: {}", e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        let role_model = roles::ActiveModel {
+            name: Set(role_name.to_string()),
+            description: Set(Some(description.to_string())),
+            permissions: Set(permissions_json),
+            ..Default::default()
+        };
+
+        let role = role_model.insert(db).await.map_err(|e| AppError {
+            message: format!("Database error: {}", e),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+        RoleCache::invalidate();
+
+        println!("✅ Role created successfully: {}", role.name);
         Ok(())
     }
 
     /// Runs the server task
     pub async fn run_server(db: DatabaseConnection) -> Result<(), Box<dyn std::error::Error>> {
+        // Precompute health snapshots in the background for the health endpoint
+        HEALTH_SNAPSHOTS.start(db.clone(), HealthSnapshotService::interval_from_env());
+
+        // Compaction and backups are scheduled jobs, they need the queue module
+        #[cfg(not(feature = "queue"))]
+        if crate::infrastructure::logging::AuditLogConfig::get()
+            .body_retention()
+            .is_some()
+        {
+            println!(
+                "⚠️  Audit body retention is set, but compaction runs on the queue module's scheduler"
+            );
+        }
+        #[cfg(not(feature = "queue"))]
+        if std::env::var("DATABASE_BACKUP_DIR").is_ok_and(|dir| !dir.trim().is_empty()) {
+            println!(
+                "⚠️  DATABASE_BACKUP_DIR is set, but backups run on the queue module's scheduler"
+            );
+        }
+
         let router = ServerManager::create_router(db);
         ServerManager::start_server(router).await?;
         Ok(())
     }
 
     /// Runs the job queue monitor task
+    #[cfg(feature = "queue")]
     pub async fn run_job_queue_monitor() -> Result<(), Box<dyn std::error::Error>> {
         let pool = DatabaseManager::create_pool().await?;
         let job_storage = JobQueueManager::create_storage(pool);
@@ -272,9 +363,11 @@ impl StartupService {
     }
 
     /// Runs the task scheduler
+    #[cfg(feature = "queue")]
     pub async fn run_scheduler() -> Result<(), Box<dyn std::error::Error>> {
-        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
-        SchedulerManager::run_scheduler(&database_url).await?;
+        let database_url = DatabaseManager::database_url()?;
+        let job_queue_url = DatabaseManager::job_queue_url()?;
+        SchedulerManager::run_scheduler(&job_queue_url, &database_url).await?;
         Ok(())
     }
 }
@@ -2,14 +2,62 @@ use crate::control::services::{
     database_service::{DatabaseMonitorService, DatabasePerformanceMetrics},
     server_config::ServerConfigService,
 };
+use crate::infrastructure::build_info::BUILD_INFO;
 use chrono::{Duration, Utc};
 use sea_orm::DatabaseConnection;
-use std::fs;
+use std::sync::Mutex;
+use std::time::Instant;
 use sysinfo::{Components, Disks, Networks, System};
 
 /// System monitoring service for collecting system metrics
 pub struct SystemMonitorService;
 
+/// Process-wide CPU sampler, so a metrics request doesn't wait for a second reading
+static CPU_SAMPLER: once_cell::sync::Lazy<Mutex<CpuSampler>> =
+    once_cell::sync::Lazy::new(|| Mutex::new(CpuSampler::new()));
+
+/// Keeps one `System` between samples, CPU usage is measured between two refreshes
+struct CpuSampler {
+    system: System,
+    last_refresh: Option<Instant>,
+    /// Whether a reading spans two refreshes, the first refresh always reads 0%
+    has_reading: bool,
+}
+
+impl CpuSampler {
+    fn new() -> Self {
+        Self {
+            system: System::new(),
+            last_refresh: None,
+            has_reading: false,
+        }
+    }
+
+    /// Average usage across all cores since the previous sample, NaN until there are two
+    /// refreshes to compare
+    ///
+    /// Samples closer together than sysinfo can measure reuse the previous reading.
+    fn sample(&mut self, now: Instant) -> f32 {
+        let due = self.last_refresh.is_none_or(|last| {
+            now.saturating_duration_since(last) >= sysinfo::MINIMUM_CPU_UPDATE_INTERVAL
+        });
+        if due {
+            self.system.refresh_cpu_usage();
+            self.has_reading = self.last_refresh.is_some();
+            self.last_refresh = Some(now);
+        }
+
+        if self.has_reading {
+            self.system.global_cpu_usage()
+        } else {
+            f32::NAN
+        }
+    }
+}
+
+/// Status and value reported when a metric is unavailable or nonsensical
+pub const UNKNOWN: &str = "Unknown";
+
 /// System metrics data structure
 #[derive(Debug, Clone)]
 pub struct SystemMetrics {
@@ -52,8 +100,8 @@ impl SystemMonitorService {
         let mut sys = System::new_all();
         sys.refresh_all();
 
-        // Get CPU usage (average across all cores)
-        let cpu_usage = sys.global_cpu_usage();
+        // Get CPU usage (average across all cores) since the previous request
+        let cpu_usage = CPU_SAMPLER.lock().unwrap().sample(Instant::now());
 
         // Get memory information
         let memory_total = sys.total_memory();
@@ -68,7 +116,7 @@ impl SystemMonitorService {
 
         for disk in &disks {
             disk_total += disk.total_space();
-            disk_used += disk.total_space() - disk.available_space();
+            disk_used += disk.total_space().saturating_sub(disk.available_space());
             disk_available += disk.available_space();
         }
 
@@ -102,7 +150,6 @@ impl SystemMonitorService {
             .find(|component| component.label().to_lowercase().contains("cpu"))
             .and_then(|component| component.temperature());
 
-        println!("temperature: {:?}", temperature);
         // Get database connection count (if available)
         let database_connections = Self::get_database_connections(db).await;
 
@@ -199,21 +246,44 @@ impl SystemMonitorService {
         }
     }
 
-    /// Get memory usage percentage
-    pub fn get_memory_usage_percentage(metrics: &SystemMetrics) -> f32 {
-        if metrics.memory_total > 0 {
-            (metrics.memory_used as f32 / metrics.memory_total as f32) * 100.0
+    /// Get CPU usage percentage, None if unavailable (no CPUs reported, no sample yet, or an
+    /// out-of-range reading as seen in restricted containers)
+    ///
+    /// 0% is a valid reading from an idle host.
+    pub fn get_cpu_usage_percentage(metrics: &SystemMetrics) -> Option<f32> {
+        let cpu_usage = metrics.cpu_usage;
+        if metrics.cpu_count == 0 || !cpu_usage.is_finite() || cpu_usage < 0.0 || cpu_usage > 100.0
+        {
+            None
+        } else {
+            Some(cpu_usage)
+        }
+    }
+
+    /// Get memory usage percentage, None if the memory totals are unavailable
+    pub fn get_memory_usage_percentage(metrics: &SystemMetrics) -> Option<f32> {
+        Self::usage_percentage(metrics.memory_used, metrics.memory_total)
+    }
+
+    /// Get disk usage percentage, None if the disk totals are unavailable
+    pub fn get_disk_usage_percentage(metrics: &SystemMetrics) -> Option<f32> {
+        Self::usage_percentage(metrics.disk_used, metrics.disk_total)
+    }
+
+    fn usage_percentage(used: u64, total: u64) -> Option<f32> {
+        if total == 0 || used > total {
+            None
         } else {
-            0.0
+            Some((used as f32 / total as f32) * 100.0)
         }
     }
 
-    /// Get disk usage percentage
-    pub fn get_disk_usage_percentage(metrics: &SystemMetrics) -> f32 {
-        if metrics.disk_total > 0 {
-            (metrics.disk_used as f32 / metrics.disk_total as f32) * 100.0
+    /// Format bytes to human readable format, or "Unknown" if the metric is unavailable
+    pub fn format_bytes_if_known(bytes: u64, known: bool) -> String {
+        if known {
+            Self::format_bytes(bytes)
         } else {
-            0.0
+            UNKNOWN.to_string()
         }
     }
 
@@ -247,59 +317,34 @@ impl SystemMonitorService {
     }
 
     /// Get system health status based on metrics
+    ///
+    /// Known metrics over a threshold still raise the status, otherwise any unavailable metric
+    /// makes it "Unknown" rather than "Healthy".
     pub fn get_health_status(metrics: &SystemMetrics) -> String {
-        let cpu_usage = metrics.cpu_usage;
-        let memory_usage = Self::get_memory_usage_percentage(metrics);
-        let disk_usage = Self::get_disk_usage_percentage(metrics);
+        let usages = [
+            Self::get_cpu_usage_percentage(metrics),
+            Self::get_memory_usage_percentage(metrics),
+            Self::get_disk_usage_percentage(metrics),
+        ];
+        let exceeds = |threshold: f32| usages.iter().flatten().any(|usage| *usage > threshold);
 
         // Define thresholds
-        if cpu_usage > 90.0 || memory_usage > 90.0 || disk_usage > 90.0 {
+        if exceeds(90.0) {
             "Critical".to_string()
-        } else if cpu_usage > 80.0 || memory_usage > 80.0 || disk_usage > 80.0 {
+        } else if exceeds(80.0) {
             "Warning".to_string()
-        } else if cpu_usage > 70.0 || memory_usage > 70.0 || disk_usage > 70.0 {
+        } else if exceeds(70.0) {
             "Degraded".to_string()
+        } else if usages.iter().any(Option::is_none) {
+            UNKNOWN.to_string()
         } else {
             "Healthy".to_string()
         }
     }
 
-    /// Get project information from Cargo.toml
+    /// Get project name and version, compiled in so they match the running binary
     pub fn get_project_info() -> (String, String) {
-        // Try to read Cargo.toml from the project root
-        let cargo_toml_path = "Cargo.toml";
-
-        match fs::read_to_string(cargo_toml_path) {
-            Ok(content) => {
-                match toml::from_str::<toml::Value>(&content) {
-                    Ok(toml_value) => {
-                        if let Some(package) = toml_value.get("package") {
-                            let name = package
-                                .get("name")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-                            let version = package
-                                .get("version")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-                            (name, version)
-                        } else {
-                            ("unknown".to_string(), "unknown".to_string())
-                        }
-                    }
-                    Err(_) => {
-                        // Fallback to hardcoded values if parsing fails
-                        ("unknown".to_string(), "unknown".to_string())
-                    }
-                }
-            }
-            Err(_) => {
-                // Fallback to hardcoded values if file can't be read
-                ("unknown".to_string(), "unknown".to_string())
-            }
-        }
+        (BUILD_INFO.name.to_string(), BUILD_INFO.version.to_string())
     }
 
     /// Get server information
@@ -359,7 +404,7 @@ mod tests {
 
         assert_eq!(
             SystemMonitorService::get_memory_usage_percentage(&metrics),
-            50.0
+            Some(50.0)
         );
     }
 
@@ -370,6 +415,45 @@ mod tests {
             memory_total: 1000,
             memory_used: 300,
             memory_available: 700,
+            disk_total: 1000,
+            disk_used: 400,
+            disk_available: 600,
+            network_bytes_sent: 0,
+            network_bytes_received: 0,
+            uptime: 0,
+            process_count: 0,
+            database_connections: None,
+            database_performance: None,
+            system_name: None,
+            kernel_version: None,
+            os_version: None,
+            host_name: None,
+            cpu_count: 4,
+            temperature: None,
+        };
+
+        assert_eq!(
+            SystemMonitorService::get_health_status(&healthy_metrics),
+            "Healthy"
+        );
+    }
+
+    #[test]
+    fn test_project_info_is_compiled_in() {
+        let expected = (
+            env!("CARGO_PKG_NAME").to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+        );
+        // Compiled-in metadata, so the working directory (and any Cargo.toml in it) doesn't matter
+        assert_eq!(SystemMonitorService::get_project_info(), expected);
+    }
+
+    fn zeroed_metrics() -> SystemMetrics {
+        SystemMetrics {
+            cpu_usage: 0.0,
+            memory_total: 0,
+            memory_used: 0,
+            memory_available: 0,
             disk_total: 0,
             disk_used: 0,
             disk_available: 0,
@@ -385,11 +469,96 @@ mod tests {
             host_name: None,
             cpu_count: 0,
             temperature: None,
+        }
+    }
+
+    #[test]
+    fn test_zeroed_metrics_are_unknown_not_healthy() {
+        let metrics = zeroed_metrics();
+
+        assert_eq!(
+            SystemMonitorService::get_cpu_usage_percentage(&metrics),
+            None
+        );
+        assert_eq!(
+            SystemMonitorService::get_memory_usage_percentage(&metrics),
+            None
+        );
+        assert_eq!(
+            SystemMonitorService::get_disk_usage_percentage(&metrics),
+            None
+        );
+        assert_eq!(SystemMonitorService::get_health_status(&metrics), UNKNOWN);
+        assert_eq!(
+            SystemMonitorService::format_bytes_if_known(metrics.memory_total, false),
+            UNKNOWN
+        );
+    }
+
+    #[test]
+    fn test_partially_unavailable_metrics() {
+        // Memory and CPU readable, disks not reported
+        let metrics = SystemMetrics {
+            cpu_usage: 20.0,
+            cpu_count: 2,
+            memory_total: 1000,
+            memory_used: 200,
+            ..zeroed_metrics()
+        };
+        assert_eq!(SystemMonitorService::get_health_status(&metrics), UNKNOWN);
+
+        // A known metric over a threshold still wins
+        let metrics = SystemMetrics {
+            memory_used: 950,
+            ..metrics
         };
+        assert_eq!(
+            SystemMonitorService::get_health_status(&metrics),
+            "Critical"
+        );
 
+        // Nonsensical readings count as unavailable
+        let metrics = SystemMetrics {
+            cpu_usage: f32::NAN,
+            cpu_count: 2,
+            disk_total: 100,
+            disk_used: 500,
+            ..zeroed_metrics()
+        };
         assert_eq!(
-            SystemMonitorService::get_health_status(&healthy_metrics),
-            "Healthy"
+            SystemMonitorService::get_cpu_usage_percentage(&metrics),
+            None
+        );
+        assert_eq!(
+            SystemMonitorService::get_disk_usage_percentage(&metrics),
+            None
+        );
+    }
+
+    #[test]
+    fn test_idle_cpu_is_known() {
+        let metrics = SystemMetrics {
+            cpu_usage: 0.0,
+            cpu_count: 4,
+            ..zeroed_metrics()
+        };
+
+        assert_eq!(
+            SystemMonitorService::get_cpu_usage_percentage(&metrics),
+            Some(0.0)
         );
     }
+
+    #[test]
+    fn test_cpu_sampler_needs_two_refreshes() {
+        let mut sampler = CpuSampler::new();
+        let start = Instant::now();
+
+        // Nothing to compare the first refresh against
+        assert!(sampler.sample(start).is_nan());
+        assert!(sampler.sample(start).is_nan());
+
+        let usage = sampler.sample(start + sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+        assert!(!usage.is_nan());
+    }
 }
@@ -1,20 +1,151 @@
 //! Token service for extracting, decoding, and validating JWT tokens
 use axum::http::{StatusCode, header};
+use base64::Engine;
 use jsonwebtoken::{DecodingKey, Validation, decode};
+use rand_core::{OsRng, RngCore};
 use sea_orm::DatabaseConnection;
-use std::env;
-use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256};
+use std::{env, sync::OnceLock};
 use uuid::Uuid;
 
 use crate::{
     control::services::session_service::SessionService,
-    infrastructure::{app_error::AppError, jwt_claims::Claims},
+    infrastructure::{
+        app_error::AppError,
+        clock::{Clock, system_clock},
+        jwt_claims::{Claims, TokenPurpose},
+        secrets::Secrets,
+        session_policy::SessionPolicyConfig,
+    },
 };
 
+/// Shortest JWT_SECRET accepted, 256 bits to match the HS256 signature
+pub const MIN_JWT_SECRET_BYTES: usize = 32;
+
+/// The placeholder from example.env, long enough to pass the length check but public
+pub const EXAMPLE_JWT_SECRET: &str = "your-secret-key-here-change-in-production";
+
+/// Default lifetime of the access tokens issued alongside a refresh token
+pub const DEFAULT_ACCESS_TOKEN_EXPIRY_MINUTES: i64 = 15;
+
+/// Secret generated for this process when JWT_SECRET is unset in development
+static EPHEMERAL_JWT_SECRET: OnceLock<String> = OnceLock::new();
+
+static TOKEN_CONFIG: OnceLock<TokenConfig> = OnceLock::new();
+
+/// Lifetimes of the tokens issued on login
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenConfig {
+    /// Hours until an admin login JWT expires, from JWT_EXPIRY_HOURS
+    pub jwt_expiry_hours: i64,
+    /// Hours until a session expires, from SESSION_EXPIRY_HOURS or [sessions] in rext.toml
+    pub session_expiry_hours: i64,
+    /// Minutes until an access token issued with a refresh token expires, from
+    /// ACCESS_TOKEN_EXPIRY_MINUTES
+    pub access_token_expiry_minutes: i64,
+}
+
+impl TokenConfig {
+    /// Global token config, loaded on first use
+    pub fn get() -> &'static TokenConfig {
+        TOKEN_CONFIG.get_or_init(|| {
+            Self::from_sources(SessionPolicyConfig::get(), |key| env::var(key).ok())
+        })
+    }
+
+    /// Builds the config from the session policy and an env lookup
+    ///
+    /// JWT_EXPIRY_HOURS defaults to the session expiry, so tokens and sessions expire together.
+    pub fn from_sources(
+        session_policy: &SessionPolicyConfig,
+        env_var: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let session_expiry_hours = session_policy.policy_for(None).expiry_hours;
+        let jwt_expiry_hours = env_var("JWT_EXPIRY_HOURS")
+            .and_then(|value| value.parse::<i64>().ok())
+            .map(|hours| hours.max(1))
+            .unwrap_or(session_expiry_hours);
+        let access_token_expiry_minutes = env_var("ACCESS_TOKEN_EXPIRY_MINUTES")
+            .and_then(|value| value.parse::<i64>().ok())
+            .map(|minutes| minutes.max(1))
+            .unwrap_or(DEFAULT_ACCESS_TOKEN_EXPIRY_MINUTES);
+
+        Self {
+            jwt_expiry_hours,
+            session_expiry_hours,
+            access_token_expiry_minutes,
+        }
+    }
+
+    /// When a token issued at `now` for a session lasting `session_expiry_hours` expires, never
+    /// after the session itself
+    pub fn jwt_expires_at(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        session_expiry_hours: i64,
+    ) -> chrono::DateTime<chrono::Utc> {
+        now + chrono::Duration::hours(self.jwt_expiry_hours.min(session_expiry_hours))
+    }
+
+    /// When an access token issued at `now` alongside a refresh token expires, never after the
+    /// session it belongs to
+    pub fn access_token_expires_at(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        session_expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> chrono::DateTime<chrono::Utc> {
+        (now + chrono::Duration::minutes(self.access_token_expiry_minutes)).min(session_expires_at)
+    }
+}
+
 /// Service for JWT token operations
 pub struct TokenService;
 
 impl TokenService {
+    /// The secret every token is signed and verified with
+    ///
+    /// Fails when JWT_SECRET is missing, shorter than `MIN_JWT_SECRET_BYTES` or still the
+    /// example.env placeholder, except that a missing secret in development is replaced by a
+    /// random one for the life of the process.
+    pub fn jwt_secret() -> Result<String, AppError> {
+        let environment = env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
+        Self::jwt_secret_from(Secrets::get("JWT_SECRET"), &environment)
+    }
+
+    /// Checks a configured JWT secret, see `jwt_secret`
+    pub fn jwt_secret_from(secret: Option<String>, environment: &str) -> Result<String, AppError> {
+        match secret.filter(|secret| !secret.is_empty()) {
+            Some(secret) if secret.len() < MIN_JWT_SECRET_BYTES => Err(AppError {
+                message: format!(
+                    "JWT_SECRET must be at least {} bytes, it is {}",
+                    MIN_JWT_SECRET_BYTES,
+                    secret.len()
+                ),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            }),
+            Some(secret) if secret == EXAMPLE_JWT_SECRET => Err(AppError {
+                message: "JWT_SECRET is the example.env placeholder, set a secret of your own"
+                    .to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            }),
+            Some(secret) => Ok(secret),
+            None if environment == "development" => Ok(EPHEMERAL_JWT_SECRET
+                .get_or_init(|| {
+                    tracing::warn!(
+                        "⚠️  JWT_SECRET is not set, signing tokens with a random secret that changes on every restart. Set JWT_SECRET before deploying"
+                    );
+                    let mut bytes = [0u8; 48];
+                    OsRng.fill_bytes(&mut bytes);
+                    base64::engine::general_purpose::STANDARD.encode(bytes)
+                })
+                .clone()),
+            None => Err(AppError {
+                message: "JWT_SECRET must be set".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            }),
+        }
+    }
+
     /// Extracts and validates a JWT token from the Authorization header
     /// Returns the user ID if the token is valid (JWT validation only)
     #[allow(dead_code)]
@@ -46,17 +177,44 @@ impl TokenService {
         })?;
 
         // Parse session ID
-        let session_id = Uuid::parse_str(&claims.session_id).map_err(|_| AppError {
-            message: "Invalid session ID in token".to_string(),
-            status_code: StatusCode::UNAUTHORIZED,
-        })?;
+        let session_id = Self::session_id_from_claims(&claims)?;
 
         // Validate session exists and is active
-        SessionService::validate_session(db, &claims.session_id).await?;
+        SessionService::validate_session(db, &session_id.to_string()).await?;
 
         Ok((user_id, session_id))
     }
 
+    /// Hex SHA-256 of an opaque token (refresh, verification or reset token), the form it is stored
+    /// and looked up in
+    pub fn hash_token(token: &str) -> String {
+        Sha256::digest(token.as_bytes())
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Returns the session ID of a session token
+    /// Distinguishes tokens that carry no session from ones with a malformed session ID
+    pub fn session_id_from_claims(claims: &Claims) -> Result<Uuid, AppError> {
+        if claims.purpose == TokenPurpose::Stateless {
+            return Err(AppError {
+                message: "Token is not bound to a session".to_string(),
+                status_code: StatusCode::UNAUTHORIZED,
+            });
+        }
+
+        let session_id = claims.session_id().ok_or(AppError {
+            message: "Missing session ID in token".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+        })?;
+
+        Uuid::parse_str(session_id).map_err(|_| AppError {
+            message: "Invalid session ID in token".to_string(),
+            status_code: StatusCode::UNAUTHORIZED,
+        })
+    }
+
     /// Extracts JWT token from Authorization header
     /// Returns the token (no validation is performed)
     pub fn extract_token_from_header(
@@ -80,35 +238,12 @@ impl TokenService {
     }
 
     /// Validates a JWT token and returns the user ID
-    /// Returns the user ID if the token is valid
+    /// Returns the user ID if the token is valid (sessions are not checked)
     pub fn validate_token(token: &str) -> Result<Uuid, AppError> {
-        let jwt_secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
-        let decoding_key = DecodingKey::from_secret(jwt_secret.as_ref());
-
-        // Decode and validate the token
-        let token_data =
-            decode::<Claims>(token, &decoding_key, &Validation::default()).map_err(|_| {
-                AppError {
-                    message: "Invalid token".to_string(),
-                    status_code: StatusCode::UNAUTHORIZED,
-                }
-            })?;
-
-        // Check if token is expired
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as usize;
-
-        if token_data.claims.exp < current_time {
-            return Err(AppError {
-                message: "Token expired".to_string(),
-                status_code: StatusCode::UNAUTHORIZED,
-            });
-        }
+        let claims = Self::validate_token_claims(token)?;
 
         // Parse user ID from token
-        let user_id = Uuid::parse_str(&token_data.claims.sub).map_err(|_| AppError {
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AppError {
             message: "Invalid user ID in token".to_string(),
             status_code: StatusCode::UNAUTHORIZED,
         })?;
@@ -117,25 +252,31 @@ impl TokenService {
     }
 
     /// Validates a JWT token and returns the Claims struct
-    #[allow(dead_code)]
     pub fn validate_token_claims(token: &str) -> Result<Claims, AppError> {
-        let jwt_secret = env::var("JWT_SECRET").unwrap_or_else(|_| "default-secret".to_string());
+        Self::validate_token_claims_with_clock(token, system_clock())
+    }
+
+    /// Validates a JWT token, checking expiry against the given clock
+    pub fn validate_token_claims_with_clock(
+        token: &str,
+        clock: &dyn Clock,
+    ) -> Result<Claims, AppError> {
+        let jwt_secret = Self::jwt_secret()?;
         let decoding_key = DecodingKey::from_secret(jwt_secret.as_ref());
 
+        // Expiry is checked below against the clock instead of the decoder's system time
+        let mut validation = Validation::default();
+        validation.validate_exp = false;
+
         // Decode and validate the token
         let token_data =
-            decode::<Claims>(token, &decoding_key, &Validation::default()).map_err(|_| {
-                AppError {
-                    message: "Invalid token".to_string(),
-                    status_code: StatusCode::UNAUTHORIZED,
-                }
+            decode::<Claims>(token, &decoding_key, &validation).map_err(|_| AppError {
+                message: "Invalid token".to_string(),
+                status_code: StatusCode::UNAUTHORIZED,
             })?;
 
         // Check if token is expired
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as usize;
+        let current_time = clock.unix_timestamp().max(0) as usize;
 
         if token_data.claims.exp < current_time {
             return Err(AppError {
@@ -144,6 +285,16 @@ impl TokenService {
             });
         }
 
+        // A session token must say which session it belongs to
+        if token_data.claims.purpose == TokenPurpose::Session
+            && token_data.claims.session_id().is_none()
+        {
+            return Err(AppError {
+                message: "Missing session ID in token".to_string(),
+                status_code: StatusCode::UNAUTHORIZED,
+            });
+        }
+
         Ok(token_data.claims)
     }
 }
@@ -151,27 +302,35 @@ impl TokenService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::infrastructure::clock::ManualClock;
+    use crate::infrastructure::test_support::{TEST_JWT_SECRET, memory_db, set_test_jwt_secret};
     use axum::http::{HeaderMap, HeaderValue};
     use jsonwebtoken::{EncodingKey, Header, encode};
     use std::time::{SystemTime, UNIX_EPOCH};
 
     fn create_test_token(user_id: &str, expires_in: i64) -> String {
-        let jwt_secret = "test-secret";
-        let encoding_key = EncodingKey::from_secret(jwt_secret.as_ref());
-
         let expiration = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs() as i64
             + expires_in;
 
-        let claims = Claims {
-            sub: user_id.to_string(),
-            exp: expiration as usize,
-            session_id: "".to_string(),
-        };
+        let claims = Claims::stateless(&Uuid::parse_str(user_id).unwrap(), expiration as usize);
+        encode_test_claims(&claims)
+    }
 
-        encode(&Header::default(), &claims, &encoding_key).unwrap()
+    fn encode_test_claims(claims: &Claims) -> String {
+        let encoding_key = EncodingKey::from_secret(TEST_JWT_SECRET.as_ref());
+
+        encode(&Header::default(), claims, &encoding_key).unwrap()
+    }
+
+    fn one_hour_from_now() -> usize {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize
+            + 3600
     }
 
     fn create_test_request_with_token(token: &str) -> axum::http::Request<axum::body::Body> {
@@ -186,6 +345,124 @@ mod tests {
         request
     }
 
+    #[test]
+    fn test_missing_jwt_secret_rejected_outside_development() {
+        let err = TokenService::jwt_secret_from(None, "production").unwrap_err();
+        assert_eq!(err.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(err.message, "JWT_SECRET must be set");
+
+        let err = TokenService::jwt_secret_from(Some(String::new()), "production").unwrap_err();
+        assert_eq!(err.message, "JWT_SECRET must be set");
+
+        // Development gets one random secret for the whole process
+        let secret = TokenService::jwt_secret_from(None, "development").unwrap();
+        assert!(secret.len() >= MIN_JWT_SECRET_BYTES);
+        assert_eq!(
+            TokenService::jwt_secret_from(None, "development").unwrap(),
+            secret
+        );
+    }
+
+    #[test]
+    fn test_jwt_expiry_hours_changes_exp() {
+        let sessions = SessionPolicyConfig::from_sources(None, |_| None);
+        let now = chrono::Utc::now();
+
+        // Defaults to the session expiry
+        let config = TokenConfig::from_sources(&sessions, |_| None);
+        assert_eq!(config.jwt_expiry_hours, 24);
+        assert_eq!(config.session_expiry_hours, 24);
+        assert_eq!(
+            config.jwt_expires_at(now, 24),
+            now + chrono::Duration::hours(24)
+        );
+
+        let config = TokenConfig::from_sources(&sessions, |key| {
+            (key == "JWT_EXPIRY_HOURS").then(|| "2".to_string())
+        });
+        assert_eq!(config.jwt_expiry_hours, 2);
+        assert_eq!(config.session_expiry_hours, 24);
+        assert_eq!(
+            config.jwt_expires_at(now, 24),
+            now + chrono::Duration::hours(2)
+        );
+
+        // Never outlives the session
+        let config = TokenConfig::from_sources(&sessions, |key| {
+            (key == "JWT_EXPIRY_HOURS").then(|| "48".to_string())
+        });
+        assert_eq!(
+            config.jwt_expires_at(now, 24),
+            now + chrono::Duration::hours(24)
+        );
+    }
+
+    #[test]
+    fn test_access_token_expiry() {
+        let sessions = SessionPolicyConfig::from_sources(None, |_| None);
+        let now = chrono::Utc::now();
+        let session_expires_at = now + chrono::Duration::hours(24);
+
+        let config = TokenConfig::from_sources(&sessions, |_| None);
+        assert_eq!(
+            config.access_token_expires_at(now, session_expires_at),
+            now + chrono::Duration::minutes(DEFAULT_ACCESS_TOKEN_EXPIRY_MINUTES)
+        );
+
+        let config = TokenConfig::from_sources(&sessions, |key| {
+            (key == "ACCESS_TOKEN_EXPIRY_MINUTES").then(|| "5".to_string())
+        });
+        assert_eq!(
+            config.access_token_expires_at(now, session_expires_at),
+            now + chrono::Duration::minutes(5)
+        );
+
+        // Never outlives the session
+        let session_expires_at = now + chrono::Duration::minutes(2);
+        assert_eq!(
+            config.access_token_expires_at(now, session_expires_at),
+            session_expires_at
+        );
+    }
+
+    #[test]
+    fn test_jwt_expiry_follows_session_expiry() {
+        let sessions = SessionPolicyConfig::from_sources(None, |key| {
+            (key == "SESSION_EXPIRY_HOURS").then(|| "8".to_string())
+        });
+
+        let config = TokenConfig::from_sources(&sessions, |_| None);
+        assert_eq!(config.jwt_expiry_hours, 8);
+        assert_eq!(config.session_expiry_hours, 8);
+    }
+
+    #[test]
+    fn test_short_jwt_secret_rejected() {
+        for environment in ["production", "development"] {
+            let err =
+                TokenService::jwt_secret_from(Some("default-secret".to_string()), environment)
+                    .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+            assert_eq!(
+                err.message,
+                "JWT_SECRET must be at least 32 bytes, it is 14"
+            );
+        }
+
+        for environment in ["production", "development"] {
+            let err =
+                TokenService::jwt_secret_from(Some(EXAMPLE_JWT_SECRET.to_string()), environment)
+                    .unwrap_err();
+            assert!(err.message.contains("placeholder"), "{}", err.message);
+        }
+
+        let secret = "a".repeat(MIN_JWT_SECRET_BYTES);
+        assert_eq!(
+            TokenService::jwt_secret_from(Some(secret.clone()), "production").unwrap(),
+            secret
+        );
+    }
+
     #[test]
     fn test_extract_token_from_header_valid() {
         let token = "test-token";
@@ -223,10 +500,7 @@ mod tests {
 
     #[test]
     fn test_validate_token_valid() {
-        // Set the JWT_SECRET environment variable for testing
-        unsafe {
-            std::env::set_var("JWT_SECRET", "test-secret");
-        }
+        set_test_jwt_secret();
 
         let user_id = "123e4567-e89b-12d3-a456-426614174000";
         let token = create_test_token(user_id, 3600); // 1 hour from now
@@ -238,10 +512,7 @@ mod tests {
 
     #[test]
     fn test_validate_token_expired() {
-        // Set the JWT_SECRET environment variable for testing
-        unsafe {
-            std::env::set_var("JWT_SECRET", "test-secret");
-        }
+        set_test_jwt_secret();
 
         let user_id = "123e4567-e89b-12d3-a456-426614174000";
         let token = create_test_token(user_id, -3600); // Expired 1 hour ago
@@ -260,10 +531,7 @@ mod tests {
 
     #[test]
     fn test_extract_and_validate_token_valid() {
-        // Set the JWT_SECRET environment variable for testing
-        unsafe {
-            std::env::set_var("JWT_SECRET", "test-secret");
-        }
+        set_test_jwt_secret();
 
         let user_id = "123e4567-e89b-12d3-a456-426614174000";
         let token = create_test_token(user_id, 3600); // 1 hour from now
@@ -276,10 +544,7 @@ mod tests {
 
     #[test]
     fn test_validate_token_claims_valid() {
-        // Set the JWT_SECRET environment variable for testing
-        unsafe {
-            std::env::set_var("JWT_SECRET", "test-secret");
-        }
+        set_test_jwt_secret();
 
         let user_id = "123e4567-e89b-12d3-a456-426614174000";
         let token = create_test_token(user_id, 3600); // 1 hour from now
@@ -289,4 +554,86 @@ mod tests {
         let claims = result.unwrap();
         assert_eq!(claims.sub, user_id);
     }
+
+    #[tokio::test]
+    async fn test_token_without_session_on_both_paths() {
+        set_test_jwt_secret();
+
+        let user_id = "123e4567-e89b-12d3-a456-426614174000";
+        let token = create_test_token(user_id, 3600);
+
+        // Plain validation ignores sessions
+        assert_eq!(
+            TokenService::validate_token(&token).unwrap().to_string(),
+            user_id
+        );
+
+        // Session validation explains that the token has no session
+        let db = memory_db().await;
+        let err = TokenService::extract_and_validate_token_with_session(&db, &token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::UNAUTHORIZED);
+        assert_eq!(err.message, "Token is not bound to a session");
+    }
+
+    #[tokio::test]
+    async fn test_session_token_with_empty_session_id_on_both_paths() {
+        set_test_jwt_secret();
+
+        let user_id = Uuid::new_v4();
+        let mut claims = Claims::for_session(&user_id, &Uuid::new_v4(), one_hour_from_now());
+        claims.session_id = Some(String::new());
+        let token = encode_test_claims(&claims);
+
+        let err = TokenService::validate_token(&token).unwrap_err();
+        assert_eq!(err.message, "Missing session ID in token");
+
+        let db = memory_db().await;
+        let err = TokenService::extract_and_validate_token_with_session(&db, &token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.message, "Missing session ID in token");
+    }
+
+    #[test]
+    fn test_token_expiry_boundary_follows_clock() {
+        set_test_jwt_secret();
+
+        let issued_at = chrono::Utc::now();
+        let exp = issued_at.timestamp() + 3600;
+        let token = encode_test_claims(&Claims::stateless(&Uuid::new_v4(), exp as usize));
+        let clock = ManualClock::new(issued_at);
+
+        assert!(TokenService::validate_token_claims_with_clock(&token, &clock).is_ok());
+
+        clock.set(chrono::DateTime::from_timestamp(exp, 0).unwrap());
+        assert!(TokenService::validate_token_claims_with_clock(&token, &clock).is_ok());
+
+        // Expired per the clock even though the real time is still within the token's lifetime
+        clock.set(chrono::DateTime::from_timestamp(exp + 1, 0).unwrap());
+        let err = TokenService::validate_token_claims_with_clock(&token, &clock).unwrap_err();
+        assert_eq!(err.message, "Token expired");
+    }
+
+    #[test]
+    fn test_session_id_from_claims() {
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+
+        let claims = Claims::for_session(&user_id, &session_id, one_hour_from_now());
+        assert_eq!(
+            TokenService::session_id_from_claims(&claims).unwrap(),
+            session_id
+        );
+
+        let mut claims = Claims::for_session(&user_id, &session_id, one_hour_from_now());
+        claims.session_id = Some("not-a-uuid".to_string());
+        assert_eq!(
+            TokenService::session_id_from_claims(&claims)
+                .unwrap_err()
+                .message,
+            "Invalid session ID in token"
+        );
+    }
 }
@@ -6,101 +6,104 @@ use sea_orm::prelude::Expr;
 use sea_orm::*;
 use uuid::Uuid;
 
+use crate::control::services::{database_service::DatabaseService, token_service::TokenService};
 use crate::domain::{user::*, validation::*};
 use crate::entity::models::{prelude::*, *};
-use crate::infrastructure::{app_error::AppError, email::EmailService};
-use crate::{
-    control::services::database_service::DatabaseService, infrastructure::email::EmailResult,
+#[cfg(feature = "email")]
+use crate::infrastructure::email::{EmailResult, EmailService, record_request_email_failure};
+use crate::infrastructure::{
+    app_error::AppError,
+    background_failures::{BackgroundOperation, record_background_failure},
+    clock::{Clock, system_clock},
+    timestamps::update_touched,
 };
 use axum::http::StatusCode;
 
+/// Default hours an email change verification link stays valid
+pub const DEFAULT_EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+/// Default hours a sign up verification link stays valid
+pub const DEFAULT_EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+
+/// How changes to a user's email address are applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmailChangeConfig {
+    /// Whether the new address must be verified before it replaces the current one
+    pub require_verification: bool,
+    /// Hours until an unverified change expires
+    pub ttl_hours: i64,
+}
+
+impl Default for EmailChangeConfig {
+    fn default() -> Self {
+        Self {
+            require_verification: true,
+            ttl_hours: DEFAULT_EMAIL_CHANGE_TTL_HOURS,
+        }
+    }
+}
+
+impl EmailChangeConfig {
+    /// Reads EMAIL_CHANGE_REQUIRE_VERIFICATION and EMAIL_CHANGE_TTL_HOURS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            require_verification: std::env::var("EMAIL_CHANGE_REQUIRE_VERIFICATION")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(defaults.require_verification),
+            ttl_hours: std::env::var("EMAIL_CHANGE_TTL_HOURS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|hours| *hours > 0)
+                .unwrap_or(defaults.ttl_hours),
+        }
+    }
+}
+
+/// How an account created on someone's behalf gets its email verified
+///
+/// One of the two must be set, an unverified account without a link could never verify.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NewUserOptions {
+    /// Email the user a verification link
+    pub send_verification: bool,
+    /// Create the account with its email already verified, no link is sent
+    pub mark_verified: bool,
+}
+
+impl Default for NewUserOptions {
+    fn default() -> Self {
+        Self {
+            send_verification: true,
+            mark_verified: false,
+        }
+    }
+}
+
+impl NewUserOptions {
+    fn sends_verification(&self) -> bool {
+        self.send_verification && !self.mark_verified
+    }
+}
+
 /// Service for user-related business operations
 pub struct UserService;
 
 impl UserService {
-    /// Creates a new user in the database
+    /// Creates a new user in the database, emailing a verification link
     pub async fn create_user(
         db: &DatabaseConnection,
         registration: UserRegistration,
     ) -> Result<User, AppError> {
-        // Validate input
-        validate_registration_input(&registration.email, &registration.password)?;
-
-        // Check if user already exists
-        let existing_user: Option<users::Model> = DatabaseService::find_one_with_tracking(
+        Self::create_user_with_options(
             db,
-            "users",
-            Users::find().filter(users::Column::Email.eq(registration.email.clone())),
+            registration.email,
+            registration.password,
+            None,
+            NewUserOptions::default(),
         )
         .await
-        .map_err(|_| AppError {
-            message: "Database error".to_string(),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
-
-        if existing_user.is_some() {
-            return Err(AppError {
-                message: "User already exists".to_string(),
-                status_code: StatusCode::CONFLICT,
-            });
-        }
-
-        // Hash password
-        let password_hash = Self::hash_password(&registration.password)?;
-
-        // Create user domain model
-        let user = User::create_new(registration.email, password_hash);
-
-        // Save to database
-        let user_active_model = users::ActiveModel {
-            id: Set(user.id),
-            email: Set(user.email.clone()),
-            password_hash: Set(user.password_hash.clone()),
-            created_at: Set(user.created_at.map(|dt| dt.fixed_offset())),
-            last_login: Set(None),
-            role_id: Set(None), // Default to no role
-            email_verified: Set(false),
-        };
-
-        // Send verification email
-        let email_service = EmailService::from_env();
-        match email_service {
-            Ok(email_service) => {
-                let email_result = email_service
-                    .send_verification_email(
-                        &user.email,
-                        &user.email,
-                        &format!("http://localhost:5173/verify-email?id={}", user.id),
-                        "Rext App",
-                    )
-                    .await;
-                match email_result {
-                    EmailResult::Success => (),
-                    EmailResult::Failed(e) => {
-                        return Err(AppError {
-                            message: format!("Failed to send verification email: {}", e),
-                            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                        });
-                    }
-                }
-            }
-            Err(e) => {
-                return Err(AppError {
-                    message: format!("Failed to send verification email: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                });
-            }
-        }
-
-        Users::insert(user_active_model)
-            .exec(db)
-            .await
-            .map_err(|_| AppError {
-                message: "Failed to create user".to_string(),
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            })?;
-
-        Ok(user)
     }
 
     /// Creates a new user with role assignment (for admin service)
@@ -109,9 +112,28 @@ impl UserService {
         email: String,
         password: String,
         role_id: Option<i32>,
+    ) -> Result<User, AppError> {
+        Self::create_user_with_options(db, email, password, role_id, NewUserOptions::default())
+            .await
+    }
+
+    /// Creates a new user with role assignment, choosing how its email gets verified
+    pub async fn create_user_with_options(
+        db: &DatabaseConnection,
+        email: String,
+        password: String,
+        role_id: Option<i32>,
+        options: NewUserOptions,
     ) -> Result<User, AppError> {
         // Validate input
+        let email = normalize_email(&email);
         validate_registration_input(&email, &password)?;
+        if !options.send_verification && !options.mark_verified {
+            return Err(AppError {
+                message: "send_verification or mark_verified is required".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
 
         // Check if user already exists
         let existing_user: Option<users::Model> = DatabaseService::find_one_with_tracking(
@@ -136,7 +158,8 @@ impl UserService {
         let password_hash = Self::hash_password(&password)?;
 
         // Create user domain model
-        let user = User::create_new(email, password_hash);
+        let mut user = User::create_new(email, password_hash);
+        user.email_verified = options.mark_verified;
 
         // Save to database
         let user_active_model = users::ActiveModel {
@@ -146,46 +169,39 @@ impl UserService {
             created_at: Set(user.created_at.map(|dt| dt.fixed_offset())),
             last_login: Set(None),
             role_id: Set(role_id),
-            email_verified: Set(false),
+            email_verified: Set(user.email_verified),
+            pending_email: Set(None),
+            pending_email_token: Set(None),
+            pending_email_expires_at: Set(None),
+            must_change_password: Set(false),
+            updated_at: Set(None),
         };
 
-        // Send verification email
-        let email_service = EmailService::from_env();
-        match email_service {
-            Ok(email_service) => {
-                let email_result = email_service
-                    .send_verification_email(
-                        &user.email,
-                        &user.email,
-                        &format!("http://localhost:5173/verify-email?id={}", user.id),
-                        "Rext App",
-                    )
-                    .await;
-                match email_result {
-                    EmailResult::Success => (),
-                    EmailResult::Failed(e) => {
-                        return Err(AppError {
-                            message: format!("Failed to send verification email: {}", e),
-                            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                        });
-                    }
-                }
-            }
-            Err(e) => {
-                return Err(AppError {
-                    message: format!("Failed to send verification email: {}", e),
-                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                });
-            }
-        }
-
+        // The user and its verification token are stored together before the link is sent, so
+        // a link never points at an account or token that doesn't exist
+        let token = options
+            .sends_verification()
+            .then(Self::new_email_verification_token);
+        let create_error = || AppError {
+            message: "Failed to create user".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let txn = db.begin().await.map_err(|_| create_error())?;
         Users::insert(user_active_model)
-            .exec(db)
+            .exec(&txn)
             .await
-            .map_err(|_| AppError {
-                message: "Failed to create user".to_string(),
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            })?;
+            .map_err(|_| create_error())?;
+        if let Some(token) = &token {
+            Self::store_email_verification(&txn, user.id, token).await?;
+        }
+        txn.commit().await.map_err(|_| create_error())?;
+
+        // A failed send keeps the account, the link can be sent again (POST /auth/resend-verification)
+        if let Some(token) = token {
+            if let Err(e) = Self::send_email_verification(&user.email, &token).await {
+                tracing::warn!(error = %e.message, "Verification email not sent on sign up");
+            }
+        }
 
         Ok(user)
     }
@@ -203,8 +219,12 @@ impl UserService {
 
         // We don't want to fail the login if this update fails
         if let Err(e) = update_result {
-            // Log the error but don't return it to avoid blocking login
-            eprintln!("Failed to update last_login for user {}: {:?}", user_id, e);
+            // Count the error but don't return it to avoid blocking login
+            record_background_failure(
+                BackgroundOperation::LastLoginUpdate,
+                &format!("Failed to update last_login for user {}: {}", user_id, e),
+            )
+            .await;
         }
 
         Ok(())
@@ -218,7 +238,7 @@ impl UserService {
         let user_model: Option<users::Model> = DatabaseService::find_one_with_tracking(
             db,
             "users",
-            Users::find().filter(users::Column::Email.eq(email)),
+            Users::find().filter(users::Column::Email.eq(normalize_email(email))),
         )
         .await
         .map_err(|_| AppError {
@@ -266,12 +286,15 @@ impl UserService {
     }
 
     /// Updates a user
+    ///
+    /// A new email only replaces the current one once verified through `confirm_email_change`,
+    /// unless EMAIL_CHANGE_REQUIRE_VERIFICATION is false.
     pub async fn update_user(
         db: &DatabaseConnection,
         user_id: Uuid,
         email: Option<String>,
         password: Option<String>,
-        role_id: Option<i32>,
+        role_id: Option<Option<i32>>,
     ) -> Result<User, AppError> {
         let user_model =
             DatabaseService::find_one_with_tracking(db, "users", Users::find_by_id(user_id))
@@ -286,9 +309,11 @@ impl UserService {
                 })?;
 
         let mut user_active_model: users::ActiveModel = user_model.clone().into();
+        let mut pending_email_change = None;
 
         // Update email if provided
         if let Some(new_email) = email {
+            let new_email = normalize_email(&new_email);
             validate_email(&new_email)?;
 
             // Check if email is already taken by another user
@@ -312,7 +337,25 @@ impl UserService {
                 });
             }
 
-            user_active_model.email = Set(new_email);
+            if new_email != user_model.email {
+                let config = EmailChangeConfig::from_env();
+                if config.require_verification {
+                    // The current address stays primary until the new one is verified
+                    // Only the hash is stored, like the other single-use tokens
+                    let token = Uuid::new_v4().to_string();
+                    let expires_at =
+                        system_clock().now() + chrono::Duration::hours(config.ttl_hours);
+                    user_active_model.pending_email = Set(Some(new_email.clone()));
+                    user_active_model.pending_email_token =
+                        Set(Some(TokenService::hash_token(&token)));
+                    user_active_model.pending_email_expires_at =
+                        Set(Some(expires_at.fixed_offset()));
+                    pending_email_change = Some((new_email, token));
+                } else {
+                    user_active_model.email = Set(new_email);
+                    user_active_model.email_verified = Set(false);
+                }
+            }
         }
 
         // Update password if provided
@@ -322,15 +365,22 @@ impl UserService {
             user_active_model.password_hash = Set(password_hash);
         }
 
-        // Update role_id if provided
+        // Update role_id if provided, Some(None) removes the role
         if let Some(new_role_id) = role_id {
-            user_active_model.role_id = Set(Some(new_role_id));
+            user_active_model.role_id = Set(new_role_id);
         }
 
-        let updated_user = user_active_model.update(db).await.map_err(|_| AppError {
-            message: "Failed to update user".to_string(),
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-        })?;
+        // Sent before anything is stored, so a failed send leaves the user unchanged
+        if let Some((new_email, token)) = pending_email_change {
+            Self::send_email_change_verification(&new_email, &token).await?;
+        }
+
+        let updated_user = update_touched(db, user_active_model)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to update user".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
 
         Ok(User::new(
             updated_user.id,
@@ -380,7 +430,7 @@ impl UserService {
     }
 
     /// Hashes a password using Argon2
-    fn hash_password(password: &str) -> Result<String, AppError> {
+    pub fn hash_password(password: &str) -> Result<String, AppError> {
         let salt = SaltString::generate(&mut rand_core::OsRng);
         let argon2 = Argon2::default();
         let password_hash = argon2
@@ -394,7 +444,203 @@ impl UserService {
         Ok(password_hash)
     }
 
-    /// Verify a user's email
+    /// Whether seeded admins and admin-created accounts must change their password before using
+    /// the API (REQUIRE_PASSWORD_CHANGE, defaults to true)
+    pub fn password_change_required_by_policy() -> bool {
+        std::env::var("REQUIRE_PASSWORD_CHANGE")
+            .map(|value| value.to_lowercase() != "false")
+            .unwrap_or(true)
+    }
+
+    /// Flags or unflags a user as having to change their password
+    pub async fn set_must_change_password(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        must_change_password: bool,
+    ) -> Result<(), AppError> {
+        Users::update_many()
+            .col_expr(
+                users::Column::MustChangePassword,
+                Expr::value(must_change_password),
+            )
+            .col_expr(
+                users::Column::UpdatedAt,
+                Expr::value(system_clock().now().fixed_offset()),
+            )
+            .filter(users::Column::Id.eq(user_id))
+            .exec(db)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to update user".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(())
+    }
+
+    /// Whether the user has to change their password before doing anything else
+    pub async fn must_change_password(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<bool, AppError> {
+        let user_model =
+            DatabaseService::find_one_with_tracking(db, "users", Users::find_by_id(user_id))
+                .await
+                .map_err(|_| AppError {
+                    message: "Database error".to_string(),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                })?;
+
+        Ok(user_model.is_some_and(|model| model.must_change_password))
+    }
+
+    /// Changes a user's password after checking the current one, and clears
+    /// `must_change_password`
+    pub async fn change_password(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), AppError> {
+        let user = Self::find_user_by_id(db, user_id)
+            .await?
+            .ok_or_else(|| AppError {
+                message: "User not found".to_string(),
+                status_code: StatusCode::NOT_FOUND,
+            })?;
+
+        if !Self::verify_password(&user, current_password)? {
+            return Err(AppError {
+                message: "Current password is incorrect".to_string(),
+                status_code: StatusCode::UNAUTHORIZED,
+            });
+        }
+
+        validate_strong_password(new_password)?;
+        if new_password == current_password {
+            return Err(AppError {
+                message: "New password must be different from the current password".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        let user_active_model = users::ActiveModel {
+            id: Set(user_id),
+            password_hash: Set(Self::hash_password(new_password)?),
+            must_change_password: Set(false),
+            ..Default::default()
+        };
+        update_touched(db, user_active_model)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to change password".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(())
+    }
+
+    /// Issues a single-use link token verifying the user's email, valid for
+    /// EMAIL_VERIFICATION_TTL_HOURS
+    pub async fn issue_email_verification(
+        db: &DatabaseConnection,
+        user_id: Uuid,
+    ) -> Result<String, AppError> {
+        let token = Self::new_email_verification_token();
+        Self::store_email_verification(db, user_id, &token).await?;
+        Ok(token)
+    }
+
+    /// Emails a new verification link to an unverified account
+    ///
+    /// Answers the same for unknown and already verified emails, and sends in the background so
+    /// the response time doesn't tell them apart either.
+    pub async fn resend_email_verification(
+        db: &DatabaseConnection,
+        email: &str,
+    ) -> Result<(), AppError> {
+        validate_email(email)?;
+
+        let Some(user) = Self::find_user_by_email(db, email).await? else {
+            tracing::debug!("Verification resend requested for an unknown email");
+            return Ok(());
+        };
+        if user.email_verified {
+            return Ok(());
+        }
+
+        let token = Self::issue_email_verification(db, user.id).await?;
+        tokio::spawn(async move {
+            if let Err(e) = Self::send_email_verification(&user.email, &token).await {
+                tracing::warn!(error = %e.message, "Verification email not resent");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Verifies the email of the user a verification link was sent to
+    pub async fn verify_email_token(db: &DatabaseConnection, token: &str) -> Result<(), AppError> {
+        Self::verify_email_token_with_clock(db, token, system_clock()).await
+    }
+
+    /// `verify_email_token` checking the expiry against the given clock
+    pub async fn verify_email_token_with_clock(
+        db: &DatabaseConnection,
+        token: &str,
+        clock: &dyn Clock,
+    ) -> Result<(), AppError> {
+        let invalid_token = || AppError {
+            message: "Invalid email verification token".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+        };
+
+        let verification = DatabaseService::find_one_with_tracking(
+            db,
+            "email_verifications",
+            EmailVerifications::find()
+                .filter(email_verifications::Column::TokenHash.eq(TokenService::hash_token(token))),
+        )
+        .await
+        .map_err(|_| AppError {
+            message: "Database error".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?
+        .ok_or_else(invalid_token)?;
+
+        if verification.expires_at.to_utc() < clock.now() {
+            return Err(AppError {
+                message: "Email verification has expired".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        // Marks the token used only if it wasn't already, so it verifies once even when
+        // followed twice at the same time
+        let result = EmailVerifications::update_many()
+            .col_expr(
+                email_verifications::Column::UsedAt,
+                Expr::value(clock.now().fixed_offset()),
+            )
+            .filter(email_verifications::Column::Id.eq(verification.id))
+            .filter(email_verifications::Column::UsedAt.is_null())
+            .exec(db)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to verify email".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+        if result.rows_affected == 0 {
+            return Err(AppError {
+                message: "Email verification link has already been used".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        Self::verify_email(db, verification.user_id).await
+    }
+
+    /// Marks a user's email as verified without a token, for admins and seeding
     pub async fn verify_email(db: &DatabaseConnection, user_id: Uuid) -> Result<(), AppError> {
         let user_model =
             DatabaseService::find_one_with_tracking(db, "users", Users::find_by_id(user_id))
@@ -409,11 +655,469 @@ impl UserService {
                 })?;
         let mut user_active_model: users::ActiveModel = user_model.into();
         user_active_model.email_verified = Set(true);
-        user_active_model.update(db).await.map_err(|_| AppError {
-            message: "Failed to verify email".to_string(),
+        update_touched(db, user_active_model)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to verify email".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(())
+    }
+
+    /// Applies a pending email change once the link sent to the new address is followed
+    pub async fn confirm_email_change(
+        db: &DatabaseConnection,
+        token: &str,
+    ) -> Result<User, AppError> {
+        Self::confirm_email_change_with_clock(db, token, system_clock()).await
+    }
+
+    /// `confirm_email_change` checking the expiry against the given clock
+    pub async fn confirm_email_change_with_clock(
+        db: &DatabaseConnection,
+        token: &str,
+        clock: &dyn Clock,
+    ) -> Result<User, AppError> {
+        let invalid_token = || AppError {
+            message: "Invalid email change token".to_string(),
+            status_code: StatusCode::BAD_REQUEST,
+        };
+
+        let user_model = DatabaseService::find_one_with_tracking(
+            db,
+            "users",
+            Users::find()
+                .filter(users::Column::PendingEmailToken.eq(TokenService::hash_token(token))),
+        )
+        .await
+        .map_err(|_| AppError {
+            message: "Database error".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?
+        .ok_or_else(invalid_token)?;
+
+        let new_email = user_model.pending_email.clone().ok_or_else(invalid_token)?;
+        let expired = user_model
+            .pending_email_expires_at
+            .is_none_or(|expires_at| expires_at.to_utc() < clock.now());
+
+        let mut user_active_model: users::ActiveModel = user_model.into();
+        user_active_model.pending_email = Set(None);
+        user_active_model.pending_email_token = Set(None);
+        user_active_model.pending_email_expires_at = Set(None);
+
+        if expired {
+            update_touched(db, user_active_model)
+                .await
+                .map_err(|_| AppError {
+                    message: "Failed to update user".to_string(),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                })?;
+            return Err(AppError {
+                message: "Email change verification has expired".to_string(),
+                status_code: StatusCode::BAD_REQUEST,
+            });
+        }
+
+        // The address may have been taken since the change was requested
+        let existing_user = DatabaseService::find_one_with_tracking(
+            db,
+            "users",
+            Users::find().filter(users::Column::Email.eq(&new_email)),
+        )
+        .await
+        .map_err(|_| AppError {
+            message: "Database error".to_string(),
             status_code: StatusCode::INTERNAL_SERVER_ERROR,
         })?;
 
+        if existing_user.is_some() {
+            return Err(AppError {
+                message: "Email already taken".to_string(),
+                status_code: StatusCode::CONFLICT,
+            });
+        }
+
+        user_active_model.email = Set(new_email);
+        user_active_model.email_verified = Set(true);
+
+        let updated_user = update_touched(db, user_active_model)
+            .await
+            .map_err(|_| AppError {
+                message: "Failed to update user".to_string(),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            })?;
+
+        Ok(User::new(
+            updated_user.id,
+            updated_user.email,
+            updated_user.password_hash,
+            updated_user.created_at.map(|dt| dt.to_utc()),
+            updated_user.last_login.map(|dt| dt.to_utc()),
+            updated_user.role_id,
+            updated_user.email_verified,
+        ))
+    }
+
+    fn new_email_verification_token() -> String {
+        Uuid::new_v4().simple().to_string()
+    }
+
+    /// Reads EMAIL_VERIFICATION_TTL_HOURS
+    fn email_verification_ttl_hours() -> i64 {
+        std::env::var("EMAIL_VERIFICATION_TTL_HOURS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|hours| *hours > 0)
+            .unwrap_or(DEFAULT_EMAIL_VERIFICATION_TTL_HOURS)
+    }
+
+    async fn store_email_verification<C: ConnectionTrait>(
+        db: &C,
+        user_id: Uuid,
+        token: &str,
+    ) -> Result<(), AppError> {
+        let now = system_clock().now();
+        email_verifications::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            token_hash: Set(TokenService::hash_token(token)),
+            created_at: Set(Some(now.fixed_offset())),
+            expires_at: Set(
+                (now + chrono::Duration::hours(Self::email_verification_ttl_hours()))
+                    .fixed_offset(),
+            ),
+            used_at: Set(None),
+        }
+        .insert(db)
+        .await
+        .map_err(|_| AppError {
+            message: "Failed to store email verification".to_string(),
+            status_code: StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+        Ok(())
+    }
+
+    /// Sends the link verifying a new user's email
+    async fn send_email_verification(email: &str, token: &str) -> Result<(), AppError> {
+        Self::send_verification_link(
+            email,
+            &format!("http://localhost:5173/verify-email?token={}", token),
+        )
+        .await
+    }
+
+    /// Sends the link confirming an email change to the new address
+    async fn send_email_change_verification(new_email: &str, token: &str) -> Result<(), AppError> {
+        Self::send_verification_link(
+            new_email,
+            &format!("http://localhost:5173/confirm-email-change?token={}", token),
+        )
+        .await
+    }
+
+    /// Sends a verification link while the request waits, counting a failure against it
+    #[cfg(feature = "email")]
+    async fn send_verification_link(address: &str, link: &str) -> Result<(), AppError> {
+        let result = match EmailService::from_env() {
+            Ok(email_service) => {
+                email_service
+                    .send_verification_email(address, address, link, "Rext App")
+                    .await
+            }
+            Err(e) => EmailResult::Failed(e),
+        };
+
+        match result {
+            EmailResult::Success => Ok(()),
+            EmailResult::Failed(e) => {
+                record_request_email_failure();
+                Err(AppError {
+                    message: format!("Failed to send verification email: {}", e),
+                    status_code: StatusCode::INTERNAL_SERVER_ERROR,
+                })
+            }
+        }
+    }
+
+    /// Without the email module there is nothing to send with, the link is only logged
+    #[cfg(not(feature = "email"))]
+    async fn send_verification_link(address: &str, link: &str) -> Result<(), AppError> {
+        tracing::info!(
+            to = address,
+            link,
+            "Email module disabled, verification link not sent"
+        );
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::clock::ManualClock;
+    use crate::infrastructure::test_support::{create_verified_user, setup_db};
+
+    /// Email changes, which need the email module to send the confirmation link
+    #[cfg(feature = "email")]
+    mod email_change {
+        use super::*;
+        use crate::infrastructure::email::{REQUEST_EMAIL_FAILURES, logged_messages_to};
+        use std::sync::atomic::Ordering;
+
+        async fn setup_user() -> (DatabaseConnection, User) {
+            let db = setup_db().await;
+
+            let user = create_verified_user(
+                &db,
+                "old@example.com".to_string(),
+                "UserPassword123!".to_string(),
+                None,
+            )
+            .await
+            .unwrap();
+            (db, user)
+        }
+
+        /// Requests a change to `new_email` and returns the token from the link sent there
+        async fn request_change(db: &DatabaseConnection, user_id: Uuid, new_email: &str) -> String {
+            UserService::update_user(db, user_id, Some(new_email.to_string()), None, None)
+                .await
+                .unwrap();
+            let email = logged_messages_to(new_email).pop().unwrap();
+            email
+                .text
+                .split("confirm-email-change?token=")
+                .nth(1)
+                .unwrap()
+                .split_whitespace()
+                .next()
+                .unwrap()
+                .to_string()
+        }
+
+        #[tokio::test]
+        async fn test_email_change_pending_until_verified() {
+            let (db, user) = setup_user().await;
+
+            let token = request_change(&db, user.id, "pending@example.com").await;
+            let model = Users::find_by_id(user.id).one(&db).await.unwrap().unwrap();
+            assert_eq!(model.email, "old@example.com");
+            assert!(model.email_verified);
+            assert_eq!(model.pending_email.as_deref(), Some("pending@example.com"));
+            // Only the token's hash is stored
+            assert_eq!(
+                model.pending_email_token,
+                Some(TokenService::hash_token(&token))
+            );
+        }
+
+        #[tokio::test]
+        async fn test_failed_email_change_send_stores_nothing() {
+            let (db, user) = setup_user().await;
+            let failures_before = REQUEST_EMAIL_FAILURES.load(Ordering::Relaxed);
+
+            // The log transport still rejects addresses lettre can't parse
+            let err = UserService::update_user(
+                &db,
+                user.id,
+                Some("unsendable@example.com\r\nBcc: x@example.com".to_string()),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+            assert!(REQUEST_EMAIL_FAILURES.load(Ordering::Relaxed) > failures_before);
+
+            let model = Users::find_by_id(user.id).one(&db).await.unwrap().unwrap();
+            assert!(model.pending_email.is_none());
+            assert!(model.pending_email_token.is_none());
+        }
+
+        #[tokio::test]
+        async fn test_verified_email_change_swaps_email() {
+            let (db, user) = setup_user().await;
+            let token = request_change(&db, user.id, "swapped@example.com").await;
+
+            let updated = UserService::confirm_email_change(&db, &token)
+                .await
+                .unwrap();
+            assert_eq!(updated.email, "swapped@example.com");
+            assert!(updated.email_verified);
+
+            let model = Users::find_by_id(user.id).one(&db).await.unwrap().unwrap();
+            assert!(model.pending_email.is_none());
+            assert!(model.pending_email_token.is_none());
+
+            // The link only works once
+            let err = UserService::confirm_email_change(&db, &token)
+                .await
+                .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        }
+
+        #[tokio::test]
+        async fn test_expired_email_change_rejected() {
+            let (db, user) = setup_user().await;
+            let token = request_change(&db, user.id, "expired@example.com").await;
+
+            let clock = ManualClock::new(
+                chrono::Utc::now()
+                    + chrono::Duration::hours(EmailChangeConfig::from_env().ttl_hours + 1),
+            );
+            let err = UserService::confirm_email_change_with_clock(&db, &token, &clock)
+                .await
+                .unwrap_err();
+            assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+
+            let model = Users::find_by_id(user.id).one(&db).await.unwrap().unwrap();
+            assert_eq!(model.email, "old@example.com");
+            assert!(model.pending_email.is_none());
+        }
+    }
+
+    async fn setup_unverified_user() -> (DatabaseConnection, User) {
+        let db = setup_db().await;
+
+        let user = UserService::create_user_with_role(
+            &db,
+            "new@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        (db, user)
+    }
+
+    async fn email_verified(db: &DatabaseConnection, user_id: Uuid) -> bool {
+        Users::find_by_id(user_id)
+            .one(db)
+            .await
+            .unwrap()
+            .unwrap()
+            .email_verified
+    }
+
+    #[tokio::test]
+    async fn test_valid_email_verification_token() {
+        let (db, user) = setup_unverified_user().await;
+        // Sign up already stored a link for the user
+        assert_eq!(EmailVerifications::find().count(&db).await.unwrap(), 1);
+
+        let token = UserService::issue_email_verification(&db, user.id)
+            .await
+            .unwrap();
+        assert!(!email_verified(&db, user.id).await);
+
+        UserService::verify_email_token(&db, &token).await.unwrap();
+        assert!(email_verified(&db, user.id).await);
+
+        // The token itself is never stored
+        let stored = EmailVerifications::find()
+            .filter(email_verifications::Column::TokenHash.eq(token.as_str()))
+            .one(&db)
+            .await
+            .unwrap();
+        assert!(stored.is_none());
+
+        let err = UserService::verify_email_token(&db, &Uuid::new_v4().simple().to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_expired_email_verification_token_rejected() {
+        let (db, user) = setup_unverified_user().await;
+        let token = UserService::issue_email_verification(&db, user.id)
+            .await
+            .unwrap();
+
+        let clock = ManualClock::new(
+            chrono::Utc::now()
+                + chrono::Duration::hours(UserService::email_verification_ttl_hours() + 1),
+        );
+        let err = UserService::verify_email_token_with_clock(&db, &token, &clock)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Email verification has expired");
+        assert!(!email_verified(&db, user.id).await);
+    }
+
+    #[tokio::test]
+    async fn test_emails_stored_and_found_case_insensitively() {
+        let db = setup_db().await;
+        let user = create_verified_user(
+            &db,
+            " Mixed.Case@Example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.email, "mixed.case@example.com");
+
+        let found = UserService::find_user_by_email(&db, "MIXED.case@example.COM")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.id, user.id);
+
+        let err = create_verified_user(
+            &db,
+            "mixed.CASE@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_resend_email_verification_only_for_unverified_accounts() {
+        let (db, user) = setup_unverified_user().await;
+        create_verified_user(
+            &db,
+            "verified@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        UserService::resend_email_verification(&db, &user.email)
+            .await
+            .unwrap();
+        assert_eq!(EmailVerifications::find().count(&db).await.unwrap(), 2);
+
+        // Verified and unknown emails get the same answer, and no new link
+        UserService::resend_email_verification(&db, "verified@example.com")
+            .await
+            .unwrap();
+        UserService::resend_email_verification(&db, "nobody@example.com")
+            .await
+            .unwrap();
+        assert_eq!(EmailVerifications::find().count(&db).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_reused_email_verification_token_rejected() {
+        let (db, user) = setup_unverified_user().await;
+        let token = UserService::issue_email_verification(&db, user.id)
+            .await
+            .unwrap();
+
+        UserService::verify_email_token(&db, &token).await.unwrap();
+        let err = UserService::verify_email_token(&db, &token)
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert_eq!(err.message, "Email verification link has already been used");
+    }
+}
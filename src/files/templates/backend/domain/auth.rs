@@ -8,14 +8,26 @@ pub struct AuthToken {
     pub token: String,
     pub user_id: Uuid,
     pub expires_at: DateTime<Utc>,
+    /// Exchanged for a new access token at /auth/refresh, valid once
+    pub refresh_token: String,
+    /// When the session, and with it the refresh token, expires
+    pub refresh_expires_at: DateTime<Utc>,
 }
 
 impl AuthToken {
-    pub fn new(token: String, user_id: Uuid, expires_at: DateTime<Utc>) -> Self {
+    pub fn new(
+        token: String,
+        user_id: Uuid,
+        expires_at: DateTime<Utc>,
+        refresh_token: String,
+        refresh_expires_at: DateTime<Utc>,
+    ) -> Self {
         Self {
             token,
             user_id,
             expires_at,
+            refresh_token,
+            refresh_expires_at,
         }
     }
 }
@@ -4,143 +4,120 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
-/// Represents all available permissions in the system
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum Permission {
+/// Declares the built-in permissions once, generating the `Permission` enum and the
+/// `KNOWN_PERMISSIONS` table that the string conversions, validity check and catalog read from
+macro_rules! define_permissions {
+    ($($variant:ident => $name:literal, $category:literal, $description:literal;)*) => {
+        /// Represents all available permissions in the system
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum Permission {
+            $($variant,)*
+
+            // Custom permissions (for dynamic roles)
+            Custom(String),
+        }
+
+        /// Every built-in permission, in catalog order
+        pub const KNOWN_PERMISSIONS: &[KnownPermission] = &[
+            $(KnownPermission {
+                permission: Permission::$variant,
+                name: $name,
+                category: $category,
+                description: $description,
+            },)*
+        ];
+    };
+}
+
+/// A built-in permission with its string form, category and description
+#[derive(Debug)]
+pub struct KnownPermission {
+    pub permission: Permission,
+    pub name: &'static str,
+    pub category: &'static str,
+    pub description: &'static str,
+}
+
+define_permissions! {
     // Super admin permission (wildcard)
-    All,
+    All => "*", "super", "Full system access";
 
     // Admin permissions
-    AdminRead,
-    AdminWrite,
-    AdminDelete,
-    AdminUsers,
-    AdminRoles,
-    AdminLogs,
-    AdminDatabase,
-    AdminHealth,
-    AdminMetrics,
+    AdminRead => "admin:read", "admin", "Read admin data";
+    AdminWrite => "admin:write", "admin", "Write admin data";
+    AdminDelete => "admin:delete", "admin", "Delete admin data";
+    AdminUsers => "admin:users", "admin", "Manage users";
+    AdminRoles => "admin:roles", "admin", "Manage roles";
+    AdminLogs => "admin:logs", "admin", "View system logs";
+    AdminDatabase => "admin:database", "admin", "Access database";
+    AdminHealth => "admin:health", "admin", "View system health";
+    AdminMetrics => "admin:metrics", "admin", "View system metrics";
 
     // User permissions
-    UserRead,
-    UserWrite,
-    UserDelete,
-    UserProfile,
-    UserCreate,
+    UserRead => "user:read", "user", "Read user data";
+    UserWrite => "user:write", "user", "Write user data";
+    UserDelete => "user:delete", "user", "Delete user data";
+    UserProfile => "user:profile", "user", "Manage user profile";
+    UserCreate => "user:create", "user", "Create users";
 
     // System permissions
-    SystemHealth,
-    SystemMetrics,
-    SystemLogs,
-    SystemDatabase,
-
-    // Custom permissions (for dynamic roles)
-    Custom(String),
+    SystemHealth => "system:health", "system", "View system health";
+    SystemMetrics => "system:metrics", "system", "View system metrics";
+    SystemLogs => "system:logs", "system", "View system logs";
+    SystemDatabase => "system:database", "system", "Access system database";
 }
 
 impl Permission {
+    /// Table entry of a built-in permission, None for custom ones
+    fn known(&self) -> Option<&'static KnownPermission> {
+        KNOWN_PERMISSIONS
+            .iter()
+            .find(|known| known.permission == *self)
+    }
+
+    /// Whether the string names a built-in permission
+    pub fn is_known(s: &str) -> bool {
+        KNOWN_PERMISSIONS.iter().any(|known| known.name == s)
+    }
+
     /// Convert permission to string representation
     pub fn to_string(&self) -> String {
         match self {
-            Permission::All => "*".to_string(),
-            Permission::AdminRead => "admin:read".to_string(),
-            Permission::AdminWrite => "admin:write".to_string(),
-            Permission::AdminDelete => "admin:delete".to_string(),
-            Permission::AdminUsers => "admin:users".to_string(),
-            Permission::AdminRoles => "admin:roles".to_string(),
-            Permission::AdminLogs => "admin:logs".to_string(),
-            Permission::AdminDatabase => "admin:database".to_string(),
-            Permission::AdminHealth => "admin:health".to_string(),
-            Permission::AdminMetrics => "admin:metrics".to_string(),
-            Permission::UserRead => "user:read".to_string(),
-            Permission::UserWrite => "user:write".to_string(),
-            Permission::UserDelete => "user:delete".to_string(),
-            Permission::UserProfile => "user:profile".to_string(),
-            Permission::UserCreate => "user:create".to_string(),
-            Permission::SystemHealth => "system:health".to_string(),
-            Permission::SystemMetrics => "system:metrics".to_string(),
-            Permission::SystemLogs => "system:logs".to_string(),
-            Permission::SystemDatabase => "system:database".to_string(),
             Permission::Custom(s) => s.clone(),
+            permission => permission
+                .known()
+                .map(|known| known.name.to_string())
+                .unwrap_or_default(),
         }
     }
 
     /// Create permission from string
     pub fn from_string(s: &str) -> Self {
-        match s {
-            "*" => Permission::All,
-            "admin:read" => Permission::AdminRead,
-            "admin:write" => Permission::AdminWrite,
-            "admin:delete" => Permission::AdminDelete,
-            "admin:users" => Permission::AdminUsers,
-            "admin:roles" => Permission::AdminRoles,
-            "admin:logs" => Permission::AdminLogs,
-            "admin:database" => Permission::AdminDatabase,
-            "admin:health" => Permission::AdminHealth,
-            "admin:metrics" => Permission::AdminMetrics,
-            "user:read" => Permission::UserRead,
-            "user:write" => Permission::UserWrite,
-            "user:delete" => Permission::UserDelete,
-            "user:profile" => Permission::UserProfile,
-            "user:create" => Permission::UserCreate,
-            "system:health" => Permission::SystemHealth,
-            "system:metrics" => Permission::SystemMetrics,
-            "system:logs" => Permission::SystemLogs,
-            "system:database" => Permission::SystemDatabase,
-            _ => Permission::Custom(s.to_string()),
-        }
+        KNOWN_PERMISSIONS
+            .iter()
+            .find(|known| known.name == s)
+            .map(|known| known.permission.clone())
+            .unwrap_or_else(|| Permission::Custom(s.to_string()))
     }
 
     /// Get permission category
     pub fn category(&self) -> &'static str {
-        match self {
-            Permission::All => "super",
-            Permission::AdminRead
-            | Permission::AdminWrite
-            | Permission::AdminDelete
-            | Permission::AdminUsers
-            | Permission::AdminRoles
-            | Permission::AdminLogs
-            | Permission::AdminDatabase
-            | Permission::AdminHealth
-            | Permission::AdminMetrics => "admin",
-            Permission::UserRead
-            | Permission::UserWrite
-            | Permission::UserDelete
-            | Permission::UserProfile
-            | Permission::UserCreate => "user",
-            Permission::SystemHealth
-            | Permission::SystemMetrics
-            | Permission::SystemLogs
-            | Permission::SystemDatabase => "system",
-            Permission::Custom(_) => "custom",
-        }
+        self.known().map(|known| known.category).unwrap_or("custom")
     }
 
     /// Get permission description
     pub fn description(&self) -> &'static str {
-        match self {
-            Permission::All => "Full system access",
-            Permission::AdminRead => "Read admin data",
-            Permission::AdminWrite => "Write admin data",
-            Permission::AdminDelete => "Delete admin data",
-            Permission::AdminUsers => "Manage users",
-            Permission::AdminRoles => "Manage roles",
-            Permission::AdminLogs => "View system logs",
-            Permission::AdminDatabase => "Access database",
-            Permission::AdminHealth => "View system health",
-            Permission::AdminMetrics => "View system metrics",
-            Permission::UserRead => "Read user data",
-            Permission::UserWrite => "Write user data",
-            Permission::UserDelete => "Delete user data",
-            Permission::UserProfile => "Manage user profile",
-            Permission::UserCreate => "Create users",
-            Permission::SystemHealth => "View system health",
-            Permission::SystemMetrics => "View system metrics",
-            Permission::SystemLogs => "View system logs",
-            Permission::SystemDatabase => "Access system database",
-            Permission::Custom(_) => "Custom permission",
-        }
+        self.known()
+            .map(|known| known.description)
+            .unwrap_or("Custom permission")
+    }
+
+    /// All built-in permissions, excluding custom ones
+    pub fn all_known() -> Vec<Permission> {
+        KNOWN_PERMISSIONS
+            .iter()
+            .map(|known| known.permission.clone())
+            .collect()
     }
 
     /// Check if this permission includes another permission
@@ -3,6 +3,7 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use sea_orm::DbErr;
 use serde::Serialize;
 use std::error::Error as StdError;
 use std::fmt;
@@ -23,6 +24,27 @@ impl fmt::Display for AppError {
 
 impl StdError for AppError {}
 
+/// Maps database errors to a 500 `AppError` that names the table and operation
+///
+/// `query.await.with_context("users", "create")?` in place of a hand-written `map_err`.
+pub trait DbResultExt<T> {
+    /// Logs the error with `table` and `operation` and turns it into
+    /// "Database error during <operation> on <table>: <error>"
+    fn with_context(self, table: &str, operation: &str) -> Result<T, AppError>;
+}
+
+impl<T> DbResultExt<T> for Result<T, DbErr> {
+    fn with_context(self, table: &str, operation: &str) -> Result<T, AppError> {
+        self.map_err(|e| {
+            tracing::error!(table, operation, error = %e, "Database error");
+            AppError {
+                message: format!("Database error during {} on {}: {}", operation, table, e),
+                status_code: StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let body = Json(ErrorResponse {
@@ -47,3 +69,56 @@ pub struct ErrorResponse {
     #[schema(example = "Email and password are required")]
     pub message: String,
 }
+
+/// Problem with a single request field
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    /// Name of the field in the request body
+    #[schema(example = "email")]
+    pub field: String,
+    /// What is wrong with the field
+    #[schema(example = "Invalid email format")]
+    pub message: String,
+}
+
+/// Body of 400 responses for requests that failed validation
+///
+/// `message` summarizes the failure; `errors` lists the offending fields and is omitted when the
+/// failure isn't tied to specific fields, so a plain `ErrorResponse` body also matches.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationErrorResponse {
+    /// Error message describing what went wrong
+    #[schema(example = "Email and password are required")]
+    pub message: String,
+    /// Field-level validation errors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_context_names_table_and_operation() {
+        let result: Result<(), DbErr> = Err(DbErr::RecordNotInserted);
+
+        let err = result.with_context("users", "create").unwrap_err();
+
+        assert_eq!(err.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(
+            err.message
+                .starts_with("Database error during create on users: ")
+        );
+        assert!(err.message.contains(&DbErr::RecordNotInserted.to_string()));
+    }
+
+    #[test]
+    fn test_with_context_keeps_ok_values() {
+        let result: Result<u64, DbErr> = Ok(3);
+
+        assert_eq!(result.with_context("user_sessions", "count").unwrap(), 3);
+    }
+}
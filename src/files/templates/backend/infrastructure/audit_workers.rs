@@ -0,0 +1,197 @@
+//! Bounded worker pool for background audit log work (DB insert and WebSocket broadcast)
+use futures_util::FutureExt;
+use std::{env, future::Future, panic::AssertUnwindSafe, sync::Arc};
+use tokio::sync::Semaphore;
+
+/// Default number of workers processing audit tasks concurrently
+pub const DEFAULT_AUDIT_WORKERS: usize = 4;
+
+/// Default number of audit tasks that can wait in the queue
+pub const DEFAULT_AUDIT_QUEUE_SIZE: usize = 1024;
+
+/// Worker pool configuration
+#[derive(Debug, Clone)]
+pub struct AuditWorkerConfig {
+    /// Tasks processed at the same time, each holds at most one DB connection
+    pub workers: usize,
+    /// Tasks queued before `submit` waits for a free slot
+    pub queue_size: usize,
+}
+
+impl AuditWorkerConfig {
+    /// Reads AUDIT_LOG_WORKERS and AUDIT_LOG_QUEUE_SIZE
+    pub fn from_env() -> Self {
+        let workers = env::var("AUDIT_LOG_WORKERS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|workers| *workers > 0)
+            .unwrap_or(DEFAULT_AUDIT_WORKERS);
+        let queue_size = env::var("AUDIT_LOG_QUEUE_SIZE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|queue_size| *queue_size > 0)
+            .unwrap_or(DEFAULT_AUDIT_QUEUE_SIZE);
+
+        Self {
+            workers,
+            queue_size,
+        }
+    }
+}
+
+/// Bounds audit work to a fixed number of concurrent tasks, so a burst of requests queues its
+/// audit work instead of competing with handlers for DB connections
+///
+/// Each task is spawned on the submitting runtime when it's queued, so the pool isn't tied to
+/// the runtime that created it, and a panicking task is caught and logged.
+pub struct AuditWorkerPool {
+    /// Running plus queued tasks, `submit` waits for one
+    slots: Arc<Semaphore>,
+    /// Running tasks, each queued task waits for one
+    workers: Arc<Semaphore>,
+}
+
+/// Global pool used by the logging middleware
+pub static AUDIT_WORKERS: once_cell::sync::Lazy<AuditWorkerPool> =
+    once_cell::sync::Lazy::new(|| AuditWorkerPool::new(AuditWorkerConfig::from_env()));
+
+impl AuditWorkerPool {
+    pub fn new(config: AuditWorkerConfig) -> Self {
+        let workers = config.workers.max(1);
+        Self {
+            slots: Arc::new(Semaphore::new(workers + config.queue_size.max(1))),
+            workers: Arc::new(Semaphore::new(workers)),
+        }
+    }
+
+    /// Queues a task, waiting for room when the queue is full
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub async fn submit<F>(&self, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // The semaphores are never closed
+        let slot = self.slots.clone().acquire_owned().await.unwrap();
+        let workers = self.workers.clone();
+        tokio::spawn(async move {
+            let _slot = slot;
+            let _worker = workers.acquire_owned().await.unwrap();
+            if AssertUnwindSafe(task).catch_unwind().await.is_err() {
+                tracing::error!("Audit task panicked, its audit entry was dropped");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_tasks_bounded_under_flood() {
+        let pool = AuditWorkerPool::new(AuditWorkerConfig {
+            workers: 3,
+            queue_size: 8,
+        });
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..200 {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            let completed = completed.clone();
+            pool.submit(async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(1)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                completed.fetch_add(1, Ordering::SeqCst);
+            })
+            .await;
+        }
+
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while completed.load(Ordering::SeqCst) < 200 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(max_running.load(Ordering::SeqCst) <= 3);
+        assert!(max_running.load(Ordering::SeqCst) >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_submit_waits_when_queue_full() {
+        let pool = AuditWorkerPool::new(AuditWorkerConfig {
+            workers: 1,
+            queue_size: 1,
+        });
+        let (release, blocked) = tokio::sync::oneshot::channel::<()>();
+
+        // Occupies the only worker, then one more task fills the queue
+        pool.submit(async move {
+            let _ = blocked.await;
+        })
+        .await;
+        tokio::task::yield_now().await;
+        pool.submit(async {}).await;
+
+        let third = tokio::time::timeout(Duration::from_millis(50), pool.submit(async {})).await;
+        assert!(third.is_err(), "submit should wait while the queue is full");
+
+        release.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), pool.submit(async {}))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_does_not_stop_the_pool() {
+        let pool = AuditWorkerPool::new(AuditWorkerConfig {
+            workers: 1,
+            queue_size: 1,
+        });
+
+        pool.submit(async { panic!("audit task failed") }).await;
+
+        let (done, finished) = tokio::sync::oneshot::channel::<()>();
+        pool.submit(async move {
+            let _ = done.send(());
+        })
+        .await;
+        tokio::time::timeout(Duration::from_secs(5), finished)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_pool_outlives_the_runtime_that_first_used_it() {
+        let pool = AuditWorkerPool::new(AuditWorkerConfig {
+            workers: 1,
+            queue_size: 1,
+        });
+
+        // Like consecutive #[tokio::test]s sharing AUDIT_WORKERS
+        for _ in 0..2 {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let (done, finished) = tokio::sync::oneshot::channel::<()>();
+                pool.submit(async move {
+                    let _ = done.send(());
+                })
+                .await;
+                tokio::time::timeout(Duration::from_secs(5), finished)
+                    .await
+                    .unwrap()
+                    .unwrap();
+            });
+        }
+    }
+}
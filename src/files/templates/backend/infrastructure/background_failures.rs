@@ -0,0 +1,120 @@
+//! Counters for operations that fail off the request path, so an audit insert or email that
+//! silently fails shows up in the health status instead of only in the logs
+use std::{
+    env,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::infrastructure::websocket::{LogLevel, WsMessage, broadcast};
+
+/// Process-wide failure counters
+pub static BACKGROUND_FAILURES: BackgroundFailures = BackgroundFailures::new();
+
+/// Operations whose failures are counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundOperation {
+    AuditLogInsert,
+    EmailSend,
+    SessionActivityUpdate,
+    LastLoginUpdate,
+}
+
+impl BackgroundOperation {
+    const COUNT: usize = 4;
+
+    fn index(self) -> usize {
+        match self {
+            BackgroundOperation::AuditLogInsert => 0,
+            BackgroundOperation::EmailSend => 1,
+            BackgroundOperation::SessionActivityUpdate => 2,
+            BackgroundOperation::LastLoginUpdate => 3,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BackgroundOperation::AuditLogInsert => "audit_log_insert",
+            BackgroundOperation::EmailSend => "email_send",
+            BackgroundOperation::SessionActivityUpdate => "session_activity_update",
+            BackgroundOperation::LastLoginUpdate => "last_login_update",
+        }
+    }
+}
+
+/// Failure count per background operation since startup
+pub struct BackgroundFailures {
+    counts: [AtomicU64; BackgroundOperation::COUNT],
+}
+
+impl Default for BackgroundFailures {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackgroundFailures {
+    pub const fn new() -> Self {
+        Self {
+            counts: [const { AtomicU64::new(0) }; BackgroundOperation::COUNT],
+        }
+    }
+
+    /// Counts a failure, returning the new total for the operation
+    pub fn increment(&self, operation: BackgroundOperation) -> u64 {
+        self.counts[operation.index()].fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// Failures of the operation since startup
+    pub fn count(&self, operation: BackgroundOperation) -> u64 {
+        self.counts[operation.index()].load(Ordering::Relaxed)
+    }
+
+    /// Whether failures are also broadcast to admin WebSocket clients
+    /// (BACKGROUND_FAILURE_ALERTS, defaults to true)
+    pub fn alerts_enabled() -> bool {
+        env::var("BACKGROUND_FAILURE_ALERTS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(true)
+    }
+}
+
+/// Counts a failed background operation, logs it and alerts WebSocket clients if enabled
+pub async fn record_background_failure(operation: BackgroundOperation, error: &str) {
+    let total = BACKGROUND_FAILURES.increment(operation);
+    tracing::error!(
+        operation = operation.as_str(),
+        failures = total,
+        error = %error,
+        "Background operation failed"
+    );
+
+    if BackgroundFailures::alerts_enabled() {
+        broadcast(WsMessage::system_log(
+            LogLevel::Error,
+            format!(
+                "Background {} failed ({} failures since startup): {}",
+                operation.as_str(),
+                total,
+                error
+            ),
+            "background_failures",
+        ))
+        .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_are_tracked_per_operation() {
+        let failures = BackgroundFailures::new();
+        assert_eq!(failures.increment(BackgroundOperation::EmailSend), 1);
+        assert_eq!(failures.increment(BackgroundOperation::EmailSend), 2);
+
+        assert_eq!(failures.count(BackgroundOperation::EmailSend), 2);
+        assert_eq!(failures.count(BackgroundOperation::AuditLogInsert), 0);
+    }
+}
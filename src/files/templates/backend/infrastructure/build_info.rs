@@ -0,0 +1,49 @@
+//! Version information captured when the binary was compiled, so it always describes the build
+//! that is actually running
+use std::env;
+
+/// Package name and version from Cargo, plus the git commit build.rs found (GIT_SHA)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuildInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub git_sha: Option<&'static str>,
+}
+
+/// The build this binary was compiled from
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    name: env!("CARGO_PKG_NAME"),
+    version: env!("CARGO_PKG_VERSION"),
+    git_sha: option_env!("GIT_SHA"),
+};
+
+/// Whether GET /version is served (VERSION_ENDPOINT_ENABLED, defaults to true)
+pub fn version_endpoint_enabled() -> bool {
+    env::var("VERSION_ENDPOINT_ENABLED")
+        .map(|value| value.to_lowercase() != "false")
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info_is_compiled_in() {
+        assert_eq!(BUILD_INFO.name, env!("CARGO_PKG_NAME"));
+        assert_eq!(BUILD_INFO.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_git_sha_is_a_single_token_or_absent() {
+        // build.rs leaves GIT_SHA unset outside a git checkout when no override is given
+        if let Some(git_sha) = BUILD_INFO.git_sha {
+            assert!(!git_sha.is_empty());
+            assert!(
+                !git_sha.contains(char::is_whitespace),
+                "unexpected GIT_SHA {:?}",
+                git_sha
+            );
+        }
+    }
+}
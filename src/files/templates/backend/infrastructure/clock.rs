@@ -0,0 +1,123 @@
+//! Time source for session and token expiry, swappable so time-dependent logic can be tested
+//! without sleeping
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{
+    Mutex,
+    atomic::{AtomicI64, Ordering},
+};
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    /// Current time as seconds since the Unix epoch, as used by JWT `exp` claims
+    fn unix_timestamp(&self) -> i64 {
+        self.now().timestamp()
+    }
+}
+
+/// Wall clock that never goes backwards
+///
+/// If the system clock jumps back (NTP correction, VM resume), the last time handed out is
+/// repeated until the system clock catches up, so expiry checks never see time reversing.
+pub struct SystemClock {
+    last_micros: AtomicI64,
+}
+
+impl SystemClock {
+    pub const fn new() -> Self {
+        Self {
+            last_micros: AtomicI64::new(i64::MIN),
+        }
+    }
+
+    /// Time for a raw system reading, clamped to the latest time seen so far
+    fn observe(&self, system_now: DateTime<Utc>) -> DateTime<Utc> {
+        let micros = system_now.timestamp_micros();
+        let previous = self.last_micros.fetch_max(micros, Ordering::SeqCst);
+
+        if micros >= previous {
+            system_now
+        } else {
+            DateTime::from_timestamp_micros(previous).unwrap_or(system_now)
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.observe(Utc::now())
+    }
+}
+
+static SYSTEM_CLOCK: SystemClock = SystemClock::new();
+
+/// The clock used by services outside of tests
+pub fn system_clock() -> &'static SystemClock {
+    &SYSTEM_CLOCK
+}
+
+/// Clock that only moves when told to, for tests
+#[allow(dead_code)]
+pub struct ManualClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+#[allow(dead_code)]
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_clock_holds_when_time_goes_backwards() {
+        let clock = SystemClock::new();
+        let start = Utc::now();
+
+        assert_eq!(clock.observe(start), start);
+        assert_eq!(clock.observe(start - Duration::minutes(5)), start);
+
+        let later = start + Duration::seconds(1);
+        assert_eq!(clock.observe(later), later);
+    }
+
+    #[test]
+    fn test_manual_clock_moves_only_when_told() {
+        let start = Utc::now();
+        let clock = ManualClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::hours(1));
+        assert_eq!(clock.now(), start + Duration::hours(1));
+
+        clock.set(start);
+        assert_eq!(clock.unix_timestamp(), start.timestamp());
+    }
+}
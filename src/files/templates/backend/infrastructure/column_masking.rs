@@ -0,0 +1,172 @@
+//! Masking of sensitive columns in the admin database browser, applied whatever the permissions
+//! of the admin browsing
+use std::{fs, sync::OnceLock};
+
+static COLUMN_MASKING_POLICY: OnceLock<ColumnMaskingPolicy> = OnceLock::new();
+
+/// Value returned in place of a redacted column
+pub const REDACTED_VALUE: &str = "[REDACTED]";
+
+/// How a masked column is shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnMask {
+    /// The column is listed but every value is replaced by `REDACTED_VALUE`
+    Redact,
+    /// The column is left out of the response entirely
+    Omit,
+}
+
+impl ColumnMask {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "redact" => Some(ColumnMask::Redact),
+            "omit" => Some(ColumnMask::Omit),
+            _ => None,
+        }
+    }
+}
+
+/// Columns that are always masked, configuration can only turn these into `Omit`
+const DEFAULT_MASKED_COLUMNS: &[(&str, &str, ColumnMask)] = &[
+    ("users", "password_hash", ColumnMask::Redact),
+    ("users", "pending_email_token", ColumnMask::Redact),
+    ("user_sessions", "session_token", ColumnMask::Redact),
+    ("user_sessions", "refresh_token_hash", ColumnMask::Redact),
+    ("email_verifications", "token_hash", ColumnMask::Redact),
+    ("password_resets", "token_hash", ColumnMask::Redact),
+];
+
+/// Per-table, per-column masks for the generic record browser
+///
+/// Loaded from `[database_browser.masking.<table>]` tables in rext.toml, mapping column names to
+/// "redact" or "omit", on top of the built-in masks for password hashes and tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMaskingPolicy {
+    masks: Vec<(String, String, ColumnMask)>,
+}
+
+impl Default for ColumnMaskingPolicy {
+    fn default() -> Self {
+        Self {
+            masks: DEFAULT_MASKED_COLUMNS
+                .iter()
+                .map(|(table, column, mask)| (table.to_string(), column.to_string(), *mask))
+                .collect(),
+        }
+    }
+}
+
+impl ColumnMaskingPolicy {
+    /// Global masking policy, loaded on first use
+    pub fn get() -> &'static ColumnMaskingPolicy {
+        COLUMN_MASKING_POLICY.get_or_init(Self::load)
+    }
+
+    /// Loads the policy from rext.toml
+    pub fn load() -> Self {
+        let rext_toml = fs::read_to_string("rext.toml").ok();
+        Self::from_sources(rext_toml.as_deref())
+    }
+
+    /// Builds the policy from rext.toml contents
+    pub fn from_sources(rext_toml: Option<&str>) -> Self {
+        let mut policy = Self::default();
+
+        let masking_table = rext_toml
+            .and_then(|content| toml::from_str::<toml::Value>(content).ok())
+            .and_then(|value| value.get("database_browser")?.get("masking").cloned());
+        let Some(tables) = masking_table.as_ref().and_then(|value| value.as_table()) else {
+            return policy;
+        };
+
+        for (table, columns) in tables {
+            let Some(columns) = columns.as_table() else {
+                continue;
+            };
+            for (column, mask) in columns {
+                if let Some(mask) = mask.as_str().and_then(ColumnMask::parse) {
+                    policy.set(table, column, mask);
+                }
+            }
+        }
+
+        policy
+    }
+
+    fn set(&mut self, table: &str, column: &str, mask: ColumnMask) {
+        match self
+            .masks
+            .iter_mut()
+            .find(|(masked_table, masked_column, _)| {
+                masked_table.eq_ignore_ascii_case(table)
+                    && masked_column.eq_ignore_ascii_case(column)
+            }) {
+            // Built-in masks can be made stricter but never lifted
+            Some((_, _, existing)) => {
+                if mask == ColumnMask::Omit {
+                    *existing = mask;
+                }
+            }
+            None => self
+                .masks
+                .push((table.to_string(), column.to_string(), mask)),
+        }
+    }
+
+    /// The mask for a column, None if it's shown as is
+    pub fn mask_for(&self, table: &str, column: &str) -> Option<ColumnMask> {
+        self.masks
+            .iter()
+            .find(|(masked_table, masked_column, _)| {
+                masked_table.eq_ignore_ascii_case(table)
+                    && masked_column.eq_ignore_ascii_case(column)
+            })
+            .map(|(_, _, mask)| *mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_masks_without_config() {
+        let policy = ColumnMaskingPolicy::from_sources(None);
+        assert_eq!(
+            policy.mask_for("users", "password_hash"),
+            Some(ColumnMask::Redact)
+        );
+        assert_eq!(policy.mask_for("users", "email"), None);
+    }
+
+    #[test]
+    fn test_configured_masks_add_to_builtin_ones() {
+        let policy = ColumnMaskingPolicy::from_sources(Some(
+            r#"
+[database_browser.masking.users]
+password_hash = "omit"
+
+[database_browser.masking.api_clients]
+api_key = "redact"
+secret = "omit"
+"#,
+        ));
+
+        assert_eq!(
+            policy.mask_for("users", "password_hash"),
+            Some(ColumnMask::Omit)
+        );
+        assert_eq!(
+            policy.mask_for("api_clients", "api_key"),
+            Some(ColumnMask::Redact)
+        );
+        assert_eq!(
+            policy.mask_for("API_CLIENTS", "Secret"),
+            Some(ColumnMask::Omit)
+        );
+        assert_eq!(
+            policy.mask_for("user_sessions", "session_token"),
+            Some(ColumnMask::Redact)
+        );
+    }
+}
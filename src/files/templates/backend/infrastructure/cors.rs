@@ -2,6 +2,12 @@ use axum::http::{HeaderName, HeaderValue, Method};
 use std::env;
 use tower_http::cors::CorsLayer;
 
+/// Vite dev server origin
+const DEVELOPMENT_ORIGIN: &str = "http://localhost:5173";
+
+/// Fallback when ALLOWED_ORIGIN isn't set
+const DEFAULT_PRODUCTION_ORIGIN: &str = "https://yourdomain.com";
+
 /// CORS configuration manager
 pub struct CorsManager;
 
@@ -17,10 +23,32 @@ impl CorsManager {
         }
     }
 
+    /// Origins allowed to make cross-origin requests, also used to check WebSocket upgrades
+    pub fn allowed_origins() -> Vec<String> {
+        let environment = env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
+
+        if environment == "development" {
+            vec![DEVELOPMENT_ORIGIN.to_string()]
+        } else {
+            vec![
+                env::var("ALLOWED_ORIGIN")
+                    .unwrap_or_else(|_| DEFAULT_PRODUCTION_ORIGIN.to_string()),
+            ]
+        }
+    }
+
+    /// Whether the origin is in the allowlist (exact match, ignoring a trailing slash)
+    pub fn is_allowed_origin(origin: &str, allowed_origins: &[String]) -> bool {
+        let origin = origin.trim_end_matches('/');
+        allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+    }
+
     /// Creates CORS configuration for development
     fn create_development_cors() -> CorsLayer {
         CorsLayer::new()
-            .allow_origin("http://localhost:5173".parse::<HeaderValue>().unwrap())
+            .allow_origin(DEVELOPMENT_ORIGIN.parse::<HeaderValue>().unwrap())
             .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
             .allow_headers([
                 "authorization".parse::<HeaderName>().unwrap(),
@@ -28,6 +56,9 @@ impl CorsManager {
                 "accept".parse::<HeaderName>().unwrap(),
                 "origin".parse::<HeaderName>().unwrap(),
                 "x-requested-with".parse::<HeaderName>().unwrap(),
+                "traceparent".parse::<HeaderName>().unwrap(),
+                "tracestate".parse::<HeaderName>().unwrap(),
+                "x-confirmation-token".parse::<HeaderName>().unwrap(),
             ])
             .allow_credentials(true)
     }
@@ -35,13 +66,13 @@ impl CorsManager {
     /// Creates CORS configuration for production
     fn create_production_cors() -> CorsLayer {
         let allowed_origin =
-            env::var("ALLOWED_ORIGIN").unwrap_or_else(|_| "https://yourdomain.com".to_string());
+            env::var("ALLOWED_ORIGIN").unwrap_or_else(|_| DEFAULT_PRODUCTION_ORIGIN.to_string());
 
         CorsLayer::new()
             .allow_origin(
                 allowed_origin
                     .parse::<HeaderValue>()
-                    .unwrap_or_else(|_| "https://yourdomain.com".parse::<HeaderValue>().unwrap()),
+                    .unwrap_or_else(|_| DEFAULT_PRODUCTION_ORIGIN.parse::<HeaderValue>().unwrap()),
             )
             .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
             .allow_headers([
@@ -50,6 +81,9 @@ impl CorsManager {
                 "accept".parse::<HeaderName>().unwrap(),
                 "origin".parse::<HeaderName>().unwrap(),
                 "x-requested-with".parse::<HeaderName>().unwrap(),
+                "traceparent".parse::<HeaderName>().unwrap(),
+                "tracestate".parse::<HeaderName>().unwrap(),
+                "x-confirmation-token".parse::<HeaderName>().unwrap(),
             ])
             .allow_credentials(true)
             .max_age(std::time::Duration::from_secs(3600)) // Cache preflight for 1 hour
@@ -1,7 +1,8 @@
 //! Email service
 //!
 //! Module to handle sending emails. Configurable via .env file.
-//! Currently supports SMTP and templates made in handlers, not file-based templates.
+//! Supports SMTP and the SendGrid API (requires building with --features sendgrid). Templates
+//! are built in, and can be overridden with files in EMAIL_TEMPLATE_DIR.
 //!
 //! Example usage:
 //! ```rust_no_run
@@ -18,21 +19,40 @@
 //! println!("Email result: {:?}", result);
 //! ```
 
-use lettre::message::Mailbox;
 use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Message, SmtpTransport, Transport};
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fmt::Display;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use tracing::{error, info};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::infrastructure::secrets::Secrets;
+
+/// Sends that failed while a request waited on them, the error went back to the client
+///
+/// Kept apart from the background failure counters, which only count failures nobody saw.
+pub static REQUEST_EMAIL_FAILURES: AtomicU64 = AtomicU64::new(0);
+
+/// Counts a send that failed while a request waited on it, returning the new total
+pub fn record_request_email_failure() -> u64 {
+    REQUEST_EMAIL_FAILURES.fetch_add(1, Ordering::Relaxed) + 1
+}
 
 /// Represents all supported email services
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EmailServiceType {
     SMTP,
+    /// SendGrid's HTTPS API, requires building with --features sendgrid
+    SendGrid,
+    /// Writes emails to the log instead of sending them, for development and tests
+    Log,
 }
 
 impl Display for EmailServiceType {
@@ -42,8 +62,16 @@ impl Display for EmailServiceType {
 }
 
 impl FromStr for EmailServiceType {
-    fn from_str(_s: &str) -> Result<Self, Self::Err> {
-        Ok(Self::SMTP)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "smtp" => Ok(Self::SMTP),
+            "sendgrid" => Ok(Self::SendGrid),
+            "log" => Ok(Self::Log),
+            other => Err(format!(
+                "Unknown email service type: {} (expected smtp, sendgrid or log)",
+                other
+            )),
+        }
     }
 
     type Err = String;
@@ -53,7 +81,7 @@ impl FromStr for EmailServiceType {
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct EmailConfig {
-    /// SMTP service provider (currently only "smtp" is supported)
+    /// Email service provider, "smtp", "sendgrid" or "log"
     pub service_type: EmailServiceType,
     /// SMTP server hostname
     pub smtp_host: String,
@@ -63,6 +91,8 @@ pub struct EmailConfig {
     pub smtp_username: String,
     /// SMTP password
     pub smtp_password: String,
+    /// SendGrid API key, required for the SendGrid service
+    pub sendgrid_api_key: Option<String>,
     /// From email address
     pub from_email: String,
     /// From name (display name)
@@ -71,6 +101,8 @@ pub struct EmailConfig {
     pub reply_to_email: Option<String>,
     /// Reply-to name (optional)
     pub reply_to_name: Option<String>,
+    /// Largest total size of an email's attachments in bytes
+    pub max_attachment_bytes: usize,
 }
 
 /// Email template for sending
@@ -79,6 +111,9 @@ pub struct EmailTemplate {
     pub subject: String,
     pub body: String,
     pub content_type: EmailContentType,
+    /// Plaintext alternative to an HTML body, both are sent as a multipart message
+    #[serde(default)]
+    pub text_body: Option<String>,
 }
 
 /// Content type for emails
@@ -88,6 +123,209 @@ pub enum EmailContentType {
     Html,
 }
 
+/// Default largest total size of an email's attachments, 10 MiB
+pub const DEFAULT_EMAIL_MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+
+/// File attached to an email
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    /// MIME type, e.g. application/pdf
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Body of an outgoing email
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub enum EmailBody {
+    /// A single part of the given content type
+    Single {
+        content: String,
+        content_type: EmailContentType,
+    },
+    /// Plaintext and HTML versions of the same content, clients show the best one they support
+    Alternative { text: String, html: String },
+}
+
+/// Default number of retries after a transient send failure
+pub const DEFAULT_EMAIL_MAX_RETRIES: u32 = 3;
+
+/// Default delay before the first retry in milliseconds, doubled for each further retry
+pub const DEFAULT_EMAIL_RETRY_DELAY_MS: u64 = 500;
+
+/// Default deadline for a send in seconds, covering every attempt and the backoff between them
+pub const DEFAULT_EMAIL_SEND_TIMEOUT_SECS: u64 = 10;
+
+/// How transient send failures are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmailRetryPolicy {
+    /// Retries after the first attempt, 0 sends once
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub base_delay: Duration,
+    /// Deadline for the whole send, a slow SMTP server can't hold the request any longer
+    pub send_timeout: Duration,
+}
+
+impl Default for EmailRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_EMAIL_MAX_RETRIES,
+            base_delay: Duration::from_millis(DEFAULT_EMAIL_RETRY_DELAY_MS),
+            send_timeout: Duration::from_secs(DEFAULT_EMAIL_SEND_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl EmailRetryPolicy {
+    /// Reads EMAIL_MAX_RETRIES, EMAIL_RETRY_DELAY_MS and EMAIL_SEND_TIMEOUT_SECS
+    pub fn from_env() -> Self {
+        let max_retries = env::var("EMAIL_MAX_RETRIES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_EMAIL_MAX_RETRIES);
+        let delay_ms = env::var("EMAIL_RETRY_DELAY_MS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_EMAIL_RETRY_DELAY_MS);
+        let timeout_secs = env::var("EMAIL_SEND_TIMEOUT_SECS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_EMAIL_SEND_TIMEOUT_SECS);
+
+        Self {
+            max_retries,
+            base_delay: Duration::from_millis(delay_ms),
+            send_timeout: Duration::from_secs(timeout_secs),
+        }
+    }
+
+    /// Delay before the given retry, counting from 1
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+/// Why a send attempt failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryError {
+    /// May succeed on a retry, e.g. connection errors, 4xx SMTP replies and rate limits
+    Transient(String),
+    /// Won't succeed on a retry, e.g. rejected recipients and 5xx SMTP replies
+    Permanent(String),
+}
+
+impl Display for DeliveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeliveryError::Transient(message) | DeliveryError::Permanent(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+/// Runs `attempt` until it succeeds, fails permanently or runs out of retries, backing off
+/// between attempts
+///
+/// Gives up with a transient error once the policy's send timeout passes, an attempt still in
+/// flight is dropped.
+pub async fn with_retries<F, Fut>(
+    policy: &EmailRetryPolicy,
+    mut attempt: F,
+) -> Result<(), DeliveryError>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<(), DeliveryError>>,
+{
+    let attempts = async {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Err(DeliveryError::Transient(e)) if retry < policy.max_retries => {
+                    retry += 1;
+                    let delay = policy.delay(retry);
+                    warn!(
+                        "Email send failed, retrying in {:?} ({}/{}): {}",
+                        delay, retry, policy.max_retries, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    };
+
+    tokio::time::timeout(policy.send_timeout, attempts)
+        .await
+        .unwrap_or_else(|_| {
+            Err(DeliveryError::Transient(format!(
+                "Email send timed out after {:?} (EMAIL_SEND_TIMEOUT_SECS)",
+                policy.send_timeout
+            )))
+        })
+}
+
+/// Who an email goes to, the primary recipient plus optional copies
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmailRecipients {
+    pub to_email: String,
+    pub to_name: Option<String>,
+    /// Copied addresses, plain or "Name <address>"
+    pub cc: Vec<String>,
+    /// Blind copied addresses, hidden from the other recipients
+    pub bcc: Vec<String>,
+}
+
+impl EmailRecipients {
+    /// A single recipient without copies
+    pub fn to(email: &str, name: Option<&str>) -> Self {
+        Self {
+            to_email: email.to_string(),
+            to_name: name.map(|name| name.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a copied address
+    #[allow(dead_code)]
+    pub fn cc(mut self, address: &str) -> Self {
+        self.cc.push(address.to_string());
+        self
+    }
+
+    /// Adds a blind copied address
+    #[allow(dead_code)]
+    pub fn bcc(mut self, address: &str) -> Self {
+        self.bcc.push(address.to_string());
+        self
+    }
+
+    /// CC mailboxes, naming the first invalid address
+    pub fn cc_mailboxes(&self) -> Result<Vec<Mailbox>, String> {
+        Self::parse_copies("CC", &self.cc)
+    }
+
+    /// BCC mailboxes, naming the first invalid address
+    pub fn bcc_mailboxes(&self) -> Result<Vec<Mailbox>, String> {
+        Self::parse_copies("BCC", &self.bcc)
+    }
+
+    fn parse_copies(kind: &str, addresses: &[String]) -> Result<Vec<Mailbox>, String> {
+        addresses
+            .iter()
+            .map(|address| {
+                address
+                    .parse::<Mailbox>()
+                    .map_err(|e| format!("Invalid {} email address '{}': {}", kind, address, e))
+            })
+            .collect()
+    }
+}
+
 /// Email sending result
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -96,21 +334,109 @@ pub enum EmailResult {
     Failed(String),
 }
 
+/// What a bulk send does after a recipient fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum BulkSendMode {
+    /// Keep sending to the remaining recipients
+    #[default]
+    ContinueOnFailure,
+    /// Stop at the first failure, leaving the remaining recipients out of the results
+    StopOnFailure,
+}
+
+/// How emails are delivered, matching `EmailConfig::service_type`
+#[allow(dead_code)]
+pub enum EmailTransport {
+    /// Non-blocking SMTP transport driven by the Tokio runtime
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    #[cfg(feature = "sendgrid")]
+    SendGrid(sendgrid::SendGridClient),
+    /// Logs each email instead of sending it
+    Log,
+}
+
+/// Email handled by the log transport, kept for test assertions
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct LoggedEmail {
+    pub to: String,
+    pub subject: String,
+    /// The plaintext body, or the text part of an alternative body
+    pub text: String,
+}
+
+/// Every email the log transport handled
+#[cfg(test)]
+pub static LOGGED_EMAILS: std::sync::Mutex<Vec<LoggedEmail>> = std::sync::Mutex::new(Vec::new());
+
+/// Subjects of the emails the log transport handled for an address
+#[cfg(test)]
+pub fn logged_emails_to(address: &str) -> Vec<String> {
+    logged_messages_to(address)
+        .into_iter()
+        .map(|email| email.subject)
+        .collect()
+}
+
+/// Emails the log transport handled for an address, oldest first
+#[cfg(test)]
+pub fn logged_messages_to(address: &str) -> Vec<LoggedEmail> {
+    LOGGED_EMAILS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|email| email.to == address)
+        .cloned()
+        .collect()
+}
+
 /// Main email service struct
 #[allow(dead_code)]
 pub struct EmailService {
     config: EmailConfig,
-    transport: SmtpTransport,
+    transport: EmailTransport,
+    /// Templates loaded from a template directory, preferred over the built-in ones
+    templates: HashMap<String, EmailTemplate>,
+    retry_policy: EmailRetryPolicy,
 }
 
 impl EmailService {
-    /// Initialize the email service from environment variables
+    /// Initialize the email service from environment variables, with templates loaded from
+    /// EMAIL_TEMPLATE_DIR when it's set
     #[allow(dead_code)]
     pub fn from_env() -> Result<Self, String> {
         let config = EmailConfig::from_env()?;
         let transport = Self::create_transport(&config)?;
 
-        Ok(Self { config, transport })
+        let service = Self {
+            config,
+            transport,
+            templates: HashMap::new(),
+            retry_policy: EmailRetryPolicy::from_env(),
+        };
+        match env::var("EMAIL_TEMPLATE_DIR") {
+            Ok(dir) if !dir.trim().is_empty() => service.with_template_dir(PathBuf::from(dir)),
+            _ => Ok(service),
+        }
+    }
+
+    /// Loads `<name>.subject`, `<name>.html` and `<name>.txt` files from `dir`, overriding the
+    /// built-in template of the same name
+    ///
+    /// When both bodies exist the email is sent with the HTML body and the plaintext one as its
+    /// alternative. A missing subject or body falls back to the built-in template's.
+    #[allow(dead_code)]
+    pub fn with_template_dir(mut self, dir: PathBuf) -> Result<Self, String> {
+        self.templates = Self::load_template_dir(&dir)?;
+        Ok(self)
+    }
+
+    /// Replace the retry policy read from EMAIL_MAX_RETRIES and EMAIL_RETRY_DELAY_MS
+    #[allow(dead_code)]
+    pub fn with_retry_policy(mut self, retry_policy: EmailRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
     }
 
     /// Create a new email service with custom configuration
@@ -118,33 +444,57 @@ impl EmailService {
     pub fn new(config: EmailConfig) -> Result<Self, String> {
         let transport = Self::create_transport(&config)?;
 
-        Ok(Self { config, transport })
+        Ok(Self {
+            config,
+            transport,
+            templates: HashMap::new(),
+            retry_policy: EmailRetryPolicy::from_env(),
+        })
     }
 
-    /// Create SMTP transport based on configuration
+    /// Create the SMTP transport or SendGrid API client based on configuration
     #[allow(dead_code)]
-    fn create_transport(config: &EmailConfig) -> Result<SmtpTransport, String> {
-        if config.service_type.to_string().to_lowercase() != "smtp" {
-            return Err(format!(
-                "Unsupported email service type: {}",
-                config.service_type
-            ));
+    fn create_transport(config: &EmailConfig) -> Result<EmailTransport, String> {
+        match config.service_type {
+            EmailServiceType::SMTP => Self::create_smtp_transport(config).map(EmailTransport::Smtp),
+            #[cfg(feature = "sendgrid")]
+            EmailServiceType::SendGrid => {
+                let api_key = config
+                    .sendgrid_api_key
+                    .clone()
+                    .ok_or_else(|| "SENDGRID_API_KEY is required for SendGrid".to_string())?;
+                Ok(EmailTransport::SendGrid(sendgrid::SendGridClient::new(
+                    api_key,
+                )))
+            }
+            #[cfg(not(feature = "sendgrid"))]
+            EmailServiceType::SendGrid => Err(
+                "EMAIL_SERVICE_TYPE=sendgrid requires building with --features sendgrid"
+                    .to_string(),
+            ),
+            EmailServiceType::Log => Ok(EmailTransport::Log),
         }
+    }
 
+    /// Create SMTP transport based on configuration
+    #[allow(dead_code)]
+    fn create_smtp_transport(
+        config: &EmailConfig,
+    ) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
         let credentials =
             Credentials::new(config.smtp_username.clone(), config.smtp_password.clone());
 
         // Configure transport with proper TLS settings
         let transport = if config.smtp_port == 465 {
             // Port 465 uses implicit TLS (SSL)
-            SmtpTransport::relay(&config.smtp_host)
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&config.smtp_host)
                 .map_err(|e| format!("Failed to create SMTP relay: {}", e))?
                 .port(config.smtp_port)
                 .credentials(credentials)
                 .build()
         } else {
             // Port 587 and others use STARTTLS
-            SmtpTransport::starttls_relay(&config.smtp_host)
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
                 .map_err(|e| format!("Failed to create SMTP STARTTLS relay: {}", e))?
                 .port(config.smtp_port)
                 .credentials(credentials)
@@ -158,12 +508,11 @@ impl EmailService {
     #[allow(dead_code)]
     pub async fn send_template_email(
         &self,
-        to_email: &str,
-        to_name: Option<&str>,
+        recipients: &EmailRecipients,
         template_name: &str,
         variables: Option<HashMap<String, String>>,
     ) -> EmailResult {
-        let template = match Self::get_email_template(template_name) {
+        let template = match self.template(template_name) {
             Ok(template) => template,
             Err(e) => {
                 error!("Failed to load email template '{}': {}", template_name, e);
@@ -171,49 +520,236 @@ impl EmailService {
             }
         };
 
-        self.send_email(to_email, to_name, &template, variables)
-            .await
+        self.send_email(recipients, &template, variables).await
+    }
+
+    /// Send a templated email to each `(email, name, variables)` recipient over the shared
+    /// transport, returning each attempted recipient's address and result in order
+    #[allow(dead_code)]
+    pub async fn send_bulk_template_email(
+        &self,
+        recipients: Vec<(String, Option<String>, HashMap<String, String>)>,
+        template_name: &str,
+        mode: BulkSendMode,
+    ) -> Vec<(String, EmailResult)> {
+        let template = match self.template(template_name) {
+            Ok(template) => template,
+            Err(e) => {
+                error!("Failed to load email template '{}': {}", template_name, e);
+                let attempted = match mode {
+                    BulkSendMode::ContinueOnFailure => recipients.len(),
+                    BulkSendMode::StopOnFailure => 1,
+                };
+                return recipients
+                    .into_iter()
+                    .take(attempted)
+                    .map(|(email, _, _)| {
+                        (email, EmailResult::Failed(format!("Template error: {}", e)))
+                    })
+                    .collect();
+            }
+        };
+
+        let mut results = Vec::with_capacity(recipients.len());
+        for (email, name, variables) in recipients {
+            let result = self
+                .send_email(
+                    &EmailRecipients::to(&email, name.as_deref()),
+                    &template,
+                    Some(variables),
+                )
+                .await;
+            let failed = matches!(result, EmailResult::Failed(_));
+            results.push((email, result));
+
+            if failed && mode == BulkSendMode::StopOnFailure {
+                break;
+            }
+        }
+
+        let sent = results
+            .iter()
+            .filter(|(_, result)| matches!(result, EmailResult::Success))
+            .count();
+        info!(
+            "Bulk '{}' email sent to {} of {} recipients",
+            template_name,
+            sent,
+            results.len()
+        );
+
+        results
     }
 
     /// Send an email with a custom template
     #[allow(dead_code)]
     pub async fn send_email(
         &self,
-        to_email: &str,
-        to_name: Option<&str>,
+        recipients: &EmailRecipients,
         template: &EmailTemplate,
         variables: Option<HashMap<String, String>>,
+    ) -> EmailResult {
+        self.send_email_with_attachments(recipients, template, variables, &[])
+            .await
+    }
+
+    /// Send an email with a custom template and attached files, failing if the attachments
+    /// exceed EMAIL_MAX_ATTACHMENT_BYTES in total
+    #[allow(dead_code)]
+    pub async fn send_email_with_attachments(
+        &self,
+        recipients: &EmailRecipients,
+        template: &EmailTemplate,
+        variables: Option<HashMap<String, String>>,
+        attachments: &[EmailAttachment],
     ) -> EmailResult {
         // Process template variables
         let processed_subject = Self::process_template_variables(&template.subject, &variables);
-        let processed_body = Self::process_template_variables(&template.body, &variables);
-
-        // Build the email message
-        let message_result = self.build_message(
-            to_email,
-            to_name,
-            &processed_subject,
-            &processed_body,
-            &template.content_type,
-        );
+        let body = match (&template.content_type, &template.text_body) {
+            (EmailContentType::Html, Some(text)) => EmailBody::Alternative {
+                text: Self::process_template_variables(text, &variables),
+                html: Self::process_template_variables(&template.body, &variables),
+            },
+            _ => EmailBody::Single {
+                content: Self::process_template_variables(&template.body, &variables),
+                content_type: template.content_type.clone(),
+            },
+        };
 
-        let message = match message_result {
-            Ok(msg) => msg,
-            Err(e) => {
-                error!("Failed to build email message: {}", e);
-                return EmailResult::Failed(format!("Message build error: {}", e));
-            }
+        self.deliver(recipients, &processed_subject, &body, attachments)
+            .await
+    }
+
+    /// Send an email with both an HTML and a plaintext part, for clients that don't render HTML
+    #[allow(dead_code)]
+    pub async fn send_multipart_email(
+        &self,
+        recipients: &EmailRecipients,
+        subject: &str,
+        html: &str,
+        text: &str,
+        variables: Option<HashMap<String, String>>,
+    ) -> EmailResult {
+        let processed_subject = Self::process_template_variables(subject, &variables);
+        let body = EmailBody::Alternative {
+            text: Self::process_template_variables(text, &variables),
+            html: Self::process_template_variables(html, &variables),
         };
 
-        // Send the email
-        match self.transport.send(&message) {
-            Ok(_) => {
+        self.deliver(recipients, &processed_subject, &body, &[])
+            .await
+    }
+
+    /// Sends the email and reports the outcome, oversized attachments fail before sending
+    async fn deliver(
+        &self,
+        recipients: &EmailRecipients,
+        subject: &str,
+        body: &EmailBody,
+        attachments: &[EmailAttachment],
+    ) -> EmailResult {
+        let to_email = &recipients.to_email;
+        let attachment_bytes: usize = attachments
+            .iter()
+            .map(|attachment| attachment.bytes.len())
+            .sum();
+        let send_result = if attachment_bytes > self.config.max_attachment_bytes {
+            Err(DeliveryError::Permanent(format!(
+                "Attachments total {} bytes, over the {} byte limit (EMAIL_MAX_ATTACHMENT_BYTES)",
+                attachment_bytes, self.config.max_attachment_bytes
+            )))
+        } else {
+            self.transmit(recipients, subject, body, attachments).await
+        };
+
+        // Report the outcome
+        match send_result {
+            Ok(()) => {
                 info!("Email sent successfully to: {}", to_email);
                 EmailResult::Success
             }
             Err(e) => {
+                // Counted by the caller, which knows whether a request is waiting on it
                 error!("Failed to send email to {}: {}", to_email, e);
-                EmailResult::Failed(format!("SMTP error: {}", e))
+                EmailResult::Failed(e.to_string())
+            }
+        }
+    }
+
+    /// Sends through the configured transport, retrying transient failures
+    async fn transmit(
+        &self,
+        recipients: &EmailRecipients,
+        subject: &str,
+        body: &EmailBody,
+        attachments: &[EmailAttachment],
+    ) -> Result<(), DeliveryError> {
+        match &self.transport {
+            EmailTransport::Smtp(transport) => {
+                match self.build_message(recipients, subject, body, attachments) {
+                    Ok(message) => {
+                        with_retries(&self.retry_policy, || {
+                            let message = message.clone();
+                            async move {
+                                transport.send(message).await.map(|_| ()).map_err(|e| {
+                                    let error = format!("SMTP error: {}", e);
+                                    if e.is_permanent() {
+                                        DeliveryError::Permanent(error)
+                                    } else {
+                                        DeliveryError::Transient(error)
+                                    }
+                                })
+                            }
+                        })
+                        .await
+                    }
+                    Err(e) => Err(DeliveryError::Permanent(format!(
+                        "Message build error: {}",
+                        e
+                    ))),
+                }
+            }
+            #[cfg(feature = "sendgrid")]
+            EmailTransport::SendGrid(client) => {
+                match sendgrid::mail_send_payload(
+                    &self.config,
+                    recipients,
+                    subject,
+                    body,
+                    attachments,
+                ) {
+                    Ok(payload) => {
+                        let payload = &payload;
+                        with_retries(
+                            &self.retry_policy,
+                            || async move { client.send(payload).await },
+                        )
+                        .await
+                    }
+                    Err(e) => Err(DeliveryError::Permanent(format!(
+                        "Message build error: {}",
+                        e
+                    ))),
+                }
+            }
+            EmailTransport::Log => {
+                // Built anyway, so bad addresses fail the same way they would over SMTP
+                self.build_message(recipients, subject, body, attachments)
+                    .map_err(|e| DeliveryError::Permanent(format!("Message build error: {}", e)))?;
+                info!(
+                    "Email to {} not sent (EMAIL_SERVICE_TYPE=log): {}",
+                    recipients.to_email, subject
+                );
+                #[cfg(test)]
+                LOGGED_EMAILS.lock().unwrap().push(LoggedEmail {
+                    to: recipients.to_email.clone(),
+                    subject: subject.to_string(),
+                    text: match body {
+                        EmailBody::Single { content, .. } => content.clone(),
+                        EmailBody::Alternative { text, .. } => text.clone(),
+                    },
+                });
+                Ok(())
             }
         }
     }
@@ -222,11 +758,10 @@ impl EmailService {
     #[allow(dead_code)]
     fn build_message(
         &self,
-        to_email: &str,
-        to_name: Option<&str>,
+        recipients: &EmailRecipients,
         subject: &str,
-        body: &str,
-        content_type: &EmailContentType,
+        body: &EmailBody,
+        attachments: &[EmailAttachment],
     ) -> Result<Message, String> {
         // Parse email addresses
         let from_mailbox = Mailbox::new(
@@ -238,8 +773,9 @@ impl EmailService {
         );
 
         let to_mailbox = Mailbox::new(
-            to_name.map(|s| s.to_owned()),
-            to_email
+            recipients.to_name.clone(),
+            recipients
+                .to_email
                 .parse()
                 .map_err(|e| format!("Invalid to email address: {}", e))?,
         );
@@ -250,6 +786,13 @@ impl EmailService {
             .to(to_mailbox)
             .subject(subject);
 
+        for cc in recipients.cc_mailboxes()? {
+            builder = builder.cc(cc);
+        }
+        for bcc in recipients.bcc_mailboxes()? {
+            builder = builder.bcc(bcc);
+        }
+
         // Add reply-to if configured
         if let (Some(reply_email), Some(reply_name)) =
             (&self.config.reply_to_email, &self.config.reply_to_name)
@@ -272,18 +815,69 @@ impl EmailService {
         }
 
         // Set content type and body
-        let message = match content_type {
-            EmailContentType::Text => builder
+        let message = match (body, attachments.is_empty()) {
+            (
+                EmailBody::Single {
+                    content,
+                    content_type: EmailContentType::Text,
+                },
+                true,
+            ) => builder
                 .header(ContentType::TEXT_PLAIN)
-                .body(body.to_string()),
-            EmailContentType::Html => builder
-                .header(ContentType::TEXT_HTML)
-                .body(body.to_string()),
+                .body(content.clone()),
+            (
+                EmailBody::Single {
+                    content,
+                    content_type: EmailContentType::Html,
+                },
+                true,
+            ) => builder.header(ContentType::TEXT_HTML).body(content.clone()),
+            (EmailBody::Alternative { text, html }, true) => {
+                builder.multipart(Self::alternative_part(text, html))
+            }
+            // The body comes first, followed by one part per attachment
+            (body, false) => {
+                let mut mixed = match body {
+                    EmailBody::Single {
+                        content,
+                        content_type: EmailContentType::Text,
+                    } => MultiPart::mixed().singlepart(SinglePart::plain(content.clone())),
+                    EmailBody::Single {
+                        content,
+                        content_type: EmailContentType::Html,
+                    } => MultiPart::mixed().singlepart(SinglePart::html(content.clone())),
+                    EmailBody::Alternative { text, html } => {
+                        MultiPart::mixed().multipart(Self::alternative_part(text, html))
+                    }
+                };
+                for attachment in attachments {
+                    let content_type =
+                        ContentType::parse(&attachment.content_type).map_err(|e| {
+                            format!(
+                                "Invalid content type '{}' for attachment {}: {}",
+                                attachment.content_type, attachment.filename, e
+                            )
+                        })?;
+                    mixed = mixed.singlepart(
+                        Attachment::new(attachment.filename.clone())
+                            .body(attachment.bytes.clone(), content_type),
+                    );
+                }
+                builder.multipart(mixed)
+            }
         };
 
         message.map_err(|e| format!("Failed to build message: {}", e))
     }
 
+    /// Plaintext and HTML alternatives, plaintext first since clients pick the last alternative
+    /// they can display
+    fn alternative_part(text: &str, html: &str) -> MultiPart {
+        MultiPart::alternative()
+            .singlepart(SinglePart::plain(text.to_string()))
+            .singlepart(SinglePart::html(html.to_string()))
+    }
+
     /// Process template variables in content
     #[allow(dead_code)]
     fn process_template_variables(
@@ -302,29 +896,124 @@ impl EmailService {
         }
     }
 
+    /// Template by name, a loaded template file before the built-in one
+    #[allow(dead_code)]
+    fn template(&self, template_name: &str) -> Result<EmailTemplate, String> {
+        match self.templates.get(template_name) {
+            Some(template) => Ok(template.clone()),
+            None => Self::get_email_template(template_name),
+        }
+    }
+
+    /// Reads the templates in a template directory, keyed by file stem
+    fn load_template_dir(dir: &Path) -> Result<HashMap<String, EmailTemplate>, String> {
+        let read_error = |e: std::io::Error| {
+            format!("Failed to read email templates in {}: {}", dir.display(), e)
+        };
+
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(read_error)? {
+            let path = entry.map_err(read_error)?.path();
+            let is_template_file = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| matches!(extension, "subject" | "html" | "txt"));
+            if let (true, Some(name)) = (
+                is_template_file,
+                path.file_stem().and_then(|stem| stem.to_str()),
+            ) {
+                if !names.iter().any(|known| known == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+
+        let mut templates = HashMap::new();
+        for name in names {
+            let read = |extension: &str| -> Result<Option<String>, String> {
+                let path = dir.join(format!("{}.{}", name, extension));
+                match std::fs::read_to_string(&path) {
+                    Ok(content) => Ok(Some(content)),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(read_error(e)),
+                }
+            };
+            let built_in = Self::get_email_template(&name).ok();
+
+            let subject = match read("subject")? {
+                Some(subject) => subject.trim().to_string(),
+                None => match &built_in {
+                    Some(template) => template.subject.clone(),
+                    None => {
+                        warn!(
+                            "Skipping email template '{}', it has no .subject file",
+                            name
+                        );
+                        continue;
+                    }
+                },
+            };
+
+            let (body, content_type, text_body) = match (read("html")?, read("txt")?) {
+                (Some(html), text) => (html, EmailContentType::Html, text),
+                (None, Some(text)) => (text, EmailContentType::Text, None),
+                (None, None) => match &built_in {
+                    Some(template) => (
+                        template.body.clone(),
+                        template.content_type.clone(),
+                        template.text_body.clone(),
+                    ),
+                    None => {
+                        warn!(
+                            "Skipping email template '{}', it has no .html or .txt file",
+                            name
+                        );
+                        continue;
+                    }
+                },
+            };
+
+            templates.insert(
+                name,
+                EmailTemplate {
+                    subject,
+                    body,
+                    content_type,
+                    text_body,
+                },
+            );
+        }
+
+        Ok(templates)
+    }
+
     /// Get a predefined email template by name
     #[allow(dead_code)]
     fn get_email_template(template_name: &str) -> Result<EmailTemplate, String> {
         match template_name {
             "welcome" => Ok(EmailTemplate {
-                subject: "Welcome to {{app_name}}!".to_string(),
-                body: "Hello {{user_name}},\n\nWelcome to {{app_name}}! We're excited to have you on board.\n\nBest regards,\nThe {{app_name}} Team".to_string(),
+                subject: "Welcome to {{{app_name}}}!".to_string(),
+                body: "Hello {{{user_name}}},\n\nWelcome to {{{app_name}}}! We're excited to have you on board.\n\nBest regards,\nThe {{{app_name}}} Team".to_string(),
                 content_type: EmailContentType::Text,
+                text_body: None,
             }),
             "password_reset" => Ok(EmailTemplate {
                 subject: "Password Reset Request".to_string(),
-                body: "Hello {{user_name}},\n\nYou have requested a password reset. Click the link below to reset your password:\n\n{{reset_link}}\n\nIf you didn't request this, please ignore this email.\n\nBest regards,\nThe {{app_name}} Team".to_string(),
+                body: "Hello {{{user_name}}},\n\nYou have requested a password reset. Click the link below to reset your password:\n\n{{{reset_link}}}\n\nIf you didn't request this, please ignore this email.\n\nBest regards,\nThe {{{app_name}}} Team".to_string(),
                 content_type: EmailContentType::Text,
+                text_body: None,
             }),
             "verification" => Ok(EmailTemplate {
                 subject: "Please verify your email address".to_string(),
-                body: "Hello {{user_name}},\n\nPlease click the link below to verify your email address:\n\n{{verification_link}}\n\nBest regards,\nThe {{app_name}} Team".to_string(),
+                body: "Hello {{{user_name}}},\n\nPlease click the link below to verify your email address:\n\n{{{verification_link}}}\n\nBest regards,\nThe {{{app_name}}} Team".to_string(),
                 content_type: EmailContentType::Text,
+                text_body: None,
             }),
             "notification" => Ok(EmailTemplate {
-                subject: "{{subject}}".to_string(),
-                body: "{{message}}".to_string(),
+                subject: "{{{subject}}}".to_string(),
+                body: "{{{message}}}".to_string(),
                 content_type: EmailContentType::Text,
+                text_body: None,
             }),
             _ => Err(format!("Unknown email template: {}", template_name)),
         }
@@ -340,11 +1029,11 @@ impl EmailService {
             subject: "Email Service Test".to_string(),
             body: "This is a test email to verify the email service configuration.".to_string(),
             content_type: EmailContentType::Text,
+            text_body: None,
         };
 
         self.send_email(
-            &self.config.from_email,
-            Some("Test Recipient"),
+            &EmailRecipients::to(&self.config.from_email, Some("Test Recipient")),
             &test_template,
             None,
         )
@@ -356,24 +1045,43 @@ impl EmailConfig {
     /// Load email configuration from environment variables
     #[allow(dead_code)]
     pub fn from_env() -> Result<Self, String> {
-        let service_type = env::var("EMAIL_SERVICE_TYPE").unwrap_or_else(|_| "smtp".to_string());
+        // Tests never reach a mail server unless they configure one
+        let default_service_type = if cfg!(test) { "log" } else { "smtp" };
+        let service_type = EmailServiceType::from_str(
+            &env::var("EMAIL_SERVICE_TYPE").unwrap_or_else(|_| default_service_type.to_string()),
+        )?;
+        let uses_smtp = service_type == EmailServiceType::SMTP;
+
+        // SMTP settings are only required when sending over SMTP
+        let smtp_setting = |name: &str, value: Option<String>| match value {
+            Some(value) => Ok(value),
+            None if !uses_smtp => Ok(String::new()),
+            None => Err(format!("{} environment variable is required", name)),
+        };
 
-        let smtp_host = env::var("EMAIL_SMTP_HOST")
-            .map_err(|_| "EMAIL_SMTP_HOST environment variable is required".to_string())?;
+        let smtp_host = smtp_setting("EMAIL_SMTP_HOST", env::var("EMAIL_SMTP_HOST").ok())?;
 
         let smtp_port = env::var("EMAIL_SMTP_PORT")
             .unwrap_or_else(|_| "587".to_string())
             .parse::<u16>()
             .map_err(|_| "EMAIL_SMTP_PORT must be a valid port number".to_string())?;
 
-        let smtp_username = env::var("EMAIL_SMTP_USERNAME")
-            .map_err(|_| "EMAIL_SMTP_USERNAME environment variable is required".to_string())?;
+        let smtp_username =
+            smtp_setting("EMAIL_SMTP_USERNAME", env::var("EMAIL_SMTP_USERNAME").ok())?;
 
-        let smtp_password = env::var("EMAIL_SMTP_PASSWORD")
-            .map_err(|_| "EMAIL_SMTP_PASSWORD environment variable is required".to_string())?;
+        let smtp_password =
+            smtp_setting("EMAIL_SMTP_PASSWORD", Secrets::get("EMAIL_SMTP_PASSWORD"))?;
 
-        let from_email = env::var("EMAIL_FROM_ADDRESS")
-            .map_err(|_| "EMAIL_FROM_ADDRESS environment variable is required".to_string())?;
+        let sendgrid_api_key = Secrets::get("SENDGRID_API_KEY");
+        if service_type == EmailServiceType::SendGrid && sendgrid_api_key.is_none() {
+            return Err("SENDGRID_API_KEY environment variable is required".to_string());
+        }
+
+        let from_email = match env::var("EMAIL_FROM_ADDRESS") {
+            Ok(from_email) => from_email,
+            Err(_) if service_type == EmailServiceType::Log => "noreply@localhost".to_string(),
+            Err(_) => return Err("EMAIL_FROM_ADDRESS environment variable is required".to_string()),
+        };
 
         let from_name =
             env::var("EMAIL_FROM_NAME").unwrap_or_else(|_| "Rext Application".to_string());
@@ -381,28 +1089,208 @@ impl EmailConfig {
         let reply_to_email = env::var("EMAIL_REPLY_TO_ADDRESS").ok();
         let reply_to_name = env::var("EMAIL_REPLY_TO_NAME").ok();
 
-        // Log configuration (without sensitive data)
-        info!("Email service configured:");
-        info!("  Service Type: {}", service_type);
-        info!("  SMTP Host: {}", smtp_host);
-        info!("  SMTP Port: {}", smtp_port);
-        info!("  From Email: {}", from_email);
-        info!("  From Name: {}", from_name);
-        if reply_to_email.is_some() {
-            info!("  Reply-To configured: Yes");
-        }
+        let max_attachment_bytes = env::var("EMAIL_MAX_ATTACHMENT_BYTES")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_EMAIL_MAX_ATTACHMENT_BYTES);
 
-        Ok(Self {
-            service_type: EmailServiceType::from_str(&service_type).map_err(|e| e.to_string())?,
+        let config = Self {
+            service_type,
             smtp_host,
             smtp_port,
             smtp_username,
             smtp_password,
+            sendgrid_api_key,
             from_email,
             from_name,
             reply_to_email,
             reply_to_name,
-        })
+            max_attachment_bytes,
+        };
+        config.validate()?;
+
+        // Log configuration (without sensitive data)
+        info!("Email service configured:");
+        info!("  Service Type: {}", config.service_type);
+        if uses_smtp {
+            info!("  SMTP Host: {}", config.smtp_host);
+            info!("  SMTP Port: {}", config.smtp_port);
+        }
+        info!("  From Email: {}", config.from_email);
+        info!("  From Name: {}", config.from_name);
+        if config.reply_to_email.is_some() {
+            info!("  Reply-To configured: Yes");
+        }
+
+        Ok(config)
+    }
+
+    /// Checks the settings that would otherwise only fail at send time
+    pub fn validate(&self) -> Result<(), String> {
+        if self.service_type == EmailServiceType::SMTP && self.smtp_port == 0 {
+            return Err("EMAIL_SMTP_PORT must be between 1 and 65535".to_string());
+        }
+
+        self.from_email.parse::<Address>().map_err(|e| {
+            format!(
+                "EMAIL_FROM_ADDRESS '{}' is not a valid email address: {}",
+                self.from_email, e
+            )
+        })?;
+
+        if let Some(reply_to_email) = &self.reply_to_email {
+            reply_to_email.parse::<Address>().map_err(|e| {
+                format!(
+                    "EMAIL_REPLY_TO_ADDRESS '{}' is not a valid email address: {}",
+                    reply_to_email, e
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// SendGrid v3 Mail Send API (requires building with --features sendgrid)
+#[cfg(feature = "sendgrid")]
+pub mod sendgrid {
+    use super::*;
+    use crate::infrastructure::trace_context::TraceContext;
+    use axum::http::HeaderMap;
+    use base64::Engine;
+
+    /// Mail Send endpoint
+    pub const SENDGRID_MAIL_SEND_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+    /// Sends mail through SendGrid's HTTPS API
+    pub struct SendGridClient {
+        client: reqwest::Client,
+        api_key: String,
+    }
+
+    impl SendGridClient {
+        pub fn new(api_key: String) -> Self {
+            Self {
+                client: reqwest::Client::new(),
+                api_key,
+            }
+        }
+
+        /// Posts a Mail Send payload, SendGrid answers 202 when the mail is queued
+        ///
+        /// Rejected requests fail permanently, rate limits, server and connection errors are
+        /// transient.
+        pub async fn send(&self, payload: &serde_json::Value) -> Result<(), DeliveryError> {
+            let mut trace_headers = HeaderMap::new();
+            TraceContext::inject_current(&mut trace_headers);
+
+            self.client
+                .post(SENDGRID_MAIL_SEND_URL)
+                .headers(trace_headers)
+                .bearer_auth(&self.api_key)
+                .json(payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| {
+                    let error = format!("SendGrid error: {}", e);
+                    match e.status() {
+                        Some(status)
+                            if status.is_client_error()
+                                && status != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+                        {
+                            DeliveryError::Permanent(error)
+                        }
+                        _ => DeliveryError::Transient(error),
+                    }
+                })
+        }
+    }
+
+    /// Mail Send content parts, SendGrid requires text/plain before text/html
+    fn content(body: &EmailBody) -> serde_json::Value {
+        match body {
+            EmailBody::Single {
+                content,
+                content_type,
+            } => serde_json::json!([{
+                "type": match content_type {
+                    EmailContentType::Text => "text/plain",
+                    EmailContentType::Html => "text/html",
+                },
+                "value": content,
+            }]),
+            EmailBody::Alternative { text, html } => serde_json::json!([
+                { "type": "text/plain", "value": text },
+                { "type": "text/html", "value": html },
+            ]),
+        }
+    }
+
+    /// Mail Send address object
+    fn address(mailbox: &Mailbox) -> serde_json::Value {
+        let mut address = serde_json::json!({ "email": mailbox.email.to_string() });
+        if let Some(name) = &mailbox.name {
+            address["name"] = name.clone().into();
+        }
+        address
+    }
+
+    /// Mail Send request body for one recipient and their copies
+    pub fn mail_send_payload(
+        config: &EmailConfig,
+        recipients: &EmailRecipients,
+        subject: &str,
+        body: &EmailBody,
+        attachments: &[EmailAttachment],
+    ) -> Result<serde_json::Value, String> {
+        let mut to = serde_json::json!({ "email": recipients.to_email });
+        if let Some(name) = &recipients.to_name {
+            to["name"] = name.clone().into();
+        }
+
+        let mut personalization = serde_json::json!({ "to": [to] });
+        let cc = recipients.cc_mailboxes()?;
+        if !cc.is_empty() {
+            personalization["cc"] = cc.iter().map(address).collect();
+        }
+        let bcc = recipients.bcc_mailboxes()?;
+        if !bcc.is_empty() {
+            personalization["bcc"] = bcc.iter().map(address).collect();
+        }
+
+        let mut payload = serde_json::json!({
+            "personalizations": [personalization],
+            "from": { "email": config.from_email, "name": config.from_name },
+            "subject": subject,
+            "content": content(body),
+        });
+
+        if let Some(reply_email) = &config.reply_to_email {
+            let mut reply_to = serde_json::json!({ "email": reply_email });
+            if let Some(reply_name) = &config.reply_to_name {
+                reply_to["name"] = reply_name.clone().into();
+            }
+            payload["reply_to"] = reply_to;
+        }
+
+        if !attachments.is_empty() {
+            payload["attachments"] = attachments
+                .iter()
+                .map(|attachment| {
+                    serde_json::json!({
+                        "content": base64::engine::general_purpose::STANDARD
+                            .encode(&attachment.bytes),
+                        "type": attachment.content_type,
+                        "filename": attachment.filename,
+                        "disposition": "attachment",
+                    })
+                })
+                .collect();
+        }
+
+        Ok(payload)
     }
 }
 
@@ -420,8 +1308,12 @@ impl EmailService {
         variables.insert("user_name".to_string(), user_name.to_string());
         variables.insert("app_name".to_string(), app_name.to_string());
 
-        self.send_template_email(user_email, Some(user_name), "welcome", Some(variables))
-            .await
+        self.send_template_email(
+            &EmailRecipients::to(user_email, Some(user_name)),
+            "welcome",
+            Some(variables),
+        )
+        .await
     }
 
     /// Send a password reset email
@@ -439,8 +1331,7 @@ impl EmailService {
         variables.insert("app_name".to_string(), app_name.to_string());
 
         self.send_template_email(
-            user_email,
-            Some(user_name),
+            &EmailRecipients::to(user_email, Some(user_name)),
             "password_reset",
             Some(variables),
         )
@@ -464,8 +1355,12 @@ impl EmailService {
         );
         variables.insert("app_name".to_string(), app_name.to_string());
 
-        self.send_template_email(user_email, Some(user_name), "verification", Some(variables))
-            .await
+        self.send_template_email(
+            &EmailRecipients::to(user_email, Some(user_name)),
+            "verification",
+            Some(variables),
+        )
+        .await
     }
 
     /// Send a notification email
@@ -481,7 +1376,650 @@ impl EmailService {
         variables.insert("subject".to_string(), subject.to_string());
         variables.insert("message".to_string(), message.to_string());
 
-        self.send_template_email(user_email, user_name, "notification", Some(variables))
+        self.send_template_email(
+            &EmailRecipients::to(user_email, user_name),
+            "notification",
+            Some(variables),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::temp_dir;
+
+    fn smtp_config() -> EmailConfig {
+        EmailConfig {
+            service_type: EmailServiceType::SMTP,
+            smtp_host: "localhost".to_string(),
+            smtp_port: 587,
+            smtp_username: "user".to_string(),
+            smtp_password: "password".to_string(),
+            sendgrid_api_key: None,
+            from_email: "noreply@example.com".to_string(),
+            from_name: "Example".to_string(),
+            reply_to_email: None,
+            reply_to_name: None,
+            max_attachment_bytes: DEFAULT_EMAIL_MAX_ATTACHMENT_BYTES,
+        }
+    }
+
+    /// One-connection SMTP server without TLS or AUTH, passes on the DATA of the first message
+    #[cfg(feature = "smtp-integration-tests")]
+    async fn spawn_mock_smtp_server() -> (u16, tokio::sync::oneshot::Receiver<String>) {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (data_tx, data_rx) = tokio::sync::oneshot::channel();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut data_tx = Some(data_tx);
+            writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+
+            while let Ok(Some(line)) = lines.next_line().await {
+                let command = line.to_uppercase();
+                let reply: &[u8] = if command.starts_with("DATA") {
+                    writer
+                        .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                        .await
+                        .unwrap();
+                    let mut data = String::new();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if line == "." {
+                            break;
+                        }
+                        data.push_str(&line);
+                        data.push('\n');
+                    }
+                    if let Some(data_tx) = data_tx.take() {
+                        let _ = data_tx.send(data);
+                    }
+                    b"250 Queued\r\n"
+                } else if command.starts_with("QUIT") {
+                    let _ = writer.write_all(b"221 Bye\r\n").await;
+                    break;
+                } else {
+                    // EHLO, MAIL FROM, RCPT TO and RSET
+                    b"250 OK\r\n"
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+        });
+
+        (port, data_rx)
+    }
+
+    #[cfg(feature = "smtp-integration-tests")]
+    #[tokio::test]
+    async fn test_send_email_over_async_smtp() {
+        let (port, data) = spawn_mock_smtp_server().await;
+        let service = EmailService {
+            config: smtp_config(),
+            // Plaintext, the mock server doesn't speak TLS
+            transport: EmailTransport::Smtp(
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+                    .port(port)
+                    .build(),
+            ),
+            templates: HashMap::new(),
+            retry_policy: EmailRetryPolicy::default(),
+        };
+
+        let result = service
+            .send_welcome_email("user@example.com", "User", "Example App")
+            .await;
+        assert!(matches!(result, EmailResult::Success), "{:?}", result);
+
+        let data = tokio::time::timeout(std::time::Duration::from_secs(5), data)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(data.contains("Subject: Welcome to Example App!"));
+        assert!(data.contains("user@example.com"));
+        assert!(data.contains("Hello User,"));
+    }
+
+    fn bulk_recipients(emails: &[&str]) -> Vec<(String, Option<String>, HashMap<String, String>)> {
+        emails
+            .iter()
+            .map(|email| {
+                let variables = HashMap::from([
+                    ("user_name".to_string(), "User".to_string()),
+                    ("app_name".to_string(), "Example App".to_string()),
+                ]);
+                (email.to_string(), Some("User".to_string()), variables)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_bulk_send_stops_on_first_failure() {
+        let service = EmailService::new(smtp_config()).unwrap();
+
+        let results = service
+            .send_bulk_template_email(
+                bulk_recipients(&["not-an-address", "user@example.com"]),
+                "welcome",
+                BulkSendMode::StopOnFailure,
+            )
+            .await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "not-an-address");
+        assert!(
+            matches!(&results[0].1, EmailResult::Failed(e) if e.contains("Invalid to email address"))
+        );
+    }
+
+    #[cfg(feature = "smtp-integration-tests")]
+    #[tokio::test]
+    async fn test_bulk_send_reports_partial_failure() {
+        let (port, data) = spawn_mock_smtp_server().await;
+        let service = EmailService {
+            config: smtp_config(),
+            transport: EmailTransport::Smtp(
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+                    .port(port)
+                    .build(),
+            ),
+            templates: HashMap::new(),
+            retry_policy: EmailRetryPolicy::default(),
+        };
+
+        let results = service
+            .send_bulk_template_email(
+                bulk_recipients(&["user@example.com", "not-an-address"]),
+                "welcome",
+                BulkSendMode::ContinueOnFailure,
+            )
+            .await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "user@example.com");
+        assert!(
+            matches!(results[0].1, EmailResult::Success),
+            "{:?}",
+            results[0].1
+        );
+        assert_eq!(results[1].0, "not-an-address");
+        assert!(matches!(results[1].1, EmailResult::Failed(_)));
+
+        let data = tokio::time::timeout(std::time::Duration::from_secs(5), data)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(data.contains("Subject: Welcome to Example App!"));
+    }
+
+    // The SMTP transport spawns its connection pool, which needs a runtime
+    #[tokio::test]
+    async fn test_multipart_message_has_text_and_html_parts() {
+        let service = EmailService::new(smtp_config()).unwrap();
+        let body = EmailBody::Alternative {
+            text: "Plain hello".to_string(),
+            html: "<p>HTML hello</p>".to_string(),
+        };
+
+        let message = service
+            .build_message(
+                &EmailRecipients::to("user@example.com", Some("User")),
+                "Hello",
+                &body,
+                &[],
+            )
+            .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("multipart/alternative"));
+        assert!(formatted.contains("text/plain"));
+        assert!(formatted.contains("Plain hello"));
+        assert!(formatted.contains("text/html"));
+        assert!(formatted.contains("<p>HTML hello</p>"));
+        assert!(formatted.find("text/plain").unwrap() < formatted.find("text/html").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_single_part_message_has_one_content_type() {
+        let service = EmailService::new(smtp_config()).unwrap();
+        let body = EmailBody::Single {
+            content: "Plain hello".to_string(),
+            content_type: EmailContentType::Text,
+        };
+
+        let message = service
+            .build_message(
+                &EmailRecipients::to("user@example.com", None),
+                "Hello",
+                &body,
+                &[],
+            )
+            .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("text/plain"));
+        assert!(!formatted.contains("multipart"));
+        assert!(!formatted.contains("text/html"));
+    }
+
+    #[tokio::test]
+    async fn test_message_copies_cc_and_bcc() {
+        let service = EmailService::new(smtp_config()).unwrap();
+        let recipients = EmailRecipients::to("user@example.com", None)
+            .cc("Manager <manager@example.com>")
+            .cc("team@example.com")
+            .bcc("audit@example.com");
+        let body = EmailBody::Single {
+            content: "Hello".to_string(),
+            content_type: EmailContentType::Text,
+        };
+
+        let message = service
+            .build_message(&recipients, "Hello", &body, &[])
+            .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("Cc: Manager <manager@example.com>, team@example.com"));
+        // BCC addresses are only in the envelope, never in the headers
+        assert!(!formatted.contains("audit@example.com"));
+        let mut envelope: Vec<String> = message
+            .envelope()
+            .to()
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        envelope.sort();
+        assert_eq!(
+            envelope,
+            vec![
+                "audit@example.com",
+                "manager@example.com",
+                "team@example.com",
+                "user@example.com"
+            ]
+        );
+    }
+
+    fn report() -> EmailAttachment {
+        EmailAttachment {
+            filename: "report.pdf".to_string(),
+            content_type: "application/pdf".to_string(),
+            bytes: b"%PDF-1.4 report".to_vec(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_message_with_attachment() {
+        let service = EmailService::new(smtp_config()).unwrap();
+        let body = EmailBody::Single {
+            content: "Your report is attached".to_string(),
+            content_type: EmailContentType::Text,
+        };
+
+        let message = service
+            .build_message(
+                &EmailRecipients::to("user@example.com", None),
+                "Report",
+                &body,
+                &[report()],
+            )
+            .unwrap();
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(formatted.contains("multipart/mixed"));
+        assert!(formatted.contains("Your report is attached"));
+        assert!(formatted.contains("application/pdf"));
+        assert!(formatted.contains("filename=\"report.pdf\""));
+        // The body comes before the attachment
+        assert!(
+            formatted.find("Your report is attached").unwrap()
+                < formatted.find("application/pdf").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_attachments_over_limit_fail() {
+        let service = EmailService::new(EmailConfig {
+            max_attachment_bytes: 8,
+            ..smtp_config()
+        })
+        .unwrap();
+        let template = EmailTemplate {
+            subject: "Report".to_string(),
+            body: "Your report is attached".to_string(),
+            content_type: EmailContentType::Text,
+            text_body: None,
+        };
+
+        match service
+            .send_email_with_attachments(
+                &EmailRecipients::to("user@example.com", None),
+                &template,
+                None,
+                &[report()],
+            )
             .await
+        {
+            EmailResult::Failed(message) => {
+                assert!(
+                    message.contains("EMAIL_MAX_ATTACHMENT_BYTES"),
+                    "{}",
+                    message
+                )
+            }
+            EmailResult::Success => panic!("expected oversized attachments to fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_cc_fails_descriptively() {
+        let service = EmailService::new(smtp_config()).unwrap();
+        let recipients = EmailRecipients::to("user@example.com", None)
+            .cc("manager@example.com")
+            .cc("not-an-address");
+        let template = EmailTemplate {
+            subject: "Hello".to_string(),
+            body: "Hello".to_string(),
+            content_type: EmailContentType::Text,
+            text_body: None,
+        };
+
+        match service.send_email(&recipients, &template, None).await {
+            EmailResult::Failed(message) => {
+                assert!(
+                    message.contains("Invalid CC email address 'not-an-address'"),
+                    "{}",
+                    message
+                )
+            }
+            EmailResult::Success => panic!("expected a malformed CC address to fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_template_dir_overrides_built_in_templates() {
+        let temp = temp_dir();
+        let dir = temp.path();
+        std::fs::write(dir.join("welcome.subject"), "Hi from {{{app_name}}}\n").unwrap();
+        std::fs::write(dir.join("welcome.html"), "<h1>Welcome {{{user_name}}}</h1>").unwrap();
+        std::fs::write(dir.join("welcome.txt"), "Welcome {{{user_name}}}").unwrap();
+        // Only overrides the body, the subject comes from the built-in template
+        std::fs::write(
+            dir.join("verification.txt"),
+            "Verify: {{{verification_link}}}",
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.md"), "not a template").unwrap();
+
+        let service = EmailService::new(EmailConfig {
+            service_type: EmailServiceType::Log,
+            ..smtp_config()
+        })
+        .unwrap()
+        .with_template_dir(dir.to_path_buf())
+        .unwrap();
+
+        let welcome = service.template("welcome").unwrap();
+        assert_eq!(welcome.subject, "Hi from {{{app_name}}}");
+        assert_eq!(welcome.body, "<h1>Welcome {{{user_name}}}</h1>");
+        assert!(matches!(welcome.content_type, EmailContentType::Html));
+        assert_eq!(
+            welcome.text_body.as_deref(),
+            Some("Welcome {{{user_name}}}")
+        );
+
+        // Both bodies are sent, the log transport records the plaintext alternative
+        let result = service
+            .send_welcome_email("template-dir@example.com", "User", "Example App")
+            .await;
+        assert!(matches!(result, EmailResult::Success), "{:?}", result);
+        let sent = logged_messages_to("template-dir@example.com");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].subject, "Hi from Example App");
+        assert_eq!(sent[0].text, "Welcome User");
+
+        let verification = service.template("verification").unwrap();
+        assert_eq!(verification.subject, "Please verify your email address");
+        assert_eq!(verification.body, "Verify: {{{verification_link}}}");
+        assert!(verification.text_body.is_none());
+
+        // Templates without files keep the built-in copy
+        let password_reset = service.template("password_reset").unwrap();
+        assert_eq!(password_reset.subject, "Password Reset Request");
+        assert!(service.template("notes").is_err());
+    }
+
+    /// Immediate retries, so tests don't wait on the backoff
+    const FAST_RETRIES: EmailRetryPolicy = EmailRetryPolicy {
+        max_retries: 3,
+        base_delay: Duration::from_millis(1),
+        send_timeout: Duration::from_secs(5),
+    };
+
+    #[tokio::test]
+    async fn test_transient_failures_retried_until_success() {
+        let mut attempts = 0;
+        let result = with_retries(&FAST_RETRIES, || {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                if attempt < 3 {
+                    Err(DeliveryError::Transient("451 greylisted".to_string()))
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_failure_not_retried() {
+        let mut attempts = 0;
+        let result = with_retries(&FAST_RETRIES, || {
+            attempts += 1;
+            async { Err(DeliveryError::Permanent("550 no such user".to_string())) }
+        })
+        .await;
+
+        assert_eq!(
+            result,
+            Err(DeliveryError::Permanent("550 no such user".to_string()))
+        );
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let mut attempts = 0;
+        let result = with_retries(&FAST_RETRIES, || {
+            attempts += 1;
+            async { Err(DeliveryError::Transient("connection refused".to_string())) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(DeliveryError::Transient(_))));
+        assert_eq!(attempts, 4);
+    }
+
+    #[tokio::test]
+    async fn test_send_timeout_stops_retries() {
+        let policy = EmailRetryPolicy {
+            max_retries: 1000,
+            base_delay: Duration::from_millis(10),
+            send_timeout: Duration::from_millis(50),
+        };
+        let mut attempts = 0;
+        let result = with_retries(&policy, || {
+            attempts += 1;
+            async { Err(DeliveryError::Transient("connection refused".to_string())) }
+        })
+        .await;
+
+        match result {
+            Err(DeliveryError::Transient(message)) => {
+                assert!(message.contains("timed out"), "{}", message)
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        assert!(attempts < 1000, "{}", attempts);
+    }
+
+    #[tokio::test]
+    async fn test_send_timeout_drops_a_hung_attempt() {
+        let policy = EmailRetryPolicy {
+            max_retries: 0,
+            send_timeout: Duration::from_millis(10),
+            ..Default::default()
+        };
+        let result = with_retries(&policy, || std::future::pending()).await;
+
+        assert!(matches!(result, Err(DeliveryError::Transient(_))));
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let policy = EmailRetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            ..Default::default()
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(500));
+        assert_eq!(policy.delay(2), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(smtp_config().validate().is_ok());
+
+        let err = EmailConfig {
+            from_email: "not an address".to_string(),
+            ..smtp_config()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(
+            err.contains("EMAIL_FROM_ADDRESS 'not an address'"),
+            "{}",
+            err
+        );
+
+        let err = EmailConfig {
+            reply_to_email: Some("support@".to_string()),
+            ..smtp_config()
+        }
+        .validate()
+        .unwrap_err();
+        assert!(err.contains("EMAIL_REPLY_TO_ADDRESS 'support@'"), "{}", err);
+    }
+
+    #[test]
+    fn test_config_validation_rejects_zero_port() {
+        let config = EmailConfig {
+            smtp_port: 0,
+            ..smtp_config()
+        };
+        assert_eq!(
+            config.validate().unwrap_err(),
+            "EMAIL_SMTP_PORT must be between 1 and 65535"
+        );
+
+        // The port isn't used by SendGrid
+        let config = EmailConfig {
+            service_type: EmailServiceType::SendGrid,
+            sendgrid_api_key: Some("key".to_string()),
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_service_type_from_str() {
+        assert_eq!(
+            EmailServiceType::from_str("smtp").unwrap(),
+            EmailServiceType::SMTP
+        );
+        assert_eq!(
+            EmailServiceType::from_str("SendGrid").unwrap(),
+            EmailServiceType::SendGrid
+        );
+        assert_eq!(
+            EmailServiceType::from_str(" sendgrid ").unwrap(),
+            EmailServiceType::SendGrid
+        );
+        assert_eq!(
+            EmailServiceType::from_str("log").unwrap(),
+            EmailServiceType::Log
+        );
+    }
+
+    #[tokio::test]
+    async fn test_log_transport_records_instead_of_sending() {
+        let service = EmailService::new(EmailConfig {
+            service_type: EmailServiceType::Log,
+            ..smtp_config()
+        })
+        .unwrap();
+
+        let result = service
+            .send_welcome_email("logged@example.com", "User", "Example App")
+            .await;
+
+        assert!(matches!(result, EmailResult::Success), "{:?}", result);
+        assert_eq!(
+            logged_emails_to("logged@example.com"),
+            vec!["Welcome to Example App!"]
+        );
+    }
+
+    #[test]
+    fn test_service_type_from_str_rejects_unknown_values() {
+        let error = EmailServiceType::from_str("mailgun").unwrap_err();
+        assert!(error.contains("mailgun"));
+        assert!(EmailServiceType::from_str("").is_err());
+    }
+
+    #[cfg(feature = "sendgrid")]
+    #[test]
+    fn test_sendgrid_payload() {
+        let config = EmailConfig {
+            service_type: EmailServiceType::SendGrid,
+            sendgrid_api_key: Some("key".to_string()),
+            reply_to_email: Some("support@example.com".to_string()),
+            ..smtp_config()
+        };
+
+        let payload = sendgrid::mail_send_payload(
+            &config,
+            &EmailRecipients::to("user@example.com", Some("User")),
+            "Hello",
+            &EmailBody::Single {
+                content: "<p>Hi</p>".to_string(),
+                content_type: EmailContentType::Html,
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "personalizations": [{ "to": [{ "email": "user@example.com", "name": "User" }] }],
+                "from": { "email": "noreply@example.com", "name": "Example" },
+                "subject": "Hello",
+                "content": [{ "type": "text/html", "value": "<p>Hi</p>" }],
+                "reply_to": { "email": "support@example.com" },
+            })
+        );
     }
 }
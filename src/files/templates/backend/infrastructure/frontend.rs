@@ -0,0 +1,204 @@
+//! How the server answers requests that no API route matched: the built frontend in production,
+//! and optionally the Vite dev server in development so the app and API share one origin
+use axum::{
+    Router,
+    body::Body,
+    extract::Request,
+    http::{StatusCode, Uri, header::HOST},
+    response::{IntoResponse, Response},
+};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use std::{env, path::PathBuf};
+use tower_http::services::{ServeDir, ServeFile};
+
+use crate::infrastructure::app_error::AppError;
+
+/// Forwards requests to the Vite dev server
+///
+/// WebSocket upgrades aren't forwarded, Vite's HMR client falls back to connecting to the dev
+/// server directly.
+#[derive(Clone)]
+pub struct DevProxy {
+    client: Client<HttpConnector, Body>,
+    /// Scheme and authority of the dev server, e.g. http://localhost:5173
+    target: String,
+}
+
+impl DevProxy {
+    pub fn new(target: &str) -> Self {
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            target: target.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Sends the request on to the dev server, 502 if it can't be reached
+    pub async fn forward(&self, mut request: Request) -> Response {
+        let path_and_query = request
+            .uri()
+            .path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or("/");
+
+        let uri = match format!("{}{}", self.target, path_and_query).parse::<Uri>() {
+            Ok(uri) => uri,
+            Err(e) => {
+                return AppError {
+                    message: format!("Invalid FRONTEND_DEV_PROXY target: {}", e),
+                    status_code: StatusCode::BAD_GATEWAY,
+                }
+                .into_response();
+            }
+        };
+        *request.uri_mut() = uri;
+        // The client sets the dev server's own host
+        request.headers_mut().remove(HOST);
+
+        match self.client.request(request).await {
+            Ok(response) => response.map(Body::new),
+            Err(e) => AppError {
+                message: format!("Frontend dev server unavailable at {}: {}", self.target, e),
+                status_code: StatusCode::BAD_GATEWAY,
+            }
+            .into_response(),
+        }
+    }
+}
+
+/// What serves the frontend
+pub enum FrontendMode {
+    /// Serve the built frontend from this directory, index.html for client-side routes
+    Static(PathBuf),
+    /// Forward every non-API request to the Vite dev server
+    DevProxy(DevProxy),
+    /// The frontend is served by Vite on its own port
+    Separate,
+}
+
+impl FrontendMode {
+    /// Static files from dist in production, otherwise the dev proxy if FRONTEND_DEV_PROXY is set
+    pub fn from_env(environment: &str) -> Self {
+        if environment == "production" {
+            return FrontendMode::Static(PathBuf::from("dist"));
+        }
+
+        match env::var("FRONTEND_DEV_PROXY") {
+            Ok(target) if !target.trim().is_empty() => {
+                FrontendMode::DevProxy(DevProxy::new(target.trim()))
+            }
+            _ => FrontendMode::Separate,
+        }
+    }
+
+    /// Sets the router's fallback to serve the frontend
+    pub fn apply(self, router: Router) -> Router {
+        match self {
+            FrontendMode::Static(dir) => {
+                let index = dir.join("index.html");
+                router.fallback_service(ServeDir::new(dir).fallback(ServeFile::new(index)))
+            }
+            FrontendMode::DevProxy(proxy) => router.fallback(move |request: Request| async move {
+                // Unknown API paths stay 404s instead of turning into the app's index page
+                if request.uri().path().starts_with("/api/") {
+                    return StatusCode::NOT_FOUND.into_response();
+                }
+                proxy.forward(request).await
+            }),
+            FrontendMode::Separate => router,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::temp_dir;
+    use axum::routing::get;
+    use tokio::net::TcpListener;
+    use tower::ServiceExt;
+
+    fn api() -> Router {
+        Router::new().route("/api/v1/ping", get(|| async { "pong" }))
+    }
+
+    async fn get_text(router: &Router, uri: &str) -> (StatusCode, String) {
+        let response = router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_dev_proxy_forwards_non_api_paths() {
+        // Stands in for the Vite dev server
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = format!("http://{}", listener.local_addr().unwrap());
+        let vite = Router::new().fallback(|uri: Uri| async move { format!("vite {}", uri) });
+        tokio::spawn(async move { axum::serve(listener, vite).await.unwrap() });
+
+        let router = FrontendMode::DevProxy(DevProxy::new(&target)).apply(api());
+
+        assert_eq!(
+            get_text(&router, "/src/main.ts?t=1").await,
+            (StatusCode::OK, "vite /src/main.ts?t=1".to_string())
+        );
+        assert_eq!(
+            get_text(&router, "/api/v1/ping").await,
+            (StatusCode::OK, "pong".to_string())
+        );
+        assert_eq!(
+            get_text(&router, "/api/v1/missing").await.0,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dev_proxy_unreachable_target_is_bad_gateway() {
+        // Bound then dropped, so nothing is listening on the port
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let target = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let router = FrontendMode::DevProxy(DevProxy::new(&target)).apply(api());
+
+        assert_eq!(get_text(&router, "/").await.0, StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn test_static_mode_serves_built_files() {
+        let temp = temp_dir();
+        let dist = temp.path();
+        std::fs::create_dir_all(dist.join("assets")).unwrap();
+        std::fs::write(dist.join("index.html"), "<div id=\"app\"></div>").unwrap();
+        std::fs::write(dist.join("assets/app.js"), "console.log('app')").unwrap();
+
+        let router = FrontendMode::Static(dist.to_path_buf()).apply(api());
+
+        assert_eq!(
+            get_text(&router, "/assets/app.js").await,
+            (StatusCode::OK, "console.log('app')".to_string())
+        );
+        // Client-side routes get the index page
+        assert_eq!(
+            get_text(&router, "/admin/users").await,
+            (StatusCode::OK, "<div id=\"app\"></div>".to_string())
+        );
+        assert_eq!(
+            get_text(&router, "/api/v1/ping").await,
+            (StatusCode::OK, "pong".to_string())
+        );
+    }
+}
@@ -29,7 +29,7 @@ impl JobQueueManager {
             storage
                 .schedule(
                     Message {
-                        to: format!("test{i}@example.com"),
+                        to: format!("test{}@example.com", i),
                         text: "Test background job from apalis".to_string(),
                         subject: "Background email job".to_string(),
                     },
@@ -0,0 +1,184 @@
+//! Nesting depth and element count limits for JSON request bodies, checked by scanning the raw
+//! bytes so a pathological payload is rejected before anything parses it recursively
+use std::{env, fmt, sync::OnceLock};
+
+static JSON_LIMITS: OnceLock<JsonLimits> = OnceLock::new();
+
+/// Default maximum nesting depth of objects and arrays
+pub const DEFAULT_JSON_MAX_DEPTH: usize = 32;
+
+/// Default maximum number of elements (array items and object members)
+pub const DEFAULT_JSON_MAX_ELEMENTS: usize = 10_000;
+
+/// Why a JSON body was rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonLimitError {
+    TooDeep { max_depth: usize },
+    TooManyElements { max_elements: usize },
+}
+
+impl fmt::Display for JsonLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonLimitError::TooDeep { max_depth } => {
+                write!(f, "JSON body is nested deeper than {} levels", max_depth)
+            }
+            JsonLimitError::TooManyElements { max_elements } => {
+                write!(f, "JSON body has more than {} elements", max_elements)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonLimitError {}
+
+/// Maximum nesting depth and element count accepted in a JSON body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JsonLimits {
+    pub max_depth: usize,
+    pub max_elements: usize,
+}
+
+impl Default for JsonLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_JSON_MAX_DEPTH,
+            max_elements: DEFAULT_JSON_MAX_ELEMENTS,
+        }
+    }
+}
+
+impl JsonLimits {
+    /// Global limits, loaded on first use
+    pub fn get() -> &'static JsonLimits {
+        JSON_LIMITS.get_or_init(Self::from_env)
+    }
+
+    /// Reads JSON_MAX_DEPTH and JSON_MAX_ELEMENTS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_depth: env::var("JSON_MAX_DEPTH")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|depth| *depth > 0)
+                .unwrap_or(defaults.max_depth),
+            max_elements: env::var("JSON_MAX_ELEMENTS")
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|elements| *elements > 0)
+                .unwrap_or(defaults.max_elements),
+        }
+    }
+
+    /// Scans the body and fails as soon as a limit is exceeded
+    ///
+    /// Only the structure is looked at, malformed JSON is left for the parser to reject.
+    pub fn check(&self, body: &[u8]) -> Result<(), JsonLimitError> {
+        let mut depth = 0usize;
+        let mut elements = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        // Whether the innermost container has no element yet
+        let mut container_empty = false;
+
+        for &byte in body {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match byte {
+                b' ' | b'\t' | b'\n' | b'\r' => continue,
+                b'{' | b'[' => {
+                    if depth > 0 && container_empty {
+                        elements += 1;
+                    }
+                    depth += 1;
+                    if depth > self.max_depth {
+                        return Err(JsonLimitError::TooDeep {
+                            max_depth: self.max_depth,
+                        });
+                    }
+                    container_empty = true;
+                    continue;
+                }
+                b'}' | b']' => {
+                    depth = depth.saturating_sub(1);
+                    container_empty = false;
+                    continue;
+                }
+                b',' => elements += 1,
+                _ => {
+                    if depth > 0 && container_empty {
+                        elements += 1;
+                    }
+                    if byte == b'"' {
+                        in_string = true;
+                    }
+                }
+            }
+
+            container_empty = false;
+            if elements > self.max_elements {
+                return Err(JsonLimitError::TooManyElements {
+                    max_elements: self.max_elements,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(max_depth: usize, max_elements: usize) -> JsonLimits {
+        JsonLimits {
+            max_depth,
+            max_elements,
+        }
+    }
+
+    fn nested(depth: usize) -> String {
+        format!("{}{}", "[".repeat(depth), "]".repeat(depth))
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let limits = limits(4, 100);
+        assert!(limits.check(nested(4).as_bytes()).is_ok());
+        assert_eq!(
+            limits.check(nested(5).as_bytes()),
+            Err(JsonLimitError::TooDeep { max_depth: 4 })
+        );
+    }
+
+    #[test]
+    fn test_element_limit() {
+        let limits = limits(4, 3);
+        assert!(limits.check(br#"{"a": 1, "b": [2]}"#).is_ok());
+        assert_eq!(
+            limits.check(b"[1, 2, 3, 4]"),
+            Err(JsonLimitError::TooManyElements { max_elements: 3 })
+        );
+        assert!(limits.check(b"[[], [], []]").is_ok());
+    }
+
+    #[test]
+    fn test_brackets_inside_strings_ignored() {
+        let limits = limits(2, 10);
+        assert!(
+            limits
+                .check(br#"{"password": "[[[[{{{{\"]]]]", "name": "x,y,z,w,v,u,t,s,r,q,p"}"#)
+                .is_ok()
+        );
+    }
+}
@@ -1,9 +1,50 @@
 use serde::{Deserialize, Serialize};
 
+/// What a JWT was minted for
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenPurpose {
+    /// Bound to a user session, must carry a session id
+    #[default]
+    Session,
+    /// Not bound to any session (e.g. service or test tokens)
+    Stateless,
+}
+
 // JWT Claims
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: String,        // subject (user id)
-    pub exp: usize,         // expiration time
-    pub session_id: String, // session UUID for tracking
+    pub sub: String, // subject (user id)
+    pub exp: usize,  // expiration time
+    #[serde(default)]
+    pub purpose: TokenPurpose,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>, // session UUID for tracking, only for session tokens
+}
+
+impl Claims {
+    /// Claims for a token bound to a session
+    pub fn for_session(user_id: &uuid::Uuid, session_id: &uuid::Uuid, exp: usize) -> Self {
+        Self {
+            sub: user_id.to_string(),
+            exp,
+            purpose: TokenPurpose::Session,
+            session_id: Some(session_id.to_string()),
+        }
+    }
+
+    /// Claims for a token that isn't bound to a session
+    pub fn stateless(user_id: &uuid::Uuid, exp: usize) -> Self {
+        Self {
+            sub: user_id.to_string(),
+            exp,
+            purpose: TokenPurpose::Stateless,
+            session_id: None,
+        }
+    }
+
+    /// The session id, None if the token doesn't carry one (empty counts as none)
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref().filter(|id| !id.is_empty())
+    }
 }
@@ -1,49 +1,308 @@
-use std::env;
+use rand_core::{OsRng, RngCore};
+use std::{env, fs, sync::OnceLock, time::Duration};
+use tracing::Subscriber;
 use tracing_subscriber::{
-    EnvFilter,
+    EnvFilter, Layer,
+    filter::{Directive, ParseError},
     fmt::{format::FmtSpan, time::UtcTime},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    util::SubscriberInitExt,
 };
 
-use crate::infrastructure::websocket::broadcast_system_log;
+use crate::infrastructure::websocket::{LogLevel, WsMessage, broadcast};
+
+#[cfg(feature = "otlp")]
+static TRACER_PROVIDER: std::sync::OnceLock<opentelemetry_sdk::trace::SdkTracerProvider> =
+    std::sync::OnceLock::new();
+
+/// OpenTelemetry OTLP export configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpConfig {
+    /// Collector endpoint (gRPC), e.g. http://localhost:4317
+    pub endpoint: String,
+    /// Service name reported to the collector
+    pub service_name: String,
+}
+
+impl OtlpConfig {
+    /// Reads OTLP_ENABLED, OTEL_EXPORTER_OTLP_ENDPOINT and OTEL_SERVICE_NAME
+    pub fn from_env() -> Option<Self> {
+        Self::from_values(
+            env::var("OTLP_ENABLED").ok().as_deref(),
+            env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().as_deref(),
+            env::var("OTEL_SERVICE_NAME").ok().as_deref(),
+        )
+    }
+
+    /// Builds the config from raw values, None when export is disabled
+    fn from_values(
+        enabled: Option<&str>,
+        endpoint: Option<&str>,
+        service_name: Option<&str>,
+    ) -> Option<Self> {
+        let enabled = enabled
+            .and_then(|value| value.trim().parse::<bool>().ok())
+            .unwrap_or(false);
+
+        if !enabled {
+            return None;
+        }
+
+        Some(Self {
+            endpoint: endpoint
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or("http://localhost:4317")
+                .to_string(),
+            service_name: service_name
+                .filter(|value| !value.trim().is_empty())
+                .unwrap_or(env!("CARGO_PKG_NAME"))
+                .to_string(),
+        })
+    }
+}
+
+static SLOW_REQUEST_THRESHOLD: OnceLock<Option<Duration>> = OnceLock::new();
+
+/// Default response time in milliseconds past which a request is reported as slow
+pub const DEFAULT_SLOW_REQUEST_MS: u64 = 1000;
+
+/// Response time past which a request is logged at warn level and broadcast as slow
+///
+/// Read from SLOW_REQUEST_MS on first use, 0 turns the check off.
+pub fn slow_request_threshold() -> Option<Duration> {
+    *SLOW_REQUEST_THRESHOLD.get_or_init(|| {
+        let millis = env::var("SLOW_REQUEST_MS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SLOW_REQUEST_MS);
+        Some(Duration::from_millis(millis)).filter(|threshold| !threshold.is_zero())
+    })
+}
+
+static AUDIT_LOG_CONFIG: OnceLock<AuditLogConfig> = OnceLock::new();
+
+/// What the audit log captures per route, and how long request and response bodies are kept
+///
+/// Loaded from `[logging]` in rext.toml, `AUDIT_METADATA_ONLY_PATHS` (comma separated),
+/// `AUDIT_SUCCESS_BODY_SAMPLE_RATE` and `AUDIT_BODY_RETENTION_DAYS` replace the rext.toml
+/// values when set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditLogConfig {
+    /// Routes audited without their request and response bodies, an exact path or a prefix
+    /// ending in `/*`
+    pub metadata_only_paths: Vec<String>,
+    /// Fraction of successful responses audited with their bodies, error responses always keep
+    /// them. 1.0 stores every body, 0.0 only those of errors
+    pub success_body_sample_rate: f64,
+    /// Days after which compaction strips the bodies from audit entries, None keeps them
+    pub body_retention_days: Option<u32>,
+}
+
+impl Default for AuditLogConfig {
+    fn default() -> Self {
+        Self {
+            metadata_only_paths: Vec::new(),
+            success_body_sample_rate: 1.0,
+            body_retention_days: None,
+        }
+    }
+}
+
+impl AuditLogConfig {
+    /// Global audit log config, loaded on first use
+    pub fn get() -> &'static AuditLogConfig {
+        AUDIT_LOG_CONFIG.get_or_init(Self::load)
+    }
+
+    /// Loads the config from rext.toml and the environment
+    pub fn load() -> Self {
+        let rext_toml = fs::read_to_string("rext.toml").ok();
+        Self::from_sources(rext_toml.as_deref(), |name| env::var(name).ok())
+    }
+
+    /// Builds the config from rext.toml contents and an env var lookup
+    pub fn from_sources(rext_toml: Option<&str>, env_var: impl Fn(&str) -> Option<String>) -> Self {
+        let logging = rext_toml
+            .and_then(|content| toml::from_str::<toml::Value>(content).ok())
+            .and_then(|value| value.get("logging").cloned());
+        let setting = |key: &str| logging.as_ref().and_then(|logging| logging.get(key));
+
+        let metadata_only_paths = match env_var("AUDIT_METADATA_ONLY_PATHS") {
+            Some(paths) => paths
+                .split(',')
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .collect(),
+            None => setting("metadata_only_paths")
+                .and_then(|paths| {
+                    paths.as_array().map(|paths| {
+                        paths
+                            .iter()
+                            .filter_map(|path| path.as_str().map(str::to_string))
+                            .collect()
+                    })
+                })
+                .unwrap_or_default(),
+        };
+
+        let success_body_sample_rate = match env_var("AUDIT_SUCCESS_BODY_SAMPLE_RATE") {
+            Some(rate) => rate.trim().parse::<f64>().ok(),
+            None => setting("success_body_sample_rate").and_then(|rate| {
+                rate.as_float()
+                    .or_else(|| rate.as_integer().map(|rate| rate as f64))
+            }),
+        }
+        .filter(|rate| rate.is_finite())
+        .map(|rate| rate.clamp(0.0, 1.0))
+        .unwrap_or(1.0);
+
+        let body_retention_days = match env_var("AUDIT_BODY_RETENTION_DAYS") {
+            Some(days) => days.trim().parse::<u32>().ok(),
+            None => setting("body_retention_days")
+                .and_then(|days| days.as_integer())
+                .and_then(|days| u32::try_from(days).ok()),
+        }
+        .filter(|days| *days > 0);
+
+        Self {
+            metadata_only_paths,
+            success_body_sample_rate,
+            body_retention_days,
+        }
+    }
+
+    /// Whether an entry with this status keeps its bodies, errors always do and successes are
+    /// sampled at `success_body_sample_rate`
+    pub fn stores_bodies(&self, status_code: i32) -> bool {
+        if status_code >= 400 || self.success_body_sample_rate >= 1.0 {
+            return true;
+        }
+        if self.success_body_sample_rate <= 0.0 {
+            return false;
+        }
+
+        let sample = OsRng.next_u32() as f64 / (u32::MAX as f64 + 1.0);
+        sample < self.success_body_sample_rate
+    }
+
+    /// How long audit entries keep their bodies before compaction strips them
+    pub fn body_retention(&self) -> Option<chrono::Duration> {
+        self.body_retention_days
+            .map(|days| chrono::Duration::days(days.into()))
+    }
+
+    /// Whether requests to this path are audited without bodies
+    pub fn is_metadata_only(&self, path: &str) -> bool {
+        self.metadata_only_paths
+            .iter()
+            .any(|pattern| match pattern.strip_suffix("/*") {
+                Some(prefix) => path == prefix || path.starts_with(&format!("{}/", prefix)),
+                None => path == pattern,
+            })
+    }
+}
+
+/// Crates that are chatty below warn, quieted unless a directive names them
+pub const NOISY_TARGETS: &[&str] = &["sqlx", "sea_orm", "apalis", "tower_http", "hyper", "h2"];
 
 /// Logging configuration manager
 pub struct LoggingManager;
 
 impl LoggingManager {
+    /// Reads `RUST_LOG`-style directives from RUST_LOG, falling back to [logging] filter in rext.toml
+    pub fn log_directives(
+        rext_toml: Option<&str>,
+        env_var: impl Fn(&str) -> Option<String>,
+    ) -> Option<String> {
+        env_var("RUST_LOG")
+            .or_else(|| {
+                rext_toml
+                    .and_then(|content| toml::from_str::<toml::Value>(content).ok())
+                    .and_then(|value| {
+                        value
+                            .get("logging")
+                            .and_then(|logging| logging.get("filter"))
+                            .and_then(|filter| filter.as_str().map(str::to_string))
+                    })
+            })
+            .filter(|directives| !directives.trim().is_empty())
+    }
+
+    /// Builds the filter from the default level, the noisy targets at warn, then the directives
+    ///
+    /// Directives are applied last, so `sqlx=debug` or a bare `warn` replaces the defaults.
+    pub fn env_filter(
+        default_level: &str,
+        directives: Option<&str>,
+    ) -> Result<EnvFilter, ParseError> {
+        let mut filter = EnvFilter::default().add_directive(default_level.parse::<Directive>()?);
+        for target in NOISY_TARGETS {
+            filter = filter.add_directive(format!("{}=warn", target).parse()?);
+        }
+
+        for directive in directives
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|directive| !directive.is_empty())
+        {
+            filter = filter.add_directive(directive.parse()?);
+        }
+
+        Ok(filter)
+    }
+
     /// Initialize logging with environment-based configuration
     pub fn initialize() {
+        // Load .env here as well, logging starts before the rest of the startup sequence
+        dotenvy::dotenv().ok();
+
         let environment = env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
-        let log_level = env::var("RUST_LOG").unwrap_or_else(|_| {
-            if environment == "development" {
-                "debug".to_string()
-            } else {
-                "info".to_string()
-            }
-        });
+        let default_level = if environment == "development" {
+            "debug"
+        } else {
+            "info"
+        };
 
-        // Create environment filter with specific crate filters to reduce noise
-        let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-            EnvFilter::new(log_level)
-                .add_directive("sqlx=warn".parse().unwrap())
-                .add_directive("apalis=warn".parse().unwrap())
-                .add_directive("tower_http=warn".parse().unwrap())
-        });
+        // Per-target directives on top of the default level, with noisy crates quieted
+        let rext_toml = fs::read_to_string("rext.toml").ok();
+        let directives = Self::log_directives(rext_toml.as_deref(), |name| env::var(name).ok());
+        let env_filter =
+            Self::env_filter(default_level, directives.as_deref()).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid log directives: {}", e);
+                Self::env_filter(default_level, None).expect("default log directives are valid")
+            });
 
-        // Configure tracing subscriber with custom layer for WebSocket broadcasting
-        let subscriber = tracing_subscriber::fmt()
-            .with_env_filter(env_filter)
+        // Use JSON format in production, pretty format in development
+        let fmt_layer = tracing_subscriber::fmt::layer()
             .with_timer(UtcTime::rfc_3339())
             .with_span_events(FmtSpan::CLOSE)
             .with_target(false)
             .with_thread_ids(true)
             .with_thread_names(true)
             .with_ansi(environment != "production");
-
-        // Use JSON format in production, pretty format in development
-        if environment == "production" {
-            subscriber.json().init();
+        let fmt_layer = if environment == "production" {
+            fmt_layer.json().boxed()
         } else {
-            subscriber.pretty().init();
+            fmt_layer.pretty().boxed()
+        };
+
+        // Optionally export spans to an OpenTelemetry collector, in addition to the local output
+        let otlp_config = OtlpConfig::from_env();
+        let otlp_layer = Self::otlp_layer(otlp_config.as_ref());
+        let otlp_endpoint = otlp_layer
+            .as_ref()
+            .and(otlp_config.map(|config| config.endpoint));
+
+        tracing_subscriber::registry()
+            .with(env_filter)
+            .with(fmt_layer)
+            .with(otlp_layer)
+            .init();
+
+        if let Some(endpoint) = otlp_endpoint {
+            tracing::info!("OTLP export enabled, sending traces to {}", endpoint);
         }
 
         // Set up custom event subscriber for WebSocket broadcasting
@@ -52,25 +311,251 @@ impl LoggingManager {
         // Broadcast the initialization message
         let environment_clone = environment.clone();
         tokio::spawn(async move {
-            broadcast_system_log(
-                "info".to_string(),
+            broadcast(WsMessage::system_log(
+                LogLevel::Info,
                 format!(
                     "Logging system initialized for environment: {}",
                     environment_clone
                 ),
-                "logging".to_string(),
-            )
+                "logging",
+            ))
             .await;
         });
     }
 
+    /// Builds the OTLP layer, None when export is not configured or the `otlp` feature is off
+    #[cfg(feature = "otlp")]
+    pub fn otlp_layer<S>(config: Option<&OtlpConfig>) -> Option<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+    {
+        use opentelemetry::trace::TracerProvider as _;
+        use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+        use opentelemetry_sdk::{Resource, trace::SdkTracerProvider};
+
+        let config = config?;
+
+        let exporter = match SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(&config.endpoint)
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!("Failed to create OTLP exporter: {}", e);
+                return None;
+            }
+        };
+
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(config.service_name.clone())
+                    .build(),
+            )
+            .build();
+
+        let tracer = provider.tracer(config.service_name.clone());
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        let _ = TRACER_PROVIDER.set(provider);
+
+        Some(tracing_opentelemetry::layer().with_tracer(tracer).boxed())
+    }
+
+    /// Builds the OTLP layer, None when export is not configured or the `otlp` feature is off
+    #[cfg(not(feature = "otlp"))]
+    pub fn otlp_layer<S>(config: Option<&OtlpConfig>) -> Option<Box<dyn Layer<S> + Send + Sync>>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+    {
+        if config.is_some() {
+            eprintln!("OTLP_ENABLED is set but the app was built without the `otlp` feature");
+        }
+        None
+    }
+
+    /// Flushes pending spans to the OTLP collector, call before the process exits
+    pub fn shutdown() {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = TRACER_PROVIDER.get() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to shut down OTLP tracer provider: {}", e);
+            }
+        }
+    }
+
     /// Create a request ID for tracking requests across the system
     pub fn generate_request_id() -> String {
         uuid::Uuid::new_v4().to_string()
     }
 
     /// Broadcast a log message to WebSocket clients
-    pub async fn broadcast_log(level: &str, message: &str, target: &str) {
-        broadcast_system_log(level.to_string(), message.to_string(), target.to_string()).await;
+    pub async fn broadcast_log(level: LogLevel, message: &str, source: &str) {
+        broadcast(WsMessage::system_log(level, message, source)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::Registry;
+
+    #[test]
+    fn test_metadata_only_paths_from_rext_toml() {
+        let config = AuditLogConfig::from_sources(
+            Some("[logging]\nmetadata_only_paths = [\"/api/v1/upload\", \"/api/v1/files/*\"]"),
+            |_| None,
+        );
+
+        assert!(config.is_metadata_only("/api/v1/upload"));
+        assert!(config.is_metadata_only("/api/v1/files"));
+        assert!(config.is_metadata_only("/api/v1/files/report.pdf"));
+        assert!(!config.is_metadata_only("/api/v1/upload/extra"));
+        assert!(!config.is_metadata_only("/api/v1/filesystem"));
+    }
+
+    #[test]
+    fn test_metadata_only_env_replaces_rext_toml() {
+        let config = AuditLogConfig::from_sources(
+            Some("[logging]\nmetadata_only_paths = [\"/api/v1/upload\"]"),
+            |name| {
+                (name == "AUDIT_METADATA_ONLY_PATHS")
+                    .then(|| "/api/v1/avatar, /api/v1/import/*".to_string())
+            },
+        );
+
+        assert!(!config.is_metadata_only("/api/v1/upload"));
+        assert!(config.is_metadata_only("/api/v1/avatar"));
+        assert!(config.is_metadata_only("/api/v1/import/csv"));
+    }
+
+    #[test]
+    fn test_body_storage_tiers() {
+        let config = AuditLogConfig::from_sources(
+            Some("[logging]\nsuccess_body_sample_rate = 0\nbody_retention_days = 30"),
+            |_| None,
+        );
+        assert_eq!(config.success_body_sample_rate, 0.0);
+        assert_eq!(config.body_retention(), Some(chrono::Duration::days(30)));
+        assert!(!config.stores_bodies(200));
+        assert!(!config.stores_bodies(302));
+        assert!(config.stores_bodies(404));
+        assert!(config.stores_bodies(500));
+
+        // Env vars replace rext.toml, out of range rates are clamped
+        let config = AuditLogConfig::from_sources(
+            Some("[logging]\nsuccess_body_sample_rate = 0.1\nbody_retention_days = 30"),
+            |name| match name {
+                "AUDIT_SUCCESS_BODY_SAMPLE_RATE" => Some("2".to_string()),
+                "AUDIT_BODY_RETENTION_DAYS" => Some("0".to_string()),
+                _ => None,
+            },
+        );
+        assert_eq!(config.success_body_sample_rate, 1.0);
+        assert_eq!(config.body_retention(), None);
+        assert!(config.stores_bodies(200));
+
+        assert_eq!(
+            AuditLogConfig::from_sources(None, |_| None),
+            AuditLogConfig::default()
+        );
+    }
+
+    fn directives(filter: &EnvFilter) -> Vec<String> {
+        let mut directives: Vec<String> =
+            filter.to_string().split(',').map(str::to_string).collect();
+        directives.sort();
+        directives
+    }
+
+    #[test]
+    fn test_per_target_directives() {
+        let filter = LoggingManager::env_filter("info", Some("app=debug,sqlx=warn")).unwrap();
+
+        let directives = directives(&filter);
+        assert!(directives.contains(&"info".to_string()));
+        assert!(directives.contains(&"app=debug".to_string()));
+        for target in NOISY_TARGETS {
+            assert!(directives.contains(&format!("{}=warn", target)));
+        }
+        assert_eq!(directives.len(), NOISY_TARGETS.len() + 2);
+
+        tracing::subscriber::with_default(Registry::default().with(filter), || {
+            assert!(tracing::enabled!(target: "app::handlers", tracing::Level::DEBUG));
+            assert!(!tracing::enabled!(target: "app::handlers", tracing::Level::TRACE));
+            assert!(!tracing::enabled!(target: "sqlx::query", tracing::Level::INFO));
+            assert!(tracing::enabled!(target: "sqlx::query", tracing::Level::WARN));
+            assert!(tracing::enabled!(target: "other", tracing::Level::INFO));
+            assert!(!tracing::enabled!(target: "other", tracing::Level::DEBUG));
+        });
+    }
+
+    #[test]
+    fn test_directives_override_defaults() {
+        let filter = LoggingManager::env_filter("info", Some("warn, sqlx=debug")).unwrap();
+
+        let directives = directives(&filter);
+        assert!(directives.contains(&"warn".to_string()));
+        assert!(directives.contains(&"sqlx=debug".to_string()));
+        assert!(!directives.contains(&"info".to_string()));
+        assert!(!directives.contains(&"sqlx=warn".to_string()));
+        assert!(directives.contains(&"hyper=warn".to_string()));
+
+        assert!(LoggingManager::env_filter("info", Some("app=loud")).is_err());
+    }
+
+    #[test]
+    fn test_log_directives_sources() {
+        let rext_toml = Some("[logging]\nfilter = \"app=debug\"");
+        assert_eq!(
+            LoggingManager::log_directives(rext_toml, |_| None).as_deref(),
+            Some("app=debug")
+        );
+        assert_eq!(
+            LoggingManager::log_directives(rext_toml, |name| {
+                (name == "RUST_LOG").then(|| "app=trace".to_string())
+            })
+            .as_deref(),
+            Some("app=trace")
+        );
+        assert_eq!(LoggingManager::log_directives(None, |_| None), None);
+    }
+
+    #[test]
+    fn test_otlp_config_absent_when_not_configured() {
+        assert_eq!(OtlpConfig::from_values(None, None, None), None);
+        assert_eq!(
+            OtlpConfig::from_values(Some("false"), Some("http://collector:4317"), None),
+            None
+        );
+        assert!(LoggingManager::otlp_layer::<Registry>(None).is_none());
+    }
+
+    #[test]
+    fn test_otlp_config_from_values() {
+        let config =
+            OtlpConfig::from_values(Some("true"), Some("http://collector:4317"), Some("my-app"))
+                .unwrap();
+        assert_eq!(config.endpoint, "http://collector:4317");
+        assert_eq!(config.service_name, "my-app");
+
+        let config = OtlpConfig::from_values(Some("true"), None, None).unwrap();
+        assert_eq!(config.endpoint, "http://localhost:4317");
+        assert_eq!(config.service_name, env!("CARGO_PKG_NAME"));
+    }
+
+    #[cfg(feature = "otlp")]
+    #[tokio::test]
+    async fn test_otlp_layer_installed_when_configured() {
+        let config = OtlpConfig::from_values(Some("true"), Some("http://localhost:4317"), None);
+        assert!(LoggingManager::otlp_layer::<Registry>(config.as_ref()).is_some());
+    }
+
+    #[cfg(not(feature = "otlp"))]
+    #[test]
+    fn test_otlp_layer_absent_without_feature() {
+        let config = OtlpConfig::from_values(Some("true"), Some("http://localhost:4317"), None);
+        assert!(LoggingManager::otlp_layer::<Registry>(config.as_ref()).is_none());
     }
 }
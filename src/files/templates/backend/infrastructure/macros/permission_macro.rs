@@ -1,6 +1,8 @@
+/// Looks up the user by email and returns a 403 unless the given `PermissionService` check passes
+#[doc(hidden)]
 #[macro_export]
-macro_rules! check_single_permission {
-    ( $a:expr, $b:expr, $c:expr ) => {{
+macro_rules! check_permissions_with {
+    ( $check:ident, $a:expr, $b:expr, $c:expr ) => {{
         // Find user by email using UserService
         let user = $crate::control::services::user_service::UserService::find_user_by_email($c, $a)
             .await?
@@ -8,9 +10,8 @@ macro_rules! check_single_permission {
                 message: "Invalid credentials".to_string(),
                 status_code: axum::http::StatusCode::UNAUTHORIZED,
             })?;
-        // check if a user has a specific permission
         let has_permission =
-            $crate::control::services::permission_service::PermissionService::has_permission(
+            $crate::control::services::permission_service::PermissionService::$check(
                 $c, user.id, $b,
             )
             .await?;
@@ -22,3 +23,120 @@ macro_rules! check_single_permission {
         }
     }};
 }
+
+/// Requires the user with the given email to have a specific permission
+#[macro_export]
+macro_rules! check_single_permission {
+    ( $a:expr, $b:expr, $c:expr ) => {
+        $crate::check_permissions_with!(has_permission, $a, $b, $c)
+    };
+}
+
+/// Requires the user with the given email to have at least one of the given permissions
+#[macro_export]
+macro_rules! check_any_permission {
+    ( $a:expr, $b:expr, $c:expr ) => {
+        $crate::check_permissions_with!(has_any_permission, $a, $b, $c)
+    };
+}
+
+/// Requires the user with the given email to have every one of the given permissions
+///
+/// An empty list is rejected rather than trivially satisfied.
+#[macro_export]
+macro_rules! check_all_permissions {
+    ( $a:expr, $b:expr, $c:expr ) => {{
+        let permissions = $b;
+        if permissions.is_empty() {
+            return Err($crate::infrastructure::app_error::AppError {
+                message: "No permissions given to check".to_string(),
+                status_code: axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            });
+        }
+        $crate::check_permissions_with!(has_all_permissions, $a, permissions, $c)
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::permissions::Permission::{self, AdminRead, AdminWrite, All, UserRead};
+    use crate::entity::models::roles;
+    use crate::infrastructure::app_error::AppError;
+    use crate::infrastructure::test_support::{create_verified_user, setup_db};
+    use axum::http::StatusCode;
+    use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+
+    async fn setup_user(permissions: &str) -> (DatabaseConnection, String) {
+        let db = setup_db().await;
+
+        let role = roles::ActiveModel {
+            name: Set("support".to_string()),
+            permissions: Set(permissions.to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let user = create_verified_user(
+            &db,
+            "support@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            Some(role.id),
+        )
+        .await
+        .unwrap();
+        (db, user.email)
+    }
+
+    async fn require_any(
+        db: &DatabaseConnection,
+        email: &str,
+        permissions: &[Permission],
+    ) -> Result<(), AppError> {
+        check_any_permission!(email, permissions, db);
+        Ok(())
+    }
+
+    async fn require_all(
+        db: &DatabaseConnection,
+        email: &str,
+        permissions: &[Permission],
+    ) -> Result<(), AppError> {
+        check_all_permissions!(email, permissions, db);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_any_permission_satisfied_by_one_of_several() {
+        let (db, email) = setup_user(r#"["admin:write"]"#).await;
+
+        assert!(require_any(&db, &email, &[AdminWrite, All]).await.is_ok());
+        let err = require_any(&db, &email, &[AdminRead, UserRead])
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_all_permissions_fails_when_one_missing() {
+        let (db, email) = setup_user(r#"["admin:read", "user:read"]"#).await;
+
+        assert!(
+            require_all(&db, &email, &[AdminRead, UserRead])
+                .await
+                .is_ok()
+        );
+        let err = require_all(&db, &email, &[AdminRead, AdminWrite])
+            .await
+            .unwrap_err();
+        assert_eq!(err.status_code, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_all_permissions_rejects_empty_list() {
+        let (db, email) = setup_user(r#"["admin:read"]"#).await;
+
+        let err = require_all(&db, &email, &[]).await.unwrap_err();
+        assert_eq!(err.status_code, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
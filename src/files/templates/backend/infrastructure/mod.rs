@@ -1,13 +1,31 @@
 pub mod app_error;
+pub mod audit_workers;
+pub mod background_failures;
+pub mod build_info;
+pub mod clock;
+pub mod column_masking;
 pub mod cors;
 pub mod database;
+#[cfg(feature = "email")]
 pub mod email;
+pub mod frontend;
+#[cfg(feature = "queue")]
 pub mod job_queue;
+pub mod json_limits;
 pub mod jwt_claims;
 pub mod logging;
 pub mod macros;
 pub mod openapi;
+pub mod pagination;
 pub mod query_performance;
+#[cfg(feature = "queue")]
 pub mod scheduler;
+pub mod secrets;
 pub mod server;
+pub mod session_policy;
+pub mod singleflight;
+#[cfg(test)]
+pub mod test_support;
+pub mod timestamps;
+pub mod trace_context;
 pub mod websocket;
@@ -1,16 +1,24 @@
 use utoipa::OpenApi;
 
 use crate::bridge::types::admin::{
-    ADMIN_TAG, AdminLoginRequest, AdminLoginResponse, AuditLogResponse, CreateUserRequest,
-    DatabaseTableResponse, HealthResponse, LogsQueryParams, PaginatedResponse, PaginationMeta,
-    TableRecordResponse, TableRecordsQueryParams, UpdateUserRequest, UserResponse,
-    UsersQueryParams,
+    ADMIN_TAG, AdminLoginRequest, AdminLoginResponse, AuditLogResponse, BackgroundFailureCounts,
+    BulkRolePermissionsRequest, BulkRolePermissionsResponse, ConfirmationResponse,
+    CreateUserRequest, DatabasePerformanceResponse, DatabaseTableResponse, ExportedSession,
+    ExportedUser, HealthQueryParams, HealthResponse, LogsQueryParams, PaginatedResponse,
+    PaginationMeta, PatchUserRequest, PermissionCatalogEntry, RolePermissionAssignment,
+    RolePermissionChange, SessionsQueryParams, SettingResponse, SetupRequest, SetupResponse,
+    TableRecordResponse, TableRecordsQueryParams, UpdateSettingRequest, UpdateUserRequest,
+    UserDataExport, UserPurgeResponse, UserResponse, UsersQueryParams,
 };
 use crate::bridge::types::auth::{
-    AUTH_TAG, AuthUser, LoginRequest, LoginResponse, ProfileResponse, RegisterRequest,
-    RegisterResponse,
+    AUTH_TAG, AuthUser, ChangePasswordRequest, ConfirmPasswordResetRequest, LoginRequest,
+    LoginResponse, MeResponse, PasswordResetRequest, ProfileResponse, RefreshRequest,
+    RegisterRequest, RegisterResponse,
+};
+use crate::bridge::types::system::{SYSTEM_TAG, VersionResponse};
+use crate::infrastructure::app_error::{
+    ErrorResponse, FieldError, MessageResponse, ValidationErrorResponse,
 };
-use crate::infrastructure::app_error::{ErrorResponse, MessageResponse};
 
 /// OpenAPI documentation structure
 #[derive(OpenApi)]
@@ -29,16 +37,22 @@ use crate::infrastructure::app_error::{ErrorResponse, MessageResponse};
     ),
     tags(
         (name = AUTH_TAG, description = "Authentication endpoints"),
-        (name = ADMIN_TAG, description = "Admin panel endpoints")
+        (name = ADMIN_TAG, description = "Admin panel endpoints"),
+        (name = SYSTEM_TAG, description = "System endpoints")
     ),
     components(
         schemas(
-            RegisterRequest, RegisterResponse, LoginRequest, LoginResponse, ProfileResponse, AuthUser,
-            MessageResponse, ErrorResponse,
+            RegisterRequest, RegisterResponse, LoginRequest, LoginResponse, RefreshRequest, ProfileResponse, MeResponse, AuthUser,
+            ChangePasswordRequest, PasswordResetRequest, ConfirmPasswordResetRequest,
+            MessageResponse, ErrorResponse, ValidationErrorResponse, FieldError,
             AdminLoginRequest, AdminLoginResponse, AuditLogResponse,
-            LogsQueryParams, UsersQueryParams, CreateUserRequest, UpdateUserRequest, UserResponse,
-            DatabaseTableResponse, TableRecordsQueryParams, TableRecordResponse, HealthResponse,
-            PaginatedResponse<AuditLogResponse>, PaginationMeta
+            LogsQueryParams, UsersQueryParams, CreateUserRequest, UpdateUserRequest, PatchUserRequest, UserResponse,
+            DatabaseTableResponse, TableRecordsQueryParams, TableRecordResponse, HealthQueryParams, HealthResponse, SessionsQueryParams, DatabasePerformanceResponse, BackgroundFailureCounts,
+            PaginatedResponse<AuditLogResponse>, PaginationMeta, PermissionCatalogEntry,
+            SetupRequest, SetupResponse, BulkRolePermissionsRequest, RolePermissionAssignment,
+            BulkRolePermissionsResponse, RolePermissionChange, ConfirmationResponse,
+            SettingResponse, UpdateSettingRequest, UserDataExport, ExportedUser, ExportedSession,
+            UserPurgeResponse, VersionResponse
         )
     ),
     security(
@@ -46,3 +60,100 @@ use crate::infrastructure::app_error::{ErrorResponse, MessageResponse};
     )
 )]
 pub struct ApiDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "admin")]
+    use crate::bridge::routes::admin::admin_router;
+    use crate::bridge::routes::auth::auth_router;
+    use crate::infrastructure::test_support::memory_db;
+    use utoipa_axum::router::OpenApiRouter;
+
+    /// Spec of the auth API, and the admin API when it is compiled in, put together the way
+    /// the server does
+    async fn spec() -> serde_json::Value {
+        let db = memory_db().await;
+        let router = OpenApiRouter::with_openapi(ApiDoc::openapi())
+            .nest("/api/v1/auth", auth_router(db.clone()));
+        #[cfg(feature = "admin")]
+        let router = router.nest("/api/v1/admin", admin_router(db));
+        let (_, api) = router.split_for_parts();
+        serde_json::to_value(&api).unwrap()
+    }
+
+    /// Every `$ref` in a JSON value
+    fn schema_refs(value: &serde_json::Value) -> Vec<String> {
+        match value {
+            serde_json::Value::Object(object) => object
+                .iter()
+                .flat_map(|(key, value)| match (key.as_str(), value.as_str()) {
+                    ("$ref", Some(reference)) => vec![reference.to_string()],
+                    _ => schema_refs(value),
+                })
+                .collect(),
+            serde_json::Value::Array(values) => values.iter().flat_map(schema_refs).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    #[cfg(feature = "admin")]
+    #[tokio::test]
+    async fn test_health_response_schema_is_fully_typed() {
+        let spec = spec().await;
+        let schemas = &spec["components"]["schemas"];
+
+        assert_eq!(
+            spec["paths"]["/api/v1/admin/health"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/HealthResponse"
+        );
+        let health = &schemas["HealthResponse"];
+        assert!(health["properties"]["cpu_usage"].is_object());
+        let health_refs = schema_refs(health);
+        for nested in ["DatabasePerformanceResponse", "BackgroundFailureCounts"] {
+            assert!(
+                health_refs.contains(&format!("#/components/schemas/{}", nested)),
+                "HealthResponse doesn't reference {}",
+                nested
+            );
+        }
+        assert!(
+            schemas["DatabasePerformanceResponse"]["properties"]["p95_execution_time_ms"]
+                .is_object()
+        );
+        assert!(schemas["BackgroundFailureCounts"]["properties"]["email_send"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_every_schema_reference_is_registered() {
+        let spec = spec().await;
+
+        for reference in schema_refs(&spec) {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("unexpected reference {}", reference));
+            assert!(
+                spec["components"]["schemas"][name].is_object(),
+                "{} isn't in the components",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_400_uses_validation_error_schema() {
+        let db = memory_db().await;
+        let (_, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+            .nest("/api/v1/auth", auth_router(db))
+            .split_for_parts();
+        let spec = serde_json::to_value(&api).unwrap();
+
+        assert!(spec["components"]["schemas"]["ValidationErrorResponse"].is_object());
+        assert_eq!(
+            spec["paths"]["/api/v1/auth/register"]["post"]["responses"]["400"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/ValidationErrorResponse"
+        );
+    }
+}
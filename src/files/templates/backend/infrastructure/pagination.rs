@@ -0,0 +1,362 @@
+use axum::http::StatusCode;
+use std::{env, fs, sync::OnceLock};
+
+use crate::infrastructure::app_error::AppError;
+
+/// Deepest offset served when nothing is configured, deeper pages scan and discard every row
+/// before them
+pub const DEFAULT_MAX_OFFSET: u64 = 10_000;
+
+static PAGINATION_CONFIG: OnceLock<PaginationConfig> = OnceLock::new();
+
+/// Entities with paginated list endpoints
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaginatedEntity {
+    AuditLogs,
+    Users,
+    Roles,
+    TableRecords,
+}
+
+impl PaginatedEntity {
+    /// Key used in rext.toml (`[pagination.<key>]`) and env vars (`PAGINATION_<KEY>_*`)
+    pub fn key(&self) -> &'static str {
+        match self {
+            PaginatedEntity::AuditLogs => "audit_logs",
+            PaginatedEntity::Users => "users",
+            PaginatedEntity::Roles => "roles",
+            PaginatedEntity::TableRecords => "table_records",
+        }
+    }
+
+    /// Limits used when nothing is configured
+    pub fn builtin_limits(&self) -> PageLimits {
+        match self {
+            PaginatedEntity::AuditLogs => PageLimits::new(50, 500),
+            PaginatedEntity::Users => PageLimits::new(25, 100),
+            PaginatedEntity::Roles => PageLimits::new(10, 100),
+            PaginatedEntity::TableRecords => PageLimits::new(25, 200),
+        }
+    }
+
+    fn all() -> [PaginatedEntity; 4] {
+        [
+            PaginatedEntity::AuditLogs,
+            PaginatedEntity::Users,
+            PaginatedEntity::Roles,
+            PaginatedEntity::TableRecords,
+        ]
+    }
+}
+
+/// Default and maximum page size for a list endpoint, and how deep its pages may go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageLimits {
+    pub default_limit: u64,
+    pub max_limit: u64,
+    /// Most rows a page may skip, None for no limit
+    pub max_offset: Option<u64>,
+}
+
+impl PageLimits {
+    pub fn new(default_limit: u64, max_limit: u64) -> Self {
+        Self {
+            default_limit,
+            max_limit,
+            max_offset: Some(DEFAULT_MAX_OFFSET),
+        }
+    }
+
+    /// Rows to skip for a 1-based page, 400 if that is deeper than `max_offset`
+    pub fn offset(&self, page: u64, limit: u64) -> Result<u64, AppError> {
+        // Databases take signed offsets, absurd pages stop at the largest one
+        let offset = page
+            .saturating_sub(1)
+            .saturating_mul(limit)
+            .min(i64::MAX as u64);
+        match self.max_offset {
+            Some(max_offset) if offset > max_offset => Err(AppError {
+                message: format!(
+                    "Page {} is too deep, only the first {} rows can be paged through; narrow the list down with filters",
+                    page,
+                    max_offset.saturating_add(limit)
+                ),
+                status_code: StatusCode::BAD_REQUEST,
+            }),
+            _ => Ok(offset),
+        }
+    }
+
+    /// Page size to use for a request, the default if omitted, clamped to 1..=max
+    pub fn resolve(&self, requested: Option<u64>) -> u64 {
+        requested
+            .unwrap_or(self.default_limit)
+            .clamp(1, self.max_limit.max(1))
+    }
+}
+
+/// Per-entity page size limits
+///
+/// Loaded from `[pagination.<entity>]` tables in rext.toml, overridden by
+/// `PAGINATION_<ENTITY>_DEFAULT_LIMIT` / `PAGINATION_<ENTITY>_MAX_LIMIT` env vars. The page depth
+/// comes from `max_offset` in `[pagination]` or `PAGINATION_MAX_OFFSET` (0 for no limit), and
+/// can be set per entity the same way as the page sizes.
+#[derive(Debug, Clone)]
+pub struct PaginationConfig {
+    limits: Vec<(PaginatedEntity, PageLimits)>,
+}
+
+impl PaginationConfig {
+    /// Global pagination config, loaded on first use
+    pub fn get() -> &'static PaginationConfig {
+        PAGINATION_CONFIG.get_or_init(Self::load)
+    }
+
+    /// Loads the config from rext.toml and the environment
+    pub fn load() -> Self {
+        let rext_toml = fs::read_to_string("rext.toml").ok();
+        Self::from_sources(rext_toml.as_deref(), |key| env::var(key).ok())
+    }
+
+    /// Builds the config from rext.toml contents and an env lookup
+    pub fn from_sources(rext_toml: Option<&str>, env_var: impl Fn(&str) -> Option<String>) -> Self {
+        let pagination_table = rext_toml
+            .and_then(|content| toml::from_str::<toml::Value>(content).ok())
+            .and_then(|value| value.get("pagination").cloned());
+
+        let parse_max_offset = |value: u64| (value > 0).then_some(value);
+        let mut max_offset = Some(DEFAULT_MAX_OFFSET);
+        if let Some(value) = pagination_table
+            .as_ref()
+            .and_then(|table| table.get("max_offset"))
+            .and_then(|value| value.as_integer())
+        {
+            max_offset = parse_max_offset(value.max(0) as u64);
+        }
+        if let Some(value) = env_var("PAGINATION_MAX_OFFSET").and_then(|value| value.parse().ok()) {
+            max_offset = parse_max_offset(value);
+        }
+
+        let limits = PaginatedEntity::all()
+            .into_iter()
+            .map(|entity| {
+                let mut limits = entity.builtin_limits();
+                limits.max_offset = max_offset;
+
+                if let Some(table) = pagination_table.as_ref().and_then(|t| t.get(entity.key())) {
+                    if let Some(value) = table.get("default_limit").and_then(|v| v.as_integer()) {
+                        limits.default_limit = value.max(1) as u64;
+                    }
+                    if let Some(value) = table.get("max_limit").and_then(|v| v.as_integer()) {
+                        limits.max_limit = value.max(1) as u64;
+                    }
+                    if let Some(value) = table.get("max_offset").and_then(|v| v.as_integer()) {
+                        limits.max_offset = parse_max_offset(value.max(0) as u64);
+                    }
+                }
+
+                let prefix = format!("PAGINATION_{}", entity.key().to_uppercase());
+                if let Some(value) = env_var(&format!("{}_DEFAULT_LIMIT", prefix))
+                    .and_then(|value| value.parse().ok())
+                {
+                    limits.default_limit = value;
+                }
+                if let Some(value) =
+                    env_var(&format!("{}_MAX_LIMIT", prefix)).and_then(|value| value.parse().ok())
+                {
+                    limits.max_limit = value;
+                }
+                if let Some(value) =
+                    env_var(&format!("{}_MAX_OFFSET", prefix)).and_then(|value| value.parse().ok())
+                {
+                    limits.max_offset = parse_max_offset(value);
+                }
+
+                (entity, limits)
+            })
+            .collect();
+
+        Self { limits }
+    }
+
+    /// Limits for an entity
+    pub fn limits(&self, entity: PaginatedEntity) -> PageLimits {
+        self.limits
+            .iter()
+            .find(|(configured, _)| *configured == entity)
+            .map(|(_, limits)| *limits)
+            .unwrap_or_else(|| entity.builtin_limits())
+    }
+}
+
+/// Query parameters for a paginated list, resolves page size against the entity's limits
+pub trait Paginated {
+    const ENTITY: PaginatedEntity;
+
+    /// Page number as sent by the client
+    fn requested_page(&self) -> u64;
+
+    /// Page size as sent by the client, None if omitted
+    fn requested_limit(&self) -> Option<u64>;
+
+    /// 1-based page number
+    fn page(&self) -> u64 {
+        self.requested_page().max(1)
+    }
+
+    /// Page size after applying the configured default and max
+    fn limit(&self) -> u64 {
+        PaginationConfig::get()
+            .limits(Self::ENTITY)
+            .resolve(self.requested_limit())
+    }
+
+    /// Number of rows to skip, 400 if the page is deeper than the configured max offset
+    fn offset(&self) -> Result<u64, AppError> {
+        PaginationConfig::get()
+            .limits(Self::ENTITY)
+            .offset(self.page(), self.limit())
+    }
+
+    /// Whether the total is counted, skipping the count saves a scan of large tables
+    fn with_total(&self) -> bool {
+        true
+    }
+}
+
+/// Splits rows fetched with a limit of `limit + 1` into the page and whether more rows follow
+pub fn split_page<T>(mut rows: Vec<T>, limit: u64) -> (Vec<T>, bool) {
+    let has_more = rows.len() as u64 > limit;
+    rows.truncate(limit as usize);
+    (rows, has_more)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const REXT_TOML: &str = r#"
+[pagination.audit_logs]
+default_limit = 40
+max_limit = 400
+
+[pagination.roles]
+default_limit = 5
+max_limit = 20
+"#;
+
+    fn config(env: &[(&str, &str)]) -> PaginationConfig {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        PaginationConfig::from_sources(Some(REXT_TOML), |key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn test_split_page() {
+        assert_eq!(split_page(vec![1, 2, 3], 2), (vec![1, 2], true));
+        assert_eq!(split_page(vec![1, 2], 2), (vec![1, 2], false));
+        assert_eq!(split_page(Vec::<i32>::new(), 2), (vec![], false));
+    }
+
+    #[test]
+    fn test_each_entity_uses_its_default_when_limit_omitted() {
+        let config = config(&[("PAGINATION_USERS_DEFAULT_LIMIT", "15")]);
+
+        assert_eq!(config.limits(PaginatedEntity::AuditLogs).resolve(None), 40);
+        assert_eq!(config.limits(PaginatedEntity::Roles).resolve(None), 5);
+        assert_eq!(config.limits(PaginatedEntity::Users).resolve(None), 15);
+        assert_eq!(
+            config.limits(PaginatedEntity::TableRecords).resolve(None),
+            25
+        );
+    }
+
+    #[test]
+    fn test_each_entity_clamps_to_its_max() {
+        let config = config(&[("PAGINATION_TABLE_RECORDS_MAX_LIMIT", "50")]);
+
+        assert_eq!(
+            config
+                .limits(PaginatedEntity::AuditLogs)
+                .resolve(Some(10_000)),
+            400
+        );
+        assert_eq!(
+            config.limits(PaginatedEntity::Roles).resolve(Some(10_000)),
+            20
+        );
+        assert_eq!(
+            config.limits(PaginatedEntity::Users).resolve(Some(10_000)),
+            100
+        );
+        assert_eq!(
+            config
+                .limits(PaginatedEntity::TableRecords)
+                .resolve(Some(10_000)),
+            50
+        );
+        assert_eq!(config.limits(PaginatedEntity::Roles).resolve(Some(0)), 1);
+    }
+
+    #[test]
+    fn test_env_overrides_rext_toml() {
+        let config = config(&[("PAGINATION_ROLES_DEFAULT_LIMIT", "12")]);
+        assert_eq!(
+            config.limits(PaginatedEntity::Roles),
+            PageLimits::new(12, 20)
+        );
+    }
+
+    #[test]
+    fn test_page_beyond_max_offset_rejected() {
+        let config = config(&[("PAGINATION_MAX_OFFSET", "100")]);
+        let limits = config.limits(PaginatedEntity::Users);
+        assert_eq!(limits.max_offset, Some(100));
+
+        // Page 5 of 25 skips exactly 100 rows
+        assert_eq!(limits.offset(1, 25).unwrap(), 0);
+        assert_eq!(limits.offset(5, 25).unwrap(), 100);
+        let err = limits.offset(6, 25).unwrap_err();
+        assert_eq!(err.status_code, StatusCode::BAD_REQUEST);
+        assert!(err.message.contains("filters"));
+
+        // Doesn't overflow on absurd pages
+        assert!(limits.offset(u64::MAX, 25).is_err());
+    }
+
+    #[test]
+    fn test_max_offset_per_entity_and_disabled() {
+        let rext_toml = r#"
+[pagination]
+max_offset = 1000
+
+[pagination.audit_logs]
+max_offset = 0
+"#;
+        let config = PaginationConfig::from_sources(Some(rext_toml), |key| {
+            (key == "PAGINATION_ROLES_MAX_OFFSET").then(|| "50".to_string())
+        });
+
+        assert_eq!(config.limits(PaginatedEntity::Users).max_offset, Some(1000));
+        assert_eq!(config.limits(PaginatedEntity::Roles).max_offset, Some(50));
+        // 0 turns the limit off
+        let audit_logs = config.limits(PaginatedEntity::AuditLogs);
+        assert_eq!(audit_logs.max_offset, None);
+        assert_eq!(audit_logs.offset(1_000_000, 50).unwrap(), 49_999_950);
+        assert_eq!(
+            audit_logs.offset(u64::MAX, u64::MAX).unwrap(),
+            i64::MAX as u64
+        );
+    }
+
+    #[test]
+    fn test_builtin_limits_without_config() {
+        let config = PaginationConfig::from_sources(None, |_| None);
+        for entity in PaginatedEntity::all() {
+            assert_eq!(config.limits(entity), entity.builtin_limits());
+        }
+    }
+}
@@ -2,9 +2,28 @@ use apalis::prelude::*;
 use apalis_cron::{CronStream, Schedule};
 use apalis_sql::sqlite::SqliteStorage;
 use chrono::{DateTime, Utc};
-use sea_orm::sqlx::SqlitePool;
+use sea_orm::{DatabaseConnection, sqlx::SqlitePool};
 use serde::{Deserialize, Serialize};
-use std::{io::Error, str::FromStr};
+use std::{
+    env, fs,
+    io::Error,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::control::services::audit_compaction_service::{
+    AUDIT_COMPACTION_SCHEDULE, AuditCompactionService,
+};
+use crate::infrastructure::{database::DatabaseManager, logging::AuditLogConfig};
+
+/// Default hours between database backups
+pub const DEFAULT_DATABASE_BACKUP_INTERVAL_HOURS: u64 = 24;
+
+/// Default number of database backups kept
+pub const DEFAULT_DATABASE_BACKUP_KEEP: usize = 7;
 
 /// Reminder structure for scheduled tasks
 #[derive(Default, Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +35,43 @@ impl From<DateTime<Utc>> for Reminder {
     }
 }
 
+/// Scheduled SQLite backups
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseBackupConfig {
+    /// Directory the backup files are written to
+    pub dir: PathBuf,
+    /// Time between backups
+    pub interval: Duration,
+    /// Most recent backups kept, older ones are deleted after each backup
+    pub keep: usize,
+}
+
+impl DatabaseBackupConfig {
+    /// Reads DATABASE_BACKUP_DIR, DATABASE_BACKUP_INTERVAL_HOURS and DATABASE_BACKUP_KEEP,
+    /// backups are disabled unless DATABASE_BACKUP_DIR is set
+    pub fn from_env() -> Option<Self> {
+        let dir = env::var("DATABASE_BACKUP_DIR")
+            .ok()
+            .filter(|dir| !dir.trim().is_empty())?;
+        let interval_hours = env::var("DATABASE_BACKUP_INTERVAL_HOURS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|hours| *hours > 0)
+            .unwrap_or(DEFAULT_DATABASE_BACKUP_INTERVAL_HOURS);
+        let keep = env::var("DATABASE_BACKUP_KEEP")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|keep| *keep > 0)
+            .unwrap_or(DEFAULT_DATABASE_BACKUP_KEEP);
+
+        Some(Self {
+            dir: PathBuf::from(dir),
+            interval: Duration::from_secs(interval_hours * 60 * 60),
+            keep,
+        })
+    }
+}
+
 /// Task scheduler manager
 pub struct SchedulerManager;
 
@@ -26,13 +82,34 @@ impl SchedulerManager {
         Ok(())
     }
 
-    /// Creates and runs the task scheduler
-    pub async fn run_scheduler(database_url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Strips the bodies from audit log entries older than `retention`
+    pub async fn compact_audit_logs(
+        _job: Reminder,
+        db: Data<DatabaseConnection>,
+        retention: Data<chrono::Duration>,
+    ) -> Result<(), Error> {
+        match AuditCompactionService::compact(&db, Utc::now() - *retention).await {
+            Ok(0) => {}
+            Ok(compacted) => info!("Stripped bodies from {} audit log entries", compacted),
+            Err(e) => error!(error = %e.message, "Audit log compaction failed"),
+        }
+        Ok(())
+    }
+
+    /// Creates and runs the task scheduler, its cron storage lives in the job queue database
+    pub async fn run_scheduler(
+        job_queue_url: &str,
+        database_url: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Create DB pool for cron
-        let cron_pool = SqlitePool::connect(database_url).await.unwrap();
+        let cron_pool = SqlitePool::connect(job_queue_url).await.unwrap();
         let schedule = Schedule::from_str("0 */1 * * * *").unwrap(); // every minute
         println!("Starting cron worker with schedule: {}", schedule);
 
+        if let Some(config) = DatabaseBackupConfig::from_env() {
+            Self::start_database_backups(database_url, config).await?;
+        }
+
         let cron_stream = CronStream::new(schedule);
         let sqlite_storage = SqliteStorage::new(cron_pool);
         let cron_backend = cron_stream.pipe_to_storage(sqlite_storage);
@@ -41,7 +118,199 @@ impl SchedulerManager {
             .backend(cron_backend)
             .build_fn(Self::handle_tick);
 
-        Monitor::new().register(worker).run().await.unwrap();
+        let mut monitor = Monitor::new().register(worker);
+
+        // Compaction writes, so it's skipped against a read-only database
+        let retention = AuditLogConfig::get()
+            .body_retention()
+            .filter(|_| !DatabaseManager::is_read_only());
+        if let Some(retention) = retention {
+            let compaction = WorkerBuilder::new("audit-compaction")
+                .data(DatabaseManager::create_connection().await?)
+                .data(retention)
+                .backend(CronStream::new(Schedule::from_str(
+                    AUDIT_COMPACTION_SCHEDULE,
+                )?))
+                .build_fn(Self::compact_audit_logs);
+            monitor = monitor.register(compaction);
+        }
+
+        monitor.run().await.unwrap();
         Ok(())
     }
+
+    /// Backs up a SQLite database on the configured interval, rotating old backups
+    ///
+    /// Other backends are skipped with a warning, their native tooling (pg_dump, mysqldump)
+    /// handles online backups better.
+    pub async fn start_database_backups(
+        database_url: &str,
+        config: DatabaseBackupConfig,
+    ) -> Result<Option<JoinHandle<()>>, Box<dyn std::error::Error>> {
+        if !database_url.trim().to_lowercase().starts_with("sqlite:") {
+            warn!(
+                "DATABASE_BACKUP_DIR is set but scheduled backups only support SQLite, use the database's native backup tooling instead"
+            );
+            return Ok(None);
+        }
+
+        let pool = SqlitePool::connect(database_url).await?;
+        info!(
+            "Backing up the database to {} every {:?}, keeping {}",
+            config.dir.display(),
+            config.interval,
+            config.keep
+        );
+
+        Ok(Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(config.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                match Self::backup_sqlite(&pool, &config.dir, Utc::now()).await {
+                    Ok(path) => info!("Database backed up to {}", path.display()),
+                    Err(e) => {
+                        error!(error = %e, "Database backup failed");
+                        continue;
+                    }
+                }
+                if let Err(e) = Self::rotate_backups(&config.dir, config.keep) {
+                    error!(error = %e, "Database backup rotation failed");
+                }
+            }
+        })))
+    }
+
+    /// Writes an online copy of the database to `dir` with `VACUUM INTO`, returning its path
+    pub async fn backup_sqlite(
+        pool: &SqlitePool,
+        dir: &Path,
+        now: DateTime<Utc>,
+    ) -> Result<PathBuf, String> {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create backup directory {}: {}", dir.display(), e))?;
+
+        let path = dir.join(format!(
+            "backup-{}.sqlite3",
+            now.format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        sea_orm::sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to back up to {}: {}", path.display(), e))?;
+
+        Ok(path)
+    }
+
+    /// Deletes all but the newest `keep` backups in `dir`, returning how many were deleted
+    pub fn rotate_backups(dir: &Path, keep: usize) -> Result<usize, Error> {
+        let mut backups: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("backup-") && name.ends_with(".sqlite3"))
+            })
+            .collect();
+
+        // Timestamped names sort oldest first
+        backups.sort();
+        let expired = backups.len().saturating_sub(keep);
+        for path in &backups[..expired] {
+            fs::remove_file(path)?;
+        }
+
+        Ok(expired)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::test_support::temp_dir;
+
+    #[tokio::test]
+    async fn test_sqlite_backup_written() {
+        let pool = sea_orm::sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sea_orm::sqlx::query("CREATE TABLE notes (body TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sea_orm::sqlx::query("INSERT INTO notes (body) VALUES ('backed up')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let dir = temp_dir();
+        let path = SchedulerManager::backup_sqlite(&pool, dir.path(), Utc::now())
+            .await
+            .unwrap();
+        assert!(path.starts_with(dir.path()));
+        assert!(path.exists());
+
+        // The backup is a complete database
+        let backup = SqlitePool::connect(&format!("sqlite:{}", path.display()))
+            .await
+            .unwrap();
+        let (body,): (String,) = sea_orm::sqlx::query_as("SELECT body FROM notes")
+            .fetch_one(&backup)
+            .await
+            .unwrap();
+        assert_eq!(body, "backed up");
+
+        backup.close().await;
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest_backups() {
+        let temp = temp_dir();
+        let dir = temp.path();
+        for day in 1..=5 {
+            fs::write(
+                dir.join(format!("backup-2025010{}T030000.000Z.sqlite3", day)),
+                b"",
+            )
+            .unwrap();
+        }
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        assert_eq!(SchedulerManager::rotate_backups(dir, 2).unwrap(), 3);
+
+        let mut remaining: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            [
+                "backup-20250104T030000.000Z.sqlite3",
+                "backup-20250105T030000.000Z.sqlite3",
+                "notes.txt"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_backups_skipped_for_other_backends() {
+        let temp = temp_dir();
+        let config = DatabaseBackupConfig {
+            dir: temp.path().join("backups"),
+            interval: Duration::from_secs(60),
+            keep: 1,
+        };
+
+        let handle =
+            SchedulerManager::start_database_backups("postgres://localhost/app", config.clone())
+                .await
+                .unwrap();
+        assert!(handle.is_none());
+        assert!(!config.dir.exists());
+    }
 }
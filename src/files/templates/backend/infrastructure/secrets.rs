@@ -0,0 +1,298 @@
+//! Sensitive configuration (JWT secret, SMTP password, database URL) resolved through a
+//! pluggable provider, environment variables by default
+use std::{collections::HashMap, env, future::Future, sync::OnceLock};
+
+/// Secrets resolved through the provider
+pub const SECRET_NAMES: &[&str] = &[
+    "JWT_SECRET",
+    "EMAIL_SMTP_PASSWORD",
+    "SENDGRID_API_KEY",
+    "DATABASE_URL",
+];
+
+/// Resolves named secrets
+pub trait SecretProvider: Send + Sync {
+    /// Provider name for startup logs
+    fn name(&self) -> &'static str;
+
+    /// Value of the secret, None if the provider doesn't have it
+    fn get_secret(&self, name: &str) -> Option<String>;
+}
+
+/// Reads each secret from the environment variable of the same name
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+    fn name(&self) -> &'static str {
+        "env"
+    }
+
+    fn get_secret(&self, name: &str) -> Option<String> {
+        env::var(name).ok()
+    }
+}
+
+/// External store secrets are fetched from once at startup, like Vault or a cloud secrets manager
+pub trait SecretSource {
+    /// Source name for startup logs
+    fn name(&self) -> &'static str;
+
+    /// Fetches the named secrets, names the source doesn't have are left out
+    fn fetch(
+        &self,
+        names: &[&str],
+    ) -> impl Future<Output = Result<HashMap<String, String>, String>> + Send;
+}
+
+/// Serves secrets fetched from a `SecretSource`, never falls back to the environment
+pub struct FetchedSecretProvider {
+    source_name: &'static str,
+    secrets: HashMap<String, String>,
+}
+
+impl FetchedSecretProvider {
+    /// Fetches the secrets once, fails if the source can't be reached
+    pub async fn load<S: SecretSource>(source: &S, names: &[&str]) -> Result<Self, String> {
+        let secrets = source
+            .fetch(names)
+            .await
+            .map_err(|e| format!("Failed to fetch secrets from {}: {}", source.name(), e))?;
+
+        Ok(Self {
+            source_name: source.name(),
+            secrets,
+        })
+    }
+}
+
+impl SecretProvider for FetchedSecretProvider {
+    fn name(&self) -> &'static str {
+        self.source_name
+    }
+
+    fn get_secret(&self, name: &str) -> Option<String> {
+        self.secrets.get(name).cloned()
+    }
+}
+
+static SECRET_PROVIDER: OnceLock<Box<dyn SecretProvider>> = OnceLock::new();
+
+/// Global access to secrets through the configured provider
+pub struct Secrets;
+
+impl Secrets {
+    /// Sets up the provider selected by SECRETS_PROVIDER ("env" or "vault", defaults to env)
+    pub async fn initialize() -> Result<(), Box<dyn std::error::Error>> {
+        let provider_name = env::var("SECRETS_PROVIDER").unwrap_or_else(|_| "env".to_string());
+
+        let provider: Box<dyn SecretProvider> = match provider_name.as_str() {
+            "env" => Box::new(EnvSecretProvider),
+            #[cfg(feature = "vault")]
+            "vault" => {
+                let source = vault::VaultSecretSource::from_env()?;
+                Box::new(FetchedSecretProvider::load(&source, SECRET_NAMES).await?)
+            }
+            #[cfg(not(feature = "vault"))]
+            "vault" => {
+                return Err(
+                    "SECRETS_PROVIDER=vault requires building with --features vault".into(),
+                );
+            }
+            other => return Err(format!("Unknown SECRETS_PROVIDER: {}", other).into()),
+        };
+
+        println!("Secrets provider: {}", provider.name());
+        Self::install(provider);
+        Ok(())
+    }
+
+    /// Installs the provider, only the first call has an effect
+    pub fn install(provider: Box<dyn SecretProvider>) {
+        if SECRET_PROVIDER.set(provider).is_err() {
+            println!("⚠️  Secrets provider already installed, ignoring");
+        }
+    }
+
+    /// Value of the secret, read from the environment until a provider is installed
+    pub fn get(name: &str) -> Option<String> {
+        match SECRET_PROVIDER.get() {
+            Some(provider) => provider.get_secret(name),
+            None => EnvSecretProvider.get_secret(name),
+        }
+    }
+}
+
+/// HashiCorp Vault KV v2 source (requires building with --features vault)
+#[cfg(feature = "vault")]
+pub mod vault {
+    use super::*;
+    use crate::infrastructure::trace_context::TraceContext;
+    use axum::http::HeaderMap;
+
+    /// Reads all secrets from one KV v2 entry whose keys are the secret names
+    pub struct VaultSecretSource {
+        /// Vault address, e.g. https://vault.example.com:8200
+        pub address: String,
+        pub token: String,
+        /// API path of the entry, e.g. secret/data/my-app
+        pub path: String,
+    }
+
+    impl VaultSecretSource {
+        /// Reads VAULT_ADDR, VAULT_TOKEN and VAULT_SECRET_PATH
+        pub fn from_env() -> Result<Self, String> {
+            let var = |name: &str| {
+                env::var(name).map_err(|_| format!("{} environment variable is required", name))
+            };
+
+            Ok(Self {
+                address: var("VAULT_ADDR")?,
+                token: var("VAULT_TOKEN")?,
+                path: var("VAULT_SECRET_PATH")?,
+            })
+        }
+    }
+
+    impl SecretSource for VaultSecretSource {
+        fn name(&self) -> &'static str {
+            "vault"
+        }
+
+        async fn fetch(&self, names: &[&str]) -> Result<HashMap<String, String>, String> {
+            let url = format!(
+                "{}/v1/{}",
+                self.address.trim_end_matches('/'),
+                self.path.trim_start_matches('/')
+            );
+
+            // Empty at startup, only set when fetched while handling a request
+            let mut trace_headers = HeaderMap::new();
+            TraceContext::inject_current(&mut trace_headers);
+
+            let response: serde_json::Value = reqwest::Client::new()
+                .get(url)
+                .headers(trace_headers)
+                .header("X-Vault-Token", &self.token)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| e.to_string())?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let data = response["data"]["data"]
+                .as_object()
+                .ok_or("Vault response has no data.data object")?;
+
+            Ok(names
+                .iter()
+                .filter_map(|name| {
+                    data.get(*name)
+                        .and_then(|value| value.as_str())
+                        .map(|value| (name.to_string(), value.to_string()))
+                })
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockSecretSource {
+        secrets: HashMap<String, String>,
+        fetches: AtomicUsize,
+    }
+
+    impl SecretSource for MockSecretSource {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        async fn fetch(&self, names: &[&str]) -> Result<HashMap<String, String>, String> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(names
+                .iter()
+                .filter_map(|name| {
+                    self.secrets
+                        .get(*name)
+                        .map(|value| (name.to_string(), value.clone()))
+                })
+                .collect())
+        }
+    }
+
+    struct UnreachableSecretSource;
+
+    impl SecretSource for UnreachableSecretSource {
+        fn name(&self) -> &'static str {
+            "unreachable"
+        }
+
+        async fn fetch(&self, _names: &[&str]) -> Result<HashMap<String, String>, String> {
+            Err("connection refused".to_string())
+        }
+    }
+
+    #[test]
+    fn test_env_provider_reads_environment() {
+        unsafe {
+            env::set_var("REXT_TEST_SECRET", "from-env");
+        }
+
+        assert_eq!(
+            EnvSecretProvider.get_secret("REXT_TEST_SECRET").as_deref(),
+            Some("from-env")
+        );
+        assert_eq!(
+            EnvSecretProvider.get_secret("REXT_TEST_MISSING_SECRET"),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetched_provider_serves_external_secrets() {
+        let source = MockSecretSource {
+            secrets: HashMap::from([
+                ("REXT_TEST_FETCHED".to_string(), "from-vault".to_string()),
+                ("UNREQUESTED".to_string(), "ignored".to_string()),
+            ]),
+            fetches: AtomicUsize::new(0),
+        };
+        unsafe {
+            env::set_var("REXT_TEST_FETCHED", "from-env");
+            env::set_var("REXT_TEST_ENV_ONLY", "from-env");
+        }
+
+        let provider =
+            FetchedSecretProvider::load(&source, &["REXT_TEST_FETCHED", "REXT_TEST_ENV_ONLY"])
+                .await
+                .unwrap();
+
+        assert_eq!(provider.name(), "mock");
+        // The external value wins and the environment is never consulted
+        assert_eq!(
+            provider.get_secret("REXT_TEST_FETCHED").as_deref(),
+            Some("from-vault")
+        );
+        assert_eq!(provider.get_secret("REXT_TEST_ENV_ONLY"), None);
+        assert_eq!(provider.get_secret("UNREQUESTED"), None);
+
+        // Fetched once at load, lookups don't hit the source
+        provider.get_secret("REXT_TEST_FETCHED");
+        assert_eq!(source.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_source_fails_load() {
+        let err = FetchedSecretProvider::load(&UnreachableSecretSource, SECRET_NAMES)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.contains("unreachable"));
+        assert!(err.contains("connection refused"));
+    }
+}
@@ -1,12 +1,9 @@
-use axum::{Router, middleware, routing::get};
+#[cfg(feature = "admin")]
+use axum::routing::get;
+use axum::{Router, middleware};
 use sea_orm::DatabaseConnection;
-use std::{
-    env,
-    io::Error,
-    net::{Ipv4Addr, SocketAddr},
-};
+use std::{env, io::Error, net::SocketAddr, sync::Arc, time::Duration};
 use tokio::net::TcpListener;
-use tower_http::services::{ServeDir, ServeFile};
 use utoipa::OpenApi;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_rapidoc::RapiDoc;
@@ -14,11 +11,34 @@ use utoipa_redoc::{Redoc, Servable};
 use utoipa_scalar::{Scalar, Servable as ScalarServable};
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::bridge::middleware::logging::request_logging_middleware;
+#[cfg(feature = "admin")]
+use crate::bridge::middleware::admin::admin_middleware;
+use crate::bridge::middleware::catch_panic::catch_panic_middleware;
+use crate::bridge::middleware::host_allowlist::{HostAllowlistConfig, host_allowlist_middleware};
+use crate::bridge::middleware::logging::{RequestLogging, request_logging_middleware};
+use crate::bridge::middleware::rate_limit::{RATE_LIMITER, ip_rate_limit_middleware};
+use crate::bridge::middleware::read_only::read_only_middleware;
+use crate::bridge::middleware::secure_transport::{
+    SecureTransportConfig, SecureTransportMode, secure_transport_middleware,
+};
+use crate::bridge::middleware::trace_context::trace_context_middleware;
+#[cfg(feature = "admin")]
 use crate::bridge::routes::admin::admin_router;
 use crate::bridge::routes::auth::auth_router;
+use crate::bridge::routes::setup::setup_router;
+use crate::bridge::routes::system::{dev_router, system_router};
+use crate::control::services::openapi_spec_service::OpenApiSpecService;
+use crate::control::services::server_config::ServerConfigService;
+use crate::control::services::setup_service::SetupService;
+use crate::infrastructure::build_info::version_endpoint_enabled;
 use crate::infrastructure::cors::CorsManager;
+use crate::infrastructure::database::DatabaseManager;
+use crate::infrastructure::frontend::FrontendMode;
 use crate::infrastructure::openapi::ApiDoc;
+use crate::infrastructure::websocket::WEBSOCKET_MANAGER;
+
+/// Seconds WebSocket clients get to receive pending broadcasts and the close frame on shutdown
+pub const WEBSOCKET_SHUTDOWN_TIMEOUT_SECS: u64 = 5;
 
 /// Server manager
 pub struct ServerManager;
@@ -29,54 +49,135 @@ impl ServerManager {
         let environment = env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string());
 
         // Create the OpenAPI Router and nested routes
-        let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-            .nest("/api/v1/auth", auth_router(db.clone()))
-            .nest("/api/v1/admin", admin_router(db.clone()))
-            .split_for_parts();
-
-        // Create WebSocket router with database state
-        let websocket_router = Router::new()
-            .route(
-                "/api/v1/admin/ws",
-                get(crate::bridge::handlers::websocket::websocket_handler),
-            )
-            .with_state(db.clone());
-
-        // Merge routes with OpenAPI documentation and websocket and middleware
+        let mut api_router = OpenApiRouter::with_openapi(ApiDoc::openapi())
+            .nest("/api/v1/auth", auth_router(db.clone()));
+
+        #[cfg(feature = "admin")]
+        {
+            api_router = api_router.nest("/api/v1/admin", admin_router(db.clone()));
+        }
+
+        // The one-time setup endpoint only exists in first-run setup mode
+        if SetupService::is_enabled() {
+            api_router = api_router.nest("/api/v1", setup_router(db.clone()));
+        }
+
+        // GET /version reports the compiled-in build unless VERSION_ENDPOINT_ENABLED is false
+        if version_endpoint_enabled() {
+            api_router = api_router.merge(system_router());
+        }
+
+        let (mut router, api) = api_router.split_for_parts();
+
+        // Keep the spec the frontend client is generated from current during development
+        if environment == "development" {
+            let spec_path = OpenApiSpecService::spec_path();
+            match OpenApiSpecService::write_spec(&api, &spec_path) {
+                Ok(true) => println!("OpenAPI spec written to {}", spec_path.display()),
+                Ok(false) => {}
+                Err(e) => println!("⚠️  {}", e.message),
+            }
+            router = router.merge(dev_router(Arc::new(api.clone()), spec_path));
+        }
+
+        // Merge routes with OpenAPI documentation
         let mut router = router
             .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", api.clone()))
             .merge(Redoc::with_url("/redoc", api.clone()))
             .merge(RapiDoc::new("/api-docs/openapi.json").path("/rapidoc"))
-            .merge(Scalar::with_url("/scalar", api))
-            .merge(websocket_router)
+            .merge(Scalar::with_url("/scalar", api));
+
+        // The admin monitoring feed, authenticated like the rest of the admin API
+        #[cfg(feature = "admin")]
+        {
+            let websocket_router = Router::new()
+                .route(
+                    "/api/v1/admin/ws",
+                    get(crate::bridge::handlers::websocket::websocket_handler),
+                )
+                .route_layer(middleware::from_fn_with_state(db.clone(), admin_middleware))
+                .with_state(db.clone());
+            router = router.merge(websocket_router);
+        }
+
+        // Add middleware
+        let mut router = router
+            // Inside request logging so a panicking handler still gets a request id and an audit entry
+            .route_layer(middleware::from_fn(catch_panic_middleware))
             .route_layer(middleware::from_fn_with_state(
-                db.clone(),
+                RequestLogging::new(db.clone()),
                 request_logging_middleware,
+            ))
+            // Outermost so every log line for the request is inside its trace span
+            .route_layer(middleware::from_fn(trace_context_middleware));
+
+        // Refuse mutating requests up front when DATABASE_READ_ONLY is set
+        if DatabaseManager::is_read_only() {
+            router = router.route_layer(middleware::from_fn(read_only_middleware));
+        }
+
+        // Limit requests per client IP, per-user limits are applied behind the auth middleware
+        if RATE_LIMITER.config().per_ip.is_some() {
+            router = router.route_layer(middleware::from_fn_with_state(
+                &*RATE_LIMITER,
+                ip_rate_limit_middleware,
+            ));
+        }
+
+        // Redirect or reject plain-HTTP requests when ENFORCE_HTTPS is set
+        let secure_transport = SecureTransportConfig::from_env();
+        if secure_transport.mode != SecureTransportMode::Off {
+            router = router.layer(middleware::from_fn_with_state(
+                secure_transport,
+                secure_transport_middleware,
             ));
+        }
+
+        // Reject spoofed Host/Origin headers, strict by default in production
+        let host_allowlist = HostAllowlistConfig::from_env();
+        if host_allowlist.strict {
+            if host_allowlist.allowed_hosts.is_empty() {
+                println!(
+                    "⚠️  Host allowlist enforced but ALLOWED_HOSTS is empty, all requests will be rejected"
+                );
+            }
+            router = router.layer(middleware::from_fn_with_state(
+                host_allowlist,
+                host_allowlist_middleware,
+            ));
+        }
 
         // Add CORS layer for development
         if environment == "development" {
             router = router.layer(CorsManager::create_cors_layer());
         }
 
-        // Check if we're in production mode and serve static files
-        if environment == "production" {
-            println!("Production mode detected - serving static files from /dist directory");
-            router = router.fallback_service(
-                ServeDir::new("dist").fallback(ServeFile::new("dist/index.html")),
-            );
-        } else {
-            println!("Development mode - static files not served by backend");
-            println!("Frontend running on http://localhost:5173");
+        // Serve static files in production, or proxy the Vite dev server if FRONTEND_DEV_PROXY is set
+        let frontend = FrontendMode::from_env(&environment);
+        match &frontend {
+            FrontendMode::Static(_) => {
+                println!("Production mode detected - serving static files from /dist directory");
+            }
+            FrontendMode::DevProxy(proxy) => {
+                println!(
+                    "Development mode - proxying the frontend from {}",
+                    proxy.target()
+                );
+            }
+            FrontendMode::Separate => {
+                println!("Development mode - static files not served by backend");
+                println!("Frontend running on http://localhost:5173");
+            }
         }
 
-        router
+        frontend.apply(router)
     }
 
-    /// Starts the server
+    /// Starts the server on SERVER_HOST and SERVER_PORT
     pub async fn start_server(router: Router) -> Result<(), Error> {
-        let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 3000));
-        let listener = TcpListener::bind(&address).await?;
+        let host = ServerConfigService::get_host();
+        let listener = TcpListener::bind((host.as_str(), ServerConfigService::get_port())).await?;
+        let address = listener.local_addr()?;
 
         println!("Server running on http://localhost:{}", address.port());
         println!("View API docs at:");
@@ -94,8 +195,56 @@ impl ServerManager {
             address.port()
         );
 
-        axum::serve(listener, router.into_make_service())
-            .await
-            .map_err(|e| Error::new(std::io::ErrorKind::Interrupted, e))
+        // Connect info gives middleware the peer address (trusted proxy checks, audit IPs)
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(Self::shutdown_signal())
+        .await
+        .map_err(|e| Error::new(std::io::ErrorKind::Interrupted, e))
+    }
+
+    /// Resolves on Ctrl+C or SIGTERM, after closing the WebSocket connections
+    ///
+    /// Upgraded WebSocket connections aren't tracked by the graceful shutdown, so they're
+    /// flushed and sent a close frame here before the server stops.
+    async fn shutdown_signal() {
+        let ctrl_c = async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                tracing::error!("Failed to listen for Ctrl+C: {}", e);
+                std::future::pending::<()>().await;
+            }
+        };
+
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to listen for SIGTERM: {}", e);
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        tokio::select! {
+            _ = ctrl_c => {}
+            _ = terminate => {}
+        }
+
+        tracing::info!("Shutting down, closing WebSocket connections");
+        let timeout = Duration::from_secs(WEBSOCKET_SHUTDOWN_TIMEOUT_SECS);
+        if !WEBSOCKET_MANAGER.shutdown(timeout).await {
+            tracing::warn!(
+                "Some WebSocket connections didn't close within {:?}",
+                timeout
+            );
+        }
     }
 }
@@ -0,0 +1,197 @@
+use std::{env, fs, sync::OnceLock};
+
+static SESSION_POLICY_CONFIG: OnceLock<SessionPolicyConfig> = OnceLock::new();
+
+/// Session lifetime used when nothing is configured
+pub const DEFAULT_SESSION_EXPIRY_HOURS: i64 = 24;
+
+/// Expiry and concurrency limits applied to sessions created on login
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionPolicy {
+    /// Hours until a new session expires
+    pub expiry_hours: i64,
+    /// Active sessions a user may hold, the oldest are invalidated past this. None for no limit
+    pub max_sessions: Option<u64>,
+}
+
+impl Default for SessionPolicy {
+    fn default() -> Self {
+        Self {
+            expiry_hours: DEFAULT_SESSION_EXPIRY_HOURS,
+            max_sessions: None,
+        }
+    }
+}
+
+/// Per-role overrides, unset fields fall back to the global policy
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct SessionPolicyOverride {
+    expiry_hours: Option<i64>,
+    max_sessions: Option<Option<u64>>,
+}
+
+/// Global session policy with per-role overrides
+///
+/// Loaded from `[sessions]` and `[sessions.roles.<role name>]` tables in rext.toml. The global
+/// values are overridden by `SESSION_EXPIRY_HOURS` / `SESSION_MAX_CONCURRENT` env vars.
+/// A `max_sessions` of 0 means no limit.
+#[derive(Debug, Clone, Default)]
+pub struct SessionPolicyConfig {
+    default: SessionPolicy,
+    roles: Vec<(String, SessionPolicyOverride)>,
+}
+
+impl SessionPolicyConfig {
+    /// Global session policy config, loaded on first use
+    pub fn get() -> &'static SessionPolicyConfig {
+        SESSION_POLICY_CONFIG.get_or_init(Self::load)
+    }
+
+    /// Loads the config from rext.toml and the environment
+    pub fn load() -> Self {
+        let rext_toml = fs::read_to_string("rext.toml").ok();
+        Self::from_sources(rext_toml.as_deref(), |key| env::var(key).ok())
+    }
+
+    /// Builds the config from rext.toml contents and an env lookup
+    pub fn from_sources(rext_toml: Option<&str>, env_var: impl Fn(&str) -> Option<String>) -> Self {
+        let sessions_table = rext_toml
+            .and_then(|content| toml::from_str::<toml::Value>(content).ok())
+            .and_then(|value| value.get("sessions").cloned());
+
+        let mut default = SessionPolicy::default();
+        if let Some(table) = sessions_table.as_ref() {
+            let table_override = Self::parse_override(table);
+            if let Some(expiry_hours) = table_override.expiry_hours {
+                default.expiry_hours = expiry_hours;
+            }
+            if let Some(max_sessions) = table_override.max_sessions {
+                default.max_sessions = max_sessions;
+            }
+        }
+        if let Some(value) = env_var("SESSION_EXPIRY_HOURS").and_then(|value| value.parse().ok()) {
+            default.expiry_hours = value.max(1);
+        }
+        if let Some(value) =
+            env_var("SESSION_MAX_CONCURRENT").and_then(|value| value.parse::<u64>().ok())
+        {
+            default.max_sessions = Some(value).filter(|max| *max > 0);
+        }
+
+        let roles = sessions_table
+            .as_ref()
+            .and_then(|table| table.get("roles"))
+            .and_then(|roles| roles.as_table())
+            .map(|roles| {
+                roles
+                    .iter()
+                    .map(|(name, table)| (name.clone(), Self::parse_override(table)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self { default, roles }
+    }
+
+    fn parse_override(table: &toml::Value) -> SessionPolicyOverride {
+        SessionPolicyOverride {
+            expiry_hours: table
+                .get("expiry_hours")
+                .and_then(|v| v.as_integer())
+                .map(|value| value.max(1)),
+            max_sessions: table
+                .get("max_sessions")
+                .and_then(|v| v.as_integer())
+                .map(|value| u64::try_from(value).ok().filter(|max| *max > 0)),
+        }
+    }
+
+    /// Policy for a user with the given role, the global policy if the role has no overrides
+    pub fn policy_for(&self, role_name: Option<&str>) -> SessionPolicy {
+        let role_override = role_name.and_then(|role_name| {
+            self.roles
+                .iter()
+                .find(|(name, _)| name == role_name)
+                .map(|(_, role_override)| *role_override)
+        });
+
+        match role_override {
+            Some(role_override) => SessionPolicy {
+                expiry_hours: role_override
+                    .expiry_hours
+                    .unwrap_or(self.default.expiry_hours),
+                max_sessions: role_override
+                    .max_sessions
+                    .unwrap_or(self.default.max_sessions),
+            },
+            None => self.default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const REXT_TOML: &str = r#"
+[sessions]
+expiry_hours = 12
+
+[sessions.roles.admin]
+expiry_hours = 1
+max_sessions = 2
+
+[sessions.roles.support]
+max_sessions = 0
+"#;
+
+    fn config(env: &[(&str, &str)]) -> SessionPolicyConfig {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        SessionPolicyConfig::from_sources(Some(REXT_TOML), |key| env.get(key).cloned())
+    }
+
+    #[test]
+    fn test_builtin_defaults_without_config() {
+        let config = SessionPolicyConfig::from_sources(None, |_| None);
+        assert_eq!(config.policy_for(Some("admin")), SessionPolicy::default());
+        assert_eq!(config.policy_for(None).expiry_hours, 24);
+    }
+
+    #[test]
+    fn test_role_overrides_fall_back_to_global_policy() {
+        let config = config(&[("SESSION_MAX_CONCURRENT", "5")]);
+
+        let admin = config.policy_for(Some("admin"));
+        assert_eq!(admin.expiry_hours, 1);
+        assert_eq!(admin.max_sessions, Some(2));
+
+        let support = config.policy_for(Some("support"));
+        assert_eq!(support.expiry_hours, 12);
+        assert_eq!(support.max_sessions, None);
+
+        let user = config.policy_for(Some("user"));
+        assert_eq!(user.expiry_hours, 12);
+        assert_eq!(user.max_sessions, Some(5));
+        assert_eq!(config.policy_for(None), user);
+    }
+
+    #[test]
+    fn test_env_overrides_global_expiry() {
+        let config = config(&[("SESSION_EXPIRY_HOURS", "48")]);
+        assert_eq!(config.policy_for(None).expiry_hours, 48);
+        assert_eq!(config.policy_for(Some("admin")).expiry_hours, 1);
+    }
+
+    #[test]
+    fn test_negative_max_sessions_means_no_limit() {
+        let rext_toml = "[sessions.roles.guest]\nmax_sessions = -1\n";
+        let config = SessionPolicyConfig::from_sources(Some(rext_toml), |key| {
+            (key == "SESSION_MAX_CONCURRENT").then(|| "5".to_string())
+        });
+        assert_eq!(config.policy_for(Some("guest")).max_sessions, None);
+    }
+}
@@ -0,0 +1,105 @@
+//! In-flight request coalescing, concurrent callers share one computation
+use futures_util::{
+    FutureExt,
+    future::{BoxFuture, Shared},
+};
+use std::{future::Future, sync::Mutex};
+
+/// Runs at most one computation at a time, callers arriving while it runs get its result
+///
+/// Nothing is cached, the first call after a computation finishes starts a new one.
+pub struct SingleFlight<T: Clone> {
+    in_flight: Mutex<Option<Shared<BoxFuture<'static, T>>>>,
+}
+
+impl<T: Clone> Default for SingleFlight<T> {
+    fn default() -> Self {
+        Self {
+            in_flight: Mutex::new(None),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> SingleFlight<T> {
+    /// Joins the computation in flight, or starts one with `compute` if there is none
+    pub async fn run<F, Fut>(&self, compute: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let flight = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.as_ref() {
+                Some(flight) => flight.clone(),
+                None => {
+                    let flight = compute().boxed().shared();
+                    *in_flight = Some(flight.clone());
+                    flight
+                }
+            }
+        };
+
+        let result = flight.clone().await;
+
+        // The first caller to finish clears the flight, unless a newer one already replaced it
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight
+            .as_ref()
+            .is_some_and(|current| current.ptr_eq(&flight))
+        {
+            *in_flight = None;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_calls_share_one_computation() {
+        let flight = Arc::new(SingleFlight::<usize>::default());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| {
+                let flight = flight.clone();
+                let runs = runs.clone();
+                tokio::spawn(async move {
+                    flight
+                        .run(|| async move {
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            runs.fetch_add(1, Ordering::SeqCst) + 1
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 1);
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_calls_compute_again() {
+        let flight = SingleFlight::<usize>::default();
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        for expected in 1..=3 {
+            let runs = runs.clone();
+            let result = flight
+                .run(|| async move { runs.fetch_add(1, Ordering::SeqCst) + 1 })
+                .await;
+            assert_eq!(result, expected);
+        }
+    }
+}
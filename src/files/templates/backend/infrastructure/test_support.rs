@@ -0,0 +1,105 @@
+//! Fixtures shared by the unit tests
+
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use sea_orm_migration::MigratorTrait;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Once};
+
+use crate::control::services::user_service::{NewUserOptions, UserService};
+use crate::domain::user::User;
+use crate::infrastructure::app_error::AppError;
+
+/// Fresh in-memory database without any tables
+///
+/// Limited to one connection, every connection to `sqlite::memory:` opens its own database.
+pub async fn memory_db() -> DatabaseConnection {
+    let mut options = ConnectOptions::new("sqlite::memory:");
+    options.max_connections(1);
+    Database::connect(options).await.unwrap()
+}
+
+/// Fresh in-memory database with every migration applied
+pub async fn setup_db() -> DatabaseConnection {
+    let db = memory_db().await;
+    migration::Migrator::up(&db, None).await.unwrap();
+    db
+}
+
+/// JWT_SECRET the tests sign tokens with
+pub const TEST_JWT_SECRET: &str = "test-secret-at-least-32-bytes-long";
+
+/// Sets JWT_SECRET to `TEST_JWT_SECRET`
+///
+/// Written once per test binary, tests run in parallel and must not write the environment while
+/// others read it.
+pub fn set_test_jwt_secret() {
+    static SET: Once = Once::new();
+    SET.call_once(|| unsafe { std::env::set_var("JWT_SECRET", TEST_JWT_SECRET) });
+}
+
+/// Creates a user whose email is already verified, no verification email is sent
+pub async fn create_verified_user(
+    db: &DatabaseConnection,
+    email: String,
+    password: String,
+    role_id: Option<i32>,
+) -> Result<User, AppError> {
+    UserService::create_user_with_options(
+        db,
+        email,
+        password,
+        role_id,
+        NewUserOptions {
+            send_verification: false,
+            mark_verified: true,
+        },
+    )
+    .await
+}
+
+/// `io::Write` collecting the formatted output of a test's tracing subscriber
+///
+/// Clones share the buffer, pass `move || logs.clone()` to `with_writer`.
+#[derive(Clone, Default)]
+pub struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    /// Everything written so far
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Empty directory under the system temp dir, removed with everything in it when dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Creates a `TempDir` unique to the caller
+pub fn temp_dir() -> TempDir {
+    let path = std::env::temp_dir().join(format!("rext-test-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&path).unwrap();
+    TempDir(path)
+}
@@ -0,0 +1,165 @@
+//! Keeps the "last changed" column of a row current whenever it is updated
+//!
+//! The entities are generated from the schema, so they can't carry a `before_save` hook. Updates
+//! go through `update_touched` instead of `ActiveModelTrait::update`.
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ConnectionTrait, DbErr, EntityTrait, IntoActiveModel,
+    Set,
+};
+
+use crate::entity::models::{roles, user_sessions, users};
+use crate::infrastructure::clock::{Clock, system_clock};
+
+/// Active models with a column recording when the row last changed
+pub trait Touch {
+    /// Sets that column to `now`
+    fn touch(&mut self, now: DateTime<Utc>);
+}
+
+impl Touch for roles::ActiveModel {
+    fn touch(&mut self, now: DateTime<Utc>) {
+        self.updated_at = Set(Some(now.fixed_offset()));
+    }
+}
+
+impl Touch for users::ActiveModel {
+    fn touch(&mut self, now: DateTime<Utc>) {
+        self.updated_at = Set(Some(now.fixed_offset()));
+    }
+}
+
+impl Touch for user_sessions::ActiveModel {
+    fn touch(&mut self, now: DateTime<Utc>) {
+        self.last_activity = Set(Some(now.fixed_offset()));
+    }
+}
+
+/// Updates the row, setting its "last changed" column to now
+pub async fn update_touched<A, C>(
+    db: &C,
+    model: A,
+) -> Result<<A::Entity as EntityTrait>::Model, DbErr>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Touch + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    update_touched_with_clock(db, model, system_clock()).await
+}
+
+/// `update_touched` taking the time from the given clock
+pub async fn update_touched_with_clock<A, C>(
+    db: &C,
+    mut model: A,
+    clock: &dyn Clock,
+) -> Result<<A::Entity as EntityTrait>::Model, DbErr>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Touch + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: ConnectionTrait,
+{
+    model.touch(clock.now());
+    model.update(db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::services::{session_service::SessionService, user_service::UserService};
+    use crate::entity::models::prelude::*;
+    use crate::infrastructure::clock::ManualClock;
+    use crate::infrastructure::test_support::{create_verified_user, setup_db};
+    use uuid::Uuid;
+
+    fn later() -> ManualClock {
+        ManualClock::new(Utc::now() + chrono::Duration::days(1))
+    }
+
+    #[tokio::test]
+    async fn test_role_update_bumps_updated_at() {
+        let db = setup_db().await;
+        let role = roles::ActiveModel {
+            name: Set("editor".to_string()),
+            permissions: Set("[]".to_string()),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+
+        let clock = later();
+        let mut model: roles::ActiveModel = role.into();
+        model.description = Set(Some("Edits content".to_string()));
+        let updated = update_touched_with_clock(&db, model, &clock).await.unwrap();
+
+        assert_eq!(updated.updated_at.unwrap().to_utc(), clock.now());
+    }
+
+    #[tokio::test]
+    async fn test_user_update_bumps_updated_at() {
+        let db = setup_db().await;
+        let user = create_verified_user(
+            &db,
+            "user@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let clock = later();
+        let model = users::ActiveModel {
+            id: Set(user.id),
+            email_verified: Set(true),
+            ..Default::default()
+        };
+        let updated = update_touched_with_clock(&db, model, &clock).await.unwrap();
+        assert_eq!(updated.updated_at.unwrap().to_utc(), clock.now());
+
+        // Service updates go through it as well
+        let before = Users::find_by_id(user.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .updated_at;
+        UserService::update_user(&db, user.id, None, None, Some(None))
+            .await
+            .unwrap();
+        let after = Users::find_by_id(user.id)
+            .one(&db)
+            .await
+            .unwrap()
+            .unwrap()
+            .updated_at;
+        assert_ne!(after, before);
+    }
+
+    #[tokio::test]
+    async fn test_session_update_bumps_last_activity() {
+        let db = setup_db().await;
+        let user = create_verified_user(
+            &db,
+            "user@example.com".to_string(),
+            "UserPassword123!".to_string(),
+            None,
+        )
+        .await
+        .unwrap();
+        let session =
+            SessionService::create_session(&db, user.id, None, None, &Uuid::new_v4().to_string())
+                .await
+                .unwrap();
+
+        let clock = later();
+        let model = user_sessions::ActiveModel {
+            id: Set(session.id),
+            ..Default::default()
+        };
+        let updated = update_touched_with_clock(&db, model, &clock).await.unwrap();
+
+        assert_eq!(updated.last_activity.unwrap().to_utc(), clock.now());
+        assert!(updated.last_activity > session.last_activity);
+    }
+}
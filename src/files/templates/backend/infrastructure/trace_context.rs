@@ -0,0 +1,204 @@
+use axum::http::{HeaderMap, HeaderValue};
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+tokio::task_local! {
+    /// Trace context of the request currently being handled
+    static CURRENT_TRACE_CONTEXT: TraceContext;
+}
+
+/// W3C trace context (https://www.w3.org/TR/trace-context/) for a single request
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceContext {
+    /// 32 hex character trace id, shared by every span in the trace
+    pub trace_id: String,
+    /// 16 hex character id of the caller's span, None when this request started the trace
+    pub parent_id: Option<String>,
+    /// 16 hex character id of the span handling this request
+    pub span_id: String,
+    /// Trace flags (bit 0 = sampled)
+    pub flags: u8,
+    /// Vendor specific tracestate, passed through untouched
+    pub tracestate: Option<String>,
+}
+
+impl TraceContext {
+    /// Starts a new trace
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: uuid::Uuid::new_v4().simple().to_string(),
+            parent_id: None,
+            span_id: Self::generate_span_id(),
+            flags: 0x01,
+            tracestate: None,
+        }
+    }
+
+    /// Parses a traceparent header, returns None if it's malformed
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Option<Self> {
+        let parts: Vec<&str> = traceparent.trim().split('-').collect();
+        if parts.len() < 4 {
+            return None;
+        }
+
+        let (version, trace_id, parent_id, flags) = (parts[0], parts[1], parts[2], parts[3]);
+
+        // Version ff is invalid, version 00 must have exactly four fields
+        if !Self::is_hex(version, 2) || version == "ff" || (version == "00" && parts.len() != 4) {
+            return None;
+        }
+        if !Self::is_hex(trace_id, 32) || trace_id.chars().all(|c| c == '0') {
+            return None;
+        }
+        if !Self::is_hex(parent_id, 16) || parent_id.chars().all(|c| c == '0') {
+            return None;
+        }
+        if !Self::is_hex(flags, 2) {
+            return None;
+        }
+
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            parent_id: Some(parent_id.to_string()),
+            span_id: Self::generate_span_id(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+            tracestate: tracestate
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+        })
+    }
+
+    /// Continues the trace from the incoming headers, or starts a new one
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let tracestate = headers
+            .get(TRACESTATE_HEADER)
+            .and_then(|value| value.to_str().ok());
+
+        headers
+            .get(TRACEPARENT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| Self::parse(value, tracestate))
+            .unwrap_or_else(Self::new_root)
+    }
+
+    /// Renders the traceparent header for outgoing requests, with this request's span as parent
+    pub fn to_traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.flags)
+    }
+
+    /// Adds traceparent/tracestate headers to an outgoing request
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        if let Ok(value) = HeaderValue::from_str(&self.to_traceparent()) {
+            headers.insert(TRACEPARENT_HEADER, value);
+        }
+        if let Some(value) = self
+            .tracestate
+            .as_ref()
+            .and_then(|tracestate| HeaderValue::from_str(tracestate).ok())
+        {
+            headers.insert(TRACESTATE_HEADER, value);
+        }
+    }
+
+    /// Trace context of the current request, if called while handling one
+    pub fn current() -> Option<Self> {
+        CURRENT_TRACE_CONTEXT
+            .try_with(|context| context.clone())
+            .ok()
+    }
+
+    /// Adds the current request's trace context to an outgoing request, if there is one
+    pub fn inject_current(headers: &mut HeaderMap) {
+        if let Some(context) = Self::current() {
+            context.inject(headers);
+        }
+    }
+
+    /// Runs a future with this trace context as the current one
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_TRACE_CONTEXT.scope(self, future).await
+    }
+
+    fn generate_span_id() -> String {
+        uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+    }
+
+    fn is_hex(value: &str, len: usize) -> bool {
+        value.len() == len
+            && value
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_valid_traceparent() {
+        let context = TraceContext::parse(TRACEPARENT, Some("vendor=value")).unwrap();
+        assert_eq!(context.trace_id, "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.parent_id.as_deref(), Some("00f067aa0ba902b7"));
+        assert_eq!(context.flags, 0x01);
+        assert_eq!(context.tracestate.as_deref(), Some("vendor=value"));
+        assert_eq!(context.span_id.len(), 16);
+        assert_ne!(context.span_id, "00f067aa0ba902b7");
+    }
+
+    #[test]
+    fn test_parse_invalid_traceparent() {
+        assert!(TraceContext::parse("garbage", None).is_none());
+        assert!(
+            TraceContext::parse(
+                "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+                None
+            )
+            .is_none()
+        );
+        assert!(
+            TraceContext::parse(
+                "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+                None
+            )
+            .is_none()
+        );
+        assert!(
+            TraceContext::parse(
+                "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+                None
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn test_inject_uses_current_span_as_parent() {
+        let context = TraceContext::parse(TRACEPARENT, None).unwrap();
+        let mut headers = HeaderMap::new();
+        context.inject(&mut headers);
+
+        let outgoing = headers.get(TRACEPARENT_HEADER).unwrap().to_str().unwrap();
+        assert_eq!(
+            outgoing,
+            format!("00-4bf92f3577b34da6a3ce929d0e0e4736-{}-01", context.span_id)
+        );
+        assert!(headers.get(TRACESTATE_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_current_is_scoped_to_request() {
+        assert!(TraceContext::current().is_none());
+
+        let context = TraceContext::parse(TRACEPARENT, None).unwrap();
+        let trace_id = context
+            .clone()
+            .scope(async { TraceContext::current().map(|context| context.trace_id) })
+            .await;
+
+        assert_eq!(trace_id, Some(context.trace_id));
+    }
+}
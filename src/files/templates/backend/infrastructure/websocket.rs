@@ -1,14 +1,38 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{RwLock, broadcast};
+use std::time::Duration;
+use tokio::sync::{RwLock, broadcast, watch};
 
-/// WebSocket message types for real-time monitoring
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Reason sent in the close frame when the server shuts down
+pub const SHUTDOWN_CLOSE_REASON: &str = "server shutting down";
+
+/// Reason sent in the close frame when the connection limit is reached
+pub const TOO_MANY_CONNECTIONS_CLOSE_REASON: &str = "too many connections";
+
+/// Default maximum number of concurrent WebSocket connections
+pub const DEFAULT_WEBSOCKET_MAX_CONNECTIONS: usize = 100;
+
+/// Default number of recent audit and system log events replayed to new connections
+pub const DEFAULT_WEBSOCKET_REPLAY_SIZE: usize = 100;
+
+/// Severity of a `WsMessage::SystemLog`, serialized in lowercase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// Messages sent over the monitoring WebSocket, serialized as JSON objects with a `type` field
+/// naming the variant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
-pub enum WebSocketMessage {
+pub enum WsMessage {
     /// New audit log entry
-    #[serde(rename = "AuditLog")]
     AuditLog {
         id: String,
         timestamp: String,
@@ -21,16 +45,14 @@ pub enum WebSocketMessage {
         user_agent: Option<String>,
         error_message: Option<String>,
     },
-    /// System log message
-    #[serde(rename = "SystemLog")]
+    /// System log message, `source` names the part of the server that logged it
     SystemLog {
-        level: String,
+        level: LogLevel,
         message: String,
+        source: String,
         timestamp: String,
-        target: String,
     },
     /// Performance metrics update
-    #[serde(rename = "PerformanceMetrics")]
     PerformanceMetrics {
         total_requests: u64,
         success_rate: f64,
@@ -39,44 +61,145 @@ pub enum WebSocketMessage {
         active_connections: u32,
     },
     /// Connection status
-    #[serde(rename = "ConnectionStatus")]
     ConnectionStatus {
         status: String,
         message: String,
         timestamp: String,
     },
     /// Ping/Pong for connection health
-    #[serde(rename = "Ping")]
     Ping,
-    #[serde(rename = "Pong")]
     Pong,
 }
 
+impl WsMessage {
+    /// System log message timestamped now
+    pub fn system_log(
+        level: LogLevel,
+        message: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Self {
+        WsMessage::SystemLog {
+            level,
+            message: message.into(),
+            source: source.into(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Whether the message is kept for replay to clients connecting later, audit and system
+    /// logs are, metrics and connection messages are only useful live
+    pub fn is_replayable(&self) -> bool {
+        matches!(
+            self,
+            WsMessage::AuditLog { .. } | WsMessage::SystemLog { .. }
+        )
+    }
+
+    /// When the event was logged, for messages carrying a timestamp
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        match self {
+            WsMessage::AuditLog { timestamp, .. }
+            | WsMessage::SystemLog { timestamp, .. }
+            | WsMessage::ConnectionStatus { timestamp, .. } => {
+                DateTime::parse_from_rfc3339(timestamp)
+                    .ok()
+                    .map(|timestamp| timestamp.with_timezone(&Utc))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A registered connection's broadcast subscription and the events logged before it connected
+pub struct WebSocketSubscription {
+    pub receiver: broadcast::Receiver<WsMessage>,
+    /// Buffered audit and system log events, oldest first
+    pub backlog: Vec<WsMessage>,
+}
+
 /// WebSocket connection manager
 pub struct WebSocketManager {
     /// Broadcast channel for sending messages to all connected clients
-    tx: broadcast::Sender<WebSocketMessage>,
+    tx: broadcast::Sender<WsMessage>,
     /// Active connections with their IDs
-    connections: Arc<RwLock<HashMap<String, broadcast::Sender<WebSocketMessage>>>>,
+    connections: Arc<RwLock<HashMap<String, broadcast::Sender<WsMessage>>>>,
+    /// Set to true once the server starts shutting down, every connection holds a receiver
+    shutdown: watch::Sender<bool>,
+    /// Concurrent connections allowed, None for no limit
+    max_connections: Option<usize>,
+    /// Most recent replayable messages, oldest first, locked while broadcasting so a new
+    /// connection's backlog and subscription neither overlap nor miss an event
+    replay: RwLock<VecDeque<WsMessage>>,
+    /// Messages kept in `replay`, 0 disables replay
+    replay_size: usize,
 }
 
 impl WebSocketManager {
-    /// Create a new WebSocket manager
+    /// Create a new WebSocket manager limited to WEBSOCKET_MAX_CONNECTIONS connections, replaying
+    /// the last WEBSOCKET_REPLAY_SIZE events to new connections
     pub fn new() -> Self {
+        Self::with_max_connections(Self::max_connections_from_env())
+            .with_replay_size(Self::replay_size_from_env())
+    }
+
+    /// Create a new WebSocket manager allowing `max_connections` connections, None for no limit
+    pub fn with_max_connections(max_connections: Option<usize>) -> Self {
         let (tx, _) = broadcast::channel(1000); // Buffer size of 1000 messages
         Self {
             tx,
             connections: Arc::new(RwLock::new(HashMap::new())),
+            shutdown: watch::channel(false).0,
+            max_connections,
+            replay: RwLock::new(VecDeque::new()),
+            replay_size: DEFAULT_WEBSOCKET_REPLAY_SIZE,
         }
     }
 
+    /// Keep the last `replay_size` audit and system log events for new connections, 0 disables
+    /// replay
+    pub fn with_replay_size(mut self, replay_size: usize) -> Self {
+        self.replay_size = replay_size;
+        self
+    }
+
+    /// Reads WEBSOCKET_MAX_CONNECTIONS, 0 disables the limit
+    pub fn max_connections_from_env() -> Option<usize> {
+        let max = std::env::var("WEBSOCKET_MAX_CONNECTIONS")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_WEBSOCKET_MAX_CONNECTIONS);
+        (max > 0).then_some(max)
+    }
+
+    /// Reads WEBSOCKET_REPLAY_SIZE
+    pub fn replay_size_from_env() -> usize {
+        std::env::var("WEBSOCKET_REPLAY_SIZE")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_WEBSOCKET_REPLAY_SIZE)
+    }
+
     /// Subscribe to the broadcast channel
-    pub fn subscribe(&self) -> broadcast::Receiver<WebSocketMessage> {
+    pub fn subscribe(&self) -> broadcast::Receiver<WsMessage> {
         self.tx.subscribe()
     }
 
-    /// Broadcast a message to all connected clients
-    pub async fn broadcast(&self, message: WebSocketMessage) {
+    /// Broadcast a message to all connected clients, keeping audit and system logs for replay
+    pub async fn broadcast(&self, message: WsMessage) {
+        if self.replay_size > 0 && message.is_replayable() {
+            let mut replay = self.replay.write().await;
+            if replay.len() >= self.replay_size {
+                replay.pop_front();
+            }
+            replay.push_back(message.clone());
+            self.send(message);
+        } else {
+            self.send(message);
+        }
+    }
+
+    /// Sends on the broadcast channel
+    fn send(&self, message: WsMessage) {
         if let Err(e) = self.tx.send(message) {
             // Don't particularly care if the channel is closed, this is normal if no one is connected
             // log all other errors
@@ -86,15 +209,38 @@ impl WebSocketManager {
         }
     }
 
-    /// Add a new connection
-    #[allow(dead_code)]
+    /// Add a new connection, returning its broadcast subscription and the buffered events logged
+    /// after `since` (all of them without a cursor), or None if the connection limit is reached
     pub async fn add_connection(
         &self,
         connection_id: String,
-    ) -> broadcast::Receiver<WebSocketMessage> {
+        since: Option<DateTime<Utc>>,
+    ) -> Option<WebSocketSubscription> {
+        let mut connections = self.connections.write().await;
+        if self
+            .max_connections
+            .is_some_and(|max| connections.len() >= max)
+        {
+            return None;
+        }
+
         let (tx, _rx) = broadcast::channel(100);
-        self.connections.write().await.insert(connection_id, tx);
-        self.subscribe()
+        connections.insert(connection_id, tx);
+
+        // Subscribing under the replay lock, events broadcast from here on go to the receiver
+        let replay = self.replay.read().await;
+        let backlog = replay
+            .iter()
+            .filter(|message| match (since, message.timestamp()) {
+                (Some(since), Some(timestamp)) => timestamp > since,
+                _ => true,
+            })
+            .cloned()
+            .collect();
+        Some(WebSocketSubscription {
+            receiver: self.subscribe(),
+            backlog,
+        })
     }
 
     /// Remove a connection
@@ -107,9 +253,26 @@ impl WebSocketManager {
         self.connections.read().await.len()
     }
 
+    /// Receiver that flips to true when the server shuts down, connections hold it until they
+    /// have closed
+    pub fn shutdown_signal(&self) -> watch::Receiver<bool> {
+        self.shutdown.subscribe()
+    }
+
+    /// Tells every connection to flush the broadcasts it has pending and close with a
+    /// "server shutting down" frame, then waits up to `timeout` for all of them to finish
+    ///
+    /// Returns false if some connections were still open when the timeout ran out.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        self.shutdown.send_replace(true);
+        tokio::time::timeout(timeout, self.shutdown.closed())
+            .await
+            .is_ok()
+    }
+
     /// Send a message to a specific connection
     #[allow(dead_code)]
-    pub async fn send_to_connection(&self, connection_id: &str, message: WebSocketMessage) -> bool {
+    pub async fn send_to_connection(&self, connection_id: &str, message: WsMessage) -> bool {
         if let Some(tx) = self.connections.read().await.get(connection_id) {
             tx.send(message).is_ok()
         } else {
@@ -122,61 +285,8 @@ impl WebSocketManager {
 pub static WEBSOCKET_MANAGER: once_cell::sync::Lazy<WebSocketManager> =
     once_cell::sync::Lazy::new(WebSocketManager::new);
 
-/// Helper function to broadcast audit log entries
-pub async fn broadcast_audit_log(
-    id: String,
-    timestamp: String,
-    method: String,
-    path: String,
-    status_code: Option<i32>,
-    response_time_ms: Option<i32>,
-    user_id: Option<String>,
-    ip_address: Option<String>,
-    user_agent: Option<String>,
-    error_message: Option<String>,
-) {
-    let message = WebSocketMessage::AuditLog {
-        id,
-        timestamp,
-        method,
-        path,
-        status_code,
-        response_time_ms,
-        user_id,
-        ip_address,
-        user_agent,
-        error_message,
-    };
-    WEBSOCKET_MANAGER.broadcast(message).await;
-}
-
-/// Helper function to broadcast system logs
-pub async fn broadcast_system_log(level: String, message: String, target: String) {
-    let message = WebSocketMessage::SystemLog {
-        level,
-        message,
-        timestamp: chrono::Utc::now().to_rfc3339(),
-        target,
-    };
-    WEBSOCKET_MANAGER.broadcast(message).await;
-}
-
-/// Helper function to broadcast performance metrics
-#[allow(dead_code)]
-pub async fn broadcast_performance_metrics(
-    total_requests: u64,
-    success_rate: f64,
-    avg_response_time: f64,
-    error_rate: f64,
-) {
-    let active_connections = WEBSOCKET_MANAGER.connection_count().await as u32;
-    let message = WebSocketMessage::PerformanceMetrics {
-        total_requests,
-        success_rate,
-        avg_response_time,
-        error_rate,
-        active_connections,
-    };
+/// Broadcast a message to every client of the global manager
+pub async fn broadcast(message: WsMessage) {
     WEBSOCKET_MANAGER.broadcast(message).await;
 }
 
@@ -195,7 +305,7 @@ pub async fn start_metrics_broadcaster() {
 
             // For now, we'll send basic metrics
             // In a real implementation, you'd calculate these from audit logs
-            let message = WebSocketMessage::PerformanceMetrics {
+            let message = WsMessage::PerformanceMetrics {
                 total_requests: 0, // This will be calculated from audit logs
                 success_rate: 0.0,
                 avg_response_time: 0.0,
@@ -206,15 +316,125 @@ pub async fn start_metrics_broadcaster() {
             manager.broadcast(message).await;
 
             // Broadcast a heartbeat log
-            broadcast_system_log(
-                "debug".to_string(),
-                format!(
-                    "Metrics broadcast - Active connections: {}",
-                    active_connections
-                ),
-                "metrics_broadcaster".to_string(),
-            )
-            .await;
+            manager
+                .broadcast(WsMessage::system_log(
+                    LogLevel::Debug,
+                    format!(
+                        "Metrics broadcast - Active connections: {}",
+                        active_connections
+                    ),
+                    "metrics_broadcaster",
+                ))
+                .await;
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_log_json_shape() {
+        let message = WsMessage::AuditLog {
+            id: "7f1c".to_string(),
+            timestamp: "2025-01-01T00:00:00+00:00".to_string(),
+            method: "GET".to_string(),
+            path: "/api/v1/users".to_string(),
+            status_code: Some(200),
+            response_time_ms: Some(4),
+            user_id: None,
+            ip_address: Some("10.0.0.1".to_string()),
+            user_agent: None,
+            error_message: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "type": "AuditLog",
+                "id": "7f1c",
+                "timestamp": "2025-01-01T00:00:00+00:00",
+                "method": "GET",
+                "path": "/api/v1/users",
+                "status_code": 200,
+                "response_time_ms": 4,
+                "user_id": null,
+                "ip_address": "10.0.0.1",
+                "user_agent": null,
+                "error_message": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_system_log_json_shape() {
+        let message = WsMessage::system_log(LogLevel::Warn, "disk almost full", "storage");
+        let value = serde_json::to_value(&message).unwrap();
+
+        assert_eq!(value["type"], "SystemLog");
+        assert_eq!(value["level"], "warn");
+        assert_eq!(value["message"], "disk almost full");
+        assert_eq!(value["source"], "storage");
+        assert!(value["timestamp"].is_string());
+        assert_eq!(value.as_object().unwrap().len(), 5);
+
+        // Round trips back to the same message
+        assert_eq!(serde_json::from_value::<WsMessage>(value).unwrap(), message);
+    }
+
+    #[test]
+    fn test_performance_metrics_json_shape() {
+        let message = WsMessage::PerformanceMetrics {
+            total_requests: 10,
+            success_rate: 0.9,
+            avg_response_time: 12.5,
+            error_rate: 0.1,
+            active_connections: 2,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "type": "PerformanceMetrics",
+                "total_requests": 10,
+                "success_rate": 0.9,
+                "avg_response_time": 12.5,
+                "error_rate": 0.1,
+                "active_connections": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_connection_status_json_shape() {
+        let message = WsMessage::ConnectionStatus {
+            status: "connected".to_string(),
+            message: "Connected to monitoring".to_string(),
+            timestamp: "2025-01-01T00:00:00+00:00".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "type": "ConnectionStatus",
+                "status": "connected",
+                "message": "Connected to monitoring",
+                "timestamp": "2025-01-01T00:00:00+00:00",
+            })
+        );
+    }
+
+    #[test]
+    fn test_ping_pong_json_shape() {
+        assert_eq!(
+            serde_json::to_value(WsMessage::Ping).unwrap(),
+            json!({ "type": "Ping" })
+        );
+        assert_eq!(
+            serde_json::to_value(WsMessage::Pong).unwrap(),
+            json!({ "type": "Pong" })
+        );
+    }
+}
@@ -17,10 +17,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize the database
     let db = StartupService::initialize().await?;
 
+    // The scheduler runs database backups and audit log compaction next to the server, the app
+    // exits when the server does
+    #[cfg(feature = "queue")]
+    let scheduler = async {
+        if let Err(e) = StartupService::run_scheduler().await {
+            tracing::error!("Scheduler stopped: {}", e);
+        }
+        std::future::pending::<()>().await
+    };
+    #[cfg(not(feature = "queue"))]
+    let scheduler = std::future::pending::<()>();
+
     // Start the server
-    let _ = tokio::join!(
-        StartupService::run_server(db)
-    );
+    tokio::select! {
+        _ = StartupService::run_server(db) => {}
+        _ = scheduler => {}
+    }
+
+    // Flush any pending spans to the OTLP collector
+    LoggingManager::shutdown();
 
     Ok(())
 }
@@ -9,6 +9,18 @@ fn main() {
     println!("cargo:rerun-if-changed=frontend/vite.config.ts");
     println!("cargo:rerun-if-changed=frontend/tsconfig.json");
     println!("cargo:rerun-if-changed=.env");
+    // A missing path counts as changed, so outside a git checkout these would rerun every build
+    for git_path in [".git/HEAD", ".git/refs/heads"] {
+        if Path::new(git_path).exists() {
+            println!("cargo:rerun-if-changed={}", git_path);
+        }
+    }
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+
+    // Compile the current commit into the binary for the /version endpoint
+    if let Some(git_sha) = git_sha() {
+        println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+    }
 
     // Load .env file if it exists
     if let Ok(contents) = fs::read_to_string(".env") {
@@ -102,6 +114,27 @@ fn build_frontend() {
     }
 }
 
+/// GIT_SHA from the environment (e.g. set by CI or a Docker build without .git), otherwise the
+/// HEAD commit if this is a git checkout
+fn git_sha() -> Option<String> {
+    if let Ok(git_sha) = env::var("GIT_SHA") {
+        if !git_sha.trim().is_empty() {
+            return Some(git_sha.trim().to_string());
+        }
+    }
+
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let git_sha = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!git_sha.is_empty()).then_some(git_sha)
+}
+
 fn is_command_available(command: &str) -> bool {
     Command::new("which")
         .arg(command)
@@ -10,6 +10,15 @@ export interface UnifiedConfig {
     description: string
   }
 
+  // Auth configuration, set by the scaffolding auth strategy
+  auth: {
+    // 'jwt' keeps the token client side and sends it as a Bearer header,
+    // 'cookie' relies on the HttpOnly session cookie set by the backend
+    strategy: 'jwt' | 'cookie'
+    // fetch credentials mode for API requests
+    credentials: RequestCredentials
+  }
+
   // Development server configuration
   devServer: {
     port: number
@@ -137,6 +146,11 @@ export const defaultConfig: UnifiedConfig = {
     description: 'Vue.js frontend for Rext demo project'
   },
 
+  auth: {
+    strategy: '{auth_strategy}',
+    credentials: '{auth_credentials}'
+  },
+
   devServer: {
     port: 5173,
     host: 'localhost',
@@ -251,7 +265,8 @@ export const defaultConfig: UnifiedConfig = {
   },
 
   openapi: {
-    input: 'http://localhost:3000/api-docs/openapi.json',
+    // Written by the backend at startup in development (OPENAPI_SPEC_PATH)
+    input: './openapi.json',
     output: {
       path: 'src/bridge/client'
     },
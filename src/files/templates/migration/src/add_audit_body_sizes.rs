@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Original sizes of truncated request and response bodies, SQLite only accepts one
+        // column per ALTER TABLE statement
+        for column in [AuditLogs::RequestBodySize, AuditLogs::ResponseBodySize] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(AuditLogs::Table)
+                        .add_column(ColumnDef::new(column).big_integer().null())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [AuditLogs::RequestBodySize, AuditLogs::ResponseBodySize] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(AuditLogs::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLogs {
+    Table,
+    RequestBodySize,
+    ResponseBodySize,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Failed logins per email and client IP, kept for unknown emails too so a lockout doesn't
+        // reveal accounts. Keyed on both so failures from one IP don't lock out everyone else.
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginAttempts::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(LoginAttempts::Email).string().not_null())
+                    .col(ColumnDef::new(LoginAttempts::IpAddress).string().not_null())
+                    .col(
+                        ColumnDef::new(LoginAttempts::FailedAttempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(LoginAttempts::LastFailedAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(LoginAttempts::LockedUntil)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(LoginAttempts::Email)
+                            .col(LoginAttempts::IpAddress),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginAttempts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginAttempts {
+    Table,
+    Email,
+    IpAddress,
+    FailedAttempts,
+    LastFailedAt,
+    LockedUntil,
+}
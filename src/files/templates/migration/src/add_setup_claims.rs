@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // At most one row, claimed together with the first admin so only one instance can create it
+        manager
+            .create_table(
+                Table::create()
+                    .table(SetupClaims::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SetupClaims::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SetupClaims::AdminId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(SetupClaims::ClaimedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SetupClaims::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SetupClaims {
+    Table,
+    Id,
+    AdminId,
+    ClaimedAt,
+}
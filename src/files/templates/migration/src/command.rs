@@ -0,0 +1,124 @@
+use sea_orm_migration::{prelude::*, sea_orm::DatabaseConnection, MigrationStatus};
+use std::str::FromStr;
+
+use crate::Migrator;
+
+/// The migration binary's main subcommands, for running them from code, e.g. deploy tooling
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MigrationCommand {
+    /// Apply all pending migrations
+    Up,
+    /// Roll back the last applied migration
+    Down,
+    /// List migrations and whether they are applied
+    Status,
+    /// Drop all tables and apply every migration again
+    Fresh,
+}
+
+impl FromStr for MigrationCommand {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "up" => Ok(MigrationCommand::Up),
+            "down" => Ok(MigrationCommand::Down),
+            "status" => Ok(MigrationCommand::Status),
+            "fresh" => Ok(MigrationCommand::Fresh),
+            other => Err(format!(
+                "Unknown migration command '{}', expected up, down, status or fresh",
+                other
+            )),
+        }
+    }
+}
+
+/// Runs the command and returns the lines to print
+pub async fn run_command(
+    db: &DatabaseConnection,
+    command: MigrationCommand,
+) -> Result<Vec<String>, DbErr> {
+    match command {
+        MigrationCommand::Up => {
+            let pending = Migrator::get_pending_migrations(db).await?.len();
+            Migrator::up(db, None).await?;
+            Ok(vec![format!("Applied {} migration(s)", pending)])
+        }
+        MigrationCommand::Down => {
+            let applied = Migrator::get_applied_migrations(db).await?;
+            match applied.last() {
+                Some(last) => {
+                    let name = last.name().to_string();
+                    Migrator::down(db, Some(1)).await?;
+                    Ok(vec![format!("Rolled back {}", name)])
+                }
+                None => Ok(vec!["No applied migrations to roll back".to_string()]),
+            }
+        }
+        MigrationCommand::Status => Ok(Migrator::get_migration_with_status(db)
+            .await?
+            .iter()
+            .map(|migration| {
+                let status = match migration.status() {
+                    MigrationStatus::Applied => "applied",
+                    MigrationStatus::Pending => "pending",
+                };
+                format!("{} {}", status, migration.name())
+            })
+            .collect()),
+        MigrationCommand::Fresh => {
+            Migrator::fresh(db).await?;
+            let applied = Migrator::get_applied_migrations(db).await?.len();
+            Ok(vec![format!(
+                "Dropped all tables and applied {} migration(s)",
+                applied
+            )])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm_migration::sea_orm::{ConnectOptions, Database};
+
+    async fn setup_db() -> DatabaseConnection {
+        let mut options = ConnectOptions::new("sqlite::memory:");
+        options.max_connections(1);
+        Database::connect(options).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_status_lists_pending_then_applied() {
+        let db = setup_db().await;
+
+        let status = run_command(&db, MigrationCommand::Status).await.unwrap();
+        assert_eq!(status.len(), Migrator::migrations().len());
+        assert!(status.iter().all(|line| line.starts_with("pending ")));
+
+        run_command(&db, MigrationCommand::Up).await.unwrap();
+
+        let status = run_command(&db, MigrationCommand::Status).await.unwrap();
+        assert!(status.iter().all(|line| line.starts_with("applied ")));
+        assert!(status[0].ends_with("initial_migration"));
+    }
+
+    #[tokio::test]
+    async fn test_down_rolls_back_last_migration() {
+        let db = setup_db().await;
+        run_command(&db, MigrationCommand::Up).await.unwrap();
+
+        let output = run_command(&db, MigrationCommand::Down).await.unwrap();
+        assert!(output[0].starts_with("Rolled back"));
+
+        let status = run_command(&db, MigrationCommand::Status).await.unwrap();
+        assert!(status.last().unwrap().starts_with("pending "));
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!("up".parse(), Ok(MigrationCommand::Up));
+        assert_eq!("fresh".parse(), Ok(MigrationCommand::Fresh));
+        assert!("sideways".parse::<MigrationCommand>().is_err());
+    }
+}
@@ -1,5 +1,18 @@
 pub use sea_orm_migration::prelude::*;
 
+pub mod command;
+mod add_audit_body_sizes;
+mod add_confirmation_tokens;
+mod add_email_verifications;
+mod add_login_attempts;
+mod add_must_change_password;
+mod add_password_resets;
+mod add_pending_email;
+mod add_refresh_token;
+mod add_seed_history;
+mod add_settings;
+mod add_setup_claims;
+mod add_users_updated_at;
 mod initial_migration;
 
 pub struct Migrator;
@@ -9,6 +22,18 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(initial_migration::Migration),
+            Box::new(add_pending_email::Migration),
+            Box::new(add_must_change_password::Migration),
+            Box::new(add_refresh_token::Migration),
+            Box::new(add_email_verifications::Migration),
+            Box::new(add_password_resets::Migration),
+            Box::new(add_users_updated_at::Migration),
+            Box::new(add_login_attempts::Migration),
+            Box::new(add_setup_claims::Migration),
+            Box::new(add_audit_body_sizes::Migration),
+            Box::new(add_confirmation_tokens::Migration),
+            Box::new(add_seed_history::Migration),
+            Box::new(add_settings::Migration),
         ]
     }
 }
@@ -1,5 +1,7 @@
 use sea_orm_migration::prelude::*;
 
+/// SeaORM migration CLI: `up`, `down -n <steps>`, `status`, `fresh` and the rest, reading
+/// DATABASE_URL from the environment or `.env` unless `-u` is given
 #[tokio::main]
 async fn main() {
     cli::run_cli(migration::Migrator).await;
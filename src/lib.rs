@@ -16,10 +16,14 @@ use crate::error::RextCoreError;
 
 // Re-export files module types and functions for public use
 pub use crate::files::{
-    FileCreationConfig, RextFile, RextFileType, RextModule, create_rext_app, get_rext_files,
+    AuthStrategy, DatabaseBackend, FileCreationConfig, FileDiff, FileDiffStatus, RextFile,
+    RextFileType, RextModule, backup_path, create_rext_app, create_rext_app_dry_run,
+    diff_against_templates, get_rext_files, list_rext_file_paths, remove_rext_app,
+    validate_and_normalize_app_name,
 };
 use std::fs::{self, File};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
 use std::process::Command;
 
 /// Constant list of data types to target (easily expandable)
@@ -28,7 +32,10 @@ pub const TYPES_TO_WRAP: [&str; 2] = ["Uuid", "DateTimeWithTimeZone"];
 /// Directory containing generated sea-orm entity files
 pub const ENTITIES_DIR: &str = "backend/entity/models";
 
-/// Configuration for the server
+/// Default address of the generated server, substituted as `{server_host}` and `{server_port}`
+/// into rext.toml. example.env takes the port, its SERVER_HOST stays localhost so a new app
+/// isn't reachable from other machines until that's changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ServerConfig {
     pub host: [u8; 4],
     pub port: u16,
@@ -43,9 +50,17 @@ impl Default for ServerConfig {
     }
 }
 
+impl ServerConfig {
+    /// Host in dotted notation, e.g. `0.0.0.0`
+    pub fn host_string(&self) -> String {
+        std::net::Ipv4Addr::from(self.host).to_string()
+    }
+}
+
 /// Check if a Rext app has been initialized in the current directory by looking for the rext_app directory
 ///
-/// Returns true if the rext_app directory exists, false otherwise.
+/// Returns true if the rext_app directory exists, false otherwise, including when the current
+/// directory can't be determined (see `try_check_for_rext_app`).
 ///
 /// # Example
 ///
@@ -57,9 +72,23 @@ impl Default for ServerConfig {
 /// assert!(!is_rext_app);
 /// ```
 pub fn check_for_rext_app() -> bool {
-    let current_dir = std::env::current_dir().unwrap();
-    let rext_app_dir = current_dir.join("rext.toml");
-    rext_app_dir.exists()
+    try_check_for_rext_app().unwrap_or(false)
+}
+
+/// Check if a Rext app has been initialized in the current directory
+///
+/// Like `check_for_rext_app`, but returns an error instead of `false` when the current directory
+/// can't be determined (deleted, or permission denied).
+pub fn try_check_for_rext_app() -> Result<bool, RextCoreError> {
+    let current_dir = std::env::current_dir().map_err(RextCoreError::CurrentDir)?;
+    Ok(check_for_rext_app_in(&current_dir))
+}
+
+/// Check if a Rext app has been initialized in the given directory
+///
+/// Returns false if the directory doesn't exist.
+pub fn check_for_rext_app_in(path: &std::path::Path) -> bool {
+    path.join("rext.toml").exists()
 }
 
 /// Scaffold a new Rext application in the current directory
@@ -71,7 +100,11 @@ pub fn check_for_rext_app() -> bool {
 /// - public/ directory for static assets
 /// - templates/ directory for HTML templates
 ///
-/// Returns an error if the app already exists or if there's an I/O error during creation.
+/// The crate name is derived from the directory name with `validate_and_normalize_app_name`,
+/// the directory name itself is kept as the display name.
+///
+/// Returns an error if the app already exists, the directory name can't be turned into a valid
+/// crate name, or if there's an I/O error during creation.
 ///
 /// # Example
 ///
@@ -85,30 +118,179 @@ pub fn check_for_rext_app() -> bool {
 /// ```
 pub fn scaffold_rext_app() -> Result<(), RextCoreError> {
     let current_dir = std::env::current_dir().map_err(RextCoreError::CurrentDir)?;
+    let config = current_app_config(&current_dir)?;
 
-    let new_app_name = current_dir
+    // Use the new files module to create the application
+    create_rext_app(&current_dir, config)
+}
+
+/// Completely destroys a Rext application in the current directory
+///
+/// Removes all files and directories created by the scaffold_rext_app function.
+/// Files that weren't generated are kept, along with the directories containing them.
+///
+/// Returns an error if there's no rext.toml in the current directory, or if there's an I/O
+/// error during destruction.
+pub fn destroy_rext_app() -> Result<(), RextCoreError> {
+    let current_dir = std::env::current_dir().map_err(RextCoreError::CurrentDir)?;
+    let config = current_app_config(&current_dir)?;
+
+    remove_rext_app(&current_dir, &config)
+}
+
+/// The configuration scaffold_rext_app uses for an app in this directory
+fn current_app_config(current_dir: &std::path::Path) -> Result<FileCreationConfig, RextCoreError> {
+    let display_name = current_dir
         .file_name()
         .and_then(|name| name.to_str())
         .unwrap_or("my-rext-app")
         .to_string();
+    let app_name = validate_and_normalize_app_name(&display_name)?;
 
     // Create configuration with default settings
-    let config = FileCreationConfig {
-        app_name: new_app_name,
-        modules: vec![RextModule::RextCore],
+    Ok(FileCreationConfig {
+        app_name,
+        display_name,
+        ..Default::default()
+    })
+}
+
+/// Schema attribute added above fields of the wrapped types
+const SCHEMA_ATTRIBUTE: &str = "    #[schema(value_type = String)]";
+
+/// Configuration for `generate_sea_orm_entities_with_config`
+#[derive(Debug, Clone)]
+pub struct GenerateEntitiesConfig {
+    /// Database sea-orm-cli reads the schema from
+    pub database_url: String,
+    /// Directory the entity files are written to
+    pub output_dir: PathBuf,
+    /// Field types that get #[schema(value_type = String)]
+    pub types_to_wrap: Vec<String>,
+    /// sea-orm-cli executable, looked up on PATH unless it's a path
+    pub sea_orm_cli: PathBuf,
+}
+
+impl Default for GenerateEntitiesConfig {
+    fn default() -> Self {
+        Self {
+            database_url: "sqlite:./sqlite.db?mode=rwc".to_string(),
+            output_dir: PathBuf::from(ENTITIES_DIR),
+            types_to_wrap: TYPES_TO_WRAP
+                .iter()
+                .map(|dtype| dtype.to_string())
+                .collect(),
+            sea_orm_cli: PathBuf::from("sea-orm-cli"),
+        }
+    }
+}
+
+impl GenerateEntitiesConfig {
+    /// Adds #[schema(value_type = String)] above every public field of a type in `types_to_wrap`
+    ///
+    /// Fields wrapped over several lines are joined up to their closing `,` (or the struct's `}`)
+    /// before the type is checked. Fields that already have the attribute directly above them
+    /// are left alone, so annotating an entity twice gives the same result as annotating it once.
+    pub fn annotate_entity_source(&self, source: &str) -> String {
+        let mut output_lines: Vec<&str> = Vec::new();
+        // Lines of the field declaration being read, and its open `<`, `(` and `[` count
+        let mut field_lines: Vec<&str> = Vec::new();
+        let mut depth = 0i32;
+
+        for line in source.lines() {
+            let trimmed_line = line.trim_start();
+
+            if field_lines.is_empty() {
+                if !is_field_start(trimmed_line) {
+                    output_lines.push(line);
+                    continue;
+                }
+            } else if depth <= 0 && trimmed_line.starts_with('}') {
+                // Last field without a trailing comma, the struct ends here
+                self.emit_field(&mut output_lines, &mut field_lines);
+                output_lines.push(line);
+                continue;
+            }
+
+            field_lines.push(line);
+            depth += bracket_depth_change(trimmed_line);
+
+            if depth <= 0 && trimmed_line.trim_end().ends_with(',') {
+                self.emit_field(&mut output_lines, &mut field_lines);
+                depth = 0;
+            }
+        }
+
+        self.emit_field(&mut output_lines, &mut field_lines);
+
+        let mut annotated = output_lines.join("\n");
+        annotated.push('\n');
+        annotated
+    }
+
+    /// Moves a complete field declaration to the output, preceded by the schema attribute if
+    /// its type is wrapped and it isn't annotated yet
+    fn emit_field<'a>(&self, output_lines: &mut Vec<&'a str>, field_lines: &mut Vec<&'a str>) {
+        if field_lines.is_empty() {
+            return;
+        }
+
+        let declaration = field_lines
+            .iter()
+            .map(|line| line.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let add_schema = self
+            .types_to_wrap
+            .iter()
+            .any(|dtype| declaration.contains(dtype.as_str()));
+
+        // Insert the schema attribute if matched and not already there
+        let already_annotated = output_lines
+            .last()
+            .is_some_and(|previous| previous.trim() == SCHEMA_ATTRIBUTE.trim());
+        if add_schema && !already_annotated {
+            output_lines.push(SCHEMA_ATTRIBUTE);
+        }
+
+        output_lines.append(field_lines);
+    }
+}
+
+/// Whether a trimmed line starts a public struct field rather than an item like `pub struct`
+fn is_field_start(trimmed_line: &str) -> bool {
+    const ITEM_KEYWORDS: [&str; 12] = [
+        "struct", "enum", "fn", "mod", "use", "type", "const", "static", "trait", "impl", "async",
+        "unsafe",
+    ];
+
+    let Some(rest) = trimmed_line.strip_prefix("pub ") else {
+        return false;
     };
+    let name: String = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
 
-    // Use the new files module to create the application
-    create_rext_app(&current_dir, config)
+    !name.is_empty() && !ITEM_KEYWORDS.contains(&name.as_str())
 }
 
-/// Completely destroys a Rext application in the current directory
-///
-/// Removes all files and directories created by the scaffold_rext_app function.
+/// Net change in open `<`, `(` and `[` brackets over a line
+fn bracket_depth_change(line: &str) -> i32 {
+    line.chars()
+        .map(|c| match c {
+            '<' | '(' | '[' => 1,
+            '>' | ')' | ']' => -1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Adds #[schema(value_type = String)] above every public field of a type in TYPES_TO_WRAP
 ///
-/// Returns an error if there's an I/O error during destruction.
-pub fn destroy_rext_app() -> Result<(), RextCoreError> {
-    Ok(())
+/// See `GenerateEntitiesConfig::annotate_entity_source`.
+pub fn annotate_entity_source(source: &str) -> String {
+    GenerateEntitiesConfig::default().annotate_entity_source(source)
 }
 
 /// Generates the SeaORM entities with OpenAPI support
@@ -117,38 +299,44 @@ pub fn destroy_rext_app() -> Result<(), RextCoreError> {
 ///
 /// Returns a RextCoreError if an error occurs during the generation process
 pub fn generate_sea_orm_entities_with_open_api_schema() -> Result<(), RextCoreError> {
+    generate_sea_orm_entities_with_config(&GenerateEntitiesConfig::default())
+}
+
+/// Generates the SeaORM entities with OpenAPI support from `config.database_url` into
+/// `config.output_dir`
+///
+/// Returns a RextCoreError if an error occurs during the generation process
+pub fn generate_sea_orm_entities_with_config(
+    config: &GenerateEntitiesConfig,
+) -> Result<(), RextCoreError> {
     // run the see-orm-cli command with serde and utoipa derives
-    let output = Command::new("sea-orm-cli")
-        .args(&[
-            "generate",
-            "entity",
-            "-u",
-            "sqlite:./sqlite.db?mode=rwc",
-            "-o",
-            format!("{}", ENTITIES_DIR).as_str(),
+    let output = Command::new(&config.sea_orm_cli)
+        .args(["generate", "entity", "-u", &config.database_url, "-o"])
+        .arg(&config.output_dir)
+        .args([
             "--model-extra-derives",
             "utoipa::ToSchema",
             "--with-serde",
             "both",
         ])
         .output()
-        .map_err(RextCoreError::SeaOrmCliGenerateEntities)?;
+        .map_err(RextCoreError::sea_orm_cli)?;
 
     if !output.status.success() {
         return Err(RextCoreError::SeaOrmCliGenerateEntities(
-            std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("sea-orm-cli command failed with status: {}", output.status),
-            ),
+            std::io::Error::other(format!(
+                "sea-orm-cli command failed with status: {}",
+                output.status
+            )),
         ));
     }
 
     // Process each .rs file in the entities directory
-    for entry in fs::read_dir(ENTITIES_DIR)? {
+    for entry in fs::read_dir(&config.output_dir)? {
         let entry = entry?;
         let path = entry.path();
 
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "rs") {
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "rs") {
             // Check if this is a SeaORM entity file
             let file = File::open(&path)?;
             let reader = BufReader::new(file);
@@ -162,37 +350,9 @@ pub fn generate_sea_orm_entities_with_open_api_schema() -> Result<(), RextCoreEr
                 continue;
             }
 
-            // Re-open file to process it
-            let file = File::open(&path)?;
-            let reader = BufReader::new(file);
-            let mut output_lines: Vec<String> = Vec::new();
-
-            for line_result in reader.lines() {
-                let line = line_result?;
-                let trimmed_line = line.trim_start();
-
-                // Check if the line is a public field with a target type
-                let mut add_schema = false;
-                for dtype in &TYPES_TO_WRAP {
-                    if trimmed_line.starts_with("pub ") && trimmed_line.contains(dtype) {
-                        add_schema = true;
-                        break;
-                    }
-                }
-
-                // Insert the schema attribute if matched
-                if add_schema {
-                    output_lines.push("    #[schema(value_type = String)]".to_string());
-                }
-
-                output_lines.push(line);
-            }
-
-            // Write the modified content back to the file
-            let mut file = File::create(&path)?;
-            for line in &output_lines {
-                writeln!(file, "{}", line)?;
-            }
+            // Re-read the file and annotate it
+            let source = fs::read_to_string(&path)?;
+            fs::write(&path, config.annotate_entity_source(&source))?;
         }
     }
 
@@ -1 +1,766 @@
+use rext_core::{
+    AuthStrategy, DatabaseBackend, FileCreationConfig, FileDiffStatus, GenerateEntitiesConfig,
+    RextFile, RextModule, ServerConfig, annotate_entity_source, backup_path, check_for_rext_app_in,
+    create_rext_app, create_rext_app_dry_run, diff_against_templates,
+    generate_sea_orm_entities_with_config, get_rext_files, list_rext_file_paths, remove_rext_app,
+    try_check_for_rext_app, validate_and_normalize_app_name,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
 
+fn files_for(auth_strategy: AuthStrategy) -> Vec<RextFile> {
+    get_rext_files(&FileCreationConfig {
+        auth_strategy,
+        ..Default::default()
+    })
+    .unwrap()
+}
+
+fn auth_transport(files: &[RextFile]) -> &RextFile {
+    let matches: Vec<&RextFile> = files
+        .iter()
+        .filter(|file| {
+            file.name == "auth_transport.rs" && file.path == Path::new("backend/bridge/middleware")
+        })
+        .collect();
+    assert_eq!(matches.len(), 1, "exactly one auth transport is emitted");
+    matches[0]
+}
+
+fn unified_config(files: &[RextFile]) -> &RextFile {
+    files
+        .iter()
+        .find(|file| file.name == "unified.config.ts")
+        .expect("frontend config is emitted")
+}
+
+#[test]
+fn test_cookie_strategy_emits_cookie_transport() {
+    let files = files_for(AuthStrategy::SessionCookie);
+    let transport = auth_transport(&files);
+
+    assert!(transport.content.contains("HttpOnly"));
+    assert!(transport.content.contains("header::COOKIE"));
+    assert!(!transport.content.contains("extract_token_from_header"));
+
+    let config = unified_config(&files);
+    assert!(config.content.contains("strategy: 'cookie'"));
+    assert!(config.content.contains("credentials: 'include'"));
+}
+
+#[test]
+fn test_jwt_strategy_emits_bearer_transport() {
+    let files = files_for(AuthStrategy::Jwt);
+    let transport = auth_transport(&files);
+
+    assert!(transport.content.contains("extract_token_from_header"));
+    assert!(!transport.content.contains("HttpOnly"));
+    assert!(!transport.content.contains("header::COOKIE"));
+
+    let config = unified_config(&files);
+    assert!(config.content.contains("strategy: 'jwt'"));
+    assert!(config.content.contains("credentials: 'same-origin'"));
+}
+
+#[test]
+fn test_default_strategy_is_jwt() {
+    assert_eq!(
+        FileCreationConfig::default().auth_strategy,
+        AuthStrategy::Jwt
+    );
+    assert!(
+        get_rext_files(&FileCreationConfig::default())
+            .unwrap()
+            .iter()
+            .all(|file| !file.content.contains("{auth_strategy}"))
+    );
+}
+
+#[test]
+fn test_all_templates_resolve() {
+    for auth_strategy in [AuthStrategy::Jwt, AuthStrategy::SessionCookie] {
+        for database_backend in [
+            DatabaseBackend::Sqlite,
+            DatabaseBackend::Postgres,
+            DatabaseBackend::Mysql,
+        ] {
+            let config = FileCreationConfig {
+                auth_strategy,
+                database_backend,
+                ..Default::default()
+            };
+            let files = get_rext_files(&config).unwrap();
+
+            for file in &files {
+                for key in config.template_variables().keys() {
+                    // `{{key}}` is an escaped placeholder, the email templates use it
+                    let placeholder = format!("{{{}}}", key);
+                    let unresolved = file
+                        .content
+                        .match_indices(&placeholder)
+                        .any(|(index, _)| !file.content[..index].ends_with('{'));
+                    assert!(!unresolved, "{} left in {}", placeholder, file.name);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_escaped_braces_are_kept() {
+    let files = get_rext_files(&FileCreationConfig::default()).unwrap();
+    let file = |path: &str, name: &str| {
+        files
+            .iter()
+            .find(|file| file.path == Path::new(path) && file.name == name)
+            .unwrap()
+            .content
+            .clone()
+    };
+
+    // Escaped route parameters lose one level of braces
+    let admin_handlers = file("backend/bridge/handlers", "admin.rs");
+    assert!(admin_handlers.contains(r#"path = "/users/{id}","#));
+    assert!(!admin_handlers.contains("{{id}}"));
+    assert!(root_file(&files, "example.env").contains("/api/v1/admin/users/{id}/export"));
+    assert!(root_file(&files, "Dockerfile").contains("ENV GIT_SHA=${GIT_SHA}"));
+
+    // So do the email templates' own variables, other braces are copied as they are
+    let email = file("backend/infrastructure", "email.rs");
+    assert!(email.contains(r#"subject: "Welcome to {{app_name}}!""#));
+    assert!(email.contains("format!(\"{{{{{}}}}}\", key)"));
+    assert_eq!(
+        file("backend/bridge/routes", "admin.rs"),
+        include_str!("../src/files/templates/backend/bridge/routes/admin.rs")
+    );
+}
+
+#[test]
+fn test_placeholders_take_config_values() {
+    let files = get_rext_files(&FileCreationConfig {
+        app_name: "placeholder-app".to_string(),
+        author: "Jane Doe".to_string(),
+        license: "Apache-2.0".to_string(),
+        version: "2.3.4".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let rext_toml = root_file(&files, "rext.toml");
+    assert!(rext_toml.contains(r#"name = "placeholder-app""#));
+    assert!(rext_toml.contains(r#"author = "Jane Doe""#));
+    assert!(rext_toml.contains(r#"license = "Apache-2.0""#));
+    assert!(rext_toml.contains(r#"version = "2.3.4""#));
+    assert!(root_file(&files, "Cargo.toml").contains(r#"version = "2.3.4""#));
+
+    // The email templates' own {{variables}} are not template placeholders
+    let email = files.iter().find(|file| file.name == "email.rs").unwrap();
+    assert!(email.content.contains("Welcome to {{app_name}}!"));
+}
+
+#[test]
+fn test_toml_values_are_escaped() {
+    let files = get_rext_files(&FileCreationConfig {
+        display_name: r#"The "Best" App\"#.to_string(),
+        author: "Jane\nDoe".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let rext_toml = root_file(&files, "rext.toml");
+    assert!(rext_toml.contains(r#"display_name = "The \"Best\" App\\""#));
+    assert!(rext_toml.contains(r#"author = "Jane\nDoe""#));
+    // Markdown takes the name as it is
+    assert!(root_file(&files, "README.md").starts_with(r#"# The "Best" App\"#));
+}
+
+#[test]
+fn test_server_config_sets_generated_host_and_port() {
+    let files = get_rext_files(&FileCreationConfig {
+        server: ServerConfig {
+            host: [127, 0, 0, 1],
+            port: 8080,
+        },
+        ..Default::default()
+    })
+    .unwrap();
+
+    let example_env = root_file(&files, "example.env");
+    assert!(example_env.contains("SERVER_PORT = 8080"));
+    // The server listens on localhost until SERVER_HOST is changed
+    assert!(example_env.contains("SERVER_HOST = localhost"));
+    let rext_toml = root_file(&files, "rext.toml");
+    assert!(rext_toml.contains("host = \"127.0.0.1\"\nport = 8080"));
+
+    let defaults = get_rext_files(&FileCreationConfig::default()).unwrap();
+    assert!(root_file(&defaults, "example.env").contains("SERVER_PORT = 3000"));
+    assert!(root_file(&defaults, "rext.toml").contains("host = \"0.0.0.0\""));
+}
+
+fn has_file(files: &[RextFile], path: &str, name: &str) -> bool {
+    files
+        .iter()
+        .any(|file| file.path == Path::new(path) && file.name == name)
+}
+
+#[test]
+fn test_core_only_scaffold_omits_optional_modules() {
+    let files = get_rext_files(&FileCreationConfig {
+        modules: vec![RextModule::RextCore],
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(files.iter().all(|file| file.module == RextModule::RextCore));
+    assert!(has_file(&files, "backend", "main.rs"));
+    assert!(has_file(&files, ".", "rext.toml"));
+
+    // Admin, its types are shared with the core services
+    assert!(!has_file(&files, "backend/bridge/handlers", "admin.rs"));
+    assert!(!has_file(&files, "backend/bridge/routes", "admin.rs"));
+    assert!(has_file(&files, "backend/bridge/types", "admin.rs"));
+    // Vue
+    assert!(!has_file(&files, "frontend", "package.json"));
+    assert!(!has_file(&files, "frontend/config", "unified.config.ts"));
+    // Email and queue
+    assert!(!has_file(&files, "backend/infrastructure", "email.rs"));
+    assert!(!has_file(&files, "backend/infrastructure", "job_queue.rs"));
+
+    // No optional module's feature is on, so nothing declares or uses their files
+    assert!(root_file(&files, "Cargo.toml").contains("default = []"));
+    let gated = [
+        (
+            "backend/bridge/handlers",
+            "mod.rs",
+            "pub mod admin;",
+            "admin",
+        ),
+        (
+            "backend/bridge/handlers",
+            "mod.rs",
+            "pub mod roles;",
+            "admin",
+        ),
+        (
+            "backend/bridge/handlers",
+            "mod.rs",
+            "pub mod settings;",
+            "admin",
+        ),
+        (
+            "backend/bridge/handlers",
+            "mod.rs",
+            "pub mod websocket;",
+            "admin",
+        ),
+        (
+            "backend/bridge/middleware",
+            "mod.rs",
+            "pub mod admin;",
+            "admin",
+        ),
+        ("backend/bridge/routes", "mod.rs", "pub mod admin;", "admin"),
+        (
+            "backend/infrastructure",
+            "server.rs",
+            "admin_router;",
+            "admin",
+        ),
+        (
+            "backend/infrastructure",
+            "mod.rs",
+            "pub mod email;",
+            "email",
+        ),
+        (
+            "backend/infrastructure",
+            "mod.rs",
+            "pub mod job_queue;",
+            "queue",
+        ),
+        (
+            "backend/infrastructure",
+            "mod.rs",
+            "pub mod scheduler;",
+            "queue",
+        ),
+    ];
+    for (path, name, item, feature) in gated {
+        let content = &files
+            .iter()
+            .find(|file| file.path == Path::new(path) && file.name == name)
+            .unwrap()
+            .content;
+        let line = content
+            .lines()
+            .position(|line| line.ends_with(item))
+            .unwrap();
+        assert_eq!(
+            content.lines().nth(line - 1).unwrap().trim(),
+            format!("#[cfg(feature = \"{}\")]", feature),
+            "{}/{} doesn't gate {}",
+            path,
+            name,
+            item
+        );
+    }
+}
+
+#[test]
+fn test_default_scaffold_includes_every_module() {
+    assert_eq!(FileCreationConfig::default().modules, RextModule::all());
+
+    let files = get_rext_files(&FileCreationConfig::default()).unwrap();
+    for module in RextModule::all() {
+        assert!(
+            files.iter().any(|file| file.module == module),
+            "{:?} has files",
+            module
+        );
+    }
+}
+
+fn root_file(files: &[RextFile], name: &str) -> String {
+    files
+        .iter()
+        .find(|file| file.name == name && file.path == Path::new("."))
+        .unwrap_or_else(|| panic!("{} is emitted", name))
+        .content
+        .clone()
+}
+
+#[test]
+fn test_database_backend_sets_database_url() {
+    for (database_backend, scheme) in [
+        (DatabaseBackend::Sqlite, "DATABASE_URL = sqlite:"),
+        (DatabaseBackend::Postgres, "DATABASE_URL = postgres://"),
+        (DatabaseBackend::Mysql, "DATABASE_URL = mysql://"),
+    ] {
+        let files = get_rext_files(&FileCreationConfig {
+            database_backend,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let example_env = root_file(&files, "example.env");
+        assert!(
+            example_env.contains(scheme),
+            "{:?} example.env should contain {}",
+            database_backend,
+            scheme
+        );
+        assert!(!example_env.contains("{database_url}"));
+        assert!(!root_file(&files, "rext.toml").contains("{database_url}"));
+        assert!(!root_file(&files, "Cargo.toml").contains("{db_driver_features}"));
+    }
+}
+
+#[test]
+fn test_default_database_backend_is_sqlite() {
+    assert_eq!(
+        FileCreationConfig::default().database_backend,
+        DatabaseBackend::Sqlite
+    );
+
+    let files = get_rext_files(&FileCreationConfig::default()).unwrap();
+    assert!(
+        root_file(&files, "example.env").contains("DATABASE_URL = sqlite:./sqlite.db?mode=rwc")
+    );
+    assert!(
+        root_file(&files, "Cargo.toml")
+            .contains(r#"features = ["sqlx-sqlite", "runtime-tokio-rustls", "macros"]"#)
+    );
+    assert!(root_file(&files, "Cargo.toml").contains(r#"default = ["admin", "queue", "email"]"#));
+}
+
+#[test]
+fn test_postgres_backend_enables_postgres_driver() {
+    let files = get_rext_files(&FileCreationConfig {
+        app_name: "my-app".to_string(),
+        database_backend: DatabaseBackend::Postgres,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert!(root_file(&files, "Cargo.toml").contains(r#""sqlx-postgres""#));
+    // The app's own feature lets it accept postgres:// URLs
+    assert!(root_file(&files, "Cargo.toml").contains(r#""email", "postgres"]"#));
+    assert!(root_file(&files, "rext.toml").contains("localhost:5432/my_app"));
+}
+
+#[test]
+fn test_diff_against_templates_reports_changed_file() {
+    let dir = TempDir::new().unwrap();
+    create_rext_app(dir.path(), FileCreationConfig::default()).unwrap();
+
+    let cors = Path::new("backend/infrastructure/cors.rs");
+    let original = std::fs::read_to_string(dir.path().join(cors)).unwrap();
+    std::fs::write(
+        dir.path().join(cors),
+        format!("// local tweak\n{}", original),
+    )
+    .unwrap();
+    std::fs::remove_file(dir.path().join("README.md")).unwrap();
+
+    let diffs = diff_against_templates(dir.path(), &FileCreationConfig::default()).unwrap();
+
+    assert_eq!(
+        diffs.len(),
+        get_rext_files(&FileCreationConfig::default())
+            .unwrap()
+            .len()
+    );
+    for diff in &diffs {
+        let expected = if diff.path == cors {
+            FileDiffStatus::Changed
+        } else if diff.path == Path::new("README.md") {
+            FileDiffStatus::Missing
+        } else {
+            FileDiffStatus::Identical
+        };
+        assert_eq!(diff.status, expected, "{}", diff.path.display());
+    }
+
+    let changed = diffs.iter().find(|diff| diff.path == cors).unwrap();
+    assert_eq!(changed.text_diff().unwrap(), "-// local tweak\n");
+}
+
+#[test]
+fn test_remove_rext_app_leaves_directory_empty() {
+    let dir = TempDir::new().unwrap();
+    create_rext_app(dir.path(), FileCreationConfig::default()).unwrap();
+    assert!(dir.path().join("backend").exists());
+
+    remove_rext_app(dir.path(), &FileCreationConfig::default()).unwrap();
+
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_remove_rext_app_keeps_files_it_did_not_create() {
+    let dir = TempDir::new().unwrap();
+    create_rext_app(dir.path(), FileCreationConfig::default()).unwrap();
+    std::fs::write(dir.path().join("backend/notes.md"), "mine").unwrap();
+
+    remove_rext_app(dir.path(), &FileCreationConfig::default()).unwrap();
+
+    let remaining: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(remaining, vec!["backend"]);
+    assert_eq!(
+        std::fs::read_dir(dir.path().join("backend"))
+            .unwrap()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_remove_rext_app_requires_rext_toml() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("main.rs"), "fn main() {}").unwrap();
+
+    let err = remove_rext_app(dir.path(), &FileCreationConfig::default()).unwrap_err();
+
+    assert!(err.to_string().contains("rext.toml"));
+    assert!(dir.path().join("main.rs").exists());
+}
+
+#[test]
+fn test_dry_run_lists_files_without_writing() {
+    let dir = TempDir::new().unwrap();
+
+    let paths = create_rext_app_dry_run(dir.path(), FileCreationConfig::default()).unwrap();
+
+    assert_eq!(paths.len(), files_for(AuthStrategy::Jwt).len());
+    assert!(paths.contains(&dir.path().join("rext.toml")));
+    assert!(paths.iter().all(|path| path.starts_with(dir.path())));
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    // The existing app checks still apply
+    std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+    assert!(create_rext_app_dry_run(dir.path(), FileCreationConfig::default()).is_err());
+}
+
+#[test]
+fn test_create_refuses_existing_project_without_overwrite() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+
+    let err = create_rext_app(dir.path(), FileCreationConfig::default()).unwrap_err();
+
+    assert!(err.to_string().contains("already exists"));
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("Cargo.toml")).unwrap(),
+        "[package]"
+    );
+    assert!(!backup_path(&dir.path().join("Cargo.toml")).exists());
+    assert!(!dir.path().join("rext.toml").exists());
+}
+
+#[test]
+fn test_create_with_overwrite_backs_up_replaced_files() {
+    let dir = TempDir::new().unwrap();
+    create_rext_app(dir.path(), FileCreationConfig::default()).unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), "[package]").unwrap();
+    let config = || FileCreationConfig {
+        overwrite: true,
+        ..Default::default()
+    };
+
+    create_rext_app(dir.path(), config()).unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(backup_path(&dir.path().join("Cargo.toml"))).unwrap(),
+        "[package]"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("Cargo.toml")).unwrap(),
+        root_file(&get_rext_files(&config()).unwrap(), "Cargo.toml")
+    );
+    // Files that already matched the templates weren't replaced, so they have no backup
+    assert!(!backup_path(&dir.path().join("rext.toml")).exists());
+
+    // A second overwrite keeps the first backup and numbers the new one
+    std::fs::write(dir.path().join("Cargo.toml"), "[workspace]").unwrap();
+    create_rext_app(dir.path(), config()).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(backup_path(&dir.path().join("Cargo.toml"))).unwrap(),
+        "[package]"
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("Cargo.toml.bak.1")).unwrap(),
+        "[workspace]"
+    );
+}
+
+#[test]
+fn test_failed_create_removes_what_it_created() {
+    let dir = TempDir::new().unwrap();
+    // A directory where the last file should go makes that write fail, even as root
+    let last_file = files_for(AuthStrategy::Jwt).pop().unwrap();
+    let blocked = last_file.full_path(dir.path());
+    std::fs::create_dir_all(&blocked).unwrap();
+    let before = std::fs::read_dir(dir.path()).unwrap().count();
+
+    assert!(create_rext_app(dir.path(), FileCreationConfig::default()).is_err());
+
+    assert!(!dir.path().join("rext.toml").exists());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), before);
+    assert!(blocked.is_dir());
+    // Retrying isn't blocked by a half-scaffolded project
+    std::fs::remove_dir_all(&blocked).unwrap();
+    create_rext_app(dir.path(), FileCreationConfig::default()).unwrap();
+}
+
+#[test]
+fn test_app_name_spaces_and_uppercase_are_normalized() {
+    assert_eq!(
+        validate_and_normalize_app_name("My App 2.0").unwrap(),
+        "my-app-2-0"
+    );
+    assert_eq!(
+        validate_and_normalize_app_name("  Rext_Demo  ").unwrap(),
+        "rext_demo"
+    );
+    assert_eq!(
+        validate_and_normalize_app_name("my-rext-app").unwrap(),
+        "my-rext-app"
+    );
+}
+
+#[test]
+fn test_app_name_leading_digit_rejected() {
+    let err = validate_and_normalize_app_name("2048 Game").unwrap_err();
+    assert!(err.to_string().starts_with("Invalid app name"));
+    assert!(err.to_string().contains("starts with a digit"));
+}
+
+#[test]
+fn test_app_name_empty_rejected() {
+    for name in ["", "   ", "!!!", "..."] {
+        let err = validate_and_normalize_app_name(name).unwrap_err();
+        assert!(err.to_string().contains("has no letters or digits"));
+    }
+
+    let err = validate_and_normalize_app_name("Crate").unwrap_err();
+    assert!(err.to_string().contains("Rust keyword"));
+}
+
+#[test]
+fn test_check_for_rext_app_in_nonexistent_path() {
+    let dir = TempDir::new().unwrap();
+    assert!(!check_for_rext_app_in(&dir.path().join("does-not-exist")));
+}
+
+#[test]
+fn test_check_for_rext_app_in_directory() {
+    let dir = TempDir::new().unwrap();
+    assert!(!check_for_rext_app_in(dir.path()));
+
+    std::fs::write(dir.path().join("rext.toml"), "").unwrap();
+    assert!(check_for_rext_app_in(dir.path()));
+}
+
+#[test]
+fn test_try_check_for_rext_app_in_crate_root() {
+    // Tests run from the crate root, which isn't a Rext app
+    assert!(!try_check_for_rext_app().unwrap());
+}
+
+const ENTITY_SOURCE: &str = r#"//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, utoipa::ToSchema)]
+#[sea_orm(table_name = "users")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub email: String,
+    pub created_at: Option<DateTimeWithTimeZone>,
+}
+"#;
+
+#[test]
+fn test_annotate_entity_source_wraps_unsupported_types() {
+    let annotated = annotate_entity_source(ENTITY_SOURCE);
+
+    assert_eq!(
+        annotated.matches("#[schema(value_type = String)]").count(),
+        2
+    );
+    assert!(annotated.contains(
+        "    #[sea_orm(primary_key, auto_increment = false)]\n    #[schema(value_type = String)]\n    pub id: Uuid,"
+    ));
+    assert!(annotated.contains("    pub email: String,\n    #[schema(value_type = String)]\n"));
+}
+
+#[test]
+fn test_annotate_entity_source_is_idempotent() {
+    let once = annotate_entity_source(ENTITY_SOURCE);
+    let twice = annotate_entity_source(&once);
+
+    assert_eq!(twice, once);
+    assert!(!twice.contains("#[schema(value_type = String)]\n    #[schema(value_type = String)]"));
+}
+
+#[test]
+fn test_generate_entities_config_types_to_wrap_drive_annotation() {
+    let config = GenerateEntitiesConfig {
+        types_to_wrap: vec!["String".to_string()],
+        ..Default::default()
+    };
+
+    let annotated = config.annotate_entity_source(ENTITY_SOURCE);
+
+    assert_eq!(
+        annotated.matches("#[schema(value_type = String)]").count(),
+        1
+    );
+    assert!(annotated.contains("    #[schema(value_type = String)]\n    pub email: String,"));
+    assert!(!annotated.contains("    #[schema(value_type = String)]\n    pub id: Uuid,"));
+}
+
+#[test]
+fn test_generate_entities_config_default_matches_previous_behavior() {
+    let config = GenerateEntitiesConfig::default();
+
+    assert_eq!(config.database_url, "sqlite:./sqlite.db?mode=rwc");
+    assert_eq!(config.output_dir, Path::new("backend/entity/models"));
+    assert_eq!(config.sea_orm_cli, Path::new("sea-orm-cli"));
+    assert_eq!(
+        config.annotate_entity_source(ENTITY_SOURCE),
+        annotate_entity_source(ENTITY_SOURCE)
+    );
+}
+
+#[test]
+fn test_annotate_entity_source_handles_wrapped_fields() {
+    let source = r#"//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
+
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, utoipa::ToSchema)]
+#[sea_orm(table_name = "user_sessions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub last_activity:
+        Option<DateTimeWithTimeZone>,
+    pub user_agent: Option<
+        String,
+    >,
+    pub expires_at: Option<
+        DateTimeWithTimeZone,
+    >
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+"#;
+
+    let annotated = annotate_entity_source(source);
+
+    assert_eq!(
+        annotated.matches("#[schema(value_type = String)]").count(),
+        3
+    );
+    assert!(annotated.contains(
+        "    #[schema(value_type = String)]\n    pub last_activity:\n        Option<DateTimeWithTimeZone>,\n"
+    ));
+    assert!(annotated.contains(
+        "    >,\n    #[schema(value_type = String)]\n    pub expires_at: Option<\n        DateTimeWithTimeZone,\n    >\n}\n"
+    ));
+    assert!(!annotated.contains("#[schema(value_type = String)]\n    pub user_agent"));
+    assert!(annotated.contains("pub struct Model {\n    #[sea_orm"));
+    assert_eq!(annotate_entity_source(&annotated), annotated);
+}
+
+#[test]
+fn test_missing_sea_orm_cli_tells_user_to_install_it() {
+    let dir = TempDir::new().unwrap();
+    let config = GenerateEntitiesConfig {
+        output_dir: dir.path().to_path_buf(),
+        sea_orm_cli: dir.path().join("sea-orm-cli-not-installed"),
+        ..Default::default()
+    };
+
+    let err = generate_sea_orm_entities_with_config(&config).unwrap_err();
+
+    assert!(err.to_string().contains("cargo install sea-orm-cli"));
+}
+
+#[test]
+fn test_list_rext_file_paths_matches_get_rext_files() {
+    for auth_strategy in [AuthStrategy::Jwt, AuthStrategy::SessionCookie] {
+        let config = FileCreationConfig {
+            auth_strategy,
+            modules: vec![RextModule::RextCore],
+            ..Default::default()
+        };
+
+        let paths = list_rext_file_paths(&config);
+        let files = get_rext_files(&config).unwrap();
+
+        assert_eq!(paths.len(), files.len());
+        for (path, file) in paths.iter().zip(&files) {
+            assert_eq!(
+                Path::new("/project").join(path),
+                file.full_path(Path::new("/project"))
+            );
+        }
+    }
+
+    let paths = list_rext_file_paths(&FileCreationConfig::default());
+    assert_eq!(
+        paths.len(),
+        get_rext_files(&FileCreationConfig::default())
+            .unwrap()
+            .len()
+    );
+    assert!(paths.contains(&PathBuf::from("rext.toml")));
+    assert!(paths.contains(&PathBuf::from("backend/main.rs")));
+}